@@ -1,7 +1,19 @@
-use crate::tree::TreeNode;
+use crate::apted_optimal::{compute_apted_distance, CostModel};
+use crate::edit_script::{EditOperation, EditScript};
+use crate::tree::{preorder_nodes, ArenaTree, AsArenaTree, TreeNode};
 use std::collections::HashMap;
 use std::rc::Rc;
 
+/// Tree edit distance implementation used by [`compute_edit_distance`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TreeEditAlgorithm {
+    /// Fast recursive approximation that aligns children level by level
+    #[default]
+    Recursive,
+    /// Exact APTED with the optimal path strategy (handles moves across levels)
+    Apted,
+}
+
 #[derive(Debug, Clone)]
 pub struct APTEDOptions {
     pub rename_cost: f64,
@@ -9,6 +21,8 @@ pub struct APTEDOptions {
     pub insert_cost: f64,
     /// Whether to compare node values in addition to labels
     pub compare_values: bool,
+    /// Which tree edit distance implementation to use
+    pub algorithm: TreeEditAlgorithm,
 }
 
 impl Default for APTEDOptions {
@@ -18,168 +32,227 @@ impl Default for APTEDOptions {
             delete_cost: 1.0,
             insert_cost: 1.0,
             compare_values: true, // Default: compare both structure and values
+            algorithm: TreeEditAlgorithm::Recursive,
         }
     }
 }
 
 #[must_use]
-#[allow(clippy::cast_precision_loss)]
-pub fn compute_edit_distance(
-    tree1: &Rc<TreeNode>,
-    tree2: &Rc<TreeNode>,
-    options: &APTEDOptions,
-) -> f64 {
-    let mut memo: HashMap<(usize, usize), f64> = HashMap::new();
-    compute_edit_distance_recursive(tree1, tree2, options, &mut memo)
+pub fn compute_edit_distance<T1, T2>(tree1: &T1, tree2: &T2, options: &APTEDOptions) -> f64
+where
+    T1: AsArenaTree + ?Sized,
+    T2: AsArenaTree + ?Sized,
+{
+    let (tree1, tree2) = (tree1.as_arena(), tree2.as_arena());
+    if options.algorithm == TreeEditAlgorithm::Apted {
+        return compute_apted_distance(&*tree1, &*tree2, options);
+    }
+    RecursiveDistance::new(&tree1, &tree2, options).root_distance()
 }
 
-fn compute_edit_distance_recursive(
-    node1: &Rc<TreeNode>,
-    node2: &Rc<TreeNode>,
-    options: &APTEDOptions,
-    memo: &mut HashMap<(usize, usize), f64>,
-) -> f64 {
-    let key = (node1.id, node2.id);
+/// Level-by-level edit distance: a node pair costs its rename plus the best alignment of
+/// the two child lists, unless deleting or inserting the whole subtree is cheaper
+struct RecursiveDistance<'t> {
+    tree1: &'t ArenaTree,
+    tree2: &'t ArenaTree,
+    options: &'t APTEDOptions,
+    memo: HashMap<(usize, usize), f64>,
+}
 
-    if let Some(&cost) = memo.get(&key) {
-        return cost;
+impl<'t> RecursiveDistance<'t> {
+    fn new(tree1: &'t ArenaTree, tree2: &'t ArenaTree, options: &'t APTEDOptions) -> Self {
+        RecursiveDistance { tree1, tree2, options, memo: HashMap::new() }
     }
 
-    // Base cases
-    if node1.children.is_empty() && node2.children.is_empty() {
-        // Both are leaves
-        let cost = if options.compare_values {
-            // Compare both label and value
-            if node1.label == node2.label && node1.value == node2.value {
-                0.0
-            } else {
-                options.rename_cost
+    fn root_distance(&mut self) -> f64 {
+        match (self.tree1.is_empty(), self.tree2.is_empty()) {
+            (false, false) => self.distance(0, 0),
+            _ => {
+                self.options.delete_cost * self.tree1.len() as f64
+                    + self.options.insert_cost * self.tree2.len() as f64
             }
-        } else {
-            // Compare only label (structural comparison)
-            if node1.label == node2.label {
-                0.0
-            } else {
-                options.rename_cost
-            }
-        };
-        memo.insert(key, cost);
-        return cost;
+        }
     }
 
-    // Calculate costs for all three operations
-    let delete_all_cost = options.delete_cost * node1.get_subtree_size() as f64;
-    let insert_all_cost = options.insert_cost * node2.get_subtree_size() as f64;
-
-    // Calculate rename + optimal children alignment
-    let mut rename_plus_cost = if options.compare_values {
-        // Compare both label and value
-        if node1.label == node2.label && node1.value == node2.value {
-            0.0
-        } else {
-            options.rename_cost
+    fn rename(&self, a: usize, b: usize) -> f64 {
+        self.options.rename(self.tree1.node(a), self.tree2.node(b))
+    }
+
+    fn delete_all(&self, a: usize) -> f64 {
+        self.options.delete_cost * self.tree1.size(a) as f64
+    }
+
+    fn insert_all(&self, b: usize) -> f64 {
+        self.options.insert_cost * self.tree2.size(b) as f64
+    }
+
+    fn distance(&mut self, a: usize, b: usize) -> f64 {
+        if let Some(&cost) = self.memo.get(&(a, b)) {
+            return cost;
         }
-    } else {
-        // Compare only label (structural comparison)
-        if node1.label == node2.label {
-            0.0
-        } else {
-            options.rename_cost
+
+        // Both are leaves
+        if self.tree1.is_leaf(a) && self.tree2.is_leaf(b) {
+            let cost = self.rename(a, b);
+            self.memo.insert((a, b), cost);
+            return cost;
         }
-    };
 
-    if !node1.children.is_empty() || !node2.children.is_empty() {
-        // Compute all pairwise costs between children
-        let mut child_cost_matrix: HashMap<(usize, usize), f64> = HashMap::new();
+        // Rename + optimal children alignment, unless replacing the whole subtree is cheaper
+        let (alignment_cost, _) = self.align_children(a, b);
+        let rename_plus_cost = self.rename(a, b) + alignment_cost;
+        let min_cost = self.delete_all(a).min(self.insert_all(b)).min(rename_plus_cost);
+        self.memo.insert((a, b), min_cost);
+        min_cost
+    }
+
+    /// Optimal alignment of the children of `a` and `b`
+    fn align_children(&mut self, a: usize, b: usize) -> (f64, ChildAlignment) {
+        let (tree1, tree2) = (self.tree1, self.tree2);
+        let children1 = tree1.children(a);
+        let children2 = tree2.children(b);
+        let m = children1.len();
+        let n = children2.len();
+
+        // Pairwise costs between children
+        let mut edit_costs = vec![0.0; m * n];
+        for (i, &child1) in children1.iter().enumerate() {
+            for (j, &child2) in children2.iter().enumerate() {
+                edit_costs[i * n + j] = self.distance(child1, child2);
+            }
+        }
+        let delete: Vec<f64> = children1.iter().map(|&child| self.delete_all(child)).collect();
+        let insert: Vec<f64> = children2.iter().map(|&child| self.insert_all(child)).collect();
 
-        for child1 in &node1.children {
-            for child2 in &node2.children {
-                let cost = compute_edit_distance_recursive(child1, child2, options, memo);
-                child_cost_matrix.insert((child1.id, child2.id), cost);
+        // dp[i][j] = minimum cost to align first i children of `a` with first j children of `b`
+        let cols = n + 1;
+        let mut dp = vec![0.0; (m + 1) * cols];
+        for i in 1..=m {
+            dp[i * cols] = dp[(i - 1) * cols] + delete[i - 1];
+        }
+        for j in 1..=n {
+            dp[j] = dp[j - 1] + insert[j - 1];
+        }
+        for i in 1..=m {
+            for j in 1..=n {
+                dp[i * cols + j] = (dp[(i - 1) * cols + j] + delete[i - 1])
+                    .min(dp[i * cols + j - 1] + insert[j - 1])
+                    .min(dp[(i - 1) * cols + j - 1] + edit_costs[(i - 1) * n + j - 1]);
             }
         }
 
-        // Find optimal alignment
-        let (alignment_cost, _) = compute_children_alignment(
-            &node1.children,
-            &node2.children,
-            &child_cost_matrix,
-            options,
-        );
+        // Backtrack to find alignment (pairs of child indices, left to right)
+        let mut alignment = Vec::new();
+        let mut i = m;
+        let mut j = n;
+        while i > 0 || j > 0 {
+            if i == 0 {
+                alignment.push((None, Some(j - 1)));
+                j -= 1;
+            } else if j == 0 {
+                alignment.push((Some(i - 1), None));
+                i -= 1;
+            } else {
+                let delete_cost = dp[(i - 1) * cols + j] + delete[i - 1];
+                let insert_cost = dp[i * cols + j - 1] + insert[j - 1];
+                let match_cost = dp[(i - 1) * cols + j - 1] + edit_costs[(i - 1) * n + j - 1];
 
-        rename_plus_cost += alignment_cost;
-    }
+                if match_cost <= delete_cost && match_cost <= insert_cost {
+                    alignment.push((Some(i - 1), Some(j - 1)));
+                    i -= 1;
+                    j -= 1;
+                } else if delete_cost <= insert_cost {
+                    alignment.push((Some(i - 1), None));
+                    i -= 1;
+                } else {
+                    alignment.push((None, Some(j - 1)));
+                    j -= 1;
+                }
+            }
+        }
+        alignment.reverse();
 
-    let min_cost = delete_all_cost.min(insert_all_cost).min(rename_plus_cost);
-    memo.insert(key, min_cost);
-    min_cost
+        (dp[m * cols + n], alignment)
+    }
 }
 
-fn compute_children_alignment(
-    children1: &[Rc<TreeNode>],
-    children2: &[Rc<TreeNode>],
-    cost_matrix: &HashMap<(usize, usize), f64>,
+/// Aligned child index pairs; `None` marks a deleted or inserted child
+type ChildAlignment = Vec<(Option<usize>, Option<usize>)>;
+
+/// Backtrack the recursive distance into a node mapping and edit operations.
+/// Subtrees the recursion drops as a whole are reported as deleted and the
+/// corresponding subtree of the second tree as inserted.
+pub(crate) fn recursive_edit_script(
+    tree1: &Rc<TreeNode>,
+    tree2: &Rc<TreeNode>,
     options: &APTEDOptions,
-) -> (f64, HashMap<usize, Option<usize>>) {
-    let m = children1.len();
-    let n = children2.len();
+) -> EditScript {
+    let (arena1, arena2) = (ArenaTree::from_node(tree1), ArenaTree::from_node(tree2));
+    let mut recursion = RecursiveDistance::new(&arena1, &arena2, options);
+    let distance = recursion.root_distance();
+    let mut script = EditScript { distance, ..Default::default() };
+    let nodes = (preorder_nodes(tree1), preorder_nodes(tree2));
+    collect_recursive_script(&mut recursion, &nodes, 0, 0, &mut script);
+    script
+}
 
-    // dp[i][j] = minimum cost to align first i children of node1 with first j children of node2
-    let mut dp = vec![vec![0.0; n + 1]; m + 1];
+fn collect_recursive_script(
+    recursion: &mut RecursiveDistance<'_>,
+    nodes: &(Vec<Rc<TreeNode>>, Vec<Rc<TreeNode>>),
+    a: usize,
+    b: usize,
+    script: &mut EditScript,
+) {
+    let (tree1, tree2) = (recursion.tree1, recursion.tree2);
+    let rename_cost = recursion.rename(a, b);
+    let mut alignment = Vec::new();
+    let mut rename_plus_cost = rename_cost;
 
-    // Initialize base cases
-    for i in 1..=m {
-        dp[i][0] = dp[i - 1][0] + options.delete_cost * children1[i - 1].get_subtree_size() as f64;
-    }
-    for j in 1..=n {
-        dp[0][j] = dp[0][j - 1] + options.insert_cost * children2[j - 1].get_subtree_size() as f64;
+    if !tree1.is_leaf(a) || !tree2.is_leaf(b) {
+        let (alignment_cost, children_alignment) = recursion.align_children(a, b);
+        rename_plus_cost += alignment_cost;
+        alignment = children_alignment;
     }
 
-    // Fill DP table
-    for i in 1..=m {
-        for j in 1..=n {
-            let child1 = &children1[i - 1];
-            let child2 = &children2[j - 1];
-            let edit_cost = cost_matrix.get(&(child1.id, child2.id)).unwrap_or(&0.0);
-
-            dp[i][j] = (dp[i - 1][j] + options.delete_cost * child1.get_subtree_size() as f64)
-                .min(dp[i][j - 1] + options.insert_cost * child2.get_subtree_size() as f64)
-                .min(dp[i - 1][j - 1] + edit_cost);
-        }
+    if rename_plus_cost > recursion.delete_all(a).min(recursion.insert_all(b)) {
+        push_subtree(&nodes.0, tree1, a, script, EditOperation::Delete);
+        push_subtree(&nodes.1, tree2, b, script, EditOperation::Insert);
+        return;
     }
 
-    // Backtrack to find alignment
-    let mut alignment = HashMap::new();
-    let mut i = m;
-    let mut j = n;
-
-    while i > 0 || j > 0 {
-        if i == 0 {
-            j -= 1;
-        } else if j == 0 {
-            alignment.insert(children1[i - 1].id, None);
-            i -= 1;
-        } else {
-            let child1 = &children1[i - 1];
-            let child2 = &children2[j - 1];
-            let edit_cost = cost_matrix.get(&(child1.id, child2.id)).unwrap_or(&0.0);
-
-            let delete_cost = dp[i - 1][j] + options.delete_cost * child1.get_subtree_size() as f64;
-            let insert_cost = dp[i][j - 1] + options.insert_cost * child2.get_subtree_size() as f64;
-            let match_cost = dp[i - 1][j - 1] + edit_cost;
-
-            if match_cost <= delete_cost && match_cost <= insert_cost {
-                alignment.insert(child1.id, Some(child2.id));
-                i -= 1;
-                j -= 1;
-            } else if delete_cost <= insert_cost {
-                alignment.insert(child1.id, None);
-                i -= 1;
-            } else {
-                j -= 1;
+    script.mapping.push((nodes.0[a].clone(), nodes.1[b].clone()));
+    if rename_cost > 0.0 {
+        script
+            .operations
+            .push(EditOperation::Rename { from: nodes.0[a].clone(), to: nodes.1[b].clone() });
+    }
+    for pair in alignment {
+        match pair {
+            (Some(i), Some(j)) => collect_recursive_script(
+                recursion,
+                nodes,
+                tree1.children(a)[i],
+                tree2.children(b)[j],
+                script,
+            ),
+            (Some(i), None) => {
+                push_subtree(&nodes.0, tree1, tree1.children(a)[i], script, EditOperation::Delete)
+            }
+            (None, Some(j)) => {
+                push_subtree(&nodes.1, tree2, tree2.children(b)[j], script, EditOperation::Insert)
             }
+            (None, None) => {}
         }
     }
+}
 
-    (dp[m][n], alignment)
+/// Report every node of the subtree rooted at `id`, in preorder
+fn push_subtree(
+    nodes: &[Rc<TreeNode>],
+    tree: &ArenaTree,
+    id: usize,
+    script: &mut EditScript,
+    op: fn(Rc<TreeNode>) -> EditOperation,
+) {
+    script.operations.extend(tree.subtree(id).map(|node| op(nodes[node].clone())));
 }
@@ -0,0 +1,797 @@
+//! Exact tree edit distance following APTED (Pawlik & Augsten, "Tree edit distance:
+//! Robust and memory-efficient", Information Systems 56, 2016).
+//!
+//! The distance is computed with GTED: for every pair of subtrees a root-to-leaf path
+//! is picked by an optimal strategy, the subtrees hanging off that path are solved
+//! recursively and the path itself is handled by a single-path function. Left and
+//! right paths use Zhang–Shasha style keyroot passes, inner (heavy) paths use a table
+//! over every subforest of the other tree. The strategy minimises the number of
+//! subproblems those functions evaluate.
+
+use crate::apted::APTEDOptions;
+use crate::edit_script::{EditOperation, EditScript};
+use crate::tree::{preorder_nodes, ArenaTree, AsArenaTree, NodeView, TreeNode};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Per-node edit costs used by the APTED backend
+pub trait CostModel {
+    /// Cost of deleting `node` from the source tree
+    fn delete(&self, node: NodeView<'_>) -> f64;
+    /// Cost of inserting `node` from the target tree
+    fn insert(&self, node: NodeView<'_>) -> f64;
+    /// Cost of relabelling `from` into `to` (0.0 when they match)
+    fn rename(&self, from: NodeView<'_>, to: NodeView<'_>) -> f64;
+}
+
+impl CostModel for APTEDOptions {
+    fn delete(&self, _node: NodeView<'_>) -> f64 {
+        self.delete_cost
+    }
+
+    fn insert(&self, _node: NodeView<'_>) -> f64 {
+        self.insert_cost
+    }
+
+    fn rename(&self, from: NodeView<'_>, to: NodeView<'_>) -> f64 {
+        let same = from.label == to.label && (!self.compare_values || from.value == to.value);
+        if same {
+            0.0
+        } else {
+            self.rename_cost
+        }
+    }
+}
+
+/// Compute the exact tree edit distance with the options' unit costs
+#[must_use]
+pub fn compute_apted_distance<T1, T2>(tree1: &T1, tree2: &T2, options: &APTEDOptions) -> f64
+where
+    T1: AsArenaTree + ?Sized,
+    T2: AsArenaTree + ?Sized,
+{
+    compute_apted_distance_with_costs(tree1, tree2, options)
+}
+
+/// Compute the exact tree edit distance with a custom cost model
+#[must_use]
+pub fn compute_apted_distance_with_costs<T1, T2, C>(tree1: &T1, tree2: &T2, costs: &C) -> f64
+where
+    T1: AsArenaTree + ?Sized,
+    T2: AsArenaTree + ?Sized,
+    C: CostModel + ?Sized,
+{
+    let (tree1, tree2) = (tree1.as_arena(), tree2.as_arena());
+    if tree1.is_empty() || tree2.is_empty() {
+        let deleted: f64 = (0..tree1.len()).map(|id| costs.delete(tree1.node(id))).sum();
+        let inserted: f64 = (0..tree2.len()).map(|id| costs.insert(tree2.node(id))).sum();
+        return deleted + inserted;
+    }
+    let t1 = IndexedTree::new(&tree1);
+    let t2 = IndexedTree::new(&tree2);
+    let mut apted = Apted::new(&t1, &t2, costs);
+    apted.gted(0, 0);
+    apted.dist.get(false, 0, 0)
+}
+
+/// Compute the exact distance together with an optimal node mapping and its edit operations
+#[must_use]
+pub fn compute_apted_edit_script_with_costs<C: CostModel + ?Sized>(
+    tree1: &Rc<TreeNode>,
+    tree2: &Rc<TreeNode>,
+    costs: &C,
+) -> EditScript {
+    let (arena1, arena2) = (ArenaTree::from_node(tree1), ArenaTree::from_node(tree2));
+    let t1 = IndexedTree::new(&arena1);
+    let t2 = IndexedTree::new(&arena2);
+    let mut apted = Apted::new(&t1, &t2, costs);
+    apted.gted(0, 0);
+
+    let nodes1 = preorder_nodes(tree1);
+    let nodes2 = preorder_nodes(tree2);
+    let mut pairs = apted.edit_mapping();
+    // Deletions and renames follow the first tree, insertions the second
+    pairs.sort_by_key(|&(a, b)| (a.is_none(), a.or(b)));
+
+    let mut script = EditScript { distance: apted.dist.get(false, 0, 0), ..Default::default() };
+    for pair in pairs {
+        match pair {
+            (Some(a), Some(b)) => {
+                let (from, to) = (nodes1[a].clone(), nodes2[b].clone());
+                if costs.rename(arena1.node(a), arena2.node(b)) > 0.0 {
+                    let rename = EditOperation::Rename { from: from.clone(), to: to.clone() };
+                    script.operations.push(rename);
+                }
+                script.mapping.push((from, to));
+            }
+            (Some(a), None) => script.operations.push(EditOperation::Delete(nodes1[a].clone())),
+            (None, Some(b)) => script.operations.push(EditOperation::Insert(nodes2[b].clone())),
+            (None, None) => {}
+        }
+    }
+    script
+}
+
+pub(crate) fn apted_edit_script(
+    tree1: &Rc<TreeNode>,
+    tree2: &Rc<TreeNode>,
+    options: &APTEDOptions,
+) -> EditScript {
+    compute_apted_edit_script_with_costs(tree1, tree2, options)
+}
+
+/// Which root-to-leaf path a single-path function follows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PathKind {
+    Left,
+    Right,
+    Heavy,
+}
+
+/// Strategy entry: the path kind and whether it lies in the second tree
+#[derive(Debug, Clone, Copy)]
+struct Path {
+    in_second: bool,
+    kind: PathKind,
+}
+
+/// How a node enters the chain of relevant subforests in the heavy-path function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    /// The node is on the path and the forest is its whole subtree
+    Tree,
+    /// The node is an off-path leftmost root
+    Left,
+    /// The node is an off-path rightmost root
+    Right,
+}
+
+/// Arena tree with the extra traversal tables GTED needs. Sizes, the left-to-right
+/// postorder and leftmost leaves come straight from the [`ArenaTree`].
+struct IndexedTree<'a> {
+    tree: &'a ArenaTree,
+    size: &'a [usize],
+    heavy: Vec<Option<usize>>,
+    /// Reversed preorder (children visited right to left)
+    pre_r: Vec<usize>,
+    pre_r_to_id: Vec<usize>,
+    /// Left-to-right postorder and the postorder index of the leftmost leaf
+    post_l: &'a [usize],
+    post_l_to_id: &'a [usize],
+    lld: &'a [usize],
+    /// Right-to-left postorder and the postorder index of the rightmost leaf
+    post_r: Vec<usize>,
+    post_r_to_id: Vec<usize>,
+    rld: Vec<usize>,
+    first_child: Vec<bool>,
+    last_child: Vec<bool>,
+    /// Relevant subforests of a left / right path decomposition
+    kr_sum: Vec<u64>,
+    rev_kr_sum: Vec<u64>,
+}
+
+impl<'a> IndexedTree<'a> {
+    fn new(tree: &'a ArenaTree) -> Self {
+        let n = tree.len();
+        let mut indexed = IndexedTree {
+            tree,
+            size: tree.sizes(),
+            heavy: vec![None; n],
+            pre_r: vec![0; n],
+            pre_r_to_id: Vec::with_capacity(n),
+            post_l: tree.postorder(),
+            post_l_to_id: tree.postorder_to_id(),
+            lld: tree.leftmost_leaves(),
+            post_r: vec![0; n],
+            post_r_to_id: Vec::with_capacity(n),
+            rld: vec![0; n],
+            first_child: vec![false; n],
+            last_child: vec![false; n],
+            kr_sum: vec![0; n],
+            rev_kr_sum: vec![0; n],
+        };
+        if n == 0 {
+            return indexed;
+        }
+        indexed.index_right_to_left();
+
+        for id in 0..n {
+            let children = tree.children(id);
+            if let (Some(&first), Some(&last)) = (children.first(), children.last()) {
+                indexed.first_child[first] = true;
+                indexed.last_child[last] = true;
+            }
+        }
+
+        // Children have larger preorder ids than their parents
+        for id in (0..n).rev() {
+            let children = tree.children(id);
+            let size = indexed.size[id] as u64;
+            match (children.first(), children.last()) {
+                (Some(&first), Some(&last)) => {
+                    let all: u64 = children.iter().map(|&c| indexed.kr_sum[c]).sum();
+                    let all_rev: u64 = children.iter().map(|&c| indexed.rev_kr_sum[c]).sum();
+                    indexed.kr_sum[id] = size + all - indexed.size[first] as u64;
+                    indexed.rev_kr_sum[id] = size + all_rev - indexed.size[last] as u64;
+                    indexed.heavy[id] = children.iter().copied().max_by_key(|&c| {
+                        // Prefer the leftmost child among equally sized ones
+                        (indexed.size[c], std::cmp::Reverse(c))
+                    });
+                }
+                _ => {
+                    indexed.kr_sum[id] = 1;
+                    indexed.rev_kr_sum[id] = 1;
+                }
+            }
+        }
+
+        indexed
+    }
+
+    /// Reversed preorder and right-to-left postorder, walking children right to left
+    fn index_right_to_left(&mut self) {
+        let tree = self.tree;
+        // (node, whether its children were already pushed)
+        let mut stack = vec![(0, false)];
+        while let Some((id, expanded)) = stack.pop() {
+            if expanded {
+                self.post_r[id] = self.post_r_to_id.len();
+                self.post_r_to_id.push(id);
+                self.rld[id] = match tree.children(id).last() {
+                    Some(&last) => self.rld[last],
+                    None => self.post_r[id],
+                };
+                continue;
+            }
+            self.pre_r[id] = self.pre_r_to_id.len();
+            self.pre_r_to_id.push(id);
+            stack.push((id, true));
+            // Popped right to left
+            stack.extend(tree.children(id).iter().map(|&child| (child, false)));
+        }
+    }
+
+    fn children(&self, id: usize) -> &'a [usize] {
+        self.tree.children(id)
+    }
+
+    fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    fn path_child(&self, id: usize, kind: PathKind) -> Option<usize> {
+        match kind {
+            PathKind::Left => self.children(id).first().copied(),
+            PathKind::Right => self.children(id).last().copied(),
+            PathKind::Heavy => self.heavy[id],
+        }
+    }
+
+    /// Postorder, postorder-to-id and leaf-descendant tables for a left or right pass
+    fn orientation(&self, kind: PathKind) -> (&[usize], &[usize], &[usize]) {
+        match kind {
+            PathKind::Right => (&self.post_r, &self.post_r_to_id, &self.rld),
+            _ => (self.post_l, self.post_l_to_id, self.lld),
+        }
+    }
+
+    /// Whether `id` is the first child of its parent in the pass direction
+    fn is_path_child(&self, id: usize, kind: PathKind) -> bool {
+        match kind {
+            PathKind::Right => self.last_child[id],
+            _ => self.first_child[id],
+        }
+    }
+}
+
+/// Subtree distances indexed by (first tree id, second tree id)
+struct DistMatrix {
+    data: Vec<f64>,
+    cols: usize,
+}
+
+impl DistMatrix {
+    fn get(&self, swapped: bool, a: usize, b: usize) -> f64 {
+        if swapped {
+            self.data[b * self.cols + a]
+        } else {
+            self.data[a * self.cols + b]
+        }
+    }
+
+    fn set(&mut self, swapped: bool, a: usize, b: usize, value: f64) {
+        if swapped {
+            self.data[b * self.cols + a] = value;
+        } else {
+            self.data[a * self.cols + b] = value;
+        }
+    }
+}
+
+/// Read-only view of both trees and their costs.
+/// `swapped` evaluates δ(G, F) with deletions and insertions exchanged.
+struct Trees<'t, 'a, C: CostModel + ?Sized> {
+    t1: &'t IndexedTree<'a>,
+    t2: &'t IndexedTree<'a>,
+    costs: &'t C,
+    delete1: Vec<f64>,
+    insert2: Vec<f64>,
+}
+
+impl<'t, 'a, C: CostModel + ?Sized> Trees<'t, 'a, C> {
+    fn a(&self, swapped: bool) -> &'t IndexedTree<'a> {
+        if swapped {
+            self.t2
+        } else {
+            self.t1
+        }
+    }
+
+    fn b(&self, swapped: bool) -> &'t IndexedTree<'a> {
+        if swapped {
+            self.t1
+        } else {
+            self.t2
+        }
+    }
+
+    fn delete(&self, swapped: bool, a: usize) -> f64 {
+        if swapped {
+            self.insert2[a]
+        } else {
+            self.delete1[a]
+        }
+    }
+
+    fn insert(&self, swapped: bool, b: usize) -> f64 {
+        if swapped {
+            self.delete1[b]
+        } else {
+            self.insert2[b]
+        }
+    }
+
+    fn rename(&self, swapped: bool, a: usize, b: usize) -> f64 {
+        if swapped {
+            self.costs.rename(self.t1.tree.node(b), self.t2.tree.node(a))
+        } else {
+            self.costs.rename(self.t1.tree.node(a), self.t2.tree.node(b))
+        }
+    }
+}
+
+struct Apted<'t, 'a, C: CostModel + ?Sized> {
+    trees: Trees<'t, 'a, C>,
+    strategy: Vec<Path>,
+    dist: DistMatrix,
+}
+
+impl<'t, 'a, C: CostModel + ?Sized> Apted<'t, 'a, C> {
+    fn new(t1: &'t IndexedTree<'a>, t2: &'t IndexedTree<'a>, costs: &'t C) -> Self {
+        let delete1 = (0..t1.len()).map(|id| costs.delete(t1.tree.node(id))).collect();
+        let insert2 = (0..t2.len()).map(|id| costs.insert(t2.tree.node(id))).collect();
+        let strategy = compute_strategy(t1, t2);
+        let dist = DistMatrix { data: vec![0.0; t1.len() * t2.len()], cols: t2.len() };
+        Apted { trees: Trees { t1, t2, costs, delete1, insert2 }, strategy, dist }
+    }
+
+    /// Compute δ(F_v', G_w') for every v' in F_v and w' in G_w
+    fn gted(&mut self, v: usize, w: usize) {
+        let path = self.strategy[v * self.trees.t2.len() + w];
+        let swapped = path.in_second;
+        let (root, other) = if swapped { (w, v) } else { (v, w) };
+        let tree = self.trees.a(swapped);
+
+        let mut node = Some(root);
+        while let Some(current) = node {
+            let next = tree.path_child(current, path.kind);
+            for &child in tree.children(current) {
+                if Some(child) != next {
+                    if swapped {
+                        self.gted(v, child);
+                    } else {
+                        self.gted(child, w);
+                    }
+                }
+            }
+            node = next;
+        }
+
+        match path.kind {
+            PathKind::Heavy => self.spf_heavy(root, other, swapped),
+            kind => self.spf_keyroots(kind, root, other, swapped),
+        }
+    }
+
+    /// Recover an optimal mapping from the subtree distances computed by `gted`.
+    /// Each subtree pair is re-solved with one forest pass and backtracked; pairs
+    /// matched through a stored subtree distance are expanded the same way.
+    fn edit_mapping(&self) -> Vec<(Option<usize>, Option<usize>)> {
+        let (t1, t2) = (self.trees.t1, self.trees.t2);
+        let close = |x: f64, y: f64| (x - y).abs() < 1e-9;
+        let mut result = Vec::new();
+        let mut stack = vec![(t1.len() - 1, t2.len() - 1)];
+        let mut fd = Vec::new();
+
+        while let Some((i, j)) = stack.pop() {
+            let li = t1.lld[t1.post_l_to_id[i]];
+            let lj = t2.lld[t2.post_l_to_id[j]];
+            let rows = i - li + 2;
+            let cols = j - lj + 2;
+            let node1 = |x: usize| t1.post_l_to_id[li + x - 1];
+            let node2 = |y: usize| t2.post_l_to_id[lj + y - 1];
+            fd.clear();
+            fd.resize(rows * cols, 0.0);
+
+            for x in 1..rows {
+                fd[x * cols] = fd[(x - 1) * cols] + self.trees.delete(false, node1(x));
+            }
+            for y in 1..cols {
+                fd[y] = fd[y - 1] + self.trees.insert(false, node2(y));
+            }
+            for x in 1..rows {
+                let a = node1(x);
+                for y in 1..cols {
+                    let b = node2(y);
+                    let by_delete = fd[(x - 1) * cols + y] + self.trees.delete(false, a);
+                    let by_insert = fd[x * cols + y - 1] + self.trees.insert(false, b);
+                    let by_match = if t1.lld[a] == li && t2.lld[b] == lj {
+                        fd[(x - 1) * cols + y - 1] + self.trees.rename(false, a, b)
+                    } else {
+                        fd[(t1.lld[a] - li) * cols + (t2.lld[b] - lj)] + self.dist.get(false, a, b)
+                    };
+                    fd[x * cols + y] = by_delete.min(by_insert).min(by_match);
+                }
+            }
+
+            let (mut x, mut y) = (rows - 1, cols - 1);
+            while x > 0 || y > 0 {
+                let current = fd[x * cols + y];
+                if x > 0
+                    && close(current, fd[(x - 1) * cols + y] + self.trees.delete(false, node1(x)))
+                {
+                    result.push((Some(node1(x)), None));
+                    x -= 1;
+                } else if y > 0
+                    && close(current, fd[x * cols + y - 1] + self.trees.insert(false, node2(y)))
+                {
+                    result.push((None, Some(node2(y))));
+                    y -= 1;
+                } else {
+                    let (a, b) = (node1(x), node2(y));
+                    if t1.lld[a] == li && t2.lld[b] == lj {
+                        result.push((Some(a), Some(b)));
+                        x -= 1;
+                        y -= 1;
+                    } else {
+                        stack.push((li + x - 1, lj + y - 1));
+                        x = t1.lld[a] - li;
+                        y = t2.lld[b] - lj;
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Single-path function for left and right paths.
+    /// One Zhang–Shasha forest pass per keyroot of B_w against the path of A_v.
+    fn spf_keyroots(&mut self, kind: PathKind, v: usize, w: usize, swapped: bool) {
+        let a = self.trees.a(swapped);
+        let b = self.trees.b(swapped);
+        let (a_post, a_post_to_id, a_leaf) = a.orientation(kind);
+        let (b_post, b_post_to_id, b_leaf) = b.orientation(kind);
+
+        let lv = a_leaf[v];
+        let rows = a_post[v] - lv + 2;
+
+        let mut keyroots: Vec<usize> =
+            (w..w + b.size[w]).filter(|&y| y == w || !b.is_path_child(y, kind)).collect();
+        keyroots.sort_by_key(|&k| b_post[k]);
+
+        let mut fd = Vec::new();
+        for k in keyroots {
+            let lk = b_leaf[k];
+            let cols = b_post[k] - lk + 2;
+            fd.clear();
+            fd.resize(rows * cols, 0.0);
+
+            for ii in 1..rows {
+                let x = a_post_to_id[lv + ii - 1];
+                fd[ii * cols] = fd[(ii - 1) * cols] + self.trees.delete(swapped, x);
+            }
+            for jj in 1..cols {
+                let y = b_post_to_id[lk + jj - 1];
+                fd[jj] = fd[jj - 1] + self.trees.insert(swapped, y);
+            }
+
+            for ii in 1..rows {
+                let x = a_post_to_id[lv + ii - 1];
+                let x_leaf = a_leaf[x];
+                let delete = self.trees.delete(swapped, x);
+                for jj in 1..cols {
+                    let y = b_post_to_id[lk + jj - 1];
+                    let y_leaf = b_leaf[y];
+                    let by_delete = fd[(ii - 1) * cols + jj] + delete;
+                    let by_insert = fd[ii * cols + jj - 1] + self.trees.insert(swapped, y);
+
+                    let value = if x_leaf == lv && y_leaf == lk {
+                        let by_rename =
+                            fd[(ii - 1) * cols + jj - 1] + self.trees.rename(swapped, x, y);
+                        let value = by_delete.min(by_insert).min(by_rename);
+                        self.dist.set(swapped, x, y, value);
+                        value
+                    } else {
+                        let before = fd[(x_leaf - lv) * cols + (y_leaf - lk)];
+                        by_delete.min(by_insert).min(before + self.dist.get(swapped, x, y))
+                    };
+                    fd[ii * cols + jj] = value;
+                }
+            }
+        }
+    }
+
+    /// Single-path function for inner paths.
+    ///
+    /// The relevant subforests of A_v form a chain (off-path roots are removed from the
+    /// left first, then from the right, then the path node itself). Each forest in the
+    /// chain is compared against every subforest S(a, b) of B_w, i.e. the nodes whose
+    /// local preorder is >= a and whose local reversed preorder is >= b.
+    fn spf_heavy(&mut self, v: usize, w: usize, swapped: bool) {
+        let a = self.trees.a(swapped);
+        let b = self.trees.b(swapped);
+
+        // Decomposition order of A_v; the chain adds nodes in reverse
+        let mut decomposition: Vec<(usize, Step)> = Vec::with_capacity(a.size[v]);
+        let mut node = Some(v);
+        while let Some(current) = node {
+            decomposition.push((current, Step::Tree));
+            let next = a.path_child(current, PathKind::Heavy);
+            let children = a.children(current);
+            let split =
+                next.and_then(|n| children.iter().position(|&c| c == n)).unwrap_or(children.len());
+            for &child in &children[..split] {
+                decomposition.extend((child..child + a.size[child]).map(|id| (id, Step::Left)));
+            }
+            for &child in children.iter().skip(split + 1).rev() {
+                let start = a.pre_r[child];
+                decomposition.extend(
+                    a.pre_r_to_id[start..start + a.size[child]].iter().map(|&id| (id, Step::Right)),
+                );
+            }
+            node = next;
+        }
+        decomposition.reverse();
+        let chain = decomposition;
+
+        // Rows that are needed again once a whole off-path subtree has been added
+        let mut pending: HashMap<usize, usize> = HashMap::new();
+        for (i, &(x, step)) in chain.iter().enumerate() {
+            if step != Step::Tree {
+                *pending.entry(i + 1 - a.size[x]).or_insert(0) += 1;
+            }
+        }
+
+        let n = b.size[w];
+        let dim = n + 1;
+        let base_r = b.pre_r[w];
+        let at = |a_local: usize, b_local: usize| a_local * dim + b_local;
+
+        // Row 0 compares the empty forest: insertion sums
+        let mut empty_row = vec![0.0; dim * dim];
+        for al in (0..n).rev() {
+            for bl in (0..n).rev() {
+                let u = w + al;
+                let u_r = b.pre_r[u] - base_r;
+                let u2 = b.pre_r_to_id[base_r + bl];
+                empty_row[at(al, bl)] = if u_r < bl {
+                    empty_row[at(al + 1, bl)]
+                } else if u2 - w < al {
+                    empty_row[at(al, bl + 1)]
+                } else {
+                    empty_row[at(al + 1, bl)] + self.trees.insert(swapped, u)
+                };
+            }
+        }
+
+        let mut saved: HashMap<usize, Vec<f64>> = HashMap::new();
+        let mut previous = empty_row.clone();
+        let mut current = vec![0.0; dim * dim];
+
+        for (index, &(x, step)) in chain.iter().enumerate() {
+            let row = index + 1;
+            let delete = self.trees.delete(swapped, x);
+            let empty = previous[at(n, n)] + delete;
+            let before_subtree = if step == Step::Tree { 0 } else { row - a.size[x] };
+            let subtree_row: &[f64] = match step {
+                Step::Tree => &[],
+                _ if before_subtree == 0 => &empty_row,
+                _ => &saved[&before_subtree],
+            };
+
+            for al in (0..=n).rev() {
+                for bl in (0..=n).rev() {
+                    if al == n || bl == n {
+                        current[at(al, bl)] = empty;
+                        continue;
+                    }
+                    let u = w + al;
+                    let u_r = b.pre_r[u] - base_r;
+                    if u_r < bl {
+                        current[at(al, bl)] = current[at(al + 1, bl)];
+                        continue;
+                    }
+                    let u2 = b.pre_r_to_id[base_r + bl];
+                    if u2 - w < al {
+                        current[at(al, bl)] = current[at(al, bl + 1)];
+                        continue;
+                    }
+
+                    let by_delete = previous[at(al, bl)] + delete;
+                    let value = match step {
+                        Step::Tree if u == u2 => {
+                            let value = by_delete
+                                .min(current[at(al + 1, bl)] + self.trees.insert(swapped, u))
+                                .min(previous[at(al + 1, bl)] + self.trees.rename(swapped, x, u));
+                            self.dist.set(swapped, x, u, value);
+                            value
+                        }
+                        Step::Tree => by_delete
+                            .min(current[at(al + 1, bl)] + self.trees.insert(swapped, u))
+                            .min(current[at(al, u_r)] + empty_row[at(al + b.size[u], bl)]),
+                        Step::Left => by_delete
+                            .min(current[at(al + 1, bl)] + self.trees.insert(swapped, u))
+                            .min(
+                                self.dist.get(swapped, x, u) + subtree_row[at(al + b.size[u], bl)],
+                            ),
+                        Step::Right => by_delete
+                            .min(current[at(al, bl + 1)] + self.trees.insert(swapped, u2))
+                            .min(
+                                self.dist.get(swapped, x, u2)
+                                    + subtree_row[at(al, bl + b.size[u2])],
+                            ),
+                    };
+                    current[at(al, bl)] = value;
+                }
+            }
+
+            if step != Step::Tree && before_subtree != 0 {
+                if let Some(count) = pending.get_mut(&before_subtree) {
+                    *count -= 1;
+                    if *count == 0 {
+                        pending.remove(&before_subtree);
+                        saved.remove(&before_subtree);
+                    }
+                }
+            }
+            if pending.contains_key(&row) {
+                saved.insert(row, current.clone());
+            }
+            std::mem::swap(&mut previous, &mut current);
+        }
+    }
+}
+
+/// Choose, for every pair of subtrees, the path that minimises the number of
+/// subproblems evaluated by the single-path functions (APTED's optimal strategy)
+fn compute_strategy(t1: &IndexedTree, t2: &IndexedTree) -> Vec<Path> {
+    let (n1, n2) = (t1.len(), t2.len());
+    let mut strategy = vec![Path { in_second: false, kind: PathKind::Left }; n1 * n2];
+    let mut cost = vec![0u64; n1 * n2];
+    // Sum of costs of subtrees hanging off each path kind, per tree
+    let mut hanging1 = [vec![0u64; n1 * n2], vec![0u64; n1 * n2], vec![0u64; n1 * n2]];
+    let mut hanging2 = [vec![0u64; n1 * n2], vec![0u64; n1 * n2], vec![0u64; n1 * n2]];
+    let kinds = [PathKind::Left, PathKind::Right, PathKind::Heavy];
+
+    for &v in t1.post_l_to_id {
+        for &w in t2.post_l_to_id {
+            let i = v * n2 + w;
+
+            if !t1.children(v).is_empty() {
+                let all: u64 = t1.children(v).iter().map(|&c| cost[c * n2 + w]).sum();
+                for (k, kind) in kinds.iter().enumerate() {
+                    let c = t1.path_child(v, *kind).unwrap_or(v);
+                    hanging1[k][i] = hanging1[k][c * n2 + w] + all - cost[c * n2 + w];
+                }
+            }
+            if !t2.children(w).is_empty() {
+                let all: u64 = t2.children(w).iter().map(|&c| cost[v * n2 + c]).sum();
+                for (k, kind) in kinds.iter().enumerate() {
+                    let c = t2.path_child(w, *kind).unwrap_or(w);
+                    hanging2[k][i] = hanging2[k][v * n2 + c] + all - cost[v * n2 + c];
+                }
+            }
+
+            let size1 = t1.size[v] as u64;
+            let size2 = t2.size[w] as u64;
+            let candidates = [
+                (size1 * t2.kr_sum[w] + hanging1[0][i], false, PathKind::Left),
+                (size1 * t2.rev_kr_sum[w] + hanging1[1][i], false, PathKind::Right),
+                (size1 * (size2 + 1) * (size2 + 1) + hanging1[2][i], false, PathKind::Heavy),
+                (size2 * t1.kr_sum[v] + hanging2[0][i], true, PathKind::Left),
+                (size2 * t1.rev_kr_sum[v] + hanging2[1][i], true, PathKind::Right),
+                (size2 * (size1 + 1) * (size1 + 1) + hanging2[2][i], true, PathKind::Heavy),
+            ];
+            let (best, in_second, kind) = candidates
+                .into_iter()
+                .min_by_key(|(c, _, _)| *c)
+                .unwrap_or((0, false, PathKind::Left));
+            cost[i] = best;
+            strategy[i] = Path { in_second, kind };
+        }
+    }
+
+    strategy
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(label: &str, children: Vec<Rc<TreeNode>>) -> Rc<TreeNode> {
+        let mut n = TreeNode::new(label.to_string(), String::new(), 0);
+        for child in children {
+            n.add_child(child);
+        }
+        Rc::new(n)
+    }
+
+    fn unit() -> APTEDOptions {
+        APTEDOptions { rename_cost: 1.0, compare_values: false, ..Default::default() }
+    }
+
+    #[test]
+    fn test_identical_trees() {
+        let t = node("a", vec![node("b", vec![]), node("c", vec![node("d", vec![])])]);
+        assert_eq!(compute_apted_distance(&t, &t, &unit()), 0.0);
+    }
+
+    #[test]
+    fn test_zhang_shasha_example() {
+        // f(d(a c(b)) e) vs f(c(d(a b)) e): distance 2
+        let t1 = node(
+            "f",
+            vec![
+                node("d", vec![node("a", vec![]), node("c", vec![node("b", vec![])])]),
+                node("e", vec![]),
+            ],
+        );
+        let t2 = node(
+            "f",
+            vec![
+                node("c", vec![node("d", vec![node("a", vec![]), node("b", vec![])])]),
+                node("e", vec![]),
+            ],
+        );
+        assert_eq!(compute_apted_distance(&t1, &t2, &unit()), 2.0);
+    }
+
+    #[test]
+    fn test_subtree_moved_across_levels() {
+        // Moving a leaf one level up costs one deletion and one insertion
+        let t1 = node("a", vec![node("b", vec![node("x", vec![])]), node("c", vec![])]);
+        let t2 = node("a", vec![node("b", vec![]), node("x", vec![]), node("c", vec![])]);
+        assert_eq!(compute_apted_distance(&t1, &t2, &unit()), 2.0);
+    }
+
+    #[test]
+    fn test_asymmetric_costs() {
+        let t1 = node("a", vec![node("b", vec![]), node("c", vec![])]);
+        let t2 = node("a", vec![]);
+        let options = APTEDOptions { delete_cost: 2.0, insert_cost: 0.5, ..unit() };
+        assert_eq!(compute_apted_distance(&t1, &t2, &options), 4.0);
+        assert_eq!(compute_apted_distance(&t2, &t1, &options), 1.0);
+    }
+
+    #[test]
+    fn test_strategy_prefers_cheap_paths_for_chains() {
+        let leaf = node("x", vec![]);
+        let chain = node("a", vec![node("b", vec![node("c", vec![leaf])])]);
+        let arena = ArenaTree::from_node(&chain);
+        let t = IndexedTree::new(&arena);
+        assert_eq!(t.kr_sum[0], 4);
+        assert_eq!(t.rev_kr_sum[0], 4);
+        assert_eq!(t.heavy[0], Some(1));
+    }
+}
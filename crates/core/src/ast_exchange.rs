@@ -1,4 +1,4 @@
-use crate::tree::TreeNode;
+use crate::tree::{ArenaTree, TreeNode};
 use serde::{Deserialize, Serialize};
 use std::rc::Rc;
 
@@ -32,6 +32,33 @@ impl From<SerializableTreeNode> for TreeNode {
     }
 }
 
+impl TryFrom<&ArenaTree> for SerializableTreeNode {
+    type Error = String;
+
+    /// Convert an arena; an empty arena has no root and is rejected. Children are built
+    /// before their parents.
+    fn try_from(tree: &ArenaTree) -> Result<Self, Self::Error> {
+        let mut built: Vec<Option<SerializableTreeNode>> = vec![None; tree.len()];
+        for id in (0..tree.len()).rev() {
+            let children =
+                tree.children(id).iter().filter_map(|&child| built[child].take()).collect();
+            built[id] = Some(SerializableTreeNode {
+                label: tree.label(id).to_string(),
+                value: tree.value(id).to_string(),
+                children,
+                id,
+            });
+        }
+        built.into_iter().next().flatten().ok_or_else(|| "arena tree has no root".to_string())
+    }
+}
+
+impl From<SerializableTreeNode> for ArenaTree {
+    fn from(node: SerializableTreeNode) -> Self {
+        ArenaTree::from_node(&node.into())
+    }
+}
+
 /// Function definition for external exchange
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExchangeFunctionDef {
@@ -70,4 +97,16 @@ mod tests {
         assert_eq!(root.label, restored.label);
         assert_eq!(root.children.len(), restored.children.len());
     }
+
+    #[test]
+    fn test_arena_tree_conversion() {
+        let mut root = TreeNode::new("function".to_string(), "foo".to_string(), 0);
+        root.add_child(Rc::new(TreeNode::new("body".to_string(), "".to_string(), 1)));
+        let arena = ArenaTree::from_node(&root);
+
+        let serializable = SerializableTreeNode::try_from(&arena).unwrap();
+        assert_eq!(serializable.label, "function");
+        assert_eq!(serializable.children[0].label, "body");
+        assert!(SerializableTreeNode::try_from(&ArenaTree::default()).is_err());
+    }
 }
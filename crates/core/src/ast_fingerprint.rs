@@ -1,3 +1,4 @@
+use crate::tree::AsArenaTree;
 use oxc_allocator::Allocator;
 use oxc_ast::ast::{
     BinaryExpression, BlockStatement, CallExpression, ClassElement, Expression, FormalParameter,
@@ -5,7 +6,12 @@ use oxc_ast::ast::{
 };
 use oxc_parser::Parser;
 use oxc_span::SourceType;
-use std::collections::HashMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+
+/// Minimum fingerprint similarity for a pair to be compared in fast mode, for every language
+pub const FAST_FINGERPRINT_THRESHOLD: f64 = 0.3;
 
 /// AST-based fingerprint for function similarity pre-filtering
 #[derive(Debug, Clone, Default)]
@@ -36,6 +42,51 @@ impl AstFingerprint {
         Ok(fingerprint)
     }
 
+    /// Create fingerprint from a tree built by one of the tree-sitter parsers, whose labels
+    /// are node kinds and whose leaves carry their text. Kinds missing from `vocabulary` are
+    /// not counted.
+    pub fn from_tree(tree: &impl AsArenaTree, vocabulary: &FingerprintVocabulary) -> Self {
+        let tree = tree.as_arena();
+        let mut fingerprint = Self::new();
+        for id in 0..tree.len() {
+            let node = tree.node(id);
+            if let Some(category) = vocabulary.category(node.label, node.value) {
+                fingerprint.count_node(category);
+            }
+        }
+        fingerprint
+    }
+
+    /// Create fingerprint directly from a tree-sitter node and its source
+    pub fn from_tree_sitter(
+        node: tree_sitter::Node,
+        source: &str,
+        vocabulary: &FingerprintVocabulary,
+    ) -> Self {
+        let mut fingerprint = Self::new();
+        let mut cursor = node.walk();
+        loop {
+            let node = cursor.node();
+            let text = if vocabulary.is_qualified(node.kind()) {
+                node.utf8_text(source.as_bytes()).unwrap_or("")
+            } else {
+                ""
+            };
+            if let Some(category) = vocabulary.category(node.kind(), text) {
+                fingerprint.count_node(category);
+            }
+
+            if cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() || cursor.node() == node {
+                    return fingerprint;
+                }
+            }
+        }
+    }
+
     /// Visit program and count node types
     fn visit_program(&mut self, program: &Program) {
         self.count_node("Program");
@@ -327,7 +378,11 @@ impl AstFingerprint {
 
     /// Count a node type and update bloom filter
     fn count_node(&mut self, node_type: &'static str) {
-        *self.node_counts.entry(node_type).or_insert(0) += 1;
+        self.add_count(node_type, 1);
+    }
+
+    fn add_count(&mut self, node_type: &'static str, count: u32) {
+        *self.node_counts.entry(node_type).or_insert(0) += count;
 
         // Update bloom filter with multiple hash functions
         let hash1 = simple_hash(node_type);
@@ -361,6 +416,12 @@ impl AstFingerprint {
         false
     }
 
+    /// Whether a pair passes the fast mode pre-filter: the bloom filters overlap and the
+    /// detailed similarity reaches `threshold`
+    pub fn is_candidate(&self, other: &Self, threshold: f64) -> bool {
+        self.might_be_similar(other, threshold) && self.similarity(other) >= threshold
+    }
+
     /// Get bloom filter bits for SIMD comparison
     pub fn bloom_bits(&self) -> u128 {
         self.bloom_bits
@@ -409,6 +470,284 @@ impl AstFingerprint {
     }
 }
 
+/// Fingerprints are stored as their node counts; the bloom filter is rebuilt from them
+impl Serialize for AstFingerprint {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let counts: BTreeMap<&str, u32> =
+            self.node_counts.iter().map(|(node_type, count)| (*node_type, *count)).collect();
+        counts.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AstFingerprint {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let counts = BTreeMap::<String, u32>::deserialize(deserializer)?;
+        let mut fingerprint = Self::new();
+        for (node_type, count) in counts {
+            fingerprint.add_count(intern_node_type(node_type), count);
+        }
+        Ok(fingerprint)
+    }
+}
+
+/// Node type names are `&'static str`; names read back from disk are leaked once per name
+fn intern_node_type(node_type: String) -> &'static str {
+    static NODE_TYPES: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut node_types = NODE_TYPES.get_or_init(Default::default).lock().unwrap();
+    if let Some(interned) = node_types.get(node_type.as_str()) {
+        return interned;
+    }
+    let interned: &'static str = Box::leak(node_type.into_boxed_str());
+    node_types.insert(interned);
+    interned
+}
+
+/// Maps the node kinds of a tree-sitter grammar onto the node types counted by
+/// [`AstFingerprint::from_source`], so fingerprints of different languages share weights and
+/// thresholds.
+///
+/// A kind can also be qualified by the node text as `kind:text`, e.g. `identifier:if` for
+/// languages like Elixir where control flow is a call to an identifier.
+#[derive(Debug, Clone, Default)]
+pub struct FingerprintVocabulary {
+    kinds: HashMap<String, &'static str>,
+    qualified: HashMap<String, HashMap<String, &'static str>>,
+}
+
+/// Node kinds shared by most tree-sitter grammars. Keyword tokens such as `if` are left out,
+/// they would count the statement they belong to twice.
+const TREE_SITTER_KINDS: &[(&str, &str)] = &[
+    // Control flow
+    ("if_statement", "IfStatement"),
+    ("if_expression", "IfStatement"),
+    ("if_let_expression", "IfStatement"),
+    ("elif_clause", "IfStatement"),
+    ("for_statement", "ForStatement"),
+    ("for_expression", "ForStatement"),
+    ("for_in_statement", "ForStatement"),
+    ("for_range_loop", "ForStatement"),
+    ("enhanced_for_statement", "ForStatement"),
+    ("foreach_statement", "ForStatement"),
+    ("while_statement", "WhileStatement"),
+    ("while_expression", "WhileStatement"),
+    ("while_let_expression", "WhileStatement"),
+    ("loop_expression", "WhileStatement"),
+    ("do_statement", "DoWhileStatement"),
+    ("switch_statement", "SwitchStatement"),
+    ("switch_expression", "SwitchStatement"),
+    ("expression_switch_statement", "SwitchStatement"),
+    ("type_switch_statement", "SwitchStatement"),
+    ("match_statement", "SwitchStatement"),
+    ("match_expression", "SwitchStatement"),
+    ("conditional_expression", "ConditionalExpression"),
+    ("ternary_expression", "ConditionalExpression"),
+    ("return_statement", "ReturnStatement"),
+    ("return_expression", "ReturnStatement"),
+    ("block", "BlockStatement"),
+    ("compound_statement", "BlockStatement"),
+    ("statement_block", "BlockStatement"),
+    ("expression_statement", "ExpressionStatement"),
+    // Error handling
+    ("try_statement", "TryStatement"),
+    ("try_expression", "TryStatement"),
+    ("raise_statement", "ThrowStatement"),
+    ("throw_statement", "ThrowStatement"),
+    ("throw_expression", "ThrowStatement"),
+    // Functions and classes
+    ("function_definition", "FunctionDeclaration"),
+    ("function_declaration", "FunctionDeclaration"),
+    ("function_item", "FunctionDeclaration"),
+    ("method_declaration", "MethodDefinition"),
+    ("method_definition", "MethodDefinition"),
+    ("lambda", "ArrowFunctionExpression"),
+    ("lambda_expression", "ArrowFunctionExpression"),
+    ("closure_expression", "ArrowFunctionExpression"),
+    ("func_literal", "ArrowFunctionExpression"),
+    ("arrow_function", "ArrowFunctionExpression"),
+    ("class_definition", "ClassDeclaration"),
+    ("class_declaration", "ClassDeclaration"),
+    ("struct_item", "ClassDeclaration"),
+    ("impl_item", "ClassDeclaration"),
+    ("parameter", "Parameter"),
+    ("self_parameter", "Parameter"),
+    ("typed_parameter", "Parameter"),
+    ("default_parameter", "Parameter"),
+    ("typed_default_parameter", "Parameter"),
+    ("formal_parameter", "Parameter"),
+    ("parameter_declaration", "Parameter"),
+    // Calls
+    ("call", "CallExpression"),
+    ("call_expression", "CallExpression"),
+    ("method_invocation", "CallExpression"),
+    ("invocation_expression", "CallExpression"),
+    ("macro_invocation", "CallExpression"),
+    ("new_expression", "NewExpression"),
+    ("object_creation_expression", "NewExpression"),
+    ("await_expression", "AwaitExpression"),
+    // Operators
+    ("+", "BinaryOp_Add"),
+    ("-", "BinaryOp_Sub"),
+    ("*", "BinaryOp_Mul"),
+    ("/", "BinaryOp_Div"),
+    ("==", "BinaryOp_Eq"),
+    ("!=", "BinaryOp_Neq"),
+    ("<", "BinaryOp_Lt"),
+    (">", "BinaryOp_Gt"),
+    ("&&", "LogicalExpression"),
+    ("||", "LogicalExpression"),
+    ("boolean_operator", "LogicalExpression"),
+    ("unary_expression", "UnaryExpression"),
+    ("unary_operator", "UnaryExpression"),
+    ("not_operator", "UnaryExpression"),
+    ("assignment", "AssignmentExpression"),
+    ("assignment_expression", "AssignmentExpression"),
+    ("assignment_statement", "AssignmentExpression"),
+    ("augmented_assignment", "AssignmentExpression"),
+    ("compound_assignment_expr", "AssignmentExpression"),
+    // Member access and collections
+    ("attribute", "MemberExpression"),
+    ("field_expression", "MemberExpression"),
+    ("field_access", "MemberExpression"),
+    ("member_expression", "MemberExpression"),
+    ("member_access_expression", "MemberExpression"),
+    ("selector_expression", "MemberExpression"),
+    ("subscript", "MemberExpression"),
+    ("index_expression", "MemberExpression"),
+    ("element_access_expression", "MemberExpression"),
+    ("list", "ArrayExpression"),
+    ("array", "ArrayExpression"),
+    ("array_expression", "ArrayExpression"),
+    ("array_creation_expression", "ArrayExpression"),
+    ("dictionary", "ObjectExpression"),
+    ("struct_expression", "ObjectExpression"),
+    ("object", "ObjectExpression"),
+    // Declarations
+    ("let_declaration", "VariableDeclaration"),
+    ("lexical_declaration", "VariableDeclaration"),
+    ("variable_declaration", "VariableDeclaration"),
+    ("var_declaration", "VariableDeclaration"),
+    ("short_var_declaration", "VariableDeclaration"),
+    ("local_variable_declaration", "VariableDeclaration"),
+    ("local_declaration_statement", "VariableDeclaration"),
+    // Literals
+    ("identifier", "Identifier"),
+    ("string", "StringLiteral"),
+    ("string_literal", "StringLiteral"),
+    ("interpreted_string_literal", "StringLiteral"),
+    ("raw_string_literal", "StringLiteral"),
+    ("integer", "NumericLiteral"),
+    ("float", "NumericLiteral"),
+    ("integer_literal", "NumericLiteral"),
+    ("float_literal", "NumericLiteral"),
+    ("int_literal", "NumericLiteral"),
+    ("number_literal", "NumericLiteral"),
+    ("decimal_integer_literal", "NumericLiteral"),
+    ("true", "BooleanLiteral"),
+    ("false", "BooleanLiteral"),
+    ("none", "NullLiteral"),
+    ("nil", "NullLiteral"),
+    ("null", "NullLiteral"),
+    ("null_literal", "NullLiteral"),
+    ("self", "ThisExpression"),
+    ("this", "ThisExpression"),
+];
+
+/// Elixir expresses control flow and definitions as calls, recognized by their identifier
+const ELIXIR_KINDS: &[(&str, &str)] = &[
+    ("identifier:if", "IfStatement"),
+    ("identifier:unless", "IfStatement"),
+    ("identifier:cond", "IfStatement"),
+    ("identifier:case", "SwitchStatement"),
+    ("identifier:with", "SwitchStatement"),
+    ("identifier:for", "ForStatement"),
+    ("identifier:try", "TryStatement"),
+    ("identifier:raise", "ThrowStatement"),
+    ("anonymous_function", "ArrowFunctionExpression"),
+    ("dot", "MemberExpression"),
+    ("map", "ObjectExpression"),
+    ("tuple", "ArrayExpression"),
+    ("boolean", "BooleanLiteral"),
+    ("atom", "StringLiteral"),
+    ("and", "LogicalExpression"),
+    ("or", "LogicalExpression"),
+    ("=", "AssignmentExpression"),
+];
+
+/// Ruby names statements after their keyword, so each of these counts twice, consistently
+const RUBY_KINDS: &[(&str, &str)] = &[
+    ("if", "IfStatement"),
+    ("unless", "IfStatement"),
+    ("while", "WhileStatement"),
+    ("until", "WhileStatement"),
+    ("for", "ForStatement"),
+    ("case", "SwitchStatement"),
+    ("return", "ReturnStatement"),
+    ("method", "MethodDefinition"),
+    ("hash", "ObjectExpression"),
+];
+
+impl FingerprintVocabulary {
+    /// Empty vocabulary, see [`FingerprintVocabulary::with_kinds`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Node kinds shared by the common tree-sitter grammars (Python, Rust, Go, Java, C, ...)
+    pub fn tree_sitter() -> Self {
+        Self::new().with_kinds(TREE_SITTER_KINDS.iter().copied())
+    }
+
+    /// The shared kinds plus Elixir's call based control flow
+    pub fn elixir() -> Self {
+        Self::tree_sitter().with_kinds(ELIXIR_KINDS.iter().copied())
+    }
+
+    /// Vocabulary for a language name as used in the CLI configs
+    pub fn for_language(language: &str) -> Self {
+        match language {
+            "elixir" | "ex" | "exs" => Self::elixir(),
+            "ruby" | "rb" => Self::tree_sitter().with_kinds(RUBY_KINDS.iter().copied()),
+            _ => Self::tree_sitter(),
+        }
+    }
+
+    /// Add or replace `(kind, node type)` entries; `kind` may be qualified as `kind:text`
+    pub fn with_kinds<K: AsRef<str>, T: AsRef<str>>(
+        mut self,
+        kinds: impl IntoIterator<Item = (K, T)>,
+    ) -> Self {
+        for (kind, node_type) in kinds {
+            let node_type = intern_node_type(node_type.as_ref().to_string());
+            match kind.as_ref().split_once(':') {
+                Some((kind, text)) if !kind.is_empty() && !text.is_empty() => {
+                    self.qualified
+                        .entry(kind.to_string())
+                        .or_default()
+                        .insert(text.to_string(), node_type);
+                }
+                _ => {
+                    self.kinds.insert(kind.as_ref().to_string(), node_type);
+                }
+            }
+        }
+        self
+    }
+
+    /// Node type counted for a node of `kind` with `text`, if any
+    pub fn category(&self, kind: &str, text: &str) -> Option<&'static str> {
+        self.qualified
+            .get(kind)
+            .and_then(|texts| texts.get(text))
+            .or_else(|| self.kinds.get(kind))
+            .copied()
+    }
+
+    /// Whether entries of `kind` depend on the node text
+    fn is_qualified(&self, kind: &str) -> bool {
+        self.qualified.contains_key(kind)
+    }
+}
+
 /// Simple hash function 1
 fn simple_hash(s: &str) -> u64 {
     let mut hash = 0u64;
@@ -512,6 +851,16 @@ mod tests {
         assert!(fp.get_node_count("BinaryOp_Gt") > 0);
     }
 
+    #[test]
+    fn test_fingerprint_serde_round_trip() {
+        let fp = AstFingerprint::from_source("function f(a) { if (a) { return a + 1; } }").unwrap();
+        let json = serde_json::to_string(&fp).unwrap();
+        let restored: AstFingerprint = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.node_counts(), fp.node_counts());
+        assert_eq!(restored.bloom_bits(), fp.bloom_bits());
+    }
+
     #[test]
     fn test_bloom_filter_overlap() {
         let code1 = "function test1() { if (x) { return x; } }";
@@ -574,6 +923,68 @@ mod tests {
         assert!(fp1.get_node_count("CallExpression") >= 2); // push calls
     }
 
+    fn go_fingerprint(code: &str) -> AstFingerprint {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_go::LANGUAGE.into()).unwrap();
+        let tree = parser.parse(code, None).unwrap();
+        AstFingerprint::from_tree_sitter(
+            tree.root_node(),
+            code,
+            &FingerprintVocabulary::tree_sitter(),
+        )
+    }
+
+    #[test]
+    fn test_tree_sitter_fingerprint() {
+        let fp1 = go_fingerprint("func add(a, b int) int { if a > b { return a + b }; return b }");
+        let fp2 = go_fingerprint("func plus(x, y int) int { if x > y { return x + y }; return y }");
+        let fp3 =
+            go_fingerprint("func scale(xs []int) { for i := range xs { xs[i] = xs[i] * 2 } }");
+
+        assert_eq!(fp1.get_node_count("IfStatement"), 1);
+        assert_eq!(fp1.get_node_count("ReturnStatement"), 2);
+        assert_eq!(fp1.get_node_count("BinaryOp_Gt"), 1);
+        assert_eq!(fp1.similarity(&fp2), 1.0);
+        assert!(fp1.similarity(&fp3) < 0.5);
+        assert!(fp1.is_candidate(&fp2, FAST_FINGERPRINT_THRESHOLD));
+    }
+
+    #[test]
+    fn test_from_tree_matches_tree_sitter() {
+        use crate::generic_parser_config::GenericParserConfig;
+        use crate::generic_tree_sitter_parser::GenericTreeSitterParser;
+        use crate::language_parser::LanguageParser;
+
+        let code = "func f(a int) []int { for a > 0 { a = a - 1 }; return []int{a} }";
+        let tree = GenericTreeSitterParser::new(
+            tree_sitter_go::LANGUAGE.into(),
+            GenericParserConfig::go(),
+        )
+        .unwrap()
+        .parse(code, "test.go")
+        .unwrap();
+        let converted = AstFingerprint::from_tree(&tree, &FingerprintVocabulary::tree_sitter());
+        let direct = go_fingerprint(code);
+
+        assert_eq!(converted.node_counts(), direct.node_counts());
+        assert_eq!(direct.get_node_count("ForStatement"), 1);
+        assert_eq!(direct.get_node_count("AssignmentExpression"), 1);
+    }
+
+    #[test]
+    fn test_qualified_vocabulary_entries() {
+        let vocabulary = FingerprintVocabulary::new()
+            .with_kinds([("identifier:if", "IfStatement"), ("call", "CallExpression")]);
+
+        assert_eq!(vocabulary.category("identifier", "if"), Some("IfStatement"));
+        assert_eq!(vocabulary.category("identifier", "x"), None);
+        assert_eq!(vocabulary.category("call", ""), Some("CallExpression"));
+
+        let elixir = FingerprintVocabulary::elixir();
+        assert_eq!(elixir.category("identifier", "case"), Some("SwitchStatement"));
+        assert_eq!(elixir.category("identifier", "x"), Some("Identifier"));
+    }
+
     #[test]
     fn test_different_algorithms() {
         let bubble_sort = r#"
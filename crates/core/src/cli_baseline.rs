@@ -0,0 +1,242 @@
+//! Baseline files for grandfathering known duplicates.
+//!
+//! `--write-baseline` records every reported pair; `--baseline <file>` hides the pairs
+//! recorded there so only newly introduced duplicates are reported. Pairs are keyed by
+//! the names and a hash of the whitespace-normalized code of both sides, so edits that
+//! only move code around (or reformat it) keep matching the baseline.
+
+use crate::cli_output::{ReportEntry, ReportKind, ReportLocation};
+use crate::hashing::fnv1a;
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// File written by `--write-baseline` when no path is given
+pub const DEFAULT_BASELINE_FILE: &str = ".similarity-baseline.json";
+
+const BASELINE_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BaselineFile {
+    version: u32,
+    duplicates: Vec<BaselineEntry>,
+}
+
+/// A recorded pair. Only `fingerprint` is matched; the rest helps reviewing the file.
+#[derive(Debug, Serialize, Deserialize)]
+struct BaselineEntry {
+    fingerprint: String,
+    kind: ReportKind,
+    first: String,
+    second: String,
+}
+
+/// Known duplicate pairs loaded from a baseline file
+#[derive(Debug, Default)]
+pub struct Baseline {
+    fingerprints: HashSet<String>,
+    fingerprinter: Fingerprinter,
+    matched: Cell<usize>,
+}
+
+impl Baseline {
+    /// Load a baseline written by [`write_baseline`]
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read baseline {}: {}", path.display(), e))?;
+        let file: BaselineFile = serde_json::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("Invalid baseline {}: {}", path.display(), e))?;
+        if file.version != BASELINE_VERSION {
+            anyhow::bail!(
+                "Unsupported baseline version {} in {} (expected {})",
+                file.version,
+                path.display(),
+                BASELINE_VERSION
+            );
+        }
+        Ok(Self {
+            fingerprints: file.duplicates.into_iter().map(|entry| entry.fingerprint).collect(),
+            ..Default::default()
+        })
+    }
+
+    /// Whether the pair is recorded in the baseline
+    pub fn contains(&self, entry: &ReportEntry) -> bool {
+        let known = self.fingerprints.contains(&self.fingerprinter.pair(entry));
+        if known {
+            self.matched.set(self.matched.get() + 1);
+        }
+        known
+    }
+
+    /// Drop the pairs recorded in the baseline from `entries` and the results they were built
+    /// from (`results[i]` must correspond to `entries[i]`)
+    pub fn retain_new<T>(&self, results: &mut Vec<T>, entries: &mut Vec<ReportEntry>) {
+        let known: Vec<bool> = entries.iter().map(|entry| self.contains(entry)).collect();
+        let mut flags = known.iter();
+        results.retain(|_| !flags.next().copied().unwrap_or(false));
+        let mut flags = known.iter();
+        entries.retain(|_| !flags.next().copied().unwrap_or(false));
+    }
+
+    /// Number of reported pairs hidden so far because they are in the baseline
+    pub fn matched(&self) -> usize {
+        self.matched.get()
+    }
+}
+
+/// Load the baseline given with `--baseline`, unless `--write-baseline` is recording a new one
+pub fn load_baseline(
+    baseline: Option<&Path>,
+    write_baseline: Option<&Path>,
+) -> anyhow::Result<Option<Baseline>> {
+    match (baseline, write_baseline) {
+        (Some(path), None) => Baseline::load(path).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// Write the baseline when `--write-baseline` was given and summarize what the baseline did
+pub fn finish_baseline(
+    baseline: Option<&Baseline>,
+    write_baseline: Option<&Path>,
+    entries: &[ReportEntry],
+    text: bool,
+) -> anyhow::Result<()> {
+    if let Some(path) = write_baseline {
+        self::write_baseline(path, entries)?;
+        if text {
+            println!("\nWrote {} duplicate pairs to baseline {}", entries.len(), path.display());
+        }
+    }
+    if let Some(baseline) = baseline {
+        if text && baseline.matched() > 0 {
+            println!("\n{} known duplicate pairs hidden by the baseline", baseline.matched());
+        }
+    }
+    Ok(())
+}
+
+/// Record `entries` as the new baseline
+pub fn write_baseline(path: &Path, entries: &[ReportEntry]) -> anyhow::Result<()> {
+    let fingerprinter = Fingerprinter::default();
+    let mut duplicates: Vec<BaselineEntry> = entries
+        .iter()
+        .map(|entry| BaselineEntry {
+            fingerprint: fingerprinter.pair(entry),
+            kind: entry.kind,
+            first: format!("{}:{}", entry.first.file, entry.first.name),
+            second: format!("{}:{}", entry.second.file, entry.second.name),
+        })
+        .collect();
+    // Stable order keeps baseline diffs reviewable
+    duplicates.sort_by(|a, b| a.fingerprint.cmp(&b.fingerprint));
+    duplicates.dedup_by(|a, b| a.fingerprint == b.fingerprint);
+
+    let file = BaselineFile { version: BASELINE_VERSION, duplicates };
+    fs::write(path, serde_json::to_string_pretty(&file)? + "\n")
+        .map_err(|e| anyhow::anyhow!("Failed to write baseline {}: {}", path.display(), e))
+}
+
+/// Position independent fingerprints of reported locations
+#[derive(Debug, Default)]
+struct Fingerprinter {
+    sources: RefCell<HashMap<String, Option<String>>>,
+}
+
+impl Fingerprinter {
+    /// Order independent fingerprint of a pair
+    fn pair(&self, entry: &ReportEntry) -> String {
+        let mut sides = [self.location(&entry.first), self.location(&entry.second)];
+        sides.sort();
+        let kind = match entry.kind {
+            ReportKind::Function => "function",
+            ReportKind::Type => "type",
+            ReportKind::Overlap => "overlap",
+        };
+        format!("{kind}:{}|{}", sides[0], sides[1])
+    }
+
+    fn location(&self, location: &ReportLocation) -> String {
+        let mut sources = self.sources.borrow_mut();
+        let source = sources
+            .entry(location.file.clone())
+            .or_insert_with(|| fs::read_to_string(&location.file).ok());
+        let code = source.as_deref().map_or(String::new(), |source| {
+            source
+                .lines()
+                .skip(location.start_line.saturating_sub(1) as usize)
+                .take(location.line_count() as usize)
+                .flat_map(str::split_whitespace)
+                .collect::<Vec<_>>()
+                .join(" ")
+        });
+        format!("{}#{:016x}", location.name, fnv1a(code.as_bytes()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(file: &Path, first: (u32, u32), second: (u32, u32)) -> ReportEntry {
+        let file = file.to_string_lossy();
+        ReportEntry::new(
+            ReportKind::Function,
+            ReportLocation::new(file.clone(), "foo", first.0, first.1),
+            ReportLocation::new(file, "bar", second.0, second.1),
+            0.9,
+        )
+    }
+
+    #[test]
+    fn test_baseline_ignores_line_moves() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.py");
+        fs::write(&file, "def foo():\n    return 1\n\ndef bar():\n    return 1\n").unwrap();
+        let baseline_path = dir.path().join(DEFAULT_BASELINE_FILE);
+        write_baseline(&baseline_path, &[entry(&file, (1, 2), (4, 5))]).unwrap();
+
+        // Same functions shifted down and reindented
+        fs::write(&file, "import os\n\ndef foo():\n  return  1\n\ndef bar():\n    return 1\n")
+            .unwrap();
+        let baseline = Baseline::load(&baseline_path).unwrap();
+        assert!(baseline.contains(&entry(&file, (3, 4), (6, 7))));
+        // Sides swapped
+        let moved = entry(&file, (3, 4), (6, 7));
+        let swapped = ReportEntry::new(moved.kind, moved.second, moved.first, moved.similarity);
+        assert!(baseline.contains(&swapped));
+
+        // Changed body is a new pair
+        fs::write(&file, "def foo():\n    return 2\n\ndef bar():\n    return 1\n").unwrap();
+        let baseline = Baseline::load(&baseline_path).unwrap();
+        assert!(!baseline.contains(&entry(&file, (1, 2), (4, 5))));
+    }
+
+    #[test]
+    fn test_retain_new() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.py");
+        fs::write(&file, "a\nb\nc\nd\n").unwrap();
+        let baseline_path = dir.path().join("baseline.json");
+        write_baseline(&baseline_path, &[entry(&file, (1, 1), (2, 2))]).unwrap();
+
+        let baseline = Baseline::load(&baseline_path).unwrap();
+        let mut entries = vec![entry(&file, (1, 1), (2, 2)), entry(&file, (3, 3), (4, 4))];
+        let mut results = vec!["known", "new"];
+        baseline.retain_new(&mut results, &mut entries);
+        assert_eq!(results, vec!["new"]);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(baseline.matched(), 1);
+    }
+
+    #[test]
+    fn test_rejects_unknown_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.json");
+        fs::write(&path, r#"{"version": 99, "duplicates": []}"#).unwrap();
+        assert!(Baseline::load(&path).is_err());
+    }
+}
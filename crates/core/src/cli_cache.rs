@@ -0,0 +1,310 @@
+//! On-disk cache used by `--incremental`.
+//!
+//! Whatever a tool extracts from one source file (function definitions, parsed trees,
+//! fingerprints) is stored as JSON in `.similarity-cache/<tool>/<version>/`, keyed by a hash
+//! of the file extension and content. An unchanged file is never parsed again, even when it
+//! is moved or renamed, and a new tool version starts from an empty cache. Entries written by
+//! other versions are removed when the cache is opened.
+//!
+//! [`PairCache`] additionally keeps the results of the last full cross-file comparison, so a
+//! rerun only compares pairs where at least one side changed.
+
+use crate::hashing::fnv1a;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Directory created by `--incremental` when no path is given
+pub const DEFAULT_CACHE_DIR: &str = ".similarity-cache";
+
+/// Per-file analysis results of one tool version
+#[derive(Debug)]
+pub struct AnalysisCache {
+    dir: PathBuf,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+    writes: AtomicUsize,
+}
+
+impl AnalysisCache {
+    /// Open (creating it if needed) the cache of `tool` at `version` below `root`
+    pub fn open(root: &Path, tool: &str, version: &str) -> anyhow::Result<Self> {
+        let tool_dir = root.join(tool);
+        let dir = tool_dir.join(version);
+        fs::create_dir_all(&dir)
+            .map_err(|e| anyhow::anyhow!("Failed to create cache {}: {}", dir.display(), e))?;
+        // The cache is local state, keep it out of version control
+        let gitignore = root.join(".gitignore");
+        if !gitignore.exists() {
+            let _ = fs::write(gitignore, "*\n");
+        }
+
+        // Entries of other versions can never be hit again
+        if let Ok(entries) = fs::read_dir(&tool_dir) {
+            for entry in entries.flatten() {
+                if entry.file_name() != version {
+                    let _ = fs::remove_dir_all(entry.path());
+                }
+            }
+        }
+
+        Ok(Self {
+            dir,
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+            writes: AtomicUsize::new(0),
+        })
+    }
+
+    /// Cached value for the file at `path` with `content`, or the result of `compute`, which
+    /// is stored for the next run. Nothing is stored when `compute` fails.
+    pub fn get_or_insert_with<T, E>(
+        &self,
+        path: &Path,
+        content: &str,
+        compute: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        let entry = self.entry_path(path, content);
+        if let Some(value) =
+            fs::read(&entry).ok().and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(value);
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let value = compute()?;
+        if let Ok(bytes) = serde_json::to_vec(&value) {
+            // Write then rename so concurrent runs never read a partial entry
+            let id = self.writes.fetch_add(1, Ordering::Relaxed);
+            let temp = entry.with_extension(format!("{}-{id}.tmp", std::process::id()));
+            if fs::write(&temp, bytes).is_ok() && fs::rename(&temp, &entry).is_err() {
+                let _ = fs::remove_file(&temp);
+            }
+        }
+        Ok(value)
+    }
+
+    /// Comparison results of the previous run with the same `threshold` and `settings` (any
+    /// options that change similarity scores, compared by their `Debug` output)
+    pub fn pairs(&self, name: &str, threshold: f64, settings: &impl Debug) -> PairCache {
+        let key = fnv1a(format!("{threshold:?}|{settings:?}").as_bytes());
+        PairCache::load(self.dir.join(format!("pairs-{name}-{key:016x}.json")), threshold)
+    }
+
+    /// Files served from the cache so far
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Files that had to be analyzed so far
+    pub fn misses(&self) -> usize {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    fn entry_path(&self, path: &Path, content: &str) -> PathBuf {
+        // The extension selects the parser (e.g. .ts vs .tsx), so it is part of the key
+        let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+        let mut key = Vec::with_capacity(extension.len() + 1 + content.len());
+        key.extend_from_slice(extension.as_bytes());
+        key.push(0);
+        key.extend_from_slice(content.as_bytes());
+        self.dir.join(format!("{:016x}-{:x}.json", fnv1a(&key), content.len()))
+    }
+}
+
+/// Identity of a compared function: the content of its file and its own code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FunctionKey {
+    pub file: u64,
+    pub code: u64,
+}
+
+impl FunctionKey {
+    /// Key of a function whose code is `code`, in a file hashed with [`file_hash`]
+    pub fn new(file: u64, code: &str) -> Self {
+        Self { file, code: fnv1a(code.as_bytes()) }
+    }
+}
+
+/// Hash of a file content for [`FunctionKey`]
+pub fn file_hash(content: &str) -> u64 {
+    fnv1a(content.as_bytes())
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PairCacheFile {
+    /// Content hashes of the files compared by the run, with how often each occurred
+    files: Vec<(u64, u32)>,
+    /// Code hashes of the pairs reaching the threshold and their similarity
+    pairs: Vec<(u64, u64, f64)>,
+}
+
+/// Similarities of function pairs computed by the previous full run.
+///
+/// A full run compares every pair of functions in different files, but only the pairs
+/// reaching the threshold are stored. When both files of a pair were part of that run, the
+/// pair was compared then: it is either stored or known to be below the threshold, so it never
+/// has to be compared again. Pairs touching a new or changed file are computed as usual.
+#[derive(Debug)]
+pub struct PairCache {
+    path: PathBuf,
+    threshold: f64,
+    previous_files: HashMap<u64, u32>,
+    previous_pairs: HashMap<(u64, u64), f64>,
+    found: Mutex<HashMap<(u64, u64), f64>>,
+    reused: AtomicUsize,
+    compared: AtomicUsize,
+}
+
+impl PairCache {
+    fn load(path: PathBuf, threshold: f64) -> Self {
+        let file: PairCacheFile = fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            threshold,
+            previous_files: file.files.into_iter().collect(),
+            previous_pairs: file.pairs.into_iter().map(|(a, b, sim)| ((a, b), sim)).collect(),
+            found: Mutex::default(),
+            reused: AtomicUsize::new(0),
+            compared: AtomicUsize::new(0),
+        }
+    }
+
+    /// Similarity of a pair when it reaches the threshold, reusing the previous run when both
+    /// files are unchanged and calling `compare` otherwise
+    pub fn similar(
+        &self,
+        first: FunctionKey,
+        second: FunctionKey,
+        compare: impl FnOnce() -> f64,
+    ) -> Option<f64> {
+        let pair = (first.code.min(second.code), first.code.max(second.code));
+        let similarity = if self.was_compared(first.file, second.file) {
+            self.reused.fetch_add(1, Ordering::Relaxed);
+            self.previous_pairs.get(&pair).copied()
+        } else {
+            self.compared.fetch_add(1, Ordering::Relaxed);
+            Some(compare())
+        };
+
+        let similarity = similarity.filter(|&similarity| similarity >= self.threshold)?;
+        self.found.lock().unwrap().insert(pair, similarity);
+        Some(similarity)
+    }
+
+    fn was_compared(&self, file1: u64, file2: u64) -> bool {
+        let count = |file| self.previous_files.get(&file).copied().unwrap_or(0);
+        if file1 == file2 {
+            // Two copies of the same file must both have been there
+            count(file1) >= 2
+        } else {
+            count(file1) > 0 && count(file2) > 0
+        }
+    }
+
+    /// Pairs answered from the previous run so far
+    pub fn reused(&self) -> usize {
+        self.reused.load(Ordering::Relaxed)
+    }
+
+    /// Pairs that had to be compared so far
+    pub fn compared(&self) -> usize {
+        self.compared.load(Ordering::Relaxed)
+    }
+
+    /// Record this run for the next one. Only call this after every cross-file pair of
+    /// `files` (content hashes from [`file_hash`]) went through [`PairCache::similar`].
+    pub fn save(&self, files: impl IntoIterator<Item = u64>) -> anyhow::Result<()> {
+        let mut counts: HashMap<u64, u32> = HashMap::new();
+        for file in files {
+            *counts.entry(file).or_default() += 1;
+        }
+        let mut file = PairCacheFile {
+            files: counts.into_iter().collect(),
+            pairs: self.found.lock().unwrap().iter().map(|(&(a, b), &sim)| (a, b, sim)).collect(),
+        };
+        file.files.sort_unstable();
+        file.pairs.sort_by_key(|&(a, b, _)| (a, b));
+        fs::write(&self.path, serde_json::to_vec(&file)?)
+            .map_err(|e| anyhow::anyhow!("Failed to write cache {}: {}", self.path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_hits_unchanged_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = AnalysisCache::open(dir.path(), "similarity-ts", "1.0.0").unwrap();
+        let compute = |value: u32| move || Ok::<_, String>(vec![value]);
+
+        let path = Path::new("src/a.ts");
+        assert_eq!(cache.get_or_insert_with(path, "code", compute(1)), Ok(vec![1]));
+        // Same content elsewhere is served from the cache
+        assert_eq!(cache.get_or_insert_with(Path::new("b.ts"), "code", compute(2)), Ok(vec![1]));
+        // Another extension or content is a miss
+        assert_eq!(cache.get_or_insert_with(Path::new("a.tsx"), "code", compute(3)), Ok(vec![3]));
+        assert_eq!(cache.get_or_insert_with(path, "code2", compute(4)), Ok(vec![4]));
+        assert_eq!((cache.hits(), cache.misses()), (1, 3));
+
+        // Failures are not cached
+        let failed =
+            cache.get_or_insert_with(path, "broken", || Err::<Vec<u32>, _>("error".to_string()));
+        assert_eq!(failed, Err("error".to_string()));
+        assert_eq!(cache.get_or_insert_with(path, "broken", compute(5)), Ok(vec![5]));
+        assert!(dir.path().join(".gitignore").exists());
+    }
+
+    #[test]
+    fn test_cache_drops_other_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = AnalysisCache::open(dir.path(), "similarity-ts", "1.0.0").unwrap();
+        old.get_or_insert_with(Path::new("a.ts"), "code", || Ok::<_, String>(1)).unwrap();
+
+        let new = AnalysisCache::open(dir.path(), "similarity-ts", "1.1.0").unwrap();
+        assert!(!dir.path().join("similarity-ts/1.0.0").exists());
+        assert_eq!(new.get_or_insert_with(Path::new("a.ts"), "code", || Ok::<_, String>(2)), Ok(2));
+    }
+
+    #[test]
+    fn test_pair_cache_reuses_unchanged_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = AnalysisCache::open(dir.path(), "similarity-ts", "1.0.0").unwrap();
+        let (a, b, c) = (file_hash("a"), file_hash("b"), file_hash("c"));
+        let key = |file, code| FunctionKey::new(file, code);
+
+        let first = cache.pairs("cross-file", 0.8, &"options");
+        assert_eq!(first.similar(key(a, "f"), key(b, "g"), || 0.9), Some(0.9));
+        assert_eq!(first.similar(key(a, "f"), key(b, "h"), || 0.5), None);
+        assert_eq!(first.compared(), 2);
+        first.save([a, b]).unwrap();
+
+        let second = cache.pairs("cross-file", 0.8, &"options");
+        let unreachable = || panic!("unchanged pair compared again");
+        assert_eq!(second.similar(key(b, "g"), key(a, "f"), unreachable), Some(0.9));
+        assert_eq!(second.similar(key(a, "f"), key(b, "h"), unreachable), None);
+        // A new file is compared
+        assert_eq!(second.similar(key(a, "f"), key(c, "g"), || 0.95), Some(0.95));
+        // Copies of one file were never compared with each other
+        assert_eq!(second.similar(key(a, "f"), key(a, "f"), || 1.0), Some(1.0));
+        assert_eq!((second.reused(), second.compared()), (2, 2));
+
+        // Other options start over
+        let other = cache.pairs("cross-file", 0.7, &"options");
+        assert_eq!(other.similar(key(a, "f"), key(b, "h"), || 0.75), Some(0.75));
+    }
+}
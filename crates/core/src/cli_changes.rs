@@ -0,0 +1,252 @@
+//! Changed-line tracking for `--changed-since` / `--diff-file`.
+//!
+//! Changed lines are read from unified diff output, either produced by `git diff` or
+//! passed in as a file, so reviews can be scoped to the functions a change touches.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Lines added or modified per file, keyed by canonical path
+#[derive(Debug, Default, Clone)]
+pub struct ChangedLines {
+    files: HashMap<PathBuf, Vec<(u32, u32)>>,
+}
+
+impl ChangedLines {
+    /// Changes in the working tree (including untracked files) since `rev`
+    pub fn from_git(rev: &str) -> anyhow::Result<Self> {
+        let root = git(&["rev-parse", "--show-toplevel"])?;
+        let root = PathBuf::from(root.trim());
+        let diff = git(&[
+            "diff",
+            "--unified=0",
+            "--no-color",
+            "--no-ext-diff",
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+            // A revision starting with `-` must not be taken for an option
+            "--end-of-options",
+            rev,
+            "--",
+        ])?;
+        let mut changes = Self::parse_diff(&root, &diff);
+
+        // New files are not part of `git diff` until they are added
+        let untracked = git(&["ls-files", "--others", "--exclude-standard", "--full-name"])?;
+        for file in untracked.lines().filter(|line| !line.is_empty()) {
+            changes.add(&root.join(file), (1, u32::MAX));
+        }
+        Ok(changes)
+    }
+
+    /// Changes listed in a unified diff file; paths are resolved against the current directory
+    pub fn from_diff_file(path: &Path) -> anyhow::Result<Self> {
+        let diff = fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read diff {}: {}", path.display(), e))?;
+        let root = std::env::current_dir()?;
+        Ok(Self::parse_diff(&root, &diff))
+    }
+
+    /// Parse unified diff output whose paths are relative to `root`
+    pub fn parse_diff(root: &Path, diff: &str) -> Self {
+        let mut changes = ChangedLines::default();
+        let mut current: Option<PathBuf> = None;
+        // Old and new lines left in the current hunk body
+        let mut remaining: (u32, u32) = (0, 0);
+        let mut after_source_header = false;
+
+        for line in diff.lines() {
+            if remaining != (0, 0) {
+                // Hunk body: `+++ ` or `@@ ` at the start of a line is file content here
+                let (old, new) = remaining;
+                remaining = match line.chars().next() {
+                    Some('+') => (old, new.saturating_sub(1)),
+                    Some('-') => (old.saturating_sub(1), new),
+                    Some('\\') => (old, new),
+                    _ => (old.saturating_sub(1), new.saturating_sub(1)),
+                };
+                continue;
+            }
+
+            let source_header = std::mem::replace(&mut after_source_header, false);
+            if line.starts_with("--- ") {
+                after_source_header = true;
+            } else if let Some(target) = line.strip_prefix("+++ ").filter(|_| source_header) {
+                // Strip the timestamp some diff tools append after a tab
+                let target = target.split('\t').next().unwrap_or(target).trim();
+                current = (target != "/dev/null").then(|| {
+                    let stripped = target.strip_prefix("b/").unwrap_or(target);
+                    if root.join(stripped).exists() || !root.join(target).exists() {
+                        root.join(stripped)
+                    } else {
+                        root.join(target)
+                    }
+                });
+            } else if let Some(hunk) = line.strip_prefix("@@ ") {
+                remaining = hunk_line_counts(hunk).unwrap_or_default();
+                let (Some(file), Some(range)) = (&current, parse_hunk(hunk)) else {
+                    continue;
+                };
+                let file = file.clone();
+                changes.add(&file, range);
+            }
+        }
+        changes
+    }
+
+    fn add(&mut self, file: &Path, range: (u32, u32)) {
+        let file = file.canonicalize().unwrap_or_else(|_| file.to_path_buf());
+        self.files.entry(file).or_default().push(range);
+    }
+
+    /// Whether nothing changed
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Whether any line of `file` changed
+    pub fn is_file_changed(&self, file: &Path) -> bool {
+        self.ranges(file).is_some()
+    }
+
+    /// Whether a change touches lines `start_line..=end_line` of `file`
+    pub fn touches(&self, file: &Path, start_line: u32, end_line: u32) -> bool {
+        self.ranges(file).is_some_and(|ranges| {
+            ranges.iter().any(|&(start, end)| start <= end_line && start_line <= end)
+        })
+    }
+
+    fn ranges(&self, file: &Path) -> Option<&Vec<(u32, u32)>> {
+        if let Some(ranges) = self.files.get(file) {
+            return Some(ranges);
+        }
+        file.canonicalize().ok().and_then(|file| self.files.get(&file))
+    }
+}
+
+/// New-file line range of a hunk header such as `-10,2 +12,3 @@ fn context`
+fn parse_hunk(hunk: &str) -> Option<(u32, u32)> {
+    let new_range = hunk.split_whitespace().find_map(|part| part.strip_prefix('+'))?;
+    let (start, count) = match new_range.split_once(',') {
+        Some((start, count)) => (start.parse::<u32>().ok()?, count.parse::<u32>().ok()?),
+        None => (new_range.parse::<u32>().ok()?, 1),
+    };
+    if count == 0 {
+        // Pure deletion after line `start`: touch both neighbours
+        Some((start.max(1), start + 1))
+    } else {
+        Some((start, start + count - 1))
+    }
+}
+
+/// Old and new line counts of a hunk header such as `-10,2 +12,3 @@`
+fn hunk_line_counts(hunk: &str) -> Option<(u32, u32)> {
+    let count = |prefix: char| {
+        let range = hunk.split_whitespace().find_map(|part| part.strip_prefix(prefix))?;
+        match range.split_once(',') {
+            Some((_, count)) => count.parse::<u32>().ok(),
+            None => range.parse::<u32>().ok().map(|_| 1),
+        }
+    };
+    Some((count('-')?, count('+')?))
+}
+
+fn git(args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| anyhow::anyhow!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hunk() {
+        assert_eq!(parse_hunk("-1,2 +3,4 @@"), Some((3, 6)));
+        assert_eq!(parse_hunk("-5 +7 @@ fn foo()"), Some((7, 7)));
+        assert_eq!(parse_hunk("-5,3 +4,0 @@"), Some((4, 5)));
+        assert_eq!(parse_hunk("-1,0 +0,0 @@"), Some((1, 1)));
+        assert_eq!(parse_hunk("garbage"), None);
+        assert_eq!(hunk_line_counts("-1,2 +3,4 @@"), Some((2, 4)));
+        assert_eq!(hunk_line_counts("-5 +7,0 @@ fn foo()"), Some((1, 0)));
+    }
+
+    #[test]
+    fn test_parse_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/a.ts"), "").unwrap();
+        fs::write(root.join("src/b.ts"), "").unwrap();
+
+        let diff = "\
+diff --git a/src/a.ts b/src/a.ts
+--- a/src/a.ts
++++ b/src/a.ts
+@@ -10,0 +11,3 @@ function foo() {
++  x();
++  y();
++  z();
+@@ -40,2 +43 @@
+-old
+-old
++new
+diff --git a/src/gone.ts b/src/gone.ts
+--- a/src/gone.ts
++++ /dev/null
+@@ -1,3 +0,0 @@
+";
+        let changes = ChangedLines::parse_diff(&root, diff);
+        let a = root.join("src/a.ts");
+        assert!(changes.is_file_changed(&a));
+        assert!(!changes.is_file_changed(&root.join("src/b.ts")));
+        assert!(changes.touches(&a, 5, 11));
+        assert!(changes.touches(&a, 13, 20));
+        assert!(!changes.touches(&a, 14, 42));
+        assert!(changes.touches(&a, 43, 50));
+        assert!(!changes.is_empty());
+    }
+
+    #[test]
+    fn test_parse_diff_added_lines_look_like_headers() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::write(root.join("a.md"), "").unwrap();
+        fs::write(root.join("b.md"), "").unwrap();
+
+        let diff = "\
+--- a/a.md
++++ b/a.md
+@@ -1,0 +2,2 @@
++++ not a header
++@@ -1 +1 @@
+@@ -9 +11 @@
+-old
++new
+--- a/b.md
++++ b/b.md
+@@ -3 +3 @@
+-x
++y
+";
+        let changes = ChangedLines::parse_diff(&root, diff);
+        let a = root.join("a.md");
+        assert!(changes.touches(&a, 2, 3));
+        assert!(changes.touches(&a, 11, 11));
+        assert!(!changes.touches(&a, 1, 1));
+        assert!(changes.touches(&root.join("b.md"), 3, 3));
+        assert!(!changes.touches(&root.join("b.md"), 11, 11));
+    }
+}
@@ -0,0 +1,326 @@
+//! Clone classes: duplicate pairs grouped into families of similar code.
+//!
+//! A function copied six times is reported as 15 pairs; `--clone-classes` folds those pairs
+//! into one class listing every copy, a representative to keep and the lines that would be
+//! removed by deduplicating the class. Classes are built either transitively (single linkage,
+//! union-find over the reported pairs) or with complete linkage, where every member must have
+//! been reported as similar to every other member.
+
+use crate::cli_output::{ReportEntry, ReportKind, ReportLocation};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// How reported pairs are merged into clone classes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Linkage {
+    /// Transitive closure of the reported pairs: A~B and B~C put A, B and C in one class
+    #[default]
+    Single,
+    /// Every pair of members must have been reported as similar
+    Complete,
+}
+
+impl std::str::FromStr for Linkage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "single" | "transitive" => Ok(Linkage::Single),
+            "complete" => Ok(Linkage::Complete),
+            _ => Err(format!("unknown linkage '{s}' (expected single or complete)")),
+        }
+    }
+}
+
+/// A family of similar code fragments
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CloneClass {
+    pub kind: ReportKind,
+    /// Member with the highest total similarity to the rest of the class
+    pub representative: ReportLocation,
+    /// All members including the representative, ordered by file and line
+    pub members: Vec<ReportLocation>,
+    /// Lowest similarity among the reported pairs inside the class
+    pub similarity: f64,
+    /// Lines of every member except the representative
+    pub removable_lines: u32,
+}
+
+/// Group reported pairs into clone classes, largest `removable_lines` first.
+/// Pairs of different kinds never end up in the same class.
+pub fn group_clone_classes<'a>(entries: &'a [ReportEntry], linkage: Linkage) -> Vec<CloneClass> {
+    let mut locations: Vec<(ReportKind, &'a ReportLocation)> = Vec::new();
+    let mut index: HashMap<(ReportKind, &'a ReportLocation), usize> = HashMap::new();
+    let mut intern = |kind: ReportKind, loc: &'a ReportLocation| -> usize {
+        *index.entry((kind, loc)).or_insert_with(|| {
+            locations.push((kind, loc));
+            locations.len() - 1
+        })
+    };
+
+    let mut edges: Vec<(usize, usize, f64)> = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let a = intern(entry.kind, &entry.first);
+        let b = intern(entry.kind, &entry.second);
+        if a != b {
+            edges.push((a.min(b), a.max(b), entry.similarity));
+        }
+    }
+
+    // Keep the best similarity when the same pair was reported more than once
+    let mut similarity: HashMap<(usize, usize), f64> = HashMap::new();
+    for &(a, b, sim) in &edges {
+        let best = similarity.entry((a, b)).or_insert(sim);
+        *best = best.max(sim);
+    }
+
+    let clusters = match linkage {
+        Linkage::Single => single_linkage(locations.len(), &similarity),
+        Linkage::Complete => complete_linkage(locations.len(), &similarity),
+    };
+
+    let mut classes: Vec<CloneClass> = clusters
+        .into_iter()
+        .filter(|members| members.len() > 1)
+        .map(|members| build_class(&members, &locations, &similarity))
+        .collect();
+    classes.sort_by(|a, b| {
+        b.removable_lines
+            .cmp(&a.removable_lines)
+            .then_with(|| b.members.len().cmp(&a.members.len()))
+            .then_with(|| location_order(&a.representative, &b.representative))
+    });
+    classes
+}
+
+fn location_order(a: &ReportLocation, b: &ReportLocation) -> std::cmp::Ordering {
+    (&a.file, a.start_line, a.end_line, &a.name).cmp(&(&b.file, b.start_line, b.end_line, &b.name))
+}
+
+fn single_linkage(count: usize, similarity: &HashMap<(usize, usize), f64>) -> Vec<Vec<usize>> {
+    let mut sets = UnionFind::new(count);
+    for &(a, b) in similarity.keys() {
+        sets.union(a, b);
+    }
+    let mut clusters: HashMap<usize, Vec<usize>> = HashMap::new();
+    for node in 0..count {
+        clusters.entry(sets.find(node)).or_default().push(node);
+    }
+    clusters.into_values().collect()
+}
+
+/// Agglomerative clustering: pairs are visited from most to least similar and two clusters
+/// are merged only when every cross pair between them was reported.
+fn complete_linkage(count: usize, similarity: &HashMap<(usize, usize), f64>) -> Vec<Vec<usize>> {
+    let mut pairs: Vec<((usize, usize), f64)> = similarity.iter().map(|(&k, &v)| (k, v)).collect();
+    pairs.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let mut cluster_of: Vec<usize> = (0..count).collect();
+    let mut clusters: Vec<Vec<usize>> = (0..count).map(|node| vec![node]).collect();
+    let linked = |a: usize, b: usize| similarity.contains_key(&(a.min(b), a.max(b)));
+
+    for ((a, b), _) in pairs {
+        let (ca, cb) = (cluster_of[a], cluster_of[b]);
+        if ca == cb {
+            continue;
+        }
+        let complete = clusters[ca].iter().all(|&x| clusters[cb].iter().all(|&y| linked(x, y)));
+        if !complete {
+            continue;
+        }
+        let moved = std::mem::take(&mut clusters[cb]);
+        for &node in &moved {
+            cluster_of[node] = ca;
+        }
+        clusters[ca].extend(moved);
+    }
+    clusters.into_iter().filter(|members| !members.is_empty()).collect()
+}
+
+fn build_class(
+    members: &[usize],
+    locations: &[(ReportKind, &ReportLocation)],
+    similarity: &HashMap<(usize, usize), f64>,
+) -> CloneClass {
+    let pair = |a: usize, b: usize| similarity.get(&(a.min(b), a.max(b))).copied();
+
+    let mut lowest = f64::INFINITY;
+    let mut representative = members[0];
+    let mut best_score = f64::NEG_INFINITY;
+    for &member in members {
+        let sims: Vec<f64> = members.iter().filter_map(|&other| pair(member, other)).collect();
+        lowest = sims.iter().copied().fold(lowest, f64::min);
+        let score: f64 = sims.iter().sum();
+        let better = score > best_score
+            || (score == best_score
+                && location_order(locations[member].1, locations[representative].1).is_lt());
+        if better {
+            best_score = score;
+            representative = member;
+        }
+    }
+
+    let mut locs: Vec<ReportLocation> =
+        members.iter().map(|&member| locations[member].1.clone()).collect();
+    locs.sort_by(location_order);
+    let total: u32 = locs.iter().map(ReportLocation::line_count).sum();
+    let representative = locations[representative].1.clone();
+
+    CloneClass {
+        kind: locations[members[0]].0,
+        removable_lines: total - representative.line_count(),
+        representative,
+        members: locs,
+        similarity: lowest,
+    }
+}
+
+struct UnionFind {
+    parent: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(count: usize) -> Self {
+        Self { parent: (0..count).collect() }
+    }
+
+    fn find(&mut self, node: usize) -> usize {
+        let mut root = node;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut node = node;
+        while self.parent[node] != root {
+            node = std::mem::replace(&mut self.parent[node], root);
+        }
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (ra, rb) = (self.find(a), self.find(b));
+        if ra != rb {
+            self.parent[ra.max(rb)] = ra.min(rb);
+        }
+    }
+}
+
+fn kind_label(kind: ReportKind) -> &'static str {
+    match kind {
+        ReportKind::Function => "functions",
+        ReportKind::Type => "types",
+        ReportKind::Overlap => "code fragments",
+    }
+}
+
+/// Print clone classes as text
+pub fn print_clone_classes(classes: &[CloneClass]) {
+    if classes.is_empty() {
+        println!("\nNo clone classes found!");
+        return;
+    }
+
+    println!("\nClone classes:");
+    println!("{}", "-".repeat(60));
+    for (i, class) in classes.iter().enumerate() {
+        println!(
+            "\nClass {}: {} {}, similarity >= {:.2}%, {} removable lines",
+            i + 1,
+            class.members.len(),
+            kind_label(class.kind),
+            class.similarity * 100.0,
+            class.removable_lines
+        );
+        for member in &class.members {
+            let marker = if *member == class.representative { "*" } else { " " };
+            println!(
+                "  {marker} {}",
+                crate::cli_output::format_function_output(
+                    &member.file,
+                    &member.name,
+                    member.start_line,
+                    member.end_line
+                )
+            );
+        }
+    }
+
+    let removable: u32 = classes.iter().map(|class| class.removable_lines).sum();
+    println!(
+        "\nTotal clone classes: {} ({} removable lines, * marks the representative)",
+        classes.len(),
+        removable
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(a: (&str, u32), b: (&str, u32), similarity: f64) -> ReportEntry {
+        ReportEntry::new(
+            ReportKind::Function,
+            ReportLocation::new("a.ts", a.0, a.1, a.1 + 9),
+            ReportLocation::new("a.ts", b.0, b.1, b.1 + 9),
+            similarity,
+        )
+    }
+
+    #[test]
+    fn test_six_copies_form_one_class() {
+        let names = ["a", "b", "c", "d", "e", "f"];
+        let mut entries = Vec::new();
+        for i in 0..names.len() {
+            for j in (i + 1)..names.len() {
+                entries.push(entry(
+                    (names[i], i as u32 * 10 + 1),
+                    (names[j], j as u32 * 10 + 1),
+                    0.9,
+                ));
+            }
+        }
+        assert_eq!(entries.len(), 15);
+
+        for linkage in [Linkage::Single, Linkage::Complete] {
+            let classes = group_clone_classes(&entries, linkage);
+            assert_eq!(classes.len(), 1);
+            assert_eq!(classes[0].members.len(), 6);
+            assert_eq!(classes[0].removable_lines, 50);
+            assert_eq!(classes[0].representative.name, "a");
+        }
+    }
+
+    #[test]
+    fn test_linkage_chain() {
+        // a~b and b~c, but a and c were not reported as similar
+        let entries = vec![entry(("a", 1), ("b", 11), 0.9), entry(("b", 11), ("c", 21), 0.85)];
+
+        let single = group_clone_classes(&entries, Linkage::Single);
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].members.len(), 3);
+        assert_eq!(single[0].representative.name, "b");
+        assert!((single[0].similarity - 0.85).abs() < 1e-9);
+
+        let complete = group_clone_classes(&entries, Linkage::Complete);
+        assert_eq!(complete.len(), 1);
+        let names: Vec<&str> = complete[0].members.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["a", "b"]);
+    }
+
+    #[test]
+    fn test_kinds_are_not_mixed() {
+        let mut entries = vec![entry(("a", 1), ("b", 11), 0.9)];
+        let mut overlap = entry(("a", 1), ("b", 11), 0.9);
+        overlap.kind = ReportKind::Overlap;
+        entries.push(overlap);
+
+        let classes = group_clone_classes(&entries, Linkage::Single);
+        assert_eq!(classes.len(), 2);
+    }
+
+    #[test]
+    fn test_linkage_from_str() {
+        assert_eq!("single".parse::<Linkage>(), Ok(Linkage::Single));
+        assert_eq!("Complete".parse::<Linkage>(), Ok(Linkage::Complete));
+        assert!("average".parse::<Linkage>().is_err());
+    }
+}
@@ -0,0 +1,487 @@
+//! Project configuration shared by the CLIs.
+//!
+//! Settings are read from the first of `similarity.toml`, `.similarity.toml`,
+//! `pyproject.toml` (`[tool.similarity]`) or `package.json` (`"similarity"` key)
+//! found in the directory of each analyzed file or one of its parents:
+//!
+//! ```toml
+//! threshold = 0.85
+//! min-lines = 5
+//!
+//! [typescript]
+//! threshold = 0.9
+//!
+//! [[overrides]]
+//! path = "packages/legacy"
+//! threshold = 0.95
+//! ```
+//!
+//! Tables are per-language sections, `[[overrides]]` apply when the analyzed file
+//! lies inside `path` (relative to the config file). Each analyzed file is resolved on
+//! its own, so packages with a config of their own keep their settings when the whole
+//! repository is scanned. Flags given on the command line always win over the file.
+
+use anyhow::{anyhow, bail, Context};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+
+/// File names probed in each directory, in priority order
+const CONFIG_FILES: [&str; 4] =
+    ["similarity.toml", ".similarity.toml", "pyproject.toml", "package.json"];
+
+/// Names of the per-language sections
+const LANGUAGE_SECTIONS: [&str; 12] = [
+    "typescript",
+    "python",
+    "rust",
+    "elixir",
+    "go",
+    "java",
+    "c",
+    "cpp",
+    "csharp",
+    "ruby",
+    "php",
+    "generic",
+];
+
+/// Options that can be set from a project config file
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct SimilaritySettings {
+    pub threshold: Option<f64>,
+    pub min_lines: Option<u32>,
+    pub min_tokens: Option<u32>,
+    pub rename_cost: Option<f64>,
+    pub size_penalty: Option<bool>,
+    pub fast: Option<bool>,
+    pub lsh: Option<bool>,
+    /// At least 1, like `--lsh-bands`
+    pub lsh_bands: Option<NonZeroU32>,
+    /// At least 1, like `--lsh-rows`
+    pub lsh_rows: Option<NonZeroU32>,
+    pub skip_test: Option<bool>,
+    pub extensions: Option<Vec<String>>,
+    /// Exclude globs, added to the ones given with `--exclude`
+    pub exclude: Vec<String>,
+    pub overlap_min_window: Option<u32>,
+    pub overlap_max_window: Option<u32>,
+    pub overlap_size_tolerance: Option<f64>,
+    pub cross_language: Option<bool>,
+}
+
+impl SimilaritySettings {
+    /// Overlay `other` on top of these settings
+    pub fn merge(&mut self, other: &SimilaritySettings) {
+        fn pick<T: Clone>(target: &mut Option<T>, value: &Option<T>) {
+            if value.is_some() {
+                target.clone_from(value);
+            }
+        }
+        pick(&mut self.threshold, &other.threshold);
+        pick(&mut self.min_lines, &other.min_lines);
+        pick(&mut self.min_tokens, &other.min_tokens);
+        pick(&mut self.rename_cost, &other.rename_cost);
+        pick(&mut self.size_penalty, &other.size_penalty);
+        pick(&mut self.fast, &other.fast);
+        pick(&mut self.lsh, &other.lsh);
+        pick(&mut self.lsh_bands, &other.lsh_bands);
+        pick(&mut self.lsh_rows, &other.lsh_rows);
+        pick(&mut self.skip_test, &other.skip_test);
+        pick(&mut self.extensions, &other.extensions);
+        pick(&mut self.overlap_min_window, &other.overlap_min_window);
+        pick(&mut self.overlap_max_window, &other.overlap_max_window);
+        pick(&mut self.overlap_size_tolerance, &other.overlap_size_tolerance);
+        pick(&mut self.cross_language, &other.cross_language);
+        self.exclude.extend(other.exclude.iter().cloned());
+    }
+}
+
+/// Settings plus per-language sections at one level of the file
+#[derive(Debug, Clone, Default)]
+struct SettingsLayer {
+    settings: SimilaritySettings,
+    languages: Vec<(String, SimilaritySettings)>,
+}
+
+impl SettingsLayer {
+    fn from_table(table: &Map<String, Value>, context: &str) -> anyhow::Result<Self> {
+        let mut scalars = Map::new();
+        let mut languages = Vec::new();
+        for (key, value) in table {
+            match value {
+                Value::Object(_) if !LANGUAGE_SECTIONS.contains(&key.as_str()) => {
+                    bail!(
+                        "unknown language section [{key}] in [{context}], expected one of: {}",
+                        LANGUAGE_SECTIONS.join(", ")
+                    );
+                }
+                Value::Object(section) => {
+                    let settings = parse_settings(section, &format!("{context}.{key}"))?;
+                    languages.push((key.clone(), settings));
+                }
+                _ => {
+                    scalars.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        Ok(SettingsLayer { settings: parse_settings(&scalars, context)?, languages })
+    }
+
+    fn apply_to(&self, target: &mut SimilaritySettings, languages: &[&str]) {
+        target.merge(&self.settings);
+        for language in languages {
+            for (name, settings) in &self.languages {
+                if name == language {
+                    target.merge(settings);
+                }
+            }
+        }
+    }
+}
+
+fn parse_settings(table: &Map<String, Value>, context: &str) -> anyhow::Result<SimilaritySettings> {
+    SimilaritySettings::deserialize(Value::Object(table.clone()))
+        .map_err(|e| anyhow!("invalid settings in [{context}]: {e}"))
+}
+
+#[derive(Debug, Clone)]
+struct DirectoryOverride {
+    dir: PathBuf,
+    layer: SettingsLayer,
+}
+
+/// A parsed project config file
+#[derive(Debug, Clone)]
+pub struct ProjectConfig {
+    /// File the settings were read from
+    pub source: PathBuf,
+    base: SettingsLayer,
+    overrides: Vec<DirectoryOverride>,
+}
+
+impl ProjectConfig {
+    /// Load a config file; `pyproject.toml` and `package.json` are read from their
+    /// `tool.similarity` / `similarity` entries
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        Self::load_optional(path)?.ok_or_else(|| match file_name(path) {
+            "pyproject.toml" => anyhow!("{} has no [tool.similarity] table", path.display()),
+            _ => anyhow!("{} has no \"similarity\" entry", path.display()),
+        })
+    }
+
+    /// Find the nearest config file in `start` or its parent directories
+    pub fn discover(start: &Path) -> anyhow::Result<Option<Self>> {
+        let start = start.canonicalize().unwrap_or_else(|_| start.to_path_buf());
+        let first_dir = if start.is_file() { start.parent() } else { Some(start.as_path()) };
+
+        for dir in first_dir.into_iter().flat_map(Path::ancestors) {
+            if let Some(config) = Self::in_dir(dir)? {
+                return Ok(Some(config));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The config file of `dir` itself, without looking at its parents
+    fn in_dir(dir: &Path) -> anyhow::Result<Option<Self>> {
+        for name in CONFIG_FILES {
+            let candidate = dir.join(name);
+            if candidate.is_file() {
+                if let Some(config) = Self::load_optional(&candidate)? {
+                    return Ok(Some(config));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn load_optional(path: &Path) -> anyhow::Result<Option<Self>> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let root = match file_name(path) {
+            "package.json" => {
+                let mut json: Value = serde_json::from_str(&content)
+                    .with_context(|| format!("failed to parse {}", path.display()))?;
+                json.get_mut("similarity").map(Value::take)
+            }
+            name => {
+                let toml_value: toml::Value = toml::from_str(&content)
+                    .with_context(|| format!("failed to parse {}", path.display()))?;
+                let json = serde_json::to_value(toml_value)?;
+                if name == "pyproject.toml" {
+                    json.get("tool").and_then(|tool| tool.get("similarity")).cloned()
+                } else {
+                    Some(json)
+                }
+            }
+        };
+        let Some(root) = root else {
+            return Ok(None);
+        };
+
+        let base_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Self::from_value(root, path, &base_dir)
+            .with_context(|| format!("invalid config in {}", path.display()))
+            .map(Some)
+    }
+
+    fn from_value(root: Value, source: &Path, base_dir: &Path) -> anyhow::Result<Self> {
+        let Value::Object(mut table) = root else {
+            bail!("expected a table of settings");
+        };
+
+        let mut overrides = Vec::new();
+        if let Some(entries) = table.remove("overrides") {
+            let Value::Array(entries) = entries else {
+                bail!("`overrides` must be an array of tables");
+            };
+            for (index, entry) in entries.into_iter().enumerate() {
+                let Value::Object(mut entry) = entry else {
+                    bail!("overrides[{index}] must be a table");
+                };
+                let Some(Value::String(dir)) = entry.remove("path") else {
+                    bail!("overrides[{index}] needs a `path`");
+                };
+                let dir = base_dir.join(dir);
+                let dir = dir.canonicalize().unwrap_or(dir);
+                let layer = SettingsLayer::from_table(&entry, &format!("overrides.{index}"))?;
+                overrides.push(DirectoryOverride { dir, layer });
+            }
+        }
+        // Deeper directories are more specific and applied last
+        overrides.sort_by_key(|o| o.dir.components().count());
+
+        Ok(ProjectConfig {
+            source: source.to_path_buf(),
+            base: SettingsLayer::from_table(&table, "root")?,
+            overrides,
+        })
+    }
+
+    /// Resolve the settings for `languages` (most specific last) when analyzing `target`
+    pub fn resolve(&self, languages: &[&str], target: &Path) -> SimilaritySettings {
+        let target = target.canonicalize().unwrap_or_else(|_| target.to_path_buf());
+        let mut settings = SimilaritySettings::default();
+        self.base.apply_to(&mut settings, languages);
+        for directory in &self.overrides {
+            if target.starts_with(&directory.dir) {
+                directory.layer.apply_to(&mut settings, languages);
+            }
+        }
+        settings
+    }
+}
+
+fn file_name(path: &Path) -> &str {
+    path.file_name().and_then(|name| name.to_str()).unwrap_or_default()
+}
+
+/// Load the project settings for a CLI run, grouping the analyzed files by their settings.
+///
+/// Uses `explicit` when given, otherwise the nearest config above each file, so a package
+/// with its own config keeps it when scanned from the repository root; `[[overrides]]` are
+/// resolved against each file. Groups keep the order of their first file.
+pub fn load_settings(
+    explicit: Option<&Path>,
+    files: &[PathBuf],
+    languages: &[&str],
+) -> anyhow::Result<Vec<(SimilaritySettings, Vec<PathBuf>)>> {
+    let explicit = explicit.map(ProjectConfig::load).transpose()?;
+    let mut discovered = DiscoveredConfigs::default();
+
+    let mut groups: Vec<(SimilaritySettings, Vec<PathBuf>)> = Vec::new();
+    for file in files {
+        let target = file.canonicalize().unwrap_or_else(|_| file.clone());
+        let config = match &explicit {
+            Some(config) => Some(config),
+            None => discovered.find(&target)?,
+        };
+        let settings = config.map(|config| config.resolve(languages, &target)).unwrap_or_default();
+        match groups.iter_mut().find(|(group_settings, _)| *group_settings == settings) {
+            Some((_, group_files)) => group_files.push(file.clone()),
+            None => groups.push((settings, vec![file.clone()])),
+        }
+    }
+    Ok(groups)
+}
+
+/// Config files found above the analyzed files, each directory probed once
+#[derive(Default)]
+struct DiscoveredConfigs {
+    configs: Vec<ProjectConfig>,
+    /// Index into `configs` of the config applying to each probed directory
+    dirs: HashMap<PathBuf, Option<usize>>,
+}
+
+impl DiscoveredConfigs {
+    /// The nearest config in the directory of the canonical `file` or its parents
+    fn find(&mut self, file: &Path) -> anyhow::Result<Option<&ProjectConfig>> {
+        let mut probed = Vec::new();
+        let mut found = None;
+        for dir in file.parent().into_iter().flat_map(Path::ancestors) {
+            if let Some(&known) = self.dirs.get(dir) {
+                found = known;
+                break;
+            }
+            probed.push(dir.to_path_buf());
+            if let Some(config) = ProjectConfig::in_dir(dir)? {
+                self.configs.push(config);
+                found = Some(self.configs.len() - 1);
+                break;
+            }
+        }
+        for dir in probed {
+            self.dirs.insert(dir, found);
+        }
+        Ok(found.map(|index| &self.configs[index]))
+    }
+}
+
+/// Take the config value unless the option was given on the command line
+pub fn fill<T>(target: &mut T, explicit: bool, value: Option<T>) {
+    if let (false, Some(value)) = (explicit, value) {
+        *target = value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn write(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_language_sections_and_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "similarity.toml",
+            r#"
+threshold = 0.8
+min-lines = 4
+exclude = ["dist"]
+
+[typescript]
+threshold = 0.9
+
+[[overrides]]
+path = "packages/legacy"
+threshold = 0.95
+exclude = ["vendor"]
+
+[overrides.python]
+min-lines = 10
+"#,
+        );
+        fs::create_dir_all(dir.path().join("packages/legacy/src")).unwrap();
+        let config =
+            ProjectConfig::discover(&dir.path().join("packages/legacy/src")).unwrap().unwrap();
+
+        let root = config.resolve(&["typescript"], dir.path());
+        assert_eq!(root.threshold, Some(0.9));
+        assert_eq!(root.min_lines, Some(4));
+        assert_eq!(root.exclude, vec!["dist"]);
+
+        let legacy = config.resolve(&["python"], &dir.path().join("packages/legacy/src"));
+        assert_eq!(legacy.threshold, Some(0.95));
+        assert_eq!(legacy.min_lines, Some(10));
+        assert_eq!(legacy.exclude, vec!["dist", "vendor"]);
+    }
+
+    #[test]
+    fn test_pyproject_and_package_json() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "py/pyproject.toml", "[tool.similarity]\nrename-cost = 0.5\n");
+        write(dir.path(), "js/package.json", r#"{"name": "x", "similarity": {"min-tokens": 40}}"#);
+        write(dir.path(), "plain/package.json", r#"{"name": "no-config"}"#);
+        let py = write(dir.path(), "py/a.py", "");
+        let js = write(dir.path(), "js/a.js", "");
+
+        let groups = load_settings(None, &[py], &[]);
+        assert_eq!(groups.unwrap()[0].0.rename_cost, Some(0.5));
+        let groups = load_settings(None, &[js], &[]);
+        assert_eq!(groups.unwrap()[0].0.min_tokens, Some(40));
+        assert!(ProjectConfig::load(&dir.path().join("plain/package.json")).is_err());
+    }
+
+    #[test]
+    fn test_settings_per_file() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "strict/similarity.toml", "threshold = 0.95\n");
+        write(dir.path(), "loose/similarity.toml", "threshold = 0.7\n");
+        let files = [
+            write(dir.path(), "strict/a.py", ""),
+            write(dir.path(), "loose/a.py", ""),
+            write(dir.path(), "strict/src/b.py", ""),
+        ];
+
+        let groups = load_settings(None, &files, &[]).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].0.threshold, Some(0.95));
+        assert_eq!(groups[0].1, [files[0].clone(), files[2].clone()]);
+        assert_eq!(groups[1].0.threshold, Some(0.7));
+        assert_eq!(groups[1].1, [files[1].clone()]);
+    }
+
+    #[test]
+    fn test_monorepo_scanned_from_root() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "similarity.toml",
+            "threshold = 0.8\n[[overrides]]\npath = \"packages/legacy\"\nthreshold = 0.99\n",
+        );
+        write(dir.path(), "packages/web/similarity.toml", "min-lines = 7\n");
+        let app = write(dir.path(), "app/a.py", "");
+        let legacy = write(dir.path(), "packages/legacy/a.py", "");
+        let web = write(dir.path(), "packages/web/src/a.py", "");
+
+        // Overrides and nested configs apply to the files found under the root
+        let groups = load_settings(None, &[app, legacy.clone(), web.clone()], &[]).unwrap();
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0].0.threshold, Some(0.8));
+        assert_eq!(groups[1].0.threshold, Some(0.99));
+        assert_eq!(groups[1].1, [legacy]);
+        assert_eq!(groups[2].0.threshold, None);
+        assert_eq!(groups[2].0.min_lines, Some(7));
+        assert_eq!(groups[2].1, [web]);
+    }
+
+    #[test]
+    fn test_unknown_keys_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(dir.path(), "similarity.toml", "treshold = 0.9\n");
+        let error = format!("{:#}", ProjectConfig::load(&path).unwrap_err());
+        assert!(error.contains("treshold"), "{error}");
+
+        let path = write(dir.path(), "similarity.toml", "lsh-rows = 0\n");
+        let error = format!("{:#}", ProjectConfig::load(&path).unwrap_err());
+        assert!(error.contains("nonzero"), "{error}");
+
+        let path = write(dir.path(), "similarity.toml", "[typscript]\nthreshold = 0.9\n");
+        let error = format!("{:#}", ProjectConfig::load(&path).unwrap_err());
+        assert!(error.contains("unknown language section [typscript]"), "{error}");
+    }
+
+    #[test]
+    fn test_fill_respects_explicit_flags() {
+        let mut threshold = 0.85;
+        fill(&mut threshold, true, Some(0.9));
+        assert_eq!(threshold, 0.85);
+        fill(&mut threshold, false, Some(0.9));
+        assert_eq!(threshold, 0.9);
+        fill(&mut threshold, false, None);
+        assert_eq!(threshold, 0.9);
+    }
+}
@@ -1,9 +1,70 @@
-use ignore::WalkBuilder;
+use crate::cli_ignore::IgnoredPaths;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::{Walk, WalkBuilder};
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
+/// Walk a directory respecting `.gitignore` and `.similarity-ignore` path patterns
+///
+/// Only the path lines of `.similarity-ignore` take part; its `file:name` rules are
+/// applied to the results by [`crate::cli_ignore::SimilarityIgnore`].
+pub fn walk_dir(path: &Path) -> Walk {
+    let ignored = IgnoredPaths::default();
+    WalkBuilder::new(path)
+        .follow_links(false)
+        .filter_entry(move |entry| {
+            let is_dir = entry.file_type().is_some_and(|file_type| file_type.is_dir());
+            !ignored.is_ignored(entry.path(), is_dir)
+        })
+        .build()
+}
+
+/// Build a matcher for `--exclude` glob patterns, or `None` when there are none
+pub fn create_exclude_matcher(exclude_patterns: &[String]) -> Option<GlobSet> {
+    if exclude_patterns.is_empty() {
+        return None;
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in exclude_patterns {
+        if let Ok(glob) = Glob::new(pattern) {
+            builder.add(glob);
+        } else {
+            eprintln!("Warning: Invalid glob pattern: {}", pattern);
+        }
+    }
+
+    builder.build().ok()
+}
+
 /// Collect files from paths with given extensions
 pub fn collect_files(paths: &[String], extensions: &[&str]) -> anyhow::Result<Vec<PathBuf>> {
+    collect_files_excluding(paths, extensions, None)
+}
+
+/// Collect files from paths with given extensions, skipping directory entries matched by
+/// `exclude`. Files named explicitly are always kept, whatever their extension.
+pub fn collect_files_excluding(
+    paths: &[String],
+    extensions: &[&str],
+    exclude: Option<&GlobSet>,
+) -> anyhow::Result<Vec<PathBuf>> {
+    collect_files_matching(paths, |path| {
+        // Check if path should be excluded
+        if exclude.is_some_and(|matcher| matcher.is_match(path)) {
+            return false;
+        }
+        // Check extension
+        path.extension().and_then(|ext| ext.to_str()).is_some_and(|ext| extensions.contains(&ext))
+    })
+}
+
+/// Collect files from paths, keeping the directory entries accepted by `keep`. Files named
+/// explicitly are always kept.
+pub fn collect_files_matching(
+    paths: &[String],
+    keep: impl Fn(&Path) -> bool,
+) -> anyhow::Result<Vec<PathBuf>> {
     let mut files = Vec::new();
     let mut visited = HashSet::new();
 
@@ -12,41 +73,28 @@ pub fn collect_files(paths: &[String], extensions: &[&str]) -> anyhow::Result<Ve
         let path = Path::new(path_str);
 
         if path.is_file() {
-            // If it's a file, check extension and add it
-            if let Some(ext) = path.extension() {
-                if let Some(ext_str) = ext.to_str() {
-                    if extensions.contains(&ext_str) {
-                        if let Ok(canonical) = path.canonicalize() {
-                            if visited.insert(canonical.clone()) {
-                                files.push(path.to_path_buf());
-                            }
-                        }
-                    }
+            // A file named explicitly is analyzed whatever its extension
+            if let Ok(canonical) = path.canonicalize() {
+                if visited.insert(canonical) {
+                    files.push(path.to_path_buf());
                 }
             }
         } else if path.is_dir() {
-            // If it's a directory, walk it respecting .gitignore
-            let walker = WalkBuilder::new(path).follow_links(false).build();
+            // If it's a directory, walk it respecting .gitignore and .similarity-ignore
+            let walker = walk_dir(path);
 
             for entry in walker {
                 let entry = entry?;
                 let entry_path = entry.path();
 
-                // Skip if not a file
-                if !entry_path.is_file() {
+                // Skip directories and the entries `keep` rejects
+                if !entry_path.is_file() || !keep(entry_path) {
                     continue;
                 }
 
-                // Check extension
-                if let Some(ext) = entry_path.extension() {
-                    if let Some(ext_str) = ext.to_str() {
-                        if extensions.contains(&ext_str) {
-                            if let Ok(canonical) = entry_path.canonicalize() {
-                                if visited.insert(canonical.clone()) {
-                                    files.push(entry_path.to_path_buf());
-                                }
-                            }
-                        }
+                if let Ok(canonical) = entry_path.canonicalize() {
+                    if visited.insert(canonical) {
+                        files.push(entry_path.to_path_buf());
                     }
                 }
             }
@@ -0,0 +1,281 @@
+//! `.similarity-ignore` support.
+//!
+//! The file uses gitignore syntax for paths. A line containing `:` ignores functions
+//! (or classes/types) by name instead, optionally limited to matching files:
+//!
+//! ```text
+//! *_pb.*             # generated files
+//! src/**:setUp       # setUp in anything under src/
+//! *:use*             # React style hooks everywhere
+//! :constructor()     # same as *:constructor
+//! ```
+//!
+//! Path patterns are applied while walking directories (including nested ignore
+//! files). Name patterns are read from the ignore files in the analyzed paths and
+//! their parent directories; `*` and `?` are the only wildcards.
+
+use crate::subtree_fingerprint::PartialOverlap;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Ignore file honoured next to `.gitignore`
+pub const IGNORE_FILE_NAME: &str = ".similarity-ignore";
+
+#[derive(Debug)]
+struct NameRule {
+    /// Directory containing the ignore file the rule came from
+    root: PathBuf,
+    /// Path part of the rule; `None` matches every file
+    files: Option<Gitignore>,
+    name: String,
+}
+
+/// Function-name rules from `.similarity-ignore` files
+#[derive(Debug, Default)]
+pub struct SimilarityIgnore {
+    rules: Vec<NameRule>,
+}
+
+impl SimilarityIgnore {
+    /// Load the ignore files in the analyzed paths and their parent directories
+    pub fn discover(paths: &[String]) -> Self {
+        let mut ignore = SimilarityIgnore::default();
+        let mut seen = HashSet::new();
+        for path in paths {
+            let path = Path::new(path);
+            let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+            let start = if path.is_file() { path.parent() } else { Some(path.as_path()) };
+            for dir in start.into_iter().flat_map(Path::ancestors) {
+                // The parents of a seen directory have been seen as well
+                if !seen.insert(dir.to_path_buf()) {
+                    break;
+                }
+                if let Ok(content) = fs::read_to_string(dir.join(IGNORE_FILE_NAME)) {
+                    ignore.add_rules(dir, &content);
+                }
+            }
+        }
+        ignore
+    }
+
+    /// Parse the name rules of an ignore file located in `root`
+    pub fn parse(root: &Path, content: &str) -> Self {
+        let mut ignore = SimilarityIgnore::default();
+        ignore.add_rules(root, content);
+        ignore
+    }
+
+    fn add_rules(&mut self, root: &Path, content: &str) {
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((files, name)) = line.rsplit_once(':') else {
+                continue;
+            };
+            let name = name.trim().trim_end_matches("()").to_string();
+            if name.is_empty() {
+                continue;
+            }
+
+            let files = match files.trim() {
+                "" | "*" | "**" => None,
+                pattern => {
+                    let mut builder = GitignoreBuilder::new(root);
+                    if let Err(e) = builder.add_line(None, pattern) {
+                        eprintln!("Warning: Invalid pattern in {}: {}", IGNORE_FILE_NAME, e);
+                        continue;
+                    }
+                    builder.build().ok()
+                }
+            };
+            self.rules.push(NameRule { root: root.to_path_buf(), files, name });
+        }
+    }
+
+    /// Whether no name rules were found
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether a function is ignored by its own name or by the class it belongs to
+    pub fn is_function_ignored(&self, file: &Path, name: &str, class_name: Option<&str>) -> bool {
+        self.is_ignored(file, name) || class_name.is_some_and(|class| self.is_ignored(file, class))
+    }
+
+    /// Whether either function spanned by an overlap between two files is ignored
+    pub fn is_overlap_ignored(
+        &self,
+        source_file: &str,
+        target_file: &str,
+        overlap: &PartialOverlap,
+    ) -> bool {
+        self.is_ignored(Path::new(source_file), &overlap.source_function)
+            || self.is_ignored(Path::new(target_file), &overlap.target_function)
+    }
+
+    /// Whether a function, class or type named `name` in `file` is ignored
+    pub fn is_ignored(&self, file: &Path, name: &str) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        let file = file.canonicalize().unwrap_or_else(|_| file.to_path_buf());
+        self.rules.iter().any(|rule| {
+            wildcard_match(&rule.name, name)
+                && match &rule.files {
+                    None => true,
+                    Some(matcher) => match file.strip_prefix(&rule.root) {
+                        Ok(relative) => relative.ancestors().any(|candidate| {
+                            !candidate.as_os_str().is_empty()
+                                && matcher.matched(candidate, candidate != relative).is_ignore()
+                        }),
+                        Err(_) => false,
+                    },
+                }
+        })
+    }
+}
+
+/// Path patterns of the ignore files found while walking, loaded once per directory
+#[derive(Debug, Default)]
+pub(crate) struct IgnoredPaths {
+    matchers: Mutex<HashMap<PathBuf, Option<Arc<Gitignore>>>>,
+}
+
+impl IgnoredPaths {
+    /// Whether `path` is ignored by the ignore files of its parent directories; the
+    /// nearest file with a matching pattern decides
+    pub(crate) fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        for dir in path.ancestors().skip(1) {
+            let Some(matcher) = self.matcher(dir) else {
+                continue;
+            };
+            let matched = matcher.matched(&path, is_dir);
+            if matched.is_ignore() {
+                return true;
+            }
+            if matched.is_whitelist() {
+                return false;
+            }
+        }
+        false
+    }
+
+    fn matcher(&self, dir: &Path) -> Option<Arc<Gitignore>> {
+        let mut matchers = self.matchers.lock().unwrap_or_else(|e| e.into_inner());
+        matchers.entry(dir.to_path_buf()).or_insert_with(|| load_path_rules(dir)).clone()
+    }
+}
+
+/// Build a matcher from the path lines of the ignore file in `dir`, leaving out the
+/// `file:name` rules
+fn load_path_rules(dir: &Path) -> Option<Arc<Gitignore>> {
+    let content = fs::read_to_string(dir.join(IGNORE_FILE_NAME)).ok()?;
+    let mut builder = GitignoreBuilder::new(dir);
+    for line in content.lines().filter(|line| !line.contains(':')) {
+        if let Err(e) = builder.add_line(None, line) {
+            eprintln!("Warning: Invalid pattern in {}: {}", IGNORE_FILE_NAME, e);
+        }
+    }
+    builder.build().ok().map(Arc::new)
+}
+
+/// Match `text` against a pattern where `*` is any run of characters and `?` one character
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("use*", "useEffect"));
+        assert!(wildcard_match("*Generated", "UserGenerated"));
+        assert!(wildcard_match("set?p", "setUp"));
+        assert!(wildcard_match("*", ""));
+        assert!(!wildcard_match("use*", "reuse"));
+        assert!(!wildcard_match("setUp", "setUpClass"));
+    }
+
+    #[test]
+    fn test_name_rules_with_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("src/nested")).unwrap();
+        fs::create_dir_all(root.join("lib")).unwrap();
+        for file in ["src/nested/a.py", "lib/b.py"] {
+            fs::write(root.join(file), "").unwrap();
+        }
+
+        let ignore = SimilarityIgnore::parse(
+            &root,
+            "# comment\n*_pb.*\nsrc/**:setUp\n*:use*\n:constructor()\nlib:helper\n",
+        );
+        assert!(ignore.is_ignored(&root.join("src/nested/a.py"), "setUp"));
+        assert!(!ignore.is_ignored(&root.join("lib/b.py"), "setUp"));
+        assert!(ignore.is_ignored(&root.join("lib/b.py"), "useState"));
+        assert!(ignore.is_ignored(&root.join("lib/b.py"), "constructor"));
+        assert!(ignore.is_ignored(&root.join("lib/b.py"), "helper"));
+        assert!(!ignore.is_ignored(&root.join("src/nested/a.py"), "helper"));
+        assert!(ignore.is_function_ignored(&root.join("lib/b.py"), "run", Some("useThing")));
+        assert!(!ignore.is_function_ignored(&root.join("lib/b.py"), "run", Some("Thing")));
+    }
+
+    #[test]
+    fn test_walk_dir_only_applies_path_patterns() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("gen")).unwrap();
+        fs::create_dir_all(dir.path().join("src/vendor")).unwrap();
+        for file in ["gen/api.py", "src/vendor/lib.py", "src/keep.py", "src/notes:user.py"] {
+            fs::write(dir.path().join(file), "").unwrap();
+        }
+        fs::write(dir.path().join(IGNORE_FILE_NAME), "gen/\n*:use*\n").unwrap();
+        fs::write(dir.path().join("src").join(IGNORE_FILE_NAME), "vendor\n").unwrap();
+
+        let mut files: Vec<String> = crate::cli_file_utils::walk_dir(dir.path())
+            .filter_map(Result::ok)
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "py"))
+            .map(|entry| entry.path().strip_prefix(dir.path()).unwrap().display().to_string())
+            .collect();
+        files.sort();
+        assert_eq!(files, ["src/keep.py", "src/notes:user.py"]);
+    }
+
+    #[test]
+    fn test_discover_reads_parent_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("pkg")).unwrap();
+        fs::write(dir.path().join(IGNORE_FILE_NAME), "*:tearDown\n").unwrap();
+
+        let ignore = SimilarityIgnore::discover(&[dir.path().join("pkg").display().to_string()]);
+        assert!(!ignore.is_empty());
+        assert!(ignore.is_ignored(&dir.path().join("pkg/test.py"), "tearDown"));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::cli_clone_classes::{group_clone_classes, print_clone_classes, CloneClass, Linkage};
+use serde::{Deserialize, Serialize};
 use std::fs;
 
 /// Format function output in VSCode-compatible format
@@ -40,6 +42,29 @@ pub fn show_function_code(file_path: &str, function_name: &str, start_line: u32,
     }
 }
 
+/// Get a path relative to the current directory for display
+pub fn relative_path(file_path: &str) -> String {
+    let path = std::path::Path::new(file_path);
+    match std::env::current_dir() {
+        Ok(current_dir) => path.strip_prefix(&current_dir).unwrap_or(path).to_string_lossy().into(),
+        Err(_) => file_path.to_string(),
+    }
+}
+
+/// Extract lines from code, failing when the range is out of bounds
+pub fn extract_code_lines(code: &str, start_line: u32, end_line: u32) -> Result<String, String> {
+    let lines: Vec<_> = code.lines().collect();
+
+    if start_line as usize > lines.len() || end_line as usize > lines.len() {
+        return Err("Line numbers out of bounds".to_string());
+    }
+
+    let start = (start_line as usize).saturating_sub(1);
+    let end = (end_line as usize).min(lines.len());
+
+    Ok(lines[start..end].join("\n"))
+}
+
 /// Generic duplicate result structure
 pub struct DuplicateResult<T> {
     pub file1: String,
@@ -60,3 +85,283 @@ impl<T> DuplicateResult<T> {
         self.similarity * avg_size
     }
 }
+
+/// Output format selected with `--format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Human readable text (default)
+    #[default]
+    Text,
+    /// Machine readable JSON report
+    Json,
+    /// SARIF 2.1.0 log for code-scanning integrations
+    Sarif,
+}
+
+impl OutputFormat {
+    /// Whether human readable progress and results should be printed
+    pub fn is_text(self) -> bool {
+        self == OutputFormat::Text
+    }
+
+    /// Format the analyzers report pairs with. Pairs grouped with `--clone-classes` are
+    /// collected silently like JSON and printed as classes by [`Report::print`].
+    pub fn for_pairs(self, clone_classes: bool) -> Self {
+        if clone_classes && self.is_text() {
+            OutputFormat::Json
+        } else {
+            self
+        }
+    }
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "sarif" => Ok(OutputFormat::Sarif),
+            _ => Err(format!("unknown output format '{s}' (expected text, json or sarif)")),
+        }
+    }
+}
+
+/// Analyzer that produced a reported pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportKind {
+    Function,
+    Type,
+    Overlap,
+}
+
+/// One side of a reported pair
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct ReportLocation {
+    pub file: String,
+    pub name: String,
+    pub start_line: u32,
+    pub end_line: u32,
+}
+
+impl ReportLocation {
+    pub fn new(
+        file: impl Into<String>,
+        name: impl Into<String>,
+        start_line: u32,
+        end_line: u32,
+    ) -> Self {
+        Self { file: file.into(), name: name.into(), start_line, end_line }
+    }
+
+    pub fn line_count(&self) -> u32 {
+        self.end_line.saturating_sub(self.start_line) + 1
+    }
+}
+
+/// A duplicate pair in the machine readable report
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReportEntry {
+    pub kind: ReportKind,
+    pub first: ReportLocation,
+    pub second: ReportLocation,
+    pub similarity: f64,
+    /// Lines of the smaller side, i.e. how much code would be removed by deduplicating
+    pub impact: u32,
+    /// Similarity × average lines, the score used to order text output
+    pub priority: f64,
+}
+
+impl ReportEntry {
+    pub fn new(
+        kind: ReportKind,
+        first: ReportLocation,
+        second: ReportLocation,
+        similarity: f64,
+    ) -> Self {
+        let (lines1, lines2) = (first.line_count(), second.line_count());
+        let impact = lines1.min(lines2);
+        let priority = similarity * (lines1 + lines2) as f64 / 2.0;
+        Self { kind, first, second, similarity, impact, priority }
+    }
+}
+
+/// Machine readable report shared by all language CLIs
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub tool: String,
+    pub version: String,
+    pub duplicates: Vec<ReportEntry>,
+    /// Duplicates grouped with `--clone-classes`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clone_classes: Option<Vec<CloneClass>>,
+}
+
+impl Report {
+    pub fn new(tool: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            tool: tool.into(),
+            version: version.into(),
+            duplicates: Vec::new(),
+            clone_classes: None,
+        }
+    }
+
+    pub fn extend(&mut self, entries: impl IntoIterator<Item = ReportEntry>) {
+        self.duplicates.extend(entries);
+    }
+
+    /// Group the collected duplicates into clone classes
+    pub fn group_clone_classes(&mut self, linkage: Linkage) {
+        self.clone_classes = Some(group_clone_classes(&self.duplicates, linkage));
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Print the report in a machine readable format.
+    /// Text output is printed by each analyzer as results are found, so only clone classes are
+    /// printed here. SARIF always lists the individual pairs.
+    pub fn print(&self, format: OutputFormat) -> serde_json::Result<()> {
+        match format {
+            OutputFormat::Text => {
+                if let Some(classes) = &self.clone_classes {
+                    print_clone_classes(classes);
+                }
+            }
+            OutputFormat::Json => println!("{}", self.to_json()?),
+            OutputFormat::Sarif => println!("{}", crate::cli_sarif::to_sarif_json(self)?),
+        }
+        Ok(())
+    }
+}
+
+/// Exit code used when duplicates exceed the limits given with `--fail-on-duplicates`,
+/// `--max-duplicates` or `--max-duplicated-lines`. Errors exit with 1 and usage errors with 2.
+pub const EXIT_DUPLICATES_EXCEEDED: i32 = 3;
+
+/// Limits that turn reported duplicates into a failing exit code for CI
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FailThresholds {
+    /// Fail when any duplicate is reported
+    pub fail_on_duplicates: bool,
+    /// Fail when more pairs than this are reported
+    pub max_duplicates: Option<usize>,
+    /// Fail when the summed `impact` of the reported pairs exceeds this
+    pub max_duplicated_lines: Option<u32>,
+}
+
+impl FailThresholds {
+    /// Describe the first limit exceeded by `entries`, if any
+    pub fn violation(&self, entries: &[ReportEntry]) -> Option<String> {
+        let count = entries.len();
+        if self.fail_on_duplicates && count > 0 {
+            return Some(format!("Found {count} duplicate pairs"));
+        }
+        if let Some(max) = self.max_duplicates.filter(|&max| count > max) {
+            return Some(format!("Found {count} duplicate pairs (max: {max})"));
+        }
+        let lines: u32 = entries.iter().map(|entry| entry.impact).sum();
+        if let Some(max) = self.max_duplicated_lines.filter(|&max| lines > max) {
+            return Some(format!("Found {lines} duplicated lines (max: {max})"));
+        }
+        None
+    }
+
+    /// Exit with [`EXIT_DUPLICATES_EXCEEDED`] when a limit is exceeded
+    pub fn enforce(&self, entries: &[ReportEntry]) {
+        if let Some(violation) = self.violation(entries) {
+            eprintln!("Error: {violation}");
+            std::process::exit(EXIT_DUPLICATES_EXCEEDED);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_format_from_str() {
+        assert_eq!("text".parse::<OutputFormat>(), Ok(OutputFormat::Text));
+        assert_eq!("JSON".parse::<OutputFormat>(), Ok(OutputFormat::Json));
+        assert_eq!("sarif".parse::<OutputFormat>(), Ok(OutputFormat::Sarif));
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
+
+    #[test]
+    fn test_report_entry_scores() {
+        let entry = ReportEntry::new(
+            ReportKind::Function,
+            ReportLocation::new("a.ts", "foo", 1, 10),
+            ReportLocation::new("b.ts", "bar", 5, 8),
+            0.9,
+        );
+        assert_eq!(entry.impact, 4);
+        assert!((entry.priority - 0.9 * 7.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fail_thresholds() {
+        let entries = vec![
+            ReportEntry::new(
+                ReportKind::Function,
+                ReportLocation::new("a.ts", "foo", 1, 10),
+                ReportLocation::new("a.ts", "bar", 11, 20),
+                0.9,
+            ),
+            ReportEntry::new(
+                ReportKind::Function,
+                ReportLocation::new("a.ts", "baz", 21, 25),
+                ReportLocation::new("b.ts", "qux", 1, 5),
+                0.9,
+            ),
+        ];
+        assert_eq!(FailThresholds::default().violation(&entries), None);
+        assert_eq!(FailThresholds::default().violation(&[]), None);
+
+        let any = FailThresholds { fail_on_duplicates: true, ..Default::default() };
+        assert!(any.violation(&entries).is_some());
+        assert_eq!(any.violation(&[]), None);
+
+        let count = FailThresholds { max_duplicates: Some(2), ..Default::default() };
+        assert_eq!(count.violation(&entries), None);
+        let count = FailThresholds { max_duplicates: Some(1), ..Default::default() };
+        assert!(count.violation(&entries).is_some());
+
+        let lines = FailThresholds { max_duplicated_lines: Some(15), ..Default::default() };
+        assert_eq!(lines.violation(&entries), None);
+        let lines = FailThresholds { max_duplicated_lines: Some(14), ..Default::default() };
+        assert_eq!(lines.violation(&entries).unwrap(), "Found 15 duplicated lines (max: 14)");
+    }
+
+    #[test]
+    fn test_report_json_schema() {
+        let mut report = Report::new("similarity-ts", "0.0.0");
+        report.extend([ReportEntry::new(
+            ReportKind::Function,
+            ReportLocation::new("a.ts", "foo", 1, 3),
+            ReportLocation::new("a.ts", "bar", 5, 7),
+            1.0,
+        )]);
+        let value: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        let entry = &value["duplicates"][0];
+        assert_eq!(value["tool"], "similarity-ts");
+        assert_eq!(entry["kind"], "function");
+        assert_eq!(entry["first"]["file"], "a.ts");
+        assert_eq!(entry["second"]["name"], "bar");
+        assert_eq!(entry["second"]["start_line"], 5);
+        assert_eq!(entry["impact"], 3);
+        assert!(value.get("clone_classes").is_none());
+
+        report.group_clone_classes(Linkage::Single);
+        let value: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        let class = &value["clone_classes"][0];
+        assert_eq!(class["members"].as_array().unwrap().len(), 2);
+        assert_eq!(class["representative"]["name"], "foo");
+        assert_eq!(class["removable_lines"], 3);
+    }
+}
@@ -0,0 +1,1084 @@
+//! Duplicate detection shared by the tree-sitter based CLIs.
+//!
+//! Every language runs the same steps: collect the files, load them, extract their
+//! functions, filter out tests and ignored functions, compare the pairs within each file
+//! (and optionally across files), rank the pairs and report them. A language only supplies
+//! its parser through [`LanguageSupport`]; the [`FunctionExtractor`] and
+//! [`SimilarityChecker`] implementations come with it. Each file is read and its functions
+//! parsed once for both comparisons. Type similarity ([`check_types`]) and overlap
+//! detection ([`check_overlaps`]) run on the same files and options.
+
+use crate::cli_baseline::Baseline;
+use crate::cli_config::{fill, load_settings, SimilaritySettings};
+use crate::cli_file_utils::{
+    collect_files_excluding, collect_files_matching, create_exclude_matcher,
+};
+use crate::cli_ignore::SimilarityIgnore;
+use crate::cli_output::{
+    extract_code_lines, extract_lines_from_content, format_function_output, relative_path,
+    show_function_code, OutputFormat, ReportEntry, ReportKind, ReportLocation,
+};
+use crate::cli_parallel::{
+    load_files_parallel, CompareOptions, FileData, FunctionExtractor, SimilarityChecker,
+    SimilarityResult,
+};
+use crate::cli_types::report_similar_types;
+use crate::generic_overlap_detector::{
+    find_overlaps_across_files_generic, PartialOverlapWithFiles,
+};
+use crate::language_parser::{GenericFunctionDef, LanguageParser};
+use crate::lsh::{pair_count, select_pairs, CandidateStats, NodeKind};
+use crate::subtree_fingerprint::OverlapOptions;
+use crate::tree::{ArenaTree, AsArenaTree};
+use crate::tsed::{calculate_tsed, TSEDOptions};
+use crate::type_comparator::{find_similar_types, TypeComparisonOptions};
+use crate::type_extractor::TypeDefinition;
+use crate::{
+    AstFingerprint, FingerprintVocabulary, FunctionDefinition, FAST_FINGERPRINT_THRESHOLD,
+};
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A language analyzed by the shared pipeline. Only the parser is required; the other
+/// hooks default to what most languages need.
+pub trait LanguageSupport: Sync {
+    /// Name used in messages, e.g. "Python"
+    fn name(&self) -> &str;
+
+    /// Extensions checked when none are given on the command line
+    fn default_extensions(&self) -> Vec<&str>;
+
+    /// Create a parser. Files are processed in parallel, each with its own parser.
+    fn create_parser(&self) -> Result<Box<dyn LanguageParser>, Box<dyn Error + Send + Sync>>;
+
+    /// Vocabulary of the fingerprints that prune distant pairs in fast mode
+    fn fingerprint_vocabulary(&self) -> FingerprintVocabulary {
+        FingerprintVocabulary::tree_sitter()
+    }
+
+    /// Adjust the TSED options, e.g. to compare node values too
+    fn configure(&self, _options: &mut TSEDOptions) {}
+
+    /// Lines of `func` that are parsed and compared: its body by default
+    fn compared_lines(&self, func: &GenericFunctionDef) -> (u32, u32) {
+        (func.body_start_line, func.body_end_line)
+    }
+
+    /// Whether `func` is a test, skipped with `--skip-test`
+    fn is_test_function(&self, _func: &GenericFunctionDef) -> bool {
+        false
+    }
+
+    /// How `func` is named in the results
+    fn describe_function(&self, func: &GenericFunctionDef) -> String {
+        let kind = if func.is_method { "method" } else { "function" };
+        format!("{} {}", kind, func.name)
+    }
+
+    /// Heading for the enclosing classes of a pair
+    fn container_label(&self) -> &str {
+        "Classes"
+    }
+
+    /// Whether every function found is listed before the duplicates
+    fn lists_functions(&self) -> bool {
+        false
+    }
+
+    /// Whether [`check_types`] compares the type definitions of this language
+    fn supports_types(&self) -> bool {
+        false
+    }
+
+    /// Type definitions of a file: the parser's types that have fields by default
+    fn extract_types(
+        &self,
+        filename: &str,
+        content: &str,
+    ) -> Result<Vec<TypeDefinition>, Box<dyn Error + Send + Sync>> {
+        let types = self.create_parser()?.extract_types(content, filename)?;
+        Ok(types
+            .iter()
+            .filter(|type_def| !type_def.fields.is_empty())
+            .map(|type_def| type_def.to_type_definition(filename))
+            .collect())
+    }
+
+    /// Adjust the type comparison options, e.g. to only compare types of the same kind
+    fn configure_types(&self, _options: &mut TypeComparisonOptions) {}
+
+    /// Final similarity of a type pair whose fields compare at `similarity`
+    fn type_similarity(
+        &self,
+        _type1: &TypeDefinition,
+        _type2: &TypeDefinition,
+        similarity: f64,
+    ) -> f64 {
+        similarity
+    }
+}
+
+impl<L: LanguageSupport> FunctionExtractor for L {
+    type Function = GenericFunctionDef;
+
+    fn extract_functions(
+        &self,
+        filename: &str,
+        content: &str,
+    ) -> Result<Vec<GenericFunctionDef>, Box<dyn Error + Send + Sync>> {
+        self.create_parser()?.extract_functions(content, filename)
+    }
+}
+
+impl<L: LanguageSupport> SimilarityChecker for L {
+    type Function = GenericFunctionDef;
+
+    fn find_similar_in_file(
+        &self,
+        filename: &str,
+        content: &str,
+        options: &CompareOptions,
+        stats: &CandidateStats,
+    ) -> Result<Vec<SimilarityResult<GenericFunctionDef>>, Box<dyn Error + Send + Sync>> {
+        let mut parser = self.create_parser()?;
+        let functions = parser.extract_functions(content, filename)?;
+        let trees = comparable_trees(
+            self,
+            parser.as_mut(),
+            filename,
+            content,
+            &functions,
+            &options.tsed_options,
+        );
+        let pairs = select_pairs(&trees, NodeKind::Label, options.lsh.as_ref(), stats);
+        Ok(compare_pairs(self, &trees, pairs, options)
+            .into_iter()
+            .map(|(i, j, similarity)| {
+                SimilarityResult::new(functions[i].clone(), functions[j].clone(), similarity)
+            })
+            .collect())
+    }
+}
+
+/// Flattened trees of the functions that take part in comparisons: long enough, not
+/// skipped as tests and with enough nodes. The others get no tree and are never paired.
+fn comparable_trees<L: LanguageSupport>(
+    language: &L,
+    parser: &mut dyn LanguageParser,
+    filename: &str,
+    content: &str,
+    functions: &[GenericFunctionDef],
+    options: &TSEDOptions,
+) -> Vec<Option<ArenaTree>> {
+    let lines: Vec<&str> = content.lines().collect();
+    functions
+        .iter()
+        .map(|func| {
+            if func.line_count() < options.min_lines
+                || (options.skip_test && language.is_test_function(func))
+            {
+                return None;
+            }
+            let source = join_lines(&lines, language.compared_lines(func));
+            let tree = parser.parse(&source, filename).ok()?;
+            let tree = ArenaTree::from_node(&tree);
+            options
+                .min_tokens
+                .is_none_or(|min_tokens| tree.len() >= min_tokens as usize)
+                .then_some(tree)
+        })
+        .collect()
+}
+
+/// The `pairs` of `trees` that are at least `options.threshold` similar, with their
+/// similarity
+fn compare_pairs<L: LanguageSupport, T: AsArenaTree + Sync>(
+    language: &L,
+    trees: &[Option<T>],
+    pairs: Vec<(usize, usize)>,
+    options: &CompareOptions,
+) -> Vec<(usize, usize, f64)> {
+    let vocabulary = options.fast_mode.then(|| language.fingerprint_vocabulary());
+    let fingerprints: Vec<Option<AstFingerprint>> = trees
+        .iter()
+        .map(|tree| Some(AstFingerprint::from_tree(tree.as_ref()?, vocabulary.as_ref()?)))
+        .collect();
+
+    pairs
+        .into_par_iter()
+        .filter_map(|(i, j)| {
+            let (tree1, tree2) = (trees[i].as_ref()?, trees[j].as_ref()?);
+
+            // Skip pairs with distant fingerprints in fast mode
+            if let (Some(fp1), Some(fp2)) = (&fingerprints[i], &fingerprints[j]) {
+                if !fp1.is_candidate(fp2, FAST_FINGERPRINT_THRESHOLD) {
+                    return None;
+                }
+            }
+
+            let similarity = calculate_tsed(tree1, tree2, &options.tsed_options);
+            (similarity >= options.threshold).then_some((i, j, similarity))
+        })
+        .collect()
+}
+
+/// Options of one [`check_paths`] run
+#[derive(Debug, Clone, Default)]
+pub struct PipelineOptions {
+    pub compare: CompareOptions,
+    /// Extensions to check instead of the language's defaults
+    pub extensions: Option<Vec<String>>,
+    /// Glob patterns of paths to skip
+    pub exclude: Vec<String>,
+    /// Only report pairs where a function name contains this
+    pub filter_function: Option<String>,
+    /// Only report pairs where a function's code contains this
+    pub filter_function_body: Option<String>,
+    /// Print the code of both functions of every pair
+    pub print: bool,
+    /// Print how many function pairs candidate generation pruned
+    pub stats: bool,
+    /// Also compare the functions of different files
+    pub cross_file: bool,
+    /// Also compare TypeScript/JavaScript functions with Python functions
+    pub cross_language: bool,
+    /// Weights of [`check_types`]; the threshold is the one of `compare`
+    pub types: TypeComparisonOptions,
+    /// Window sizes and threshold of [`check_overlaps`]
+    pub overlap: OverlapOptions,
+}
+
+impl PipelineOptions {
+    /// Collect the files under `paths` and group them by the project config settings that
+    /// apply to them, each group with these options filled from its settings where not given
+    /// on the command line. Files found in directories are kept when they have one of the
+    /// group's extensions (`default_extensions` unless set, plus Python's with
+    /// `cross_language`) and match none of its exclude globs. `explicit` tells whether the
+    /// CLI argument with the given id was passed.
+    pub fn for_files(
+        &self,
+        project_config: Option<&Path>,
+        paths: &[String],
+        languages: &[&str],
+        default_extensions: &[&str],
+        explicit: impl Fn(&str) -> bool,
+    ) -> anyhow::Result<Vec<(Vec<String>, PipelineOptions)>> {
+        let files = collect_files_matching(paths, |_| true)?;
+        let named: HashSet<&Path> = paths.iter().map(Path::new).collect();
+
+        let mut runs = Vec::new();
+        for (settings, files) in load_settings(project_config, &files, languages)? {
+            let mut options = self.clone();
+            options.apply_settings(&settings, &explicit);
+            let mut extensions: Vec<&str> = match &options.extensions {
+                Some(extensions) => extensions.iter().map(String::as_str).collect(),
+                None => default_extensions.to_vec(),
+            };
+            if options.cross_language {
+                extensions.push("py");
+            }
+            let exclude = create_exclude_matcher(&options.exclude);
+            let files: Vec<String> = files
+                .into_iter()
+                .filter(|file| {
+                    named.contains(file.as_path())
+                        || (exclude.as_ref().is_none_or(|matcher| !matcher.is_match(file))
+                            && file
+                                .extension()
+                                .and_then(|ext| ext.to_str())
+                                .is_some_and(|ext| extensions.contains(&ext)))
+                })
+                .map(|file| file.to_string_lossy().to_string())
+                .collect();
+            if !files.is_empty() {
+                runs.push((files, options));
+            }
+        }
+        // Still run once so that the analyzers report that no files were found
+        if runs.is_empty() {
+            runs.push((Vec::new(), self.clone()));
+        }
+        Ok(runs)
+    }
+
+    /// Fill the options not given on the command line from `settings`
+    pub fn apply_settings(
+        &mut self,
+        settings: &SimilaritySettings,
+        explicit: impl Fn(&str) -> bool,
+    ) {
+        let compare = &mut self.compare;
+        let tsed_options = &mut compare.tsed_options;
+        fill(&mut compare.threshold, explicit("threshold"), settings.threshold);
+        fill(&mut self.overlap.threshold, explicit("threshold"), settings.threshold);
+        fill(&mut tsed_options.min_lines, explicit("min_lines"), settings.min_lines);
+        fill(&mut tsed_options.min_tokens, explicit("min_tokens"), settings.min_tokens.map(Some));
+        fill(
+            &mut tsed_options.apted_options.rename_cost,
+            explicit("rename_cost"),
+            settings.rename_cost,
+        );
+        fill(&mut tsed_options.size_penalty, explicit("no_size_penalty"), settings.size_penalty);
+        fill(&mut tsed_options.skip_test, explicit("skip_test"), settings.skip_test);
+        fill(&mut compare.fast_mode, explicit("no_fast"), settings.fast);
+
+        let mut lsh_enabled = compare.lsh.is_some();
+        let mut lsh = compare.lsh.unwrap_or_default();
+        fill(&mut lsh_enabled, explicit("lsh"), settings.lsh);
+        fill(&mut lsh.bands, explicit("lsh_bands"), settings.lsh_bands.map(|n| n.get() as usize));
+        fill(&mut lsh.rows, explicit("lsh_rows"), settings.lsh_rows.map(|n| n.get() as usize));
+        compare.lsh = lsh_enabled.then_some(lsh);
+
+        fill(&mut self.extensions, explicit("extensions"), settings.extensions.clone().map(Some));
+        fill(&mut self.cross_language, explicit("cross_language"), settings.cross_language);
+        self.exclude.extend(settings.exclude.iter().cloned());
+        let overlap = &mut self.overlap;
+        fill(
+            &mut overlap.min_window_size,
+            explicit("overlap_min_window"),
+            settings.overlap_min_window,
+        );
+        fill(
+            &mut overlap.max_window_size,
+            explicit("overlap_max_window"),
+            settings.overlap_max_window,
+        );
+        fill(
+            &mut overlap.size_tolerance,
+            explicit("overlap_size_tolerance"),
+            settings.overlap_size_tolerance,
+        );
+    }
+}
+
+/// A function of a reported pair: the tree-sitter parsers' [`GenericFunctionDef`] or the
+/// [`FunctionDefinition`] of similarity-ts
+pub trait ReportedFunction {
+    fn name(&self) -> &str;
+    fn start_line(&self) -> u32;
+    fn end_line(&self) -> u32;
+    fn class_name(&self) -> Option<&str>;
+
+    fn line_count(&self) -> u32 {
+        self.end_line() - self.start_line() + 1
+    }
+}
+
+impl ReportedFunction for GenericFunctionDef {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn start_line(&self) -> u32 {
+        self.start_line
+    }
+
+    fn end_line(&self) -> u32 {
+        self.end_line
+    }
+
+    fn class_name(&self) -> Option<&str> {
+        self.class_name.as_deref()
+    }
+}
+
+impl ReportedFunction for FunctionDefinition {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn start_line(&self) -> u32 {
+        self.start_line
+    }
+
+    fn end_line(&self) -> u32 {
+        self.end_line
+    }
+
+    fn class_name(&self) -> Option<&str> {
+        self.class_name.as_deref()
+    }
+}
+
+/// A pair of similar functions, found in one file or across two files
+pub struct DuplicateResult<F = GenericFunctionDef> {
+    pub file1: PathBuf,
+    pub file2: PathBuf,
+    pub result: SimilarityResult<F>,
+}
+
+impl<F: ReportedFunction> DuplicateResult<F> {
+    fn priority(&self) -> f64 {
+        // Score = Similarity × Average lines
+        let avg_lines =
+            (self.result.func1.line_count() + self.result.func2.line_count()) as f64 / 2.0;
+        self.result.similarity * avg_lines
+    }
+}
+
+/// Check every file of `language` under `paths` for duplicate functions
+pub fn check_paths<L: LanguageSupport>(
+    language: &L,
+    paths: Vec<String>,
+    options: &PipelineOptions,
+    format: OutputFormat,
+    baseline: Option<&Baseline>,
+) -> anyhow::Result<Vec<ReportEntry>> {
+    let files = collect_language_files(language, &paths, options)?;
+    if files.is_empty() {
+        if format.is_text() {
+            println!("No {} files found in the specified paths.", language.name());
+        }
+        return Ok(Vec::new());
+    }
+
+    // Every file is read, parsed and flattened once for both kinds of comparison
+    let file_data = load_files_parallel(&files, language);
+    if format.is_text() {
+        println!("Checking {} files for duplicates...", files.len());
+        if language.lists_functions() {
+            list_functions(&file_data);
+        }
+    }
+
+    let mut compare = options.compare.clone();
+    language.configure(&mut compare.tsed_options);
+    let trees = function_trees(language, &file_data, &compare.tsed_options);
+
+    let candidate_stats = CandidateStats::default();
+    let mut all_results =
+        check_within_file_duplicates(language, &file_data, &trees, &compare, &candidate_stats);
+    if options.cross_file {
+        all_results.extend(check_cross_file_duplicates(
+            language,
+            &file_data,
+            &trees,
+            &compare,
+            &candidate_stats,
+        ));
+    }
+    if options.stats {
+        eprintln!("{}", candidate_stats.summary());
+    }
+
+    let describe = |func: &GenericFunctionDef| language.describe_function(func);
+    let display = ResultDisplay {
+        describe: &describe,
+        container_label: language.container_label(),
+        details: None,
+    };
+    Ok(report_duplicates(all_results, &paths, options, format, baseline, &display))
+}
+
+/// Check the type definitions of every file of `language` under `paths` for similar types
+pub fn check_types<L: LanguageSupport>(
+    language: &L,
+    paths: Vec<String>,
+    options: &PipelineOptions,
+    format: OutputFormat,
+    baseline: Option<&Baseline>,
+) -> anyhow::Result<Vec<ReportEntry>> {
+    let files = collect_language_files(language, &paths, options)?;
+    if files.is_empty() {
+        if format.is_text() {
+            println!("No {} files found in the specified paths.", language.name());
+        }
+        return Ok(Vec::new());
+    }
+
+    if format.is_text() {
+        println!("Checking {} files for similar types...\n", files.len());
+    }
+
+    let mut all_types = Vec::new();
+    for file in &files {
+        let content = match fs::read_to_string(file) {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!("Error reading {}: {}", file.display(), e);
+                continue;
+            }
+        };
+        match language.extract_types(&file.to_string_lossy(), &content) {
+            Ok(types) => all_types.extend(types),
+            Err(e) => eprintln!("Error in {}: {}", file.display(), e),
+        }
+    }
+
+    // Drop types listed in .similarity-ignore
+    let ignore = SimilarityIgnore::discover(&paths);
+    all_types.retain(|type_def| !ignore.is_ignored(Path::new(&type_def.file_path), &type_def.name));
+
+    if all_types.is_empty() {
+        if format.is_text() {
+            println!("No type definitions found!");
+        }
+        return Ok(Vec::new());
+    }
+
+    if format.is_text() {
+        println!("Found {} type definitions", all_types.len());
+    }
+
+    let mut type_options = options.types.clone();
+    language.configure_types(&mut type_options);
+    if (type_options.structural_weight + type_options.naming_weight - 1.0).abs() > 0.001 {
+        eprintln!("Warning: structural_weight + naming_weight should equal 1.0");
+    }
+
+    let threshold = options.compare.threshold;
+    let mut similar_pairs = find_similar_types(&all_types, threshold, &type_options);
+    for pair in &mut similar_pairs {
+        pair.result.similarity =
+            language.type_similarity(&pair.type1, &pair.type2, pair.result.similarity);
+    }
+    similar_pairs.retain(|pair| pair.result.similarity >= threshold);
+    similar_pairs.sort_by(|a, b| {
+        b.result.similarity.partial_cmp(&a.result.similarity).unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    Ok(report_similar_types(similar_pairs, options.print, format, baseline))
+}
+
+/// Find code that overlaps within and across every file of `language` under `paths`
+pub fn check_overlaps<L: LanguageSupport>(
+    language: &L,
+    paths: Vec<String>,
+    options: &PipelineOptions,
+    format: OutputFormat,
+    baseline: Option<&Baseline>,
+) -> anyhow::Result<Vec<ReportEntry>> {
+    let files = collect_language_files(language, &paths, options)?;
+    if files.is_empty() {
+        if format.is_text() {
+            println!("No {} files found in the specified paths.", language.name());
+        }
+        return Ok(Vec::new());
+    }
+
+    if format.is_text() {
+        println!("Checking {} files for overlapping code...\n", files.len());
+    }
+
+    let mut file_contents = HashMap::new();
+    for file in &files {
+        match fs::read_to_string(file) {
+            Ok(content) => {
+                file_contents.insert(file.to_string_lossy().to_string(), content);
+            }
+            Err(e) => eprintln!("Error reading {}: {}", file.display(), e),
+        }
+    }
+
+    let mut parser = language
+        .create_parser()
+        .map_err(|e| anyhow::anyhow!("Failed to create {} parser: {}", language.name(), e))?;
+    let mut overlaps =
+        find_overlaps_across_files_generic(parser.as_mut(), &file_contents, &options.overlap)
+            .map_err(|e| anyhow::anyhow!("Failed to find overlaps: {}", e))?;
+
+    // Drop overlaps in functions listed in .similarity-ignore
+    let ignore = SimilarityIgnore::discover(&paths);
+    overlaps.retain(|found| {
+        !ignore.is_overlap_ignored(&found.source_file, &found.target_file, &found.overlap)
+    });
+
+    let mut entries = overlap_entries(&overlaps);
+    if let Some(baseline) = baseline {
+        baseline.retain_new(&mut overlaps, &mut entries);
+    }
+
+    if format.is_text() {
+        display_overlaps(&overlaps, &file_contents, options.print);
+    }
+
+    Ok(entries)
+}
+
+/// Files of `language` under `paths`, honoring the extension and exclude options
+pub fn collect_language_files<L: LanguageSupport>(
+    language: &L,
+    paths: &[String],
+    options: &PipelineOptions,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let exts: Vec<&str> = match &options.extensions {
+        Some(extensions) => extensions.iter().map(String::as_str).collect(),
+        None => language.default_extensions(),
+    };
+    let exclude_matcher = create_exclude_matcher(&options.exclude);
+    collect_files_excluding(paths, &exts, exclude_matcher.as_ref())
+}
+
+/// [`comparable_trees`] of every loaded file
+fn function_trees<L: LanguageSupport>(
+    language: &L,
+    file_data: &[FileData<GenericFunctionDef>],
+    options: &TSEDOptions,
+) -> Vec<Vec<Option<ArenaTree>>> {
+    file_data
+        .par_iter()
+        .map(|data| {
+            let Ok(mut parser) = language.create_parser() else {
+                return vec![None; data.functions.len()];
+            };
+            comparable_trees(
+                language,
+                parser.as_mut(),
+                &data.path.to_string_lossy(),
+                &data.content,
+                &data.functions,
+                options,
+            )
+        })
+        .collect()
+}
+
+/// Compare the functions within each file
+fn check_within_file_duplicates<L: LanguageSupport>(
+    language: &L,
+    file_data: &[FileData<GenericFunctionDef>],
+    trees: &[Vec<Option<ArenaTree>>],
+    options: &CompareOptions,
+    stats: &CandidateStats,
+) -> Vec<DuplicateResult> {
+    file_data
+        .par_iter()
+        .zip(trees)
+        .flat_map_iter(|(data, trees)| {
+            let pairs = select_pairs(trees, NodeKind::Label, options.lsh.as_ref(), stats);
+            compare_pairs(language, trees, pairs, options).into_iter().map(
+                move |(i, j, similarity)| DuplicateResult {
+                    file1: data.path.clone(),
+                    file2: data.path.clone(),
+                    result: SimilarityResult::new(
+                        data.functions[i].clone(),
+                        data.functions[j].clone(),
+                        similarity,
+                    ),
+                },
+            )
+        })
+        .collect()
+}
+
+/// Compare the functions of different files, reusing the trees of the within-file check
+fn check_cross_file_duplicates<L: LanguageSupport>(
+    language: &L,
+    file_data: &[FileData<GenericFunctionDef>],
+    trees: &[Vec<Option<ArenaTree>>],
+    options: &CompareOptions,
+    stats: &CandidateStats,
+) -> Vec<DuplicateResult> {
+    let functions: Vec<(usize, &GenericFunctionDef)> = file_data
+        .iter()
+        .enumerate()
+        .flat_map(|(file, data)| data.functions.iter().map(move |func| (file, func)))
+        .collect();
+    let per_file: Vec<usize> =
+        trees.iter().map(|trees| trees.iter().filter(|tree| tree.is_some()).count()).collect();
+    let trees: Vec<Option<&ArenaTree>> = trees.iter().flatten().map(Option::as_ref).collect();
+
+    // Only pairs of different files; the pairs within a file are recorded by their own check
+    let pairs: Vec<(usize, usize)> =
+        select_pairs(&trees, NodeKind::Label, options.lsh.as_ref(), &CandidateStats::default())
+            .into_iter()
+            .filter(|&(i, j)| functions[i].0 != functions[j].0)
+            .collect();
+    let same_file: usize = per_file.iter().map(|&n| pair_count(n)).sum();
+    stats.record(pair_count(per_file.iter().sum()) - same_file, pairs.len());
+
+    compare_pairs(language, &trees, pairs, options)
+        .into_iter()
+        .map(|(i, j, similarity)| {
+            let ((file1, func1), (file2, func2)) = (functions[i], functions[j]);
+            DuplicateResult {
+                file1: file_data[file1].path.clone(),
+                file2: file_data[file2].path.clone(),
+                result: SimilarityResult::new(func1.clone(), func2.clone(), similarity),
+            }
+        })
+        .collect()
+}
+
+/// List every function found, before the duplicates
+fn list_functions(file_data: &[FileData<GenericFunctionDef>]) {
+    let functions: Vec<&GenericFunctionDef> =
+        file_data.iter().flat_map(|data| &data.functions).collect();
+    if functions.is_empty() {
+        return;
+    }
+
+    println!("\nFound {} functions", functions.len());
+    for func in functions {
+        println!("  - {}", func.name);
+    }
+}
+
+/// How the functions of duplicate pairs are shown in text output
+pub struct ResultDisplay<'a, F> {
+    /// How a function is named, e.g. "method area"
+    pub describe: &'a dyn Fn(&F) -> String,
+    /// Heading for the enclosing classes of a pair
+    pub container_label: &'a str,
+    /// Extra lines printed under each pair, e.g. the statements that differ
+    pub details: Option<&'a PairDetails<'a, F>>,
+}
+
+/// Prints extra lines under a pair of [`ResultDisplay`]
+pub type PairDetails<'a, F> = dyn Fn(&DuplicateResult<F>) + 'a;
+
+/// Drop the ignored and filtered out pairs, rank the others and report them: the entries
+/// not in `baseline` are returned and, in text output, printed with `display`
+pub fn report_duplicates<F: ReportedFunction>(
+    mut all_results: Vec<DuplicateResult<F>>,
+    paths: &[String],
+    options: &PipelineOptions,
+    format: OutputFormat,
+    baseline: Option<&Baseline>,
+    display: &ResultDisplay<F>,
+) -> Vec<ReportEntry> {
+    // Drop pairs involving functions listed in .similarity-ignore
+    let ignore = SimilarityIgnore::discover(paths);
+    all_results.retain(|dup| {
+        let (func1, func2) = (&dup.result.func1, &dup.result.func2);
+        !ignore.is_function_ignored(&dup.file1, func1.name(), func1.class_name())
+            && !ignore.is_function_ignored(&dup.file2, func2.name(), func2.class_name())
+    });
+
+    filter_and_sort_results(&mut all_results, options);
+    let mut entries = to_report_entries(&all_results);
+    if let Some(baseline) = baseline {
+        baseline.retain_new(&mut all_results, &mut entries);
+    }
+
+    if format.is_text() {
+        display_all_results(&all_results, options.print, display);
+    }
+    entries
+}
+
+/// Apply the name/body filters and sort by priority
+fn filter_and_sort_results<F: ReportedFunction>(
+    all_results: &mut Vec<DuplicateResult<F>>,
+    options: &PipelineOptions,
+) {
+    if let Some(filter) = &options.filter_function {
+        all_results.retain(|dup| {
+            dup.result.func1.name().contains(filter.as_str())
+                || dup.result.func2.name().contains(filter.as_str())
+        });
+    }
+
+    if let Some(filter) = &options.filter_function_body {
+        all_results.retain(|dup| {
+            [(&dup.file1, &dup.result.func1), (&dup.file2, &dup.result.func2)].into_iter().any(
+                |(file, func)| match fs::read_to_string(file) {
+                    Ok(content) => {
+                        extract_lines_from_content(&content, func.start_line(), func.end_line())
+                            .contains(filter.as_str())
+                    }
+                    Err(_) => false,
+                },
+            )
+        });
+    }
+
+    // Sort by priority (higher similarity × larger functions first)
+    all_results.sort_by(|a, b| {
+        b.priority().partial_cmp(&a.priority()).unwrap_or(std::cmp::Ordering::Equal)
+    });
+}
+
+/// Convert results into machine readable report entries
+fn to_report_entries<F: ReportedFunction>(all_results: &[DuplicateResult<F>]) -> Vec<ReportEntry> {
+    all_results
+        .iter()
+        .map(|dup| {
+            let location = |file: &Path, func: &F| {
+                let file = relative_path(&file.to_string_lossy());
+                ReportLocation::new(&file, func.name(), func.start_line(), func.end_line())
+            };
+            ReportEntry::new(
+                ReportKind::Function,
+                location(&dup.file1, &dup.result.func1),
+                location(&dup.file2, &dup.result.func2),
+                dup.result.similarity,
+            )
+        })
+        .collect()
+}
+
+/// Convert overlaps into machine readable report entries
+pub fn overlap_entries(overlaps: &[PartialOverlapWithFiles]) -> Vec<ReportEntry> {
+    overlaps
+        .iter()
+        .map(|overlap_with_files| {
+            let overlap = &overlap_with_files.overlap;
+            ReportEntry::new(
+                ReportKind::Overlap,
+                ReportLocation::new(
+                    relative_path(&overlap_with_files.source_file),
+                    &overlap.source_function,
+                    overlap.source_lines.0,
+                    overlap.source_lines.1,
+                ),
+                ReportLocation::new(
+                    relative_path(&overlap_with_files.target_file),
+                    &overlap.target_function,
+                    overlap.target_lines.0,
+                    overlap.target_lines.1,
+                ),
+                overlap.similarity,
+            )
+        })
+        .collect()
+}
+
+/// Display overlaps in the order they were found
+pub fn display_overlaps(
+    overlaps: &[PartialOverlapWithFiles],
+    file_contents: &HashMap<String, String>,
+    print: bool,
+) {
+    if overlaps.is_empty() {
+        println!("\nNo code overlaps found!");
+        return;
+    }
+
+    println!("\nCode overlaps found:");
+    println!("{}", "-".repeat(60));
+
+    for overlap_with_files in overlaps {
+        let overlap = &overlap_with_files.overlap;
+        println!(
+            "\nSimilarity: {:.2}% | {} nodes | {}",
+            overlap.similarity * 100.0,
+            overlap.node_count,
+            overlap.node_type
+        );
+        let sides = [
+            (&overlap_with_files.source_file, overlap.source_lines, &overlap.source_function),
+            (&overlap_with_files.target_file, overlap.target_lines, &overlap.target_function),
+        ];
+        for (file, (start_line, end_line), function) in sides {
+            println!(
+                "  {}:{} | L{}-{} in function: {}",
+                relative_path(file),
+                start_line,
+                start_line,
+                end_line,
+                function
+            );
+        }
+
+        if print {
+            // Extract and display the overlapping code
+            for (heading, (file, (start_line, end_line), _)) in
+                ["Source", "Target"].iter().zip(sides)
+            {
+                let Some(content) = file_contents.get(file) else {
+                    continue;
+                };
+                println!("\n\x1b[36m--- {heading} Code ---\x1b[0m");
+                if let Ok(segment) = extract_code_lines(content, start_line, end_line) {
+                    println!("{segment}");
+                }
+            }
+        }
+    }
+
+    println!("\nTotal overlaps found: {}", overlaps.len());
+}
+
+/// Display similarity results grouped by the file of their first function, highest ranked
+/// file first
+fn display_all_results<F: ReportedFunction>(
+    all_results: &[DuplicateResult<F>],
+    print: bool,
+    display: &ResultDisplay<F>,
+) {
+    if all_results.is_empty() {
+        println!("\nNo duplicate functions found!");
+        return;
+    }
+
+    let mut file_groups: Vec<(&Path, Vec<&DuplicateResult<F>>)> = Vec::new();
+    for dup in all_results {
+        match file_groups.iter_mut().find(|(file, _)| *file == dup.file1) {
+            Some((_, group)) => group.push(dup),
+            None => file_groups.push((&dup.file1, vec![dup])),
+        }
+    }
+
+    for (file, duplicates) in file_groups {
+        let file_path = file.to_string_lossy();
+        println!("\nDuplicates in {file_path}:");
+        println!("{}", "-".repeat(60));
+
+        for dup in duplicates {
+            let (func1, func2) = (&dup.result.func1, &dup.result.func2);
+            let other_path = dup.file2.to_string_lossy();
+            let describe = |path: &str, func: &F| {
+                let name = (display.describe)(func);
+                format_function_output(path, &name, func.start_line(), func.end_line())
+            };
+            println!("  {} <-> {}", describe(&file_path, func1), describe(&other_path, func2));
+            println!("  Similarity: {:.2}%", dup.result.similarity * 100.0);
+
+            if let (Some(class1), Some(class2)) = (func1.class_name(), func2.class_name()) {
+                println!("  {}: {} <-> {}", display.container_label, class1, class2);
+            }
+            if let Some(details) = display.details {
+                details(dup);
+            }
+
+            if print {
+                show_function_code(&file_path, func1.name(), func1.start_line(), func1.end_line());
+                show_function_code(&other_path, func2.name(), func2.start_line(), func2.end_line());
+                println!();
+            }
+        }
+    }
+
+    println!("\nTotal duplicate pairs found: {}", all_results.len());
+}
+
+fn join_lines(lines: &[&str], (start_line, end_line): (u32, u32)) -> String {
+    let start_idx = (start_line.saturating_sub(1)) as usize;
+    let end_idx = std::cmp::min(end_line as usize, lines.len());
+
+    if start_idx >= end_idx {
+        return String::new();
+    }
+
+    lines[start_idx..end_idx].join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generic_tree_sitter_parser::GenericTreeSitterParser;
+    use crate::lsh::LshOptions;
+    use std::num::NonZeroU32;
+
+    struct Go;
+
+    impl LanguageSupport for Go {
+        fn name(&self) -> &str {
+            "Go"
+        }
+
+        fn default_extensions(&self) -> Vec<&str> {
+            vec!["go"]
+        }
+
+        fn create_parser(&self) -> Result<Box<dyn LanguageParser>, Box<dyn Error + Send + Sync>> {
+            Ok(Box::new(GenericTreeSitterParser::from_language_name("go")?))
+        }
+
+        fn is_test_function(&self, func: &GenericFunctionDef) -> bool {
+            func.name.starts_with("Test")
+        }
+    }
+
+    const SOURCE: &str = r#"package main
+
+func SumA(xs []int) int {
+	total := 0
+	for _, x := range xs {
+		total += x
+	}
+	return total
+}
+
+func TestSumB(values []int) int {
+	acc := 0
+	for _, v := range values {
+		acc += v
+	}
+	return acc
+}
+"#;
+
+    fn compare_options() -> CompareOptions {
+        CompareOptions { threshold: 0.8, ..Default::default() }
+    }
+
+    #[test]
+    fn test_find_similar_in_file() {
+        let stats = CandidateStats::default();
+        let results =
+            Go.find_similar_in_file("sum.go", SOURCE, &compare_options(), &stats).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].func1.name, "SumA");
+        assert_eq!(results[0].func2.name, "TestSumB");
+    }
+
+    #[test]
+    fn test_skip_test_and_min_tokens() {
+        let stats = CandidateStats::default();
+        let mut options = compare_options();
+        options.tsed_options.skip_test = true;
+        assert!(Go.find_similar_in_file("sum.go", SOURCE, &options, &stats).unwrap().is_empty());
+
+        let mut options = compare_options();
+        options.tsed_options.min_tokens = Some(10_000);
+        assert!(Go.find_similar_in_file("sum.go", SOURCE, &options, &stats).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_apply_settings() {
+        let settings = SimilaritySettings {
+            threshold: Some(0.7),
+            min_lines: Some(8),
+            lsh: Some(true),
+            lsh_rows: NonZeroU32::new(4),
+            exclude: vec!["vendor/**".to_string()],
+            overlap_min_window: Some(12),
+            ..Default::default()
+        };
+        let mut options = PipelineOptions {
+            compare: compare_options(),
+            exclude: vec!["target/**".to_string()],
+            ..Default::default()
+        };
+        options.apply_settings(&settings, |id| id == "min_lines");
+
+        assert_eq!(options.compare.threshold, 0.7);
+        assert_eq!(options.overlap.threshold, 0.7);
+        assert_eq!(options.compare.tsed_options.min_lines, TSEDOptions::default().min_lines);
+        assert_eq!(options.compare.lsh, Some(LshOptions { rows: 4, ..Default::default() }));
+        assert_eq!(options.exclude, ["target/**", "vendor/**"]);
+        assert_eq!(options.overlap.min_window_size, 12);
+    }
+
+    #[test]
+    fn test_cross_file_duplicates() {
+        let (sum_a, test_sum_b) = SOURCE.split_at(SOURCE.find("func TestSumB").unwrap());
+        let file = |path: &str, content: &str| FileData {
+            path: PathBuf::from(path),
+            content: content.to_string(),
+            functions: Go.extract_functions(path, content).unwrap(),
+        };
+        let file_data = vec![file("a.go", sum_a), file("b.go", test_sum_b), file("c.go", sum_a)];
+
+        let stats = CandidateStats::default();
+        let options = compare_options();
+        let trees = function_trees(&Go, &file_data, &options.tsed_options);
+        let results = check_cross_file_duplicates(&Go, &file_data, &trees, &options, &stats);
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|dup| dup.file1 != dup.file2));
+        assert_eq!(stats.pairs(), 3);
+
+        let mut options = compare_options();
+        options.tsed_options.skip_test = true;
+        let trees = function_trees(&Go, &file_data, &options.tsed_options);
+        let results = check_cross_file_duplicates(&Go, &file_data, &trees, &options, &stats);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].file1, PathBuf::from("a.go"));
+        assert_eq!(results[0].file2, PathBuf::from("c.go"));
+    }
+}
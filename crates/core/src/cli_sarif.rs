@@ -0,0 +1,273 @@
+//! SARIF 2.1.0 output so duplicates can be uploaded as code-scanning alerts
+
+use crate::cli_output::{Report, ReportEntry, ReportKind, ReportLocation};
+use serde::Serialize;
+
+const SARIF_VERSION: &str = "2.1.0";
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const INFORMATION_URI: &str = "https://github.com/mizchi/similarity";
+
+#[derive(Debug, Serialize)]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    pub schema: &'static str,
+    pub version: &'static str,
+    pub runs: Vec<SarifRun>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SarifRun {
+    pub tool: SarifTool,
+    pub results: Vec<SarifResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SarifTool {
+    pub driver: SarifDriver,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifDriver {
+    pub name: String,
+    pub version: String,
+    pub information_uri: &'static str,
+    pub rules: Vec<SarifRule>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRule {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub short_description: SarifMessage,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SarifMessage {
+    pub text: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifResult {
+    pub rule_id: &'static str,
+    pub rule_index: usize,
+    pub level: &'static str,
+    pub message: SarifMessage,
+    pub locations: Vec<SarifLocation>,
+    pub related_locations: Vec<SarifLocation>,
+    pub properties: SarifResultProperties,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SarifResultProperties {
+    pub similarity: f64,
+    pub impact: u32,
+    pub priority: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifLocation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<usize>,
+    pub physical_location: SarifPhysicalLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<SarifMessage>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifPhysicalLocation {
+    pub artifact_location: SarifArtifactLocation,
+    pub region: SarifRegion,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifArtifactLocation {
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri_base_id: Option<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRegion {
+    pub start_line: u32,
+    pub end_line: u32,
+}
+
+/// Rules in the order used for `ruleIndex`
+const RULES: [ReportKind; 3] = [ReportKind::Function, ReportKind::Type, ReportKind::Overlap];
+
+/// Stable rule ID for each analyzer
+pub fn rule_id(kind: ReportKind) -> &'static str {
+    match kind {
+        ReportKind::Function => "duplicate-function",
+        ReportKind::Type => "duplicate-type",
+        ReportKind::Overlap => "code-overlap",
+    }
+}
+
+fn rule(kind: ReportKind) -> SarifRule {
+    let (name, description) = match kind {
+        ReportKind::Function => {
+            ("DuplicateFunction", "Function is structurally similar to another")
+        }
+        ReportKind::Type => ("DuplicateType", "Type definition is similar to another"),
+        ReportKind::Overlap => ("CodeOverlap", "Code fragment overlaps with another fragment"),
+    };
+    SarifRule {
+        id: rule_id(kind),
+        name,
+        short_description: SarifMessage { text: description.to_string() },
+    }
+}
+
+fn label(kind: ReportKind) -> &'static str {
+    match kind {
+        ReportKind::Function => "Function",
+        ReportKind::Type => "Type",
+        ReportKind::Overlap => "Code in",
+    }
+}
+
+/// Convert a report path into a SARIF artifact location
+fn artifact_location(file: &str) -> SarifArtifactLocation {
+    let normalized = file.replace('\\', "/");
+    if normalized.starts_with('/') {
+        SarifArtifactLocation { uri: format!("file://{normalized}"), uri_base_id: None }
+    } else {
+        let relative = normalized.trim_start_matches("./").to_string();
+        SarifArtifactLocation { uri: relative, uri_base_id: Some("%SRCROOT%") }
+    }
+}
+
+fn location(
+    loc: &ReportLocation,
+    id: Option<usize>,
+    message: Option<SarifMessage>,
+) -> SarifLocation {
+    SarifLocation {
+        id,
+        physical_location: SarifPhysicalLocation {
+            artifact_location: artifact_location(&loc.file),
+            region: SarifRegion {
+                start_line: loc.start_line.max(1),
+                end_line: loc.end_line.max(1),
+            },
+        },
+        message,
+    }
+}
+
+fn to_result(entry: &ReportEntry) -> SarifResult {
+    let rule_index = RULES.iter().position(|kind| *kind == entry.kind).unwrap_or(0);
+    let text = format!(
+        "{} '{}' is {:.2}% similar to [{}](1) ({}:{}-{})",
+        label(entry.kind),
+        entry.first.name,
+        entry.similarity * 100.0,
+        entry.second.name,
+        entry.second.file,
+        entry.second.start_line,
+        entry.second.end_line
+    );
+    let related_message = SarifMessage { text: entry.second.name.clone() };
+
+    SarifResult {
+        rule_id: rule_id(entry.kind),
+        rule_index,
+        level: "warning",
+        message: SarifMessage { text },
+        locations: vec![location(&entry.first, None, None)],
+        related_locations: vec![location(&entry.second, Some(1), Some(related_message))],
+        properties: SarifResultProperties {
+            similarity: entry.similarity,
+            impact: entry.impact,
+            priority: entry.priority,
+        },
+    }
+}
+
+/// Build a SARIF log with one run for the report
+pub fn to_sarif(report: &Report) -> SarifLog {
+    SarifLog {
+        schema: SARIF_SCHEMA,
+        version: SARIF_VERSION,
+        runs: vec![SarifRun {
+            tool: SarifTool {
+                driver: SarifDriver {
+                    name: report.tool.clone(),
+                    version: report.version.clone(),
+                    information_uri: INFORMATION_URI,
+                    rules: RULES.iter().map(|kind| rule(*kind)).collect(),
+                },
+            },
+            results: report.duplicates.iter().map(to_result).collect(),
+        }],
+    }
+}
+
+/// Serialize the report as a SARIF 2.1.0 document
+pub fn to_sarif_json(report: &Report) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(&to_sarif(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_report() -> Report {
+        let mut report = Report::new("similarity-ts", "0.0.0");
+        report.extend([
+            ReportEntry::new(
+                ReportKind::Function,
+                ReportLocation::new("./src/a.ts", "foo", 1, 10),
+                ReportLocation::new("src/b.ts", "bar", 20, 30),
+                0.95,
+            ),
+            ReportEntry::new(
+                ReportKind::Overlap,
+                ReportLocation::new("/abs/c.ts", "baz", 3, 5),
+                ReportLocation::new("/abs/c.ts", "qux", 8, 10),
+                0.9,
+            ),
+        ]);
+        report
+    }
+
+    #[test]
+    fn test_sarif_structure() {
+        let value: serde_json::Value =
+            serde_json::from_str(&to_sarif_json(&sample_report()).unwrap()).unwrap();
+
+        assert_eq!(value["version"], "2.1.0");
+        let run = &value["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "similarity-ts");
+        assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 3);
+
+        let result = &run["results"][0];
+        assert_eq!(result["ruleId"], "duplicate-function");
+        assert_eq!(result["ruleIndex"], 0);
+        let primary = &result["locations"][0]["physicalLocation"];
+        assert_eq!(primary["artifactLocation"]["uri"], "src/a.ts");
+        assert_eq!(primary["artifactLocation"]["uriBaseId"], "%SRCROOT%");
+        assert_eq!(primary["region"]["startLine"], 1);
+        let related = &result["relatedLocations"][0];
+        assert_eq!(related["id"], 1);
+        assert_eq!(related["physicalLocation"]["region"]["startLine"], 20);
+    }
+
+    #[test]
+    fn test_sarif_rule_per_analyzer() {
+        let sarif = to_sarif(&sample_report());
+        let result = &sarif.runs[0].results[1];
+        assert_eq!(result.rule_id, "code-overlap");
+        assert_eq!(result.rule_index, 2);
+        assert_eq!(result.locations[0].physical_location.artifact_location.uri, "file:///abs/c.ts");
+        assert_eq!(rule_id(ReportKind::Type), "duplicate-type");
+    }
+}
@@ -0,0 +1,212 @@
+//! Reporting of similar type definitions.
+//!
+//! The type analyzers of every CLI extract [`TypeDefinition`]s in their own way, score them
+//! with the [`type_comparator`](crate::type_comparator) and print the pairs here, so an
+//! interface, a struct or a class looks the same in every report.
+
+use crate::cli_baseline::Baseline;
+use crate::cli_output::{relative_path, OutputFormat, ReportEntry, ReportKind, ReportLocation};
+use crate::type_comparator::{SimilarTypePair, TypeComparisonResult};
+use crate::type_extractor::{TypeDefinition, TypeKind};
+
+/// Short name of a type kind for the report
+pub fn format_type_kind(kind: &TypeKind) -> &'static str {
+    match kind {
+        TypeKind::Interface => "interface",
+        TypeKind::TypeAlias => "type",
+        TypeKind::TypeLiteral => "type literal",
+        TypeKind::Struct => "struct",
+        TypeKind::Enum => "enum",
+        TypeKind::Class => "class",
+    }
+}
+
+/// Report location of a type definition
+pub fn type_location(type_def: &TypeDefinition) -> ReportLocation {
+    ReportLocation::new(
+        relative_path(&type_def.file_path),
+        &type_def.name,
+        type_def.start_line as u32,
+        type_def.end_line as u32,
+    )
+}
+
+/// Display the generics, supertypes and properties of a type definition
+pub fn show_type_details(type_def: &TypeDefinition) {
+    println!("\n\x1b[36m--- {} ({}) ---\x1b[0m", type_def.name, format_type_kind(&type_def.kind));
+
+    if !type_def.generics.is_empty() {
+        println!("Generics: <{}>", type_def.generics.join(", "));
+    }
+
+    // Rust items record their derives and Python classes their bases where TypeScript
+    // records extended interfaces; their optional fields are spelled out in the type
+    let (extends_label, properties_label, marks_optional) = match type_def.kind {
+        TypeKind::Struct => ("Derives", "Fields", false),
+        TypeKind::Enum => ("Derives", "Variants", false),
+        TypeKind::Class => ("Bases", "Attributes", false),
+        _ => ("Extends", "Properties", true),
+    };
+
+    if !type_def.extends.is_empty() {
+        println!("{}: {}", extends_label, type_def.extends.join(", "));
+    }
+
+    if !type_def.properties.is_empty() {
+        println!("{properties_label}:");
+        for prop in &type_def.properties {
+            let modifiers = if prop.readonly { "readonly " } else { "" };
+            let optional = if prop.optional && marks_optional { "?" } else { "" };
+            println!("  {}{}{}: {}", modifiers, prop.name, optional, prop.type_annotation);
+        }
+    }
+}
+
+/// Display how the properties of two compared types differ
+pub fn show_comparison_details(result: &TypeComparisonResult) {
+    if !result.differences.missing_properties.is_empty() {
+        println!("Missing properties: {}", result.differences.missing_properties.join(", "));
+    }
+
+    if !result.differences.extra_properties.is_empty() {
+        println!("Extra properties: {}", result.differences.extra_properties.join(", "));
+    }
+
+    if !result.differences.type_mismatches.is_empty() {
+        println!("Type mismatches:");
+        for mismatch in &result.differences.type_mismatches {
+            println!("  {}: {} vs {}", mismatch.property, mismatch.type1, mismatch.type2);
+        }
+    }
+
+    if !result.differences.optionality_differences.is_empty() {
+        println!(
+            "Optionality differences: {}",
+            result.differences.optionality_differences.join(", ")
+        );
+    }
+}
+
+/// Convert similar type pairs into machine readable report entries
+pub fn type_report_entries(pairs: &[SimilarTypePair]) -> Vec<ReportEntry> {
+    pairs
+        .iter()
+        .map(|pair| {
+            ReportEntry::new(
+                ReportKind::Type,
+                type_location(&pair.type1),
+                type_location(&pair.type2),
+                pair.result.similarity,
+            )
+        })
+        .collect()
+}
+
+/// Display similar type pairs in the `path:line | Lstart-end similar-type: Name (kind)` format
+pub fn display_similar_types(pairs: &[SimilarTypePair], print: bool) {
+    println!("\nSimilar types found:");
+    println!("{}", "-".repeat(60));
+
+    for pair in pairs {
+        println!(
+            "\nSimilarity: {:.2}% (structural: {:.2}%, naming: {:.2}%)",
+            pair.result.similarity * 100.0,
+            pair.result.structural_similarity * 100.0,
+            pair.result.naming_similarity * 100.0
+        );
+        for type_def in [&pair.type1, &pair.type2] {
+            println!(
+                "  {}:{} | L{}-{} similar-type: {} ({})",
+                relative_path(&type_def.file_path),
+                type_def.start_line,
+                type_def.start_line,
+                type_def.end_line,
+                type_def.name,
+                format_type_kind(&type_def.kind)
+            );
+        }
+
+        if print {
+            show_type_details(&pair.type1);
+            show_type_details(&pair.type2);
+            show_comparison_details(&pair.result);
+        }
+    }
+
+    println!("\nTotal similar type pairs found: {}", pairs.len());
+}
+
+/// Drop baselined pairs, display the rest and return their report entries
+pub fn report_similar_types(
+    mut pairs: Vec<SimilarTypePair>,
+    print: bool,
+    format: OutputFormat,
+    baseline: Option<&Baseline>,
+) -> Vec<ReportEntry> {
+    let mut entries = type_report_entries(&pairs);
+    if let Some(baseline) = baseline {
+        baseline.retain_new(&mut pairs, &mut entries);
+    }
+
+    if format.is_text() {
+        if pairs.is_empty() {
+            println!("\nNo similar types found!");
+        } else {
+            display_similar_types(&pairs, print);
+        }
+    }
+
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::type_comparator::{find_similar_types, TypeComparisonOptions};
+    use crate::type_extractor::PropertyDefinition;
+
+    fn struct_def(name: &str, fields: &[(&str, &str)]) -> TypeDefinition {
+        TypeDefinition {
+            name: name.to_string(),
+            kind: TypeKind::Struct,
+            properties: fields
+                .iter()
+                .map(|(name, type_annotation)| PropertyDefinition {
+                    name: name.to_string(),
+                    type_annotation: type_annotation.to_string(),
+                    optional: false,
+                    readonly: false,
+                })
+                .collect(),
+            generics: Vec::new(),
+            extends: Vec::new(),
+            start_line: 1,
+            end_line: 4,
+            file_path: "src/model.rs".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_type_report_entries() {
+        let types = vec![
+            struct_def("UserDto", &[("id", "u64"), ("name", "String")]),
+            struct_def("UserRecord", &[("id", "u64"), ("name", "String")]),
+        ];
+        let pairs = find_similar_types(&types, 0.8, &TypeComparisonOptions::default());
+
+        let entries = type_report_entries(&pairs);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].kind, ReportKind::Type);
+        assert_eq!(entries[0].first.name, "UserDto");
+        assert_eq!(entries[0].second.name, "UserRecord");
+        assert_eq!(entries[0].first.file, "src/model.rs");
+    }
+
+    #[test]
+    fn test_format_type_kind() {
+        assert_eq!(format_type_kind(&TypeKind::Interface), "interface");
+        assert_eq!(format_type_kind(&TypeKind::Struct), "struct");
+        assert_eq!(format_type_kind(&TypeKind::Enum), "enum");
+        assert_eq!(format_type_kind(&TypeKind::Class), "class");
+    }
+}
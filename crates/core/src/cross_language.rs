@@ -0,0 +1,1267 @@
+//! Cross-language comparison of TypeScript/JavaScript and Python functions.
+//!
+//! Both languages are lowered into a common intermediate representation: a [`TreeNode`] tree
+//! whose labels are language independent kinds (`Loop`, `If`, `Call`, `Map`, ...) and whose
+//! values carry names and literals. `for item in items` and `for (const item of items)` both
+//! become `Loop[Name, Name, Block]`, ternaries, `if` statements and `switch` chains all become
+//! `If`, and array comprehensions, `map`, `filter`, `reduce` and `forEach` are unified with the
+//! equivalent method calls. Type annotations are dropped, so typed and untyped code compare
+//! equal. The IR trees are compared with TSED like any other tree.
+
+use crate::language_parser::Language;
+use crate::tree::{ArenaTree, LineIndex, TreeNode};
+use crate::tsed::{calculate_tsed, TSEDOptions};
+use oxc_allocator::Allocator;
+use oxc_ast::ast::{
+    Argument, ArrayExpressionElement, AssignmentTarget, BindingPatternKind, ChainElement,
+    ClassElement, Declaration, ExportDefaultDeclarationKind, Expression, ForStatementInit,
+    ForStatementLeft, FormalParameters, Function, FunctionBody, ObjectPropertyKind, PropertyKey,
+    SimpleAssignmentTarget, Statement, VariableDeclaration,
+};
+use oxc_parser::Parser;
+use oxc_span::{SourceType, Span};
+use rayon::prelude::*;
+use std::rc::Rc;
+use tree_sitter::Node;
+
+/// A function lowered into the cross-language IR
+#[derive(Debug, Clone)]
+pub struct NormalizedFunction {
+    /// Function name, `Class.method` for methods
+    pub name: String,
+    pub language: Language,
+    pub start_line: u32,
+    pub end_line: u32,
+    pub tree: ArenaTree,
+}
+
+impl NormalizedFunction {
+    pub fn line_count(&self) -> u32 {
+        self.end_line.saturating_sub(self.start_line) + 1
+    }
+
+    /// TypeScript and JavaScript count as one language
+    fn language_family(&self) -> Language {
+        match self.language {
+            Language::JavaScript => Language::TypeScript,
+            language => language,
+        }
+    }
+}
+
+/// Two functions in different languages whose IR trees are similar
+#[derive(Debug, Clone)]
+pub struct CrossLanguageMatch<'a> {
+    pub file1: &'a str,
+    pub func1: &'a NormalizedFunction,
+    pub file2: &'a str,
+    pub func2: &'a NormalizedFunction,
+    pub similarity: f64,
+}
+
+/// Whether `filename` is in a language the IR can be built for
+pub fn is_supported_file(filename: &str) -> bool {
+    matches!(
+        Language::from_filename(filename),
+        Some(Language::TypeScript | Language::JavaScript | Language::Python)
+    )
+}
+
+/// Lower every top-level function and method of a TypeScript/JavaScript or Python file
+pub fn normalize_functions(
+    filename: &str,
+    source: &str,
+) -> Result<Vec<NormalizedFunction>, String> {
+    match Language::from_filename(filename) {
+        Some(Language::Python) => normalize_python(source),
+        _ if is_supported_file(filename) => normalize_typescript(filename, source),
+        _ => Err(format!("Unsupported language for cross-language comparison: {filename}")),
+    }
+}
+
+/// Find similar pairs of functions written in different languages.
+///
+/// `files` holds the functions of each file as returned by [`normalize_functions`]. Functions
+/// shorter than `options.min_lines` (or `options.min_tokens` IR nodes) are skipped; results
+/// are sorted by similarity, then by size.
+pub fn find_cross_language_duplicates<'a>(
+    files: &'a [(String, Vec<NormalizedFunction>)],
+    threshold: f64,
+    options: &TSEDOptions,
+) -> Vec<CrossLanguageMatch<'a>> {
+    let long_enough = |func: &NormalizedFunction| match options.min_tokens {
+        Some(min_tokens) => func.tree.len() >= min_tokens as usize,
+        None => func.line_count() >= options.min_lines,
+    };
+    let functions: Vec<(&str, &NormalizedFunction)> = files
+        .iter()
+        .flat_map(|(file, functions)| functions.iter().map(move |func| (file.as_str(), func)))
+        .filter(|(_, func)| long_enough(func))
+        .collect();
+
+    let pairs: Vec<(usize, usize)> = (0..functions.len())
+        .flat_map(|i| ((i + 1)..functions.len()).map(move |j| (i, j)))
+        .filter(|&(i, j)| functions[i].1.language_family() != functions[j].1.language_family())
+        .collect();
+
+    let mut matches: Vec<CrossLanguageMatch> = pairs
+        .into_par_iter()
+        .filter_map(|(i, j)| {
+            let ((file1, func1), (file2, func2)) = (functions[i], functions[j]);
+            let similarity = calculate_tsed(&func1.tree, &func2.tree, options);
+            (similarity >= threshold).then_some(CrossLanguageMatch {
+                file1,
+                func1,
+                file2,
+                func2,
+                similarity,
+            })
+        })
+        .collect();
+
+    matches.sort_by(|a, b| {
+        b.similarity
+            .partial_cmp(&a.similarity)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| b.func1.line_count().cmp(&a.func1.line_count()))
+    });
+    matches
+}
+
+fn node(label: &str, value: &str, children: Vec<TreeNode>) -> TreeNode {
+    let mut node = TreeNode::new(label.to_string(), value.to_string(), 0);
+    for child in children {
+        node.add_child(Rc::new(child));
+    }
+    node
+}
+
+fn leaf(label: &str, value: &str) -> TreeNode {
+    node(label, value, Vec::new())
+}
+
+fn function_node(params: Vec<TreeNode>, body: TreeNode) -> TreeNode {
+    node("Function", "", vec![node("Params", "", params), body])
+}
+
+/// Unified operator spelling: `===` and `is` are `==`, `and` is `&&`, ...
+fn normalize_operator(op: &str) -> &str {
+    match op {
+        "===" | "is" => "==",
+        "!==" | "is not" => "!=",
+        "and" => "&&",
+        "or" => "||",
+        "not" => "!",
+        "//" => "/",
+        "not in" => "!in",
+        op => op,
+    }
+}
+
+/// Calls with a cross-language equivalent: `console.log` / `print`, `x.length` / `len(x)`
+fn builtin_name(name: &str) -> Option<&'static str> {
+    match name {
+        "console.log" | "console.error" | "console.warn" | "print" => Some("print"),
+        _ => None,
+    }
+}
+
+/// Higher-order function kinds shared by both languages
+fn higher_order_kind(name: &str) -> Option<&'static str> {
+    match name {
+        "map" => Some("Map"),
+        "filter" => Some("Filter"),
+        "reduce" => Some("Reduce"),
+        _ => None,
+    }
+}
+
+// ---------------------------------------------------------------------------------------------
+// TypeScript / JavaScript (oxc)
+// ---------------------------------------------------------------------------------------------
+
+/// Lower the functions of a TypeScript or JavaScript file
+pub fn normalize_typescript(
+    filename: &str,
+    source: &str,
+) -> Result<Vec<NormalizedFunction>, String> {
+    let allocator = Allocator::default();
+    let source_type = SourceType::from_path(filename).unwrap_or_else(|_| SourceType::tsx());
+    let ret = Parser::new(&allocator, source, source_type).parse();
+    if !ret.errors.is_empty() {
+        return Err(format!("Parse errors: {:?}", ret.errors));
+    }
+
+    let language = match Language::from_filename(filename) {
+        Some(Language::JavaScript) => Language::JavaScript,
+        _ => Language::TypeScript,
+    };
+    let mut lowering = OxcLowering { lines: LineIndex::new(source), language, functions: vec![] };
+    for stmt in &ret.program.body {
+        lowering.collect_statement(stmt);
+    }
+    Ok(lowering.functions)
+}
+
+struct OxcLowering {
+    lines: LineIndex,
+    language: Language,
+    functions: Vec<NormalizedFunction>,
+}
+
+impl OxcLowering {
+    fn push(&mut self, name: String, span: Span, tree: TreeNode) {
+        let (start_line, _) = self.lines.line_column(span.start as usize);
+        let (end_line, _) = self.lines.line_column(span.end as usize);
+        self.functions.push(NormalizedFunction {
+            name,
+            language: self.language,
+            start_line,
+            end_line,
+            tree: ArenaTree::from_node(&tree),
+        });
+    }
+
+    /// Collect top-level functions, functions assigned to variables and class methods
+    fn collect_statement(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::FunctionDeclaration(func) => self.collect_function(func, None),
+            Statement::ClassDeclaration(class) => self.collect_class(class),
+            Statement::VariableDeclaration(decl) => self.collect_variables(decl),
+            Statement::ExportNamedDeclaration(export) => match &export.declaration {
+                Some(Declaration::FunctionDeclaration(func)) => self.collect_function(func, None),
+                Some(Declaration::ClassDeclaration(class)) => self.collect_class(class),
+                Some(Declaration::VariableDeclaration(decl)) => self.collect_variables(decl),
+                _ => {}
+            },
+            Statement::ExportDefaultDeclaration(export) => match &export.declaration {
+                ExportDefaultDeclarationKind::FunctionDeclaration(func) => {
+                    self.collect_function(func, None)
+                }
+                ExportDefaultDeclarationKind::ClassDeclaration(class) => self.collect_class(class),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn collect_function(&mut self, func: &Function, name: Option<String>) {
+        let Some(body) = &func.body else { return };
+        let name = name
+            .or_else(|| func.id.as_ref().map(|id| id.name.to_string()))
+            .unwrap_or_else(|| "default".to_string());
+        let tree = function_node(lower_params(&func.params), lower_body(body));
+        self.push(name, func.span, tree);
+    }
+
+    fn collect_class(&mut self, class: &oxc_ast::ast::Class) {
+        let class_name = class.id.as_ref().map_or("Class", |id| id.name.as_str());
+        for element in &class.body.body {
+            if let ClassElement::MethodDefinition(method) = element {
+                let method_name = method.key.static_name().unwrap_or_default();
+                let name = format!("{class_name}.{method_name}");
+                let Some(body) = &method.value.body else { continue };
+                let tree = function_node(lower_params(&method.value.params), lower_body(body));
+                self.push(name, method.span, tree);
+            }
+        }
+    }
+
+    fn collect_variables(&mut self, decl: &VariableDeclaration) {
+        for declarator in &decl.declarations {
+            let BindingPatternKind::BindingIdentifier(id) = &declarator.id.kind else {
+                continue;
+            };
+            match &declarator.init {
+                Some(Expression::ArrowFunctionExpression(arrow)) => {
+                    let body = if arrow.expression {
+                        node(
+                            "Block",
+                            "",
+                            vec![node("Return", "", lower_body_statements(&arrow.body))],
+                        )
+                    } else {
+                        lower_body(&arrow.body)
+                    };
+                    let tree = function_node(lower_params(&arrow.params), body);
+                    self.push(id.name.to_string(), declarator.span, tree);
+                }
+                Some(Expression::FunctionExpression(func)) => {
+                    self.collect_function(func, Some(id.name.to_string()))
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn lower_params(params: &FormalParameters) -> Vec<TreeNode> {
+    let mut nodes: Vec<TreeNode> = params
+        .items
+        .iter()
+        .map(|param| match &param.pattern.kind {
+            BindingPatternKind::AssignmentPattern(pattern) => {
+                let name = binding_name(&pattern.left.kind);
+                node("Param", &name, vec![lower_expr(&pattern.right)])
+            }
+            kind => leaf("Param", &binding_name(kind)),
+        })
+        .collect();
+    if let Some(rest) = &params.rest {
+        nodes.push(leaf("Param", &binding_name(&rest.argument.kind)));
+    }
+    nodes
+}
+
+fn binding_name(kind: &BindingPatternKind) -> String {
+    match kind {
+        BindingPatternKind::BindingIdentifier(id) => id.name.to_string(),
+        BindingPatternKind::AssignmentPattern(pattern) => binding_name(&pattern.left.kind),
+        _ => "pattern".to_string(),
+    }
+}
+
+fn lower_body(body: &FunctionBody) -> TreeNode {
+    node("Block", "", body.statements.iter().filter_map(lower_stmt).collect())
+}
+
+/// Expressions of an arrow function with an expression body
+fn lower_body_statements(body: &FunctionBody) -> Vec<TreeNode> {
+    body.statements
+        .iter()
+        .filter_map(|stmt| match stmt {
+            Statement::ExpressionStatement(expr) => Some(lower_expr(&expr.expression)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Statements are always wrapped in a `Block`, like Python's indented suites
+fn lower_block(stmt: &Statement) -> TreeNode {
+    match stmt {
+        Statement::BlockStatement(block) => {
+            node("Block", "", block.body.iter().filter_map(lower_stmt).collect())
+        }
+        stmt => node("Block", "", lower_stmt(stmt).into_iter().collect()),
+    }
+}
+
+fn lower_stmt(stmt: &Statement) -> Option<TreeNode> {
+    Some(match stmt {
+        Statement::ExpressionStatement(expr) => lower_expr(&expr.expression),
+        Statement::BlockStatement(_) => lower_block(stmt),
+        Statement::VariableDeclaration(decl) => lower_variables(decl)?,
+        Statement::ReturnStatement(ret) => {
+            node("Return", "", ret.argument.iter().map(lower_expr).collect())
+        }
+        Statement::IfStatement(if_stmt) => {
+            let mut children = vec![lower_expr(&if_stmt.test), lower_block(&if_stmt.consequent)];
+            if let Some(alternate) = &if_stmt.alternate {
+                children.push(match alternate {
+                    // `else if` chains nest like Python's `elif`
+                    Statement::IfStatement(_) => lower_stmt(alternate)?,
+                    _ => lower_block(alternate),
+                });
+            }
+            node("If", "", children)
+        }
+        Statement::ForOfStatement(for_of) => node(
+            "Loop",
+            "",
+            vec![
+                lower_for_left(&for_of.left),
+                lower_expr(&for_of.right),
+                lower_block(&for_of.body),
+            ],
+        ),
+        Statement::ForInStatement(for_in) => node(
+            "Loop",
+            "",
+            vec![
+                lower_for_left(&for_in.left),
+                lower_expr(&for_in.right),
+                lower_block(&for_in.body),
+            ],
+        ),
+        Statement::ForStatement(for_stmt) => lower_counting_loop(for_stmt),
+        Statement::WhileStatement(while_stmt) => {
+            node("Loop", "", vec![lower_expr(&while_stmt.test), lower_block(&while_stmt.body)])
+        }
+        Statement::DoWhileStatement(do_while) => {
+            node("Loop", "", vec![lower_expr(&do_while.test), lower_block(&do_while.body)])
+        }
+        Statement::SwitchStatement(switch) => {
+            // A chain of `If`s comparing the discriminant with each case
+            let mut chain: Option<TreeNode> = None;
+            for case in switch.cases.iter().rev() {
+                let body =
+                    node("Block", "", case.consequent.iter().filter_map(lower_stmt).collect());
+                chain = Some(match &case.test {
+                    Some(test) => {
+                        let cond = node(
+                            "==",
+                            "",
+                            vec![lower_expr(&switch.discriminant), lower_expr(test)],
+                        );
+                        node("If", "", [cond, body].into_iter().chain(chain).collect())
+                    }
+                    None => body,
+                });
+            }
+            chain?
+        }
+        Statement::ThrowStatement(throw) => node("Throw", "", vec![lower_expr(&throw.argument)]),
+        Statement::TryStatement(try_stmt) => {
+            let block = |stmts: &[Statement]| {
+                node("Block", "", stmts.iter().filter_map(lower_stmt).collect())
+            };
+            let mut children = vec![block(&try_stmt.block.body)];
+            if let Some(handler) = &try_stmt.handler {
+                children.push(node("Catch", "", vec![block(&handler.body.body)]));
+            }
+            if let Some(finalizer) = &try_stmt.finalizer {
+                children.push(node("Finally", "", vec![block(&finalizer.body)]));
+            }
+            node("Try", "", children)
+        }
+        Statement::BreakStatement(_) => leaf("Break", ""),
+        Statement::ContinueStatement(_) => leaf("Continue", ""),
+        Statement::EmptyStatement(_) => return None,
+        Statement::FunctionDeclaration(func) => {
+            let body =
+                func.body.as_ref().map_or_else(|| leaf("Block", ""), |body| lower_body(body));
+            function_node(lower_params(&func.params), body)
+        }
+        _ => leaf("Statement", ""),
+    })
+}
+
+fn lower_variables(decl: &VariableDeclaration) -> Option<TreeNode> {
+    let mut assignments: Vec<TreeNode> = decl
+        .declarations
+        .iter()
+        .filter_map(|declarator| {
+            let init = declarator.init.as_ref()?;
+            Some(node(
+                "Assign",
+                "=",
+                vec![leaf("Name", &binding_name(&declarator.id.kind)), lower_expr(init)],
+            ))
+        })
+        .collect();
+    match assignments.len() {
+        0 => None,
+        1 => assignments.pop(),
+        _ => Some(node("Block", "", assignments)),
+    }
+}
+
+fn lower_for_left(left: &ForStatementLeft) -> TreeNode {
+    match left {
+        ForStatementLeft::VariableDeclaration(decl) => decl.declarations.first().map_or_else(
+            || leaf("Name", ""),
+            |declarator| leaf("Name", &binding_name(&declarator.id.kind)),
+        ),
+        left => {
+            left.as_assignment_target().map_or_else(|| leaf("Name", ""), lower_assignment_target)
+        }
+    }
+}
+
+/// `for (let i = a; i < n; i++)` becomes the loop over `range(a, n)` a Python author would
+/// write; other `for` loops keep their init, test and update
+fn lower_counting_loop(for_stmt: &oxc_ast::ast::ForStatement) -> TreeNode {
+    let counter = match &for_stmt.init {
+        Some(ForStatementInit::VariableDeclaration(decl)) if decl.declarations.len() == 1 => {
+            let declarator = &decl.declarations[0];
+            match (&declarator.id.kind, &declarator.init) {
+                (BindingPatternKind::BindingIdentifier(id), Some(init)) => {
+                    Some((id.name.as_str(), init))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    let bound = match (&for_stmt.test, counter) {
+        (Some(Expression::BinaryExpression(test)), Some((name, _)))
+            if test.operator.as_str() == "<"
+                && matches!(&test.left, Expression::Identifier(id) if id.name == name) =>
+        {
+            Some(&test.right)
+        }
+        _ => None,
+    };
+    let increments = match &for_stmt.update {
+        Some(Expression::UpdateExpression(update)) => update.operator.as_str() == "++",
+        _ => false,
+    };
+
+    if let (Some((name, start)), Some(bound), true) = (counter, bound, increments) {
+        let mut args = Vec::new();
+        if !matches!(start, Expression::NumericLiteral(lit) if lit.value == 0.0) {
+            args.push(lower_expr(start));
+        }
+        args.push(lower_expr(bound));
+        let range = node("Call", "", [leaf("Name", "range")].into_iter().chain(args).collect());
+        return node("Loop", "", vec![leaf("Name", name), range, lower_block(&for_stmt.body)]);
+    }
+
+    let mut children = Vec::new();
+    if let Some(init) = &for_stmt.init {
+        match init {
+            ForStatementInit::VariableDeclaration(decl) => children.extend(lower_variables(decl)),
+            init => children.extend(init.as_expression().map(lower_expr)),
+        }
+    }
+    children.extend(for_stmt.test.iter().map(lower_expr));
+    children.extend(for_stmt.update.iter().map(lower_expr));
+    children.push(lower_block(&for_stmt.body));
+    node("Loop", "", children)
+}
+
+fn lower_assignment_target(target: &AssignmentTarget) -> TreeNode {
+    match target.as_simple_assignment_target() {
+        Some(simple) => lower_simple_target(simple),
+        None => leaf("Name", "pattern"),
+    }
+}
+
+fn lower_simple_target(target: &SimpleAssignmentTarget) -> TreeNode {
+    match target {
+        SimpleAssignmentTarget::AssignmentTargetIdentifier(id) => leaf("Name", &id.name),
+        SimpleAssignmentTarget::TSAsExpression(expr) => lower_expr(&expr.expression),
+        SimpleAssignmentTarget::TSNonNullExpression(expr) => lower_expr(&expr.expression),
+        SimpleAssignmentTarget::TSSatisfiesExpression(expr) => lower_expr(&expr.expression),
+        target => match target.as_member_expression() {
+            Some(member) => lower_member(member),
+            None => leaf("Name", ""),
+        },
+    }
+}
+
+fn lower_member(member: &oxc_ast::ast::MemberExpression) -> TreeNode {
+    use oxc_ast::ast::MemberExpression;
+    match member {
+        MemberExpression::StaticMemberExpression(expr) => {
+            node("Member", "", vec![lower_expr(&expr.object), leaf("Name", &expr.property.name)])
+        }
+        MemberExpression::ComputedMemberExpression(expr) => {
+            node("Index", "", vec![lower_expr(&expr.object), lower_expr(&expr.expression)])
+        }
+        MemberExpression::PrivateFieldExpression(expr) => {
+            node("Member", "", vec![lower_expr(&expr.object), leaf("Name", &expr.field.name)])
+        }
+    }
+}
+
+fn lower_arguments(arguments: &[Argument]) -> Vec<TreeNode> {
+    arguments
+        .iter()
+        .map(|arg| arg.as_expression().map_or_else(|| leaf("Spread", ""), lower_expr))
+        .collect()
+}
+
+/// Dotted name of a callee like `console.log`, if it is one
+fn callee_path(expr: &Expression) -> Option<String> {
+    match expr {
+        Expression::Identifier(id) => Some(id.name.to_string()),
+        Expression::StaticMemberExpression(member) => {
+            Some(format!("{}.{}", callee_path(&member.object)?, member.property.name))
+        }
+        _ => None,
+    }
+}
+
+fn lower_call(callee: &Expression, arguments: &[Argument]) -> TreeNode {
+    if let Some(name) = callee_path(callee).as_deref().and_then(builtin_name) {
+        return node(
+            "Call",
+            "",
+            [leaf("Name", name)].into_iter().chain(lower_arguments(arguments)).collect(),
+        );
+    }
+    if let Expression::StaticMemberExpression(member) = callee {
+        let method = member.property.name.as_str();
+        // `xs.map(f)` is `Map[xs, f]`, like Python's `map(f, xs)` and comprehensions
+        if let Some(kind) = higher_order_kind(method) {
+            let children =
+                [lower_expr(&member.object)].into_iter().chain(lower_arguments(arguments));
+            return node(kind, "", children.collect());
+        }
+        // `xs.forEach(x => ...)` is a loop over `xs`
+        if method == "forEach" {
+            if let Some(Expression::ArrowFunctionExpression(arrow)) =
+                arguments.first().and_then(Argument::as_expression)
+            {
+                let item = arrow
+                    .params
+                    .items
+                    .first()
+                    .map_or_else(String::new, |p| binding_name(&p.pattern.kind));
+                let body = if arrow.expression {
+                    node("Block", "", lower_body_statements(&arrow.body))
+                } else {
+                    lower_body(&arrow.body)
+                };
+                return node(
+                    "Loop",
+                    "",
+                    vec![leaf("Name", &item), lower_expr(&member.object), body],
+                );
+            }
+        }
+    }
+    node("Call", "", [lower_expr(callee)].into_iter().chain(lower_arguments(arguments)).collect())
+}
+
+fn lower_expr(expr: &Expression) -> TreeNode {
+    match expr {
+        Expression::Identifier(id) if id.name == "undefined" => leaf("Null", ""),
+        Expression::Identifier(id) => leaf("Name", &id.name),
+        Expression::ThisExpression(_) => leaf("Self", ""),
+        Expression::StringLiteral(lit) => leaf("String", &lit.value),
+        Expression::TemplateLiteral(_) => leaf("String", ""),
+        Expression::NumericLiteral(lit) => leaf("Number", &lit.value.to_string()),
+        Expression::BooleanLiteral(lit) => leaf("Bool", &lit.value.to_string()),
+        Expression::NullLiteral(_) => leaf("Null", ""),
+        Expression::BinaryExpression(bin) => node(
+            normalize_operator(bin.operator.as_str()),
+            "",
+            vec![lower_expr(&bin.left), lower_expr(&bin.right)],
+        ),
+        Expression::LogicalExpression(logical) => node(
+            normalize_operator(logical.operator.as_str()),
+            "",
+            vec![lower_expr(&logical.left), lower_expr(&logical.right)],
+        ),
+        Expression::UnaryExpression(unary) => {
+            node(normalize_operator(unary.operator.as_str()), "", vec![lower_expr(&unary.argument)])
+        }
+        // `i++` is `i += 1`
+        Expression::UpdateExpression(update) => {
+            let op = if update.operator.as_str() == "++" { "+=" } else { "-=" };
+            node("Assign", op, vec![lower_simple_target(&update.argument), leaf("Number", "1")])
+        }
+        Expression::AssignmentExpression(assign) => node(
+            "Assign",
+            assign.operator.as_str(),
+            vec![lower_assignment_target(&assign.left), lower_expr(&assign.right)],
+        ),
+        Expression::ConditionalExpression(cond) => node(
+            "If",
+            "",
+            vec![lower_expr(&cond.test), lower_expr(&cond.consequent), lower_expr(&cond.alternate)],
+        ),
+        Expression::CallExpression(call) => lower_call(&call.callee, &call.arguments),
+        // Python constructs objects with plain calls
+        Expression::NewExpression(new) => lower_call(&new.callee, &new.arguments),
+        // `xs.length` is `len(xs)`
+        Expression::StaticMemberExpression(member) if member.property.name == "length" => {
+            node("Call", "", vec![leaf("Name", "len"), lower_expr(&member.object)])
+        }
+        Expression::StaticMemberExpression(_)
+        | Expression::ComputedMemberExpression(_)
+        | Expression::PrivateFieldExpression(_) => match expr.as_member_expression() {
+            Some(member) => lower_member(member),
+            None => leaf("Expression", ""),
+        },
+        Expression::ChainExpression(chain) => match &chain.expression {
+            ChainElement::CallExpression(call) => lower_call(&call.callee, &call.arguments),
+            ChainElement::TSNonNullExpression(expr) => lower_expr(&expr.expression),
+            element => {
+                element.as_member_expression().map_or_else(|| leaf("Expression", ""), lower_member)
+            }
+        },
+        Expression::ArrayExpression(array) => node(
+            "List",
+            "",
+            array
+                .elements
+                .iter()
+                .map(|element| match element {
+                    ArrayExpressionElement::SpreadElement(spread) => {
+                        node("Spread", "", vec![lower_expr(&spread.argument)])
+                    }
+                    element => element.as_expression().map_or_else(|| leaf("Null", ""), lower_expr),
+                })
+                .collect(),
+        ),
+        Expression::ObjectExpression(object) => node(
+            "Dict",
+            "",
+            object
+                .properties
+                .iter()
+                .map(|property| match property {
+                    ObjectPropertyKind::ObjectProperty(prop) => {
+                        let key = match &prop.key {
+                            PropertyKey::StaticIdentifier(id) => leaf("String", &id.name),
+                            key => {
+                                key.as_expression().map_or_else(|| leaf("String", ""), lower_expr)
+                            }
+                        };
+                        node("Pair", "", vec![key, lower_expr(&prop.value)])
+                    }
+                    ObjectPropertyKind::SpreadProperty(spread) => {
+                        node("Spread", "", vec![lower_expr(&spread.argument)])
+                    }
+                })
+                .collect(),
+        ),
+        Expression::ArrowFunctionExpression(arrow) => {
+            let body = if arrow.expression {
+                lower_body_statements(&arrow.body).pop().unwrap_or_else(|| leaf("Null", ""))
+            } else {
+                lower_body(&arrow.body)
+            };
+            node("Lambda", "", vec![node("Params", "", lower_params(&arrow.params)), body])
+        }
+        Expression::FunctionExpression(func) => {
+            let body =
+                func.body.as_ref().map_or_else(|| leaf("Block", ""), |body| lower_body(body));
+            node("Lambda", "", vec![node("Params", "", lower_params(&func.params)), body])
+        }
+        Expression::AwaitExpression(await_expr) => {
+            node("Await", "", vec![lower_expr(&await_expr.argument)])
+        }
+        Expression::ParenthesizedExpression(expr) => lower_expr(&expr.expression),
+        Expression::TSAsExpression(expr) => lower_expr(&expr.expression),
+        Expression::TSSatisfiesExpression(expr) => lower_expr(&expr.expression),
+        Expression::TSNonNullExpression(expr) => lower_expr(&expr.expression),
+        Expression::TSTypeAssertion(expr) => lower_expr(&expr.expression),
+        _ => leaf("Expression", ""),
+    }
+}
+
+// ---------------------------------------------------------------------------------------------
+// Python (tree-sitter)
+// ---------------------------------------------------------------------------------------------
+
+/// Lower the functions and methods of a Python file
+pub fn normalize_python(source: &str) -> Result<Vec<NormalizedFunction>, String> {
+    let mut parser = tree_sitter::Parser::new();
+    parser
+        .set_language(&tree_sitter_python::LANGUAGE.into())
+        .map_err(|e| format!("Failed to set Python language: {e:?}"))?;
+    let tree = parser.parse(source, None).ok_or("Failed to parse Python code")?;
+
+    let lowering = PythonLowering { source };
+    let mut functions = Vec::new();
+    lowering.collect(tree.root_node(), None, &mut functions);
+    Ok(functions)
+}
+
+struct PythonLowering<'s> {
+    source: &'s str,
+}
+
+impl PythonLowering<'_> {
+    fn text(&self, node: Node) -> &str {
+        node.utf8_text(self.source.as_bytes()).unwrap_or("")
+    }
+
+    fn collect(
+        &self,
+        node: Node,
+        class_name: Option<&str>,
+        functions: &mut Vec<NormalizedFunction>,
+    ) {
+        for child in node.named_children(&mut node.walk()) {
+            let definition = match child.kind() {
+                "decorated_definition" => child.child_by_field_name("definition"),
+                _ => Some(child),
+            };
+            let Some(definition) = definition else { continue };
+            match definition.kind() {
+                "function_definition" => {
+                    let Some(name) = definition.child_by_field_name("name") else { continue };
+                    let name = match class_name {
+                        Some(class_name) => format!("{class_name}.{}", self.text(name)),
+                        None => self.text(name).to_string(),
+                    };
+                    let tree = self.lower_function(definition, class_name.is_some());
+                    functions.push(NormalizedFunction {
+                        name,
+                        language: Language::Python,
+                        start_line: child.start_position().row as u32 + 1,
+                        end_line: child.end_position().row as u32 + 1,
+                        tree: ArenaTree::from_node(&tree),
+                    });
+                }
+                "class_definition" => {
+                    let name =
+                        definition.child_by_field_name("name").map_or("Class", |n| self.text(n));
+                    if let Some(body) = definition.child_by_field_name("body") {
+                        self.collect(body, Some(name), functions);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn lower_function(&self, func: Node, is_method: bool) -> TreeNode {
+        let mut params = self.lower_params(func.child_by_field_name("parameters"));
+        // `self` and `cls` have no counterpart in JavaScript methods
+        if is_method && params.first().is_some_and(|p| p.value == "self" || p.value == "cls") {
+            params.remove(0);
+        }
+        let body = func
+            .child_by_field_name("body")
+            .map_or_else(|| leaf("Block", ""), |body| self.lower_block(body));
+        function_node(params, body)
+    }
+
+    fn lower_params(&self, params: Option<Node>) -> Vec<TreeNode> {
+        let Some(params) = params else { return Vec::new() };
+        params
+            .named_children(&mut params.walk())
+            .filter_map(|param| match param.kind() {
+                "identifier" => Some(leaf("Param", self.text(param))),
+                "typed_parameter" | "list_splat_pattern" | "dictionary_splat_pattern" => {
+                    let name = param.named_child(0).map_or("", |n| self.text(n));
+                    Some(leaf("Param", name))
+                }
+                "default_parameter" | "typed_default_parameter" => {
+                    let name = param.child_by_field_name("name").map_or("", |n| self.text(n));
+                    let default = param.child_by_field_name("value").map(|v| self.lower_expr(v));
+                    Some(node("Param", name, default.into_iter().collect()))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// A suite of statements; a leading docstring is dropped
+    fn lower_block(&self, block: Node) -> TreeNode {
+        let statements: Vec<Node> = block.named_children(&mut block.walk()).collect();
+        let skip = statements.first().is_some_and(|first| {
+            first.kind() == "expression_statement"
+                && first.named_child_count() == 1
+                && first.named_child(0).is_some_and(|n| n.kind() == "string")
+        });
+        node(
+            "Block",
+            "",
+            statements
+                .into_iter()
+                .skip(usize::from(skip))
+                .filter_map(|s| self.lower_stmt(s))
+                .collect(),
+        )
+    }
+
+    fn lower_field_block(&self, node: Node, field: &str) -> TreeNode {
+        node.child_by_field_name(field)
+            .map_or_else(|| leaf("Block", ""), |block| self.lower_block(block))
+    }
+
+    fn lower_stmt(&self, stmt: Node) -> Option<TreeNode> {
+        Some(match stmt.kind() {
+            "expression_statement" => {
+                let mut expressions: Vec<TreeNode> = stmt
+                    .named_children(&mut stmt.walk())
+                    .map(|expr| self.lower_expr(expr))
+                    .collect();
+                match expressions.len() {
+                    1 => expressions.pop()?,
+                    _ => node("List", "", expressions),
+                }
+            }
+            "return_statement" => node(
+                "Return",
+                "",
+                stmt.named_child(0).map(|expr| self.lower_expr(expr)).into_iter().collect(),
+            ),
+            "if_statement" | "elif_clause" => {
+                let mut children = vec![
+                    stmt.child_by_field_name("condition")
+                        .map_or_else(|| leaf("Bool", ""), |c| self.lower_expr(c)),
+                    self.lower_field_block(stmt, "consequence"),
+                ];
+                // `elif` clauses nest like `else if`
+                let alternatives: Vec<Node> =
+                    stmt.children_by_field_name("alternative", &mut stmt.walk()).collect();
+                if let Some(alternative) = self.lower_alternatives(&alternatives) {
+                    children.push(alternative);
+                }
+                node("If", "", children)
+            }
+            "for_statement" => node(
+                "Loop",
+                "",
+                vec![
+                    stmt.child_by_field_name("left")
+                        .map_or_else(|| leaf("Name", ""), |n| self.lower_expr(n)),
+                    stmt.child_by_field_name("right")
+                        .map_or_else(|| leaf("Name", ""), |n| self.lower_expr(n)),
+                    self.lower_field_block(stmt, "body"),
+                ],
+            ),
+            "while_statement" => node(
+                "Loop",
+                "",
+                vec![
+                    stmt.child_by_field_name("condition")
+                        .map_or_else(|| leaf("Bool", ""), |n| self.lower_expr(n)),
+                    self.lower_field_block(stmt, "body"),
+                ],
+            ),
+            "raise_statement" => node(
+                "Throw",
+                "",
+                stmt.named_child(0).map(|expr| self.lower_expr(expr)).into_iter().collect(),
+            ),
+            "try_statement" => {
+                let mut children = vec![self.lower_field_block(stmt, "body")];
+                for clause in stmt.named_children(&mut stmt.walk()) {
+                    let body = clause
+                        .named_children(&mut clause.walk())
+                        .find(|n| n.kind() == "block")
+                        .map_or_else(|| leaf("Block", ""), |block| self.lower_block(block));
+                    match clause.kind() {
+                        "except_clause" | "except_group_clause" => {
+                            children.push(node("Catch", "", vec![body]))
+                        }
+                        "finally_clause" => children.push(node("Finally", "", vec![body])),
+                        _ => {}
+                    }
+                }
+                node("Try", "", children)
+            }
+            "break_statement" => leaf("Break", ""),
+            "continue_statement" => leaf("Continue", ""),
+            "pass_statement" => return None,
+            "function_definition" => self.lower_function(stmt, false),
+            "decorated_definition" => self.lower_stmt(stmt.child_by_field_name("definition")?)?,
+            _ => leaf("Statement", ""),
+        })
+    }
+
+    fn lower_alternatives(&self, alternatives: &[Node]) -> Option<TreeNode> {
+        let (first, rest) = alternatives.split_first()?;
+        match first.kind() {
+            "elif_clause" => {
+                let mut elif = vec![
+                    first
+                        .child_by_field_name("condition")
+                        .map_or_else(|| leaf("Bool", ""), |c| self.lower_expr(c)),
+                    self.lower_field_block(*first, "consequence"),
+                ];
+                elif.extend(self.lower_alternatives(rest));
+                Some(node("If", "", elif))
+            }
+            _ => Some(self.lower_field_block(*first, "body")),
+        }
+    }
+
+    fn lower_expr(&self, expr: Node) -> TreeNode {
+        match expr.kind() {
+            "identifier" if self.text(expr) == "self" => leaf("Self", ""),
+            "identifier" => leaf("Name", self.text(expr)),
+            "integer" | "float" => leaf("Number", self.text(expr)),
+            "string" | "concatenated_string" => {
+                leaf("String", self.text(expr).trim_matches(['"', '\'']))
+            }
+            "true" | "false" => leaf("Bool", &self.text(expr).to_lowercase()),
+            "none" => leaf("Null", ""),
+            "assignment" => {
+                let target = expr
+                    .child_by_field_name("left")
+                    .map_or_else(|| leaf("Name", ""), |n| self.lower_expr(n));
+                match expr.child_by_field_name("right") {
+                    Some(value) => node("Assign", "=", vec![target, self.lower_expr(value)]),
+                    // A bare annotation like `x: int` declares nothing comparable
+                    None => leaf("Statement", ""),
+                }
+            }
+            "augmented_assignment" => node(
+                "Assign",
+                expr.child_by_field_name("operator").map_or("", |op| op.kind()),
+                self.lower_fields(expr, &["left", "right"]),
+            ),
+            "binary_operator" | "boolean_operator" => {
+                let op = expr.child_by_field_name("operator").map_or("", |op| op.kind());
+                node(normalize_operator(op), "", self.lower_fields(expr, &["left", "right"]))
+            }
+            "comparison_operator" => {
+                let operands: Vec<TreeNode> =
+                    expr.named_children(&mut expr.walk()).map(|n| self.lower_expr(n)).collect();
+                let op = expr
+                    .children(&mut expr.walk())
+                    .find(|n| !n.is_named())
+                    .map_or(String::new(), |op| {
+                        self.text(op).split_whitespace().collect::<Vec<_>>().join(" ")
+                    });
+                node(normalize_operator(&op), "", operands)
+            }
+            "not_operator" => node("!", "", self.lower_fields(expr, &["argument"])),
+            "unary_operator" => {
+                let op = expr.child_by_field_name("operator").map_or("", |op| op.kind());
+                node(op, "", self.lower_fields(expr, &["argument"]))
+            }
+            "conditional_expression" => {
+                // `a if cond else b` is `cond ? a : b`
+                let parts: Vec<TreeNode> =
+                    expr.named_children(&mut expr.walk()).map(|n| self.lower_expr(n)).collect();
+                let mut parts = parts.into_iter();
+                match (parts.next(), parts.next(), parts.next()) {
+                    (Some(a), Some(cond), Some(b)) => node("If", "", vec![cond, a, b]),
+                    _ => leaf("Expression", ""),
+                }
+            }
+            "call" => self.lower_call(expr),
+            "attribute" => node("Member", "", self.lower_fields(expr, &["object", "attribute"])),
+            "subscript" => node("Index", "", self.lower_fields(expr, &["value", "subscript"])),
+            "list" | "tuple" | "set" => node(
+                "List",
+                "",
+                expr.named_children(&mut expr.walk()).map(|n| self.lower_expr(n)).collect(),
+            ),
+            "dictionary" => node(
+                "Dict",
+                "",
+                expr.named_children(&mut expr.walk())
+                    .map(|pair| match pair.kind() {
+                        "pair" => node("Pair", "", self.lower_fields(pair, &["key", "value"])),
+                        _ => node(
+                            "Spread",
+                            "",
+                            pair.named_child(0).map(|n| self.lower_expr(n)).into_iter().collect(),
+                        ),
+                    })
+                    .collect(),
+            ),
+            "list_comprehension" | "generator_expression" | "set_comprehension" => {
+                self.lower_comprehension(expr)
+            }
+            "lambda" => {
+                let params = self.lower_params(expr.child_by_field_name("parameters"));
+                let body = expr
+                    .child_by_field_name("body")
+                    .map_or_else(|| leaf("Null", ""), |b| self.lower_expr(b));
+                node("Lambda", "", vec![node("Params", "", params), body])
+            }
+            "await" => node(
+                "Await",
+                "",
+                expr.named_child(0).map(|n| self.lower_expr(n)).into_iter().collect(),
+            ),
+            "list_splat" | "dictionary_splat" => node(
+                "Spread",
+                "",
+                expr.named_child(0).map(|n| self.lower_expr(n)).into_iter().collect(),
+            ),
+            "keyword_argument" => expr
+                .child_by_field_name("value")
+                .map_or_else(|| leaf("Expression", ""), |v| self.lower_expr(v)),
+            "parenthesized_expression" => {
+                expr.named_child(0).map_or_else(|| leaf("Expression", ""), |n| self.lower_expr(n))
+            }
+            _ => leaf("Expression", ""),
+        }
+    }
+
+    fn lower_fields(&self, node: Node, fields: &[&str]) -> Vec<TreeNode> {
+        fields
+            .iter()
+            .filter_map(|field| node.child_by_field_name(field))
+            .map(|child| match child.kind() {
+                // Attribute names are names, not variable references
+                "identifier"
+                    if child.parent().is_some_and(|p| p.kind() == "attribute")
+                        && child.prev_sibling().is_some() =>
+                {
+                    leaf("Name", self.text(child))
+                }
+                _ => self.lower_expr(child),
+            })
+            .collect()
+    }
+
+    fn lower_call(&self, call: Node) -> TreeNode {
+        let Some(function) = call.child_by_field_name("function") else {
+            return leaf("Call", "");
+        };
+        let args: Vec<Node> = call
+            .child_by_field_name("arguments")
+            .map(|args| args.named_children(&mut args.walk()).collect())
+            .unwrap_or_default();
+        let name = (function.kind() == "identifier").then(|| self.text(function));
+
+        match name {
+            // `map(f, xs)` is `Map[xs, f]`, like `xs.map(f)`; `reduce(f, xs, init)` is `Reduce[xs, f, init]`
+            Some(name) if higher_order_kind(name).is_some() && args.len() >= 2 => {
+                let kind = higher_order_kind(name).unwrap_or_default();
+                let mut children = vec![self.lower_expr(args[1]), self.lower_expr(args[0])];
+                children.extend(args[2..].iter().map(|arg| self.lower_expr(*arg)));
+                node(kind, "", children)
+            }
+            // `list(map(...))` and `list(x for x in xs)` are the JavaScript array itself
+            Some("list")
+                if args.len() == 1 && {
+                    let inner = self.lower_expr(args[0]);
+                    matches!(inner.label.as_str(), "Map" | "Filter")
+                } =>
+            {
+                self.lower_expr(args[0])
+            }
+            Some(name) => {
+                let name = builtin_name(name).unwrap_or(name);
+                node(
+                    "Call",
+                    "",
+                    [leaf("Name", name)]
+                        .into_iter()
+                        .chain(args.iter().map(|arg| self.lower_expr(*arg)))
+                        .collect(),
+                )
+            }
+            None => node(
+                "Call",
+                "",
+                [self.lower_expr(function)]
+                    .into_iter()
+                    .chain(args.iter().map(|arg| self.lower_expr(*arg)))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// `[f(x) for x in xs if c]` is `xs.filter(x => c).map(x => f(x))`
+    fn lower_comprehension(&self, expr: Node) -> TreeNode {
+        let Some(body) = expr.child_by_field_name("body") else {
+            return leaf("Expression", "");
+        };
+        let clauses: Vec<Node> = expr.named_children(&mut expr.walk()).skip(1).collect();
+        let Some(for_clause) = clauses.iter().find(|c| c.kind() == "for_in_clause") else {
+            return leaf("Expression", "");
+        };
+        let params = || {
+            let item = for_clause.child_by_field_name("left").map_or("", |n| self.text(n));
+            node("Params", "", vec![leaf("Param", item)])
+        };
+        let mut source = for_clause
+            .child_by_field_name("right")
+            .map_or_else(|| leaf("Name", ""), |n| self.lower_expr(n));
+        for condition in clauses.iter().filter(|c| c.kind() == "if_clause") {
+            let test =
+                condition.named_child(0).map_or_else(|| leaf("Bool", ""), |n| self.lower_expr(n));
+            source = node("Filter", "", vec![source, node("Lambda", "", vec![params(), test])]);
+        }
+        // `[x for x in xs if c]` only filters
+        let is_identity = body.kind() == "identifier"
+            && for_clause
+                .child_by_field_name("left")
+                .is_some_and(|left| self.text(left) == self.text(body));
+        if is_identity && source.label == "Filter" {
+            return source;
+        }
+        node("Map", "", vec![source, node("Lambda", "", vec![params(), self.lower_expr(body)])])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(tree: &ArenaTree) -> Vec<String> {
+        (0..tree.len()).map(|id| tree.label(id).to_string()).collect()
+    }
+
+    #[test]
+    fn test_for_of_and_for_in_share_a_shape() {
+        let ts = normalize_typescript(
+            "utils.ts",
+            "function processData(data: number[]): number[] {\n  const result = [];\n  for (const item of data) {\n    if (item > 0) {\n      result.push(item * 2);\n    }\n  }\n  return result;\n}\n",
+        )
+        .unwrap();
+        let py = normalize_python(
+            "def process_data(data):\n    \"\"\"Process data.\"\"\"\n    result = []\n    for item in data:\n        if item > 0:\n            result.append(item * 2)\n    return result\n",
+        )
+        .unwrap();
+
+        assert_eq!(ts[0].name, "processData");
+        assert_eq!(py[0].name, "process_data");
+        assert_eq!((py[0].start_line, py[0].end_line), (1, 7));
+        assert_eq!(labels(&ts[0].tree), labels(&py[0].tree));
+    }
+
+    #[test]
+    fn test_conditionals_and_higher_order_functions_unify() {
+        let ts = normalize_typescript(
+            "a.ts",
+            "const positives = (xs) => xs.filter((x) => x > 0).map((x) => x * 2);\nfunction sign(x) { return x < 0 ? -1 : 1; }\n",
+        )
+        .unwrap();
+        let py = normalize_python(
+            "def positives(xs):\n    return [x * 2 for x in xs if x > 0]\n\ndef sign(x):\n    return -1 if x < 0 else 1\n",
+        )
+        .unwrap();
+
+        assert_eq!(labels(&ts[0].tree), labels(&py[0].tree));
+        assert_eq!(labels(&ts[1].tree), labels(&py[1].tree));
+    }
+
+    #[test]
+    fn test_counting_loop_matches_range() {
+        let ts = normalize_typescript(
+            "a.js",
+            "function total(n) { let t = 0; for (let i = 0; i < n; i++) { t += i; } return t; }",
+        )
+        .unwrap();
+        let py = normalize_python(
+            "def total(n):\n    t = 0\n    for i in range(n):\n        t += i\n    return t\n",
+        )
+        .unwrap();
+
+        assert_eq!(ts[0].language, Language::JavaScript);
+        assert_eq!(labels(&ts[0].tree), labels(&py[0].tree));
+    }
+
+    #[test]
+    fn test_methods_drop_self() {
+        let ts =
+            normalize_typescript("a.ts", "class Store { add(item) { this.items.push(item); } }")
+                .unwrap();
+        let py = normalize_python(
+            "class Store:\n    def add(self, item):\n        self.items.append(item)\n",
+        )
+        .unwrap();
+
+        assert_eq!(ts[0].name, "Store.add");
+        assert_eq!(py[0].name, "Store.add");
+        assert_eq!(labels(&ts[0].tree), labels(&py[0].tree));
+    }
+
+    #[test]
+    fn test_find_cross_language_duplicates() {
+        let files = vec![
+            (
+                "utils.ts".to_string(),
+                normalize_functions(
+                    "utils.ts",
+                    "function sum(xs: number[]) {\n  let total = 0;\n  for (const x of xs) {\n    total += x;\n  }\n  return total;\n}\n\nfunction twice(xs: number[]) {\n  let total = 0;\n  for (const x of xs) {\n    total += x;\n  }\n  return total;\n}\n",
+                )
+                .unwrap(),
+            ),
+            (
+                "helpers.py".to_string(),
+                normalize_functions(
+                    "helpers.py",
+                    "def calculate_sum(numbers):\n    total = 0\n    for num in numbers:\n        total += num\n    return total\n",
+                )
+                .unwrap(),
+            ),
+        ];
+
+        let options = TSEDOptions { size_penalty: false, min_lines: 3, ..TSEDOptions::default() };
+        let matches = find_cross_language_duplicates(&files, 0.9, &options);
+
+        // The two TypeScript functions are never paired with each other
+        assert_eq!(matches.len(), 2);
+        assert!(matches.iter().all(|m| m.file1 == "utils.ts" && m.file2 == "helpers.py"));
+        assert_eq!(matches[0].similarity, 1.0);
+    }
+}
@@ -0,0 +1,213 @@
+use crate::apted::{APTEDOptions, TreeEditAlgorithm};
+use crate::tree::TreeNode;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+/// A single edit that turns the first tree into the second
+#[derive(Debug, Clone)]
+pub enum EditOperation {
+    /// Node only present in the first tree
+    Delete(Rc<TreeNode>),
+    /// Node only present in the second tree
+    Insert(Rc<TreeNode>),
+    /// Mapped node pair whose label (or value) differs
+    Rename { from: Rc<TreeNode>, to: Rc<TreeNode> },
+}
+
+impl EditOperation {
+    /// The node this operation is reported against (the source node for renames)
+    #[must_use]
+    pub fn node(&self) -> &Rc<TreeNode> {
+        match self {
+            EditOperation::Delete(node) | EditOperation::Insert(node) => node,
+            EditOperation::Rename { from, .. } => from,
+        }
+    }
+}
+
+/// Node mapping and edit operations behind a tree edit distance
+#[derive(Debug, Clone, Default)]
+pub struct EditScript {
+    /// Edit distance reported by the selected backend
+    pub distance: f64,
+    /// Mapped node pairs (first tree, second tree), including unchanged ones
+    pub mapping: Vec<(Rc<TreeNode>, Rc<TreeNode>)>,
+    /// Insert, delete and rename operations in source order
+    pub operations: Vec<EditOperation>,
+}
+
+impl EditScript {
+    /// Whether the two trees are identical under the cost model
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Node counts of both trees. Every node is either mapped or deleted (first tree) or
+    /// inserted (second tree), so the sizes fall out of the script without another walk.
+    #[must_use]
+    pub fn tree_sizes(&self) -> (usize, usize) {
+        let (mut deleted, mut inserted) = (0, 0);
+        for op in &self.operations {
+            match op {
+                EditOperation::Delete(_) => deleted += 1,
+                EditOperation::Insert(_) => inserted += 1,
+                EditOperation::Rename { .. } => {}
+            }
+        }
+        (self.mapping.len() + deleted, self.mapping.len() + inserted)
+    }
+
+    /// Operations whose node is not inside a subtree that was deleted or inserted as a whole.
+    /// This keeps one entry per changed statement instead of one per AST node.
+    #[must_use]
+    pub fn top_level_operations(&self) -> Vec<&EditOperation> {
+        let mut deleted = HashSet::new();
+        let mut inserted = HashSet::new();
+        for op in &self.operations {
+            match op {
+                EditOperation::Delete(node) => {
+                    deleted.insert(Rc::as_ptr(node));
+                }
+                EditOperation::Insert(node) => {
+                    inserted.insert(Rc::as_ptr(node));
+                }
+                EditOperation::Rename { .. } => {}
+            }
+        }
+
+        let mut covered = HashSet::new();
+        for op in &self.operations {
+            let set = match op {
+                EditOperation::Delete(_) => &deleted,
+                EditOperation::Insert(_) => &inserted,
+                EditOperation::Rename { .. } => continue,
+            };
+            if fully_contained(op.node(), set) {
+                for child in &op.node().children {
+                    mark_descendants(child, &mut covered);
+                }
+            }
+        }
+
+        self.operations.iter().filter(|op| !covered.contains(&Rc::as_ptr(op.node()))).collect()
+    }
+}
+
+fn fully_contained(node: &Rc<TreeNode>, set: &HashSet<*const TreeNode>) -> bool {
+    set.contains(&Rc::as_ptr(node)) && node.children.iter().all(|c| fully_contained(c, set))
+}
+
+fn mark_descendants(node: &Rc<TreeNode>, covered: &mut HashSet<*const TreeNode>) {
+    covered.insert(Rc::as_ptr(node));
+    for child in &node.children {
+        mark_descendants(child, covered);
+    }
+}
+
+/// Short human readable description of a node, e.g. `IfStatement` or `foo (Identifier)`
+#[must_use]
+pub fn describe_node(node: &TreeNode) -> String {
+    if node.value.is_empty() || node.label == node.value {
+        node.label.clone()
+    } else {
+        format!("{} ({})", node.label, node.value)
+    }
+}
+
+/// Compute the edit script between two trees with the backend selected in `options`
+#[must_use]
+pub fn compute_edit_script(
+    tree1: &Rc<TreeNode>,
+    tree2: &Rc<TreeNode>,
+    options: &APTEDOptions,
+) -> EditScript {
+    match options.algorithm {
+        TreeEditAlgorithm::Recursive => crate::apted::recursive_edit_script(tree1, tree2, options),
+        TreeEditAlgorithm::Apted => crate::apted_optimal::apted_edit_script(tree1, tree2, options),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(label: &str, children: Vec<Rc<TreeNode>>) -> Rc<TreeNode> {
+        let mut n = TreeNode::new(label.to_string(), label.to_string(), 0);
+        for child in children {
+            n.add_child(child);
+        }
+        Rc::new(n)
+    }
+
+    fn summary(script: &EditScript) -> Vec<String> {
+        script
+            .top_level_operations()
+            .iter()
+            .map(|op| match op {
+                EditOperation::Delete(n) => format!("-{}", n.label),
+                EditOperation::Insert(n) => format!("+{}", n.label),
+                EditOperation::Rename { from, to } => format!("{}->{}", from.label, to.label),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_identical_trees_have_empty_script() {
+        let t = node("a", vec![node("b", vec![]), node("c", vec![])]);
+        for algorithm in [TreeEditAlgorithm::Recursive, TreeEditAlgorithm::Apted] {
+            let options = APTEDOptions { algorithm, ..Default::default() };
+            let script = compute_edit_script(&t, &t, &options);
+            assert!(script.is_empty());
+            assert_eq!(script.mapping.len(), 3);
+            assert_eq!(script.distance, 0.0);
+        }
+    }
+
+    #[test]
+    fn test_inserted_statement_reported_once() {
+        let t1 = node("Block", vec![node("Return", vec![node("x", vec![])])]);
+        let t2 = node(
+            "Block",
+            vec![node("If", vec![node("cond", vec![]), node("y", vec![])]), t1.children[0].clone()],
+        );
+        for algorithm in [TreeEditAlgorithm::Recursive, TreeEditAlgorithm::Apted] {
+            let options = APTEDOptions { algorithm, ..Default::default() };
+            let script = compute_edit_script(&t1, &t2, &options);
+            assert_eq!(script.operations.len(), 3, "{algorithm:?}");
+            assert_eq!(summary(&script), vec!["+If"], "{algorithm:?}");
+            assert_eq!(script.distance, 3.0);
+        }
+    }
+
+    #[test]
+    fn test_rename_and_delete() {
+        let t1 = node("f", vec![node("a", vec![]), node("b", vec![])]);
+        let t2 = node("f", vec![node("c", vec![])]);
+        let options = APTEDOptions { algorithm: TreeEditAlgorithm::Apted, ..Default::default() };
+        let script = compute_edit_script(&t1, &t2, &options);
+        assert_eq!(script.distance, 2.0);
+        assert_eq!(script.operations.len(), 2);
+        assert_eq!(script.mapping.len(), 2);
+    }
+
+    #[test]
+    fn test_tree_sizes_match_subtree_sizes() {
+        let t1 = node("f", vec![node("a", vec![node("x", vec![])]), node("b", vec![])]);
+        let t2 = node("g", vec![node("c", vec![]), node("a", vec![node("y", vec![])])]);
+        for algorithm in [TreeEditAlgorithm::Recursive, TreeEditAlgorithm::Apted] {
+            let options = APTEDOptions { algorithm, ..Default::default() };
+            let script = compute_edit_script(&t1, &t2, &options);
+            assert_eq!(script.tree_sizes(), (t1.get_subtree_size(), t2.get_subtree_size()));
+        }
+    }
+
+    #[test]
+    fn test_describe_node() {
+        assert_eq!(describe_node(&TreeNode::new("If".into(), "If".into(), 0)), "If");
+        let ident = TreeNode::new("foo".into(), "Identifier".into(), 0);
+        assert_eq!(describe_node(&ident), "foo (Identifier)");
+        let block = TreeNode::new("block".into(), String::new(), 0);
+        assert_eq!(describe_node(&block), "block");
+    }
+}
@@ -1,5 +1,5 @@
 use crate::apted::{compute_edit_distance, APTEDOptions};
-use crate::tree::TreeNode;
+use crate::tree::{ArenaTree, TreeNode};
 use std::rc::Rc;
 
 /// Enhanced similarity calculation that considers multiple factors
@@ -35,13 +35,14 @@ pub fn calculate_enhanced_similarity(
     options: &EnhancedSimilarityOptions,
 ) -> f64 {
     // 1. Structural similarity using APTED
-    let distance = compute_edit_distance(tree1, tree2, &options.apted_options);
-    let max_size = tree1.get_subtree_size().max(tree2.get_subtree_size()) as f64;
+    let (arena1, arena2) = (ArenaTree::from_node(tree1), ArenaTree::from_node(tree2));
+    let distance = compute_edit_distance(&arena1, &arena2, &options.apted_options);
+    let max_size = arena1.len().max(arena2.len()) as f64;
     let structural_similarity = if max_size > 0.0 { 1.0 - (distance / max_size) } else { 1.0 };
 
     // 2. Size similarity
-    let size1 = tree1.get_subtree_size() as f64;
-    let size2 = tree2.get_subtree_size() as f64;
+    let size1 = arena1.len() as f64;
+    let size2 = arena2.len() as f64;
     let size_ratio = size1.min(size2) / size1.max(size2).max(1.0);
     let size_similarity = if size_ratio < options.min_size_ratio {
         size_ratio / options.min_size_ratio // Penalize very different sizes
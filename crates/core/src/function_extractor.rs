@@ -1,8 +1,12 @@
 use oxc_ast::ast::*;
 use oxc_span::Span;
+use serde::{Deserialize, Serialize};
 
+use crate::ast_fingerprint::AstFingerprint;
+use crate::edit_script::EditScript;
 use crate::parser::parse_and_convert_to_tree;
-use crate::tsed::{calculate_tsed, TSEDOptions};
+use crate::tree::ArenaTree;
+use crate::tsed::{calculate_tsed, calculate_tsed_with_edit_script, TSEDOptions};
 
 type CrossFileSimilarityResult = Vec<(String, SimilarityResult, String)>;
 
@@ -22,11 +26,12 @@ impl SimilarityResult {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FunctionDefinition {
     pub name: String,
     pub function_type: FunctionType,
     pub parameters: Vec<String>,
+    #[serde(with = "span_offsets")]
     pub body_span: Span,
     pub start_line: u32,
     pub end_line: u32,
@@ -58,7 +63,7 @@ impl FunctionDefinition {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum FunctionType {
     Function,
     Method,
@@ -66,6 +71,45 @@ pub enum FunctionType {
     Constructor,
 }
 
+/// `Span` stored as its `(start, end)` byte offsets
+mod span_offsets {
+    use oxc_span::Span;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(span: &Span, serializer: S) -> Result<S::Ok, S::Error> {
+        (span.start, span.end).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Span, D::Error> {
+        let (start, end) = <(u32, u32)>::deserialize(deserializer)?;
+        Ok(Span::new(start, end))
+    }
+}
+
+/// A function whose body was parsed once: the tree compared with TSED and the fingerprint
+/// used by the fast mode pre-filter. `None` when the body could not be parsed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParsedFunction {
+    pub definition: FunctionDefinition,
+    pub tree: Option<ArenaTree>,
+    pub fingerprint: Option<AstFingerprint>,
+}
+
+/// Extract the functions of a file and parse each body once
+pub fn parse_functions(filename: &str, source_text: &str) -> Result<Vec<ParsedFunction>, String> {
+    let functions = extract_functions(filename, source_text)?;
+    Ok(functions
+        .into_iter()
+        .map(|definition| {
+            let tree = parse_function_tree(&definition, source_text).ok();
+            let body = &source_text
+                [definition.body_span.start as usize..definition.body_span.end as usize];
+            let fingerprint = AstFingerprint::from_source(body).ok();
+            ParsedFunction { definition, tree, fingerprint }
+        })
+        .collect())
+}
+
 /// Extract all functions from TypeScript/JavaScript code
 pub fn extract_functions(
     filename: &str,
@@ -92,6 +136,7 @@ pub fn extract_functions(
     };
 
     extract_from_program(&ret.program, &mut context);
+    crate::suppression::retain_unsuppressed(source_text, &mut functions, |f| f.start_line);
     Ok(functions)
 }
 
@@ -405,15 +450,27 @@ pub fn compare_functions(
     source2: &str,
     options: &TSEDOptions,
 ) -> Result<f64, String> {
-    // Extract function body text
-    let body1 = extract_body_text(func1, source1);
-    let body2 = extract_body_text(func2, source2);
+    let tree1 = parse_function_tree(func1, source1)?;
+    let tree2 = parse_function_tree(func2, source2)?;
+    Ok(compare_function_trees(func1, func2, &tree1, &tree2, options))
+}
 
-    // Parse and compare
-    let tree1 = parse_and_convert_to_tree("func1.ts", &body1)?;
-    let tree2 = parse_and_convert_to_tree("func2.ts", &body2)?;
+/// Parse the body of `func` once so it can be compared against many functions, possibly
+/// from several threads
+pub fn parse_function_tree(func: &FunctionDefinition, source: &str) -> Result<ArenaTree, String> {
+    let tree = parse_and_convert_to_tree("func.ts", &extract_body_text(func, source))?;
+    Ok(ArenaTree::from_node(&tree))
+}
 
-    let mut similarity = calculate_tsed(&tree1, &tree2, options);
+/// Similarity of two functions whose bodies were parsed with [`parse_function_tree`]
+pub fn compare_function_trees(
+    func1: &FunctionDefinition,
+    func2: &FunctionDefinition,
+    tree1: &ArenaTree,
+    tree2: &ArenaTree,
+    options: &TSEDOptions,
+) -> f64 {
+    let mut similarity = calculate_tsed(tree1, tree2, options);
 
     // Apply size penalty for short functions if enabled
     if options.size_penalty {
@@ -425,7 +482,22 @@ pub fn compare_functions(
         }
     }
 
-    Ok(similarity)
+    similarity
+}
+
+/// Compute the edit script between two function bodies (what changes turn `func1` into `func2`)
+pub fn diff_functions(
+    func1: &FunctionDefinition,
+    func2: &FunctionDefinition,
+    source1: &str,
+    source2: &str,
+    options: &TSEDOptions,
+) -> Result<EditScript, String> {
+    let tree1 = parse_and_convert_to_tree("func1.ts", &extract_body_text(func1, source1))?;
+    let tree2 = parse_and_convert_to_tree("func2.ts", &extract_body_text(func2, source2))?;
+
+    let (_, script) = calculate_tsed_with_edit_script(&tree1, &tree2, options);
+    Ok(script)
 }
 
 fn extract_body_text(func: &FunctionDefinition, source: &str) -> String {
@@ -501,6 +573,8 @@ pub fn find_similar_functions_in_file(
     options: &TSEDOptions,
 ) -> Result<Vec<SimilarityResult>, String> {
     let functions = extract_functions(filename, source_text)?;
+    let trees: Vec<_> =
+        functions.iter().map(|func| parse_function_tree(func, source_text)).collect();
     let mut similar_pairs = Vec::new();
 
     // Compare all pairs
@@ -528,8 +602,9 @@ pub fn find_similar_functions_in_file(
                 continue;
             }
 
+            let (tree1, tree2) = (trees[i].as_ref()?, trees[j].as_ref()?);
             let similarity =
-                compare_functions(&functions[i], &functions[j], source_text, source_text, options)?;
+                compare_function_trees(&functions[i], &functions[j], tree1, tree2, options);
 
             if similarity >= threshold {
                 similar_pairs.push(SimilarityResult::new(
@@ -551,65 +626,163 @@ pub fn find_similar_functions_in_file(
     Ok(similar_pairs)
 }
 
+/// Find similar pairs among the functions of one file parsed with [`parse_functions`].
+///
+/// With `fingerprint_threshold` (fast mode) pairs whose fingerprints are further apart are
+/// skipped before computing TSED, like [`crate::find_similar_functions_fast`]. With
+/// `candidates` only these `(i, j)` index pairs into `functions` are considered, e.g. the
+/// collisions of an [`crate::lsh::LshIndex`].
+pub fn find_similar_parsed_functions(
+    functions: &[ParsedFunction],
+    threshold: f64,
+    options: &TSEDOptions,
+    fingerprint_threshold: Option<f64>,
+    candidates: Option<&[(usize, usize)]>,
+) -> Vec<SimilarityResult> {
+    let long_enough = |func: &FunctionDefinition| match options.min_tokens {
+        Some(min_tokens) => func.node_count.unwrap_or(0) >= min_tokens,
+        None => func.line_count() >= options.min_lines,
+    };
+    let eligible: Vec<bool> = functions.iter().map(|func| long_enough(&func.definition)).collect();
+
+    let compare = |i: usize, j: usize| -> Option<SimilarityResult> {
+        if !eligible[i] || !eligible[j] {
+            return None;
+        }
+        let (func1, func2) = (&functions[i], &functions[j]);
+        let (def1, def2) = (&func1.definition, &func2.definition);
+        let skip = match fingerprint_threshold {
+            // Nested functions are only skipped by the full comparison, as before
+            None => def1.is_parent_child_relationship(def2),
+            Some(min) => match (&func1.fingerprint, &func2.fingerprint) {
+                (Some(fp1), Some(fp2)) => !fp1.is_candidate(fp2, min),
+                _ => true,
+            },
+        };
+        if skip {
+            return None;
+        }
+
+        let (tree1, tree2) = (func1.tree.as_ref()?, func2.tree.as_ref()?);
+        let similarity = compare_function_trees(def1, def2, tree1, tree2, options);
+        (similarity >= threshold)
+            .then(|| SimilarityResult::new(def1.clone(), def2.clone(), similarity))
+    };
+
+    let mut similar_pairs: Vec<SimilarityResult> = match candidates {
+        Some(candidates) => candidates.iter().filter_map(|&(i, j)| compare(i, j)).collect(),
+        None => (0..functions.len())
+            .flat_map(|i| ((i + 1)..functions.len()).map(move |j| (i, j)))
+            .filter_map(|(i, j)| compare(i, j))
+            .collect(),
+    };
+
+    // Sort by impact (descending), then by similarity (descending)
+    similar_pairs.sort_by(|a, b| {
+        b.impact
+            .cmp(&a.impact)
+            .then(b.similarity.partial_cmp(&a.similarity).unwrap_or(std::cmp::Ordering::Equal))
+    });
+    similar_pairs
+}
+
 /// Find similar functions across multiple files
 pub fn find_similar_functions_across_files(
     files: &[(String, String)], // (filename, source_text)
     threshold: f64,
     options: &TSEDOptions,
 ) -> Result<CrossFileSimilarityResult, String> {
-    let mut all_functions = Vec::new();
+    let all_functions = extract_from_files(files)?;
+
+    // Compare all pairs across files (same-file pairs are handled by
+    // find_similar_functions_in_file)
+    let pairs = (0..all_functions.len()).flat_map(|i| {
+        let all_functions = &all_functions;
+        ((i + 1)..all_functions.len())
+            .filter(move |&j| all_functions[i].0 != all_functions[j].0)
+            .map(move |j| (i, j))
+    });
+    compare_pairs(&all_functions, pairs, threshold, options)
+}
 
-    // Extract functions from all files
+/// Find functions similar to changed ones, e.g. the functions touched by a diff.
+/// Each pair has a changed function first; it is compared against every other function,
+/// in its own file or any other one.
+pub fn find_similar_changed_functions(
+    files: &[(String, String)], // (filename, source_text)
+    threshold: f64,
+    options: &TSEDOptions,
+    is_changed: impl Fn(&str, &FunctionDefinition) -> bool,
+) -> Result<CrossFileSimilarityResult, String> {
+    let all_functions = extract_from_files(files)?;
+    let changed: Vec<bool> =
+        all_functions.iter().map(|(file, _, func)| is_changed(file, func)).collect();
+
+    // Pairs of two changed functions are only compared once
+    let pairs = (0..all_functions.len()).filter(|&i| changed[i]).flat_map(|i| {
+        let changed = &changed;
+        (0..all_functions.len())
+            .filter(move |&j| j != i && !(changed[j] && j < i))
+            .map(move |j| (i, j))
+    });
+    compare_pairs(&all_functions, pairs, threshold, options)
+}
+
+fn extract_from_files(
+    files: &[(String, String)],
+) -> Result<Vec<(&str, &str, FunctionDefinition)>, String> {
+    let mut all_functions = Vec::new();
     for (filename, source) in files {
-        let functions = extract_functions(filename, source)?;
-        for func in functions {
-            all_functions.push((filename.clone(), source.clone(), func));
+        for func in extract_functions(filename, source)? {
+            all_functions.push((filename.as_str(), source.as_str(), func));
         }
     }
+    Ok(all_functions)
+}
 
+fn compare_pairs(
+    all_functions: &[(&str, &str, FunctionDefinition)],
+    pairs: impl Iterator<Item = (usize, usize)>,
+    threshold: f64,
+    options: &TSEDOptions,
+) -> Result<CrossFileSimilarityResult, String> {
     let mut similar_pairs = Vec::new();
-
-    // Compare all pairs across files
-    for i in 0..all_functions.len() {
-        for j in (i + 1)..all_functions.len() {
-            let (first_file, source1, func1) = &all_functions[i];
-            let (second_file, source2, func2) = &all_functions[j];
-
-            // Skip if same file (already handled by find_similar_functions_in_file)
-            if first_file == second_file {
+    let trees: Vec<_> =
+        all_functions.iter().map(|(_, source, func)| parse_function_tree(func, source)).collect();
+
+    for (i, j) in pairs {
+        let (first_file, _, func1) = &all_functions[i];
+        let (second_file, _, func2) = &all_functions[j];
+
+        // Skip if either function is too short
+        if let Some(min_tokens) = options.min_tokens {
+            // If min_tokens is specified, use token count instead of line count
+            let tokens1 = func1.node_count.unwrap_or(0);
+            let tokens2 = func2.node_count.unwrap_or(0);
+            if tokens1 < min_tokens || tokens2 < min_tokens {
                 continue;
             }
-
-            // Skip if either function is too short
-            if let Some(min_tokens) = options.min_tokens {
-                // If min_tokens is specified, use token count instead of line count
-                let tokens1 = func1.node_count.unwrap_or(0);
-                let tokens2 = func2.node_count.unwrap_or(0);
-                if tokens1 < min_tokens || tokens2 < min_tokens {
-                    continue;
-                }
-            } else {
-                // Otherwise use line count
-                if func1.line_count() < options.min_lines || func2.line_count() < options.min_lines
-                {
-                    continue;
-                }
-            }
-
-            // Skip if functions have parent-child relationship (across files)
-            if func1.is_parent_child_relationship(func2) {
+        } else {
+            // Otherwise use line count
+            if func1.line_count() < options.min_lines || func2.line_count() < options.min_lines {
                 continue;
             }
+        }
 
-            let similarity = compare_functions(func1, func2, source1, source2, options)?;
+        // Skip if functions have parent-child relationship
+        if func1.is_parent_child_relationship(func2) {
+            continue;
+        }
 
-            if similarity >= threshold {
-                similar_pairs.push((
-                    first_file.clone(),
-                    SimilarityResult::new(func1.clone(), func2.clone(), similarity),
-                    second_file.clone(),
-                ));
-            }
+        let (tree1, tree2) = (trees[i].as_ref()?, trees[j].as_ref()?);
+        let similarity = compare_function_trees(func1, func2, tree1, tree2, options);
+
+        if similarity >= threshold {
+            similar_pairs.push((
+                first_file.to_string(),
+                SimilarityResult::new(func1.clone(), func2.clone(), similarity),
+                second_file.to_string(),
+            ));
         }
     }
 
@@ -829,4 +1002,49 @@ mod tests {
         });
         assert!(validate_check.is_some());
     }
+
+    #[test]
+    fn test_find_similar_changed_functions() {
+        let file1 = (
+            "file1.ts".to_string(),
+            r#"
+            function sumA(xs: number[]): number {
+                let t = 0;
+                for (const x of xs) { t += x; }
+                return t;
+            }
+
+            function sumB(ys: number[]): number {
+                let t = 0;
+                for (const y of ys) { t += y; }
+                return t;
+            }
+        "#
+            .to_string(),
+        );
+        let file2 = (
+            "file2.ts".to_string(),
+            r#"
+            function sumC(zs: number[]): number {
+                let t = 0;
+                for (const z of zs) { t += z; }
+                return t;
+            }
+        "#
+            .to_string(),
+        );
+
+        let options = TSEDOptions { size_penalty: false, min_lines: 1, ..Default::default() };
+
+        let pairs = find_similar_changed_functions(&[file1, file2], 0.8, &options, |file, f| {
+            file == "file2.ts" && f.name == "sumC"
+        })
+        .unwrap();
+
+        // Only pairs with the changed function, which always comes first
+        assert_eq!(pairs.len(), 2);
+        assert!(pairs
+            .iter()
+            .all(|(file, result, _)| file == "file2.ts" && result.func1.name == "sumC"));
+    }
 }
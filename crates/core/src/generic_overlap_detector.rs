@@ -4,6 +4,7 @@ use crate::{
         detect_partial_overlaps, generate_subtree_fingerprints, IndexedFunction, OverlapOptions,
         PartialOverlap,
     },
+    tree::ArenaTree,
     tsed::{calculate_tsed, TSEDOptions},
 };
 use std::collections::HashMap;
@@ -22,23 +23,27 @@ pub fn find_function_overlaps_generic(
     let source_functions = parser.extract_functions(source_code, source_filename)?;
     let target_functions = parser.extract_functions(target_code, target_filename)?;
 
-    // Parse and index functions
+    // Parse and index every function once
     let mut all_overlaps = Vec::new();
+    if source_functions.is_empty() {
+        return Ok(all_overlaps);
+    }
+    let mut targets = Vec::with_capacity(target_functions.len());
+    for target_func in &target_functions {
+        targets.push(index_function_generic(parser, target_func, target_code, target_filename)?);
+    }
 
     for source_func in &source_functions {
         let source_indexed =
             index_function_generic(parser, source_func, source_code, source_filename)?;
 
-        for target_func in &target_functions {
+        for (target_func, target_indexed) in target_functions.iter().zip(&targets) {
             // Skip if comparing the same function in the same file
             // (but allow comparing functions with same name in different files)
             if source_func.name == target_func.name && source_code == target_code {
                 continue;
             }
 
-            let target_indexed =
-                index_function_generic(parser, target_func, target_code, target_filename)?;
-
             // Debug output
             #[cfg(test)]
             {
@@ -48,7 +53,7 @@ pub fn find_function_overlaps_generic(
             }
 
             // Detect overlaps
-            let overlaps = detect_partial_overlaps(&source_indexed, &target_indexed, options);
+            let overlaps = detect_partial_overlaps(&source_indexed, target_indexed, options);
             all_overlaps.extend(overlaps);
         }
     }
@@ -135,7 +140,10 @@ fn index_function_generic(
     let tree = parser.parse(&func_code, file_name)?;
 
     // Generate fingerprints for all subtrees
-    let (root_fp, subtrees) = generate_subtree_fingerprints(&tree, 0, func.start_line);
+    let (root_fp, subtrees) = generate_subtree_fingerprints(
+        &ArenaTree::from_node(&tree),
+        func.start_line.saturating_sub(1),
+    );
 
     // Create indexed function
     let mut indexed = IndexedFunction::new(func.name.clone(), file_name.to_string(), root_fp);
@@ -172,33 +180,34 @@ pub fn find_overlaps_with_similarity_generic(
     let mut detailed_overlaps = Vec::new();
 
     for overlap in overlaps {
+        // Spans put the overlap on real lines, so the duplicated block can always be shown
+        let source_segment =
+            extract_code_segment(source_code, overlap.source_lines.0, overlap.source_lines.1)?;
+        let target_segment =
+            extract_code_segment(target_code, overlap.target_lines.0, overlap.target_lines.1)?;
+
         // For high-similarity overlaps, calculate exact TSED similarity
-        if overlap.similarity > 0.9 {
-            // Extract the overlapping code segments
-            let source_segment =
-                extract_code_segment(source_code, overlap.source_lines.0, overlap.source_lines.1)?;
-            let target_segment =
-                extract_code_segment(target_code, overlap.target_lines.0, overlap.target_lines.1)?;
-
-            // Parse and calculate exact similarity
-            let source_tree = parser.parse(&source_segment, source_filename)?;
-            let target_tree = parser.parse(&target_segment, target_filename)?;
-            let exact_similarity = calculate_tsed(&source_tree, &target_tree, tsed_options);
-
-            detailed_overlaps.push(DetailedOverlap {
-                overlap: overlap.clone(),
-                exact_similarity,
-                source_code: source_segment,
-                target_code: target_segment,
-            });
+        let exact_similarity = if overlap.similarity > 0.9 {
+            // Windows may cut through a statement; keep the estimate when they don't parse
+            match (
+                parser.parse(&source_segment, source_filename),
+                parser.parse(&target_segment, target_filename),
+            ) {
+                (Ok(source_tree), Ok(target_tree)) => {
+                    calculate_tsed(&source_tree, &target_tree, tsed_options)
+                }
+                _ => overlap.similarity,
+            }
         } else {
-            detailed_overlaps.push(DetailedOverlap {
-                overlap: overlap.clone(),
-                exact_similarity: overlap.similarity,
-                source_code: String::new(),
-                target_code: String::new(),
-            });
-        }
+            overlap.similarity
+        };
+
+        detailed_overlaps.push(DetailedOverlap {
+            overlap,
+            exact_similarity,
+            source_code: source_segment,
+            target_code: target_segment,
+        });
     }
 
     Ok(detailed_overlaps)
@@ -1,7 +1,8 @@
+use crate::ast_fingerprint::FingerprintVocabulary;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Configuration for a generic tree-sitter based parser
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +16,10 @@ pub struct GenericParserConfig {
     /// Node types that represent types/classes
     pub type_nodes: Vec<String>,
 
+    /// Optional: Node types that declare the fields of a type (or the members of an enum)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub field_nodes: Vec<String>,
+
     /// Field mappings for extracting information from nodes
     pub field_mappings: FieldMappings,
 
@@ -26,6 +31,40 @@ pub struct GenericParserConfig {
 
     /// Optional: Custom node type mappings
     pub custom_mappings: Option<HashMap<String, String>>,
+
+    /// Optional: Node kinds counted by the fast mode fingerprint, added to the built-in
+    /// vocabulary (e.g. `"unless": "IfStatement"` or `"identifier:if": "IfStatement"`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint_kinds: Option<HashMap<String, String>>,
+
+    /// Optional: File extensions of the language's files (e.g. `["kt", "kts"]`), needed when
+    /// the language is not one of the built-in ones
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<String>,
+
+    /// Optional: Compiled tree-sitter grammar to load at runtime instead of a bundled one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grammar: Option<GrammarLibrary>,
+}
+
+/// A tree-sitter grammar compiled into a shared library (`.so`, `.dylib` or `.dll`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrammarLibrary {
+    /// Path of the library; relative paths are resolved against the config file
+    pub path: PathBuf,
+
+    /// Optional: Exported function returning the grammar (default: `tree_sitter_<language>`)
+    pub symbol: Option<String>,
+}
+
+impl GrammarLibrary {
+    /// Name of the exported language function, e.g. `tree_sitter_kotlin`
+    pub fn symbol_name(&self, language: &str) -> String {
+        match &self.symbol {
+            Some(symbol) => symbol.clone(),
+            None => format!("tree_sitter_{}", language.replace('-', "_")),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -61,12 +100,25 @@ pub struct TestPatterns {
 impl GenericParserConfig {
     /// Load configuration from a JSON file
     pub fn from_file<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
-        let content = fs::read_to_string(path)?;
-        let config = serde_json::from_str(&content)
+        let content = fs::read_to_string(&path)?;
+        let mut config: Self = serde_json::from_str(&content)
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+        // A grammar next to the config is found wherever the tool is run from
+        if let (Some(grammar), Some(dir)) = (&mut config.grammar, path.as_ref().parent()) {
+            if grammar.path.is_relative() {
+                grammar.path = dir.join(&grammar.path);
+            }
+        }
         Ok(config)
     }
 
+    /// Vocabulary of the fast mode fingerprint for this language
+    pub fn fingerprint_vocabulary(&self) -> FingerprintVocabulary {
+        FingerprintVocabulary::for_language(&self.language)
+            .with_kinds(self.fingerprint_kinds.iter().flatten())
+    }
+
     /// Save configuration to a JSON file
     pub fn to_file<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
         let content = serde_json::to_string_pretty(self)
@@ -82,6 +134,7 @@ impl Default for GenericParserConfig {
             language: "unknown".to_string(),
             function_nodes: vec![],
             type_nodes: vec![],
+            field_nodes: vec![],
             field_mappings: FieldMappings {
                 name_field: "name".to_string(),
                 params_field: "parameters".to_string(),
@@ -92,6 +145,9 @@ impl Default for GenericParserConfig {
             value_nodes: vec!["identifier".to_string(), "string".to_string()],
             test_patterns: None,
             custom_mappings: None,
+            fingerprint_kinds: None,
+            extensions: vec![],
+            grammar: None,
         }
     }
 }
@@ -103,6 +159,7 @@ impl GenericParserConfig {
             language: "python".to_string(),
             function_nodes: vec!["function_definition".to_string()],
             type_nodes: vec!["class_definition".to_string()],
+            field_nodes: vec![],
             field_mappings: FieldMappings {
                 name_field: "name".to_string(),
                 params_field: "parameters".to_string(),
@@ -125,6 +182,9 @@ impl GenericParserConfig {
                 name_suffixes: vec!["_test".to_string()],
             }),
             custom_mappings: None,
+            fingerprint_kinds: None,
+            extensions: vec![],
+            grammar: None,
         }
     }
 
@@ -133,6 +193,7 @@ impl GenericParserConfig {
             language: "rust".to_string(),
             function_nodes: vec!["function_item".to_string()],
             type_nodes: vec!["struct_item".to_string(), "impl_item".to_string()],
+            field_nodes: vec!["field_declaration".to_string(), "enum_variant".to_string()],
             field_mappings: FieldMappings {
                 name_field: "name".to_string(),
                 params_field: "parameters".to_string(),
@@ -153,6 +214,9 @@ impl GenericParserConfig {
                 name_suffixes: vec![],
             }),
             custom_mappings: None,
+            fingerprint_kinds: None,
+            extensions: vec![],
+            grammar: None,
         }
     }
 
@@ -166,6 +230,7 @@ impl GenericParserConfig {
                 "method_definition".to_string(),
             ],
             type_nodes: vec!["class_declaration".to_string()],
+            field_nodes: vec![],
             field_mappings: FieldMappings {
                 name_field: "name".to_string(),
                 params_field: "parameters".to_string(),
@@ -188,6 +253,9 @@ impl GenericParserConfig {
                 name_suffixes: vec![".test".to_string(), ".spec".to_string()],
             }),
             custom_mappings: None,
+            fingerprint_kinds: None,
+            extensions: vec![],
+            grammar: None,
         }
     }
 
@@ -203,6 +271,7 @@ impl GenericParserConfig {
                 "struct_type".to_string(),
                 "interface_type".to_string(),
             ],
+            field_nodes: vec!["field_declaration".to_string()],
             field_mappings: FieldMappings {
                 name_field: "name".to_string(),
                 params_field: "parameters".to_string(),
@@ -226,6 +295,9 @@ impl GenericParserConfig {
                 name_suffixes: vec!["_test".to_string()],
             }),
             custom_mappings: None,
+            fingerprint_kinds: None,
+            extensions: vec![],
+            grammar: None,
         }
     }
 
@@ -242,6 +314,7 @@ impl GenericParserConfig {
                 "enum_declaration".to_string(),
                 "annotation_type_declaration".to_string(),
             ],
+            field_nodes: vec!["field_declaration".to_string(), "enum_constant".to_string()],
             field_mappings: FieldMappings {
                 name_field: "name".to_string(),
                 params_field: "parameters".to_string(),
@@ -264,6 +337,9 @@ impl GenericParserConfig {
                 name_suffixes: vec!["Test".to_string()],
             }),
             custom_mappings: None,
+            fingerprint_kinds: None,
+            extensions: vec![],
+            grammar: None,
         }
     }
 
@@ -277,6 +353,7 @@ impl GenericParserConfig {
                 "union_specifier".to_string(),
                 "type_definition".to_string(),
             ],
+            field_nodes: vec!["field_declaration".to_string(), "enumerator".to_string()],
             field_mappings: FieldMappings {
                 name_field: "name".to_string(),
                 params_field: "parameters".to_string(),
@@ -298,6 +375,9 @@ impl GenericParserConfig {
                 name_suffixes: vec!["_test".to_string()],
             }),
             custom_mappings: None,
+            fingerprint_kinds: None,
+            extensions: vec![],
+            grammar: None,
         }
     }
 
@@ -314,6 +394,7 @@ impl GenericParserConfig {
                 "enum_specifier".to_string(),
                 "union_specifier".to_string(),
             ],
+            field_nodes: vec!["field_declaration".to_string(), "enumerator".to_string()],
             field_mappings: FieldMappings {
                 name_field: "name".to_string(),
                 params_field: "parameters".to_string(),
@@ -335,6 +416,9 @@ impl GenericParserConfig {
                 name_suffixes: vec!["_test".to_string(), "Test".to_string()],
             }),
             custom_mappings: None,
+            fingerprint_kinds: None,
+            extensions: vec![],
+            grammar: None,
         }
     }
 
@@ -355,6 +439,11 @@ impl GenericParserConfig {
                 "enum_declaration".to_string(),
                 "record_declaration".to_string(),
             ],
+            field_nodes: vec![
+                "field_declaration".to_string(),
+                "property_declaration".to_string(),
+                "enum_member_declaration".to_string(),
+            ],
             field_mappings: FieldMappings {
                 name_field: "name".to_string(),
                 params_field: "parameters".to_string(),
@@ -381,6 +470,9 @@ impl GenericParserConfig {
                 name_suffixes: vec!["Test".to_string(), "Tests".to_string()],
             }),
             custom_mappings: None,
+            fingerprint_kinds: None,
+            extensions: vec![],
+            grammar: None,
         }
     }
 
@@ -389,6 +481,7 @@ impl GenericParserConfig {
             language: "ruby".to_string(),
             function_nodes: vec!["method".to_string(), "singleton_method".to_string()],
             type_nodes: vec!["class".to_string(), "module".to_string()],
+            field_nodes: vec![],
             field_mappings: FieldMappings {
                 name_field: "name".to_string(),
                 params_field: "parameters".to_string(),
@@ -411,6 +504,9 @@ impl GenericParserConfig {
                 name_suffixes: vec!["_test".to_string(), "_spec".to_string()],
             }),
             custom_mappings: None,
+            fingerprint_kinds: None,
+            extensions: vec![],
+            grammar: None,
         }
     }
 
@@ -426,6 +522,7 @@ impl GenericParserConfig {
                 "interface_declaration".to_string(),
                 "trait_declaration".to_string(),
             ],
+            field_nodes: vec!["property_declaration".to_string()],
             field_mappings: FieldMappings {
                 name_field: "name".to_string(),
                 params_field: "parameters".to_string(),
@@ -448,6 +545,9 @@ impl GenericParserConfig {
                 name_suffixes: vec!["Test".to_string()],
             }),
             custom_mappings: None,
+            fingerprint_kinds: None,
+            extensions: vec![],
+            grammar: None,
         }
     }
 }
@@ -478,4 +578,36 @@ mod tests {
         assert_eq!(c_config.language, "c");
         assert!(c_config.function_nodes.contains(&"function_definition".to_string()));
     }
+
+    #[test]
+    fn test_grammar_library() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("kotlin.json");
+        let mut config = GenericParserConfig {
+            language: "kotlin".to_string(),
+            extensions: vec!["kt".to_string()],
+            grammar: Some(GrammarLibrary { path: "grammars/kotlin.so".into(), symbol: None }),
+            ..Default::default()
+        };
+        config.to_file(&path).unwrap();
+
+        let loaded = GenericParserConfig::from_file(&path).unwrap();
+        let grammar = loaded.grammar.unwrap();
+        assert_eq!(grammar.path, dir.path().join("grammars/kotlin.so"));
+        assert_eq!(grammar.symbol_name("kotlin"), "tree_sitter_kotlin");
+        assert_eq!(loaded.extensions, vec!["kt"]);
+
+        config.grammar = Some(GrammarLibrary {
+            path: "/opt/grammars/dsl.so".into(),
+            symbol: Some("tree_sitter_my_dsl".to_string()),
+        });
+        config.to_file(&path).unwrap();
+        let grammar = GenericParserConfig::from_file(&path).unwrap().grammar.unwrap();
+        assert_eq!(grammar.path, PathBuf::from("/opt/grammars/dsl.so"));
+        assert_eq!(grammar.symbol_name("my-dsl"), "tree_sitter_my_dsl");
+        assert_eq!(
+            GrammarLibrary { symbol: None, ..grammar }.symbol_name("my-dsl"),
+            "tree_sitter_my_dsl"
+        );
+    }
 }
@@ -1,8 +1,10 @@
 #![allow(clippy::io_other_error)]
 
 use crate::generic_parser_config::GenericParserConfig;
-use crate::language_parser::{GenericFunctionDef, GenericTypeDef, Language, LanguageParser};
-use crate::tree::TreeNode;
+use crate::language_parser::{
+    GenericFieldDef, GenericFunctionDef, GenericTypeDef, Language, LanguageParser, TypeDefKind,
+};
+use crate::tree::{SourceSpan, TreeNode};
 use std::error::Error;
 use std::rc::Rc;
 use tree_sitter::{Node, Parser};
@@ -29,6 +31,11 @@ impl GenericTreeSitterParser {
         Ok(Self { parser, config })
     }
 
+    /// Configuration the parser was created with
+    pub fn config(&self) -> &GenericParserConfig {
+        &self.config
+    }
+
     /// Create from a pre-configured language
     pub fn from_language_name(language_name: &str) -> Result<Self, Box<dyn Error + Send + Sync>> {
         let (language, config) = match language_name {
@@ -62,7 +69,8 @@ impl GenericTreeSitterParser {
             "".to_string()
         };
 
-        let mut tree_node = TreeNode::new(label, value, current_id);
+        let mut tree_node =
+            TreeNode::new(label, value, current_id).with_span(SourceSpan::from(node.range()));
 
         for child in node.children(&mut node.walk()) {
             let child_node = self.convert_node(child, source, id_counter);
@@ -349,19 +357,123 @@ impl GenericTreeSitterParser {
             (name, actual_type)
         } else {
             // For other languages, use the standard field mapping
-            let name_node = node.child_by_field_name(&self.config.field_mappings.name_field)?;
+            let name_node = node
+                .child_by_field_name(&self.config.field_mappings.name_field)
+                .or_else(|| node.child_by_field_name("name"))?;
             let name = name_node.utf8_text(source.as_bytes()).ok()?;
             (name, node)
         };
 
+        let mut fields = Vec::new();
+        for child in actual_type_node.children(&mut actual_type_node.walk()) {
+            self.extract_fields_from_node(child, source, &mut fields);
+        }
+
         Some(GenericTypeDef {
             name: name.to_string(),
-            kind: actual_type_node.kind().to_string(),
+            kind: TypeDefKind::from_node_kind(actual_type_node.kind()),
             start_line: node.start_position().row as u32 + 1,
             end_line: node.end_position().row as u32 + 1,
-            fields: Vec::new(), // TODO: Extract fields based on language
+            fields,
         })
     }
+
+    /// Collect the fields declared by `field_nodes`, skipping nested types and methods
+    fn extract_fields_from_node(
+        &self,
+        node: Node,
+        source: &str,
+        fields: &mut Vec<GenericFieldDef>,
+    ) {
+        let node_kind = node.kind().to_string();
+        if self.config.type_nodes.contains(&node_kind)
+            || self.config.function_nodes.contains(&node_kind)
+        {
+            return;
+        }
+        if self.config.field_nodes.contains(&node_kind) {
+            fields.extend(field_declarations(node, source));
+            return;
+        }
+
+        for child in node.children(&mut node.walk()) {
+            self.extract_fields_from_node(child, source, fields);
+        }
+    }
+}
+
+/// The fields declared by one field node, e.g. both `id` and `age` of `int id, age;`
+fn field_declarations(node: Node, source: &str) -> Vec<GenericFieldDef> {
+    let text = |node: Node| node.utf8_text(source.as_bytes()).unwrap_or_default().to_string();
+
+    // C# nests the type and the declarators in a `variable_declaration`
+    let declaration = if node.child_by_field_name("type").is_some() {
+        node
+    } else {
+        node.named_children(&mut node.walk())
+            .find(|child| child.child_by_field_name("type").is_some())
+            .unwrap_or(node)
+    };
+    let type_annotation = declaration.child_by_field_name("type").map(text).unwrap_or_default();
+
+    let mut fields = Vec::new();
+    for child in declaration.named_children(&mut declaration.walk()) {
+        let declarator = match child.kind() {
+            "variable_declarator" => declarator_name(child, source),
+            _ => None,
+        };
+        if let Some((name, suffix)) = declarator {
+            fields.push(GenericFieldDef::new(name, format!("{type_annotation}{suffix}")));
+        }
+    }
+    for name in declaration.children_by_field_name("name", &mut declaration.walk()) {
+        fields.push(GenericFieldDef::new(text(name), type_annotation.clone()));
+    }
+    for declarator in declaration.children_by_field_name("declarator", &mut declaration.walk()) {
+        if declarator.kind() == "variable_declarator" {
+            continue;
+        }
+        if let Some((name, suffix)) = declarator_name(declarator, source) {
+            fields.push(GenericFieldDef::new(name, format!("{type_annotation}{suffix}")));
+        }
+    }
+
+    // An embedded field (Go) is named after its type
+    let is_method = declaration
+        .children_by_field_name("declarator", &mut declaration.walk())
+        .any(|declarator| declarator_name(declarator, source).is_none());
+    if let (true, false, Some(type_node)) =
+        (fields.is_empty(), is_method, declaration.child_by_field_name("type"))
+    {
+        // Keep the `*` of an embedded pointer, which is not part of the type node
+        let embedded = &source[declaration.start_byte()..type_node.end_byte()];
+        let name = type_annotation.rsplit('.').next().unwrap_or(&type_annotation);
+        fields.push(GenericFieldDef::new(name, embedded));
+    }
+
+    fields
+}
+
+/// Name declared by a (possibly nested) declarator, with the type suffix it adds
+/// (`*` for pointers, `[]` for arrays); `None` for function declarators
+fn declarator_name(node: Node, source: &str) -> Option<(String, String)> {
+    let kind = node.kind();
+    if kind.ends_with("identifier") {
+        return Some((node.utf8_text(source.as_bytes()).ok()?.to_string(), String::new()));
+    }
+    let suffix = match kind {
+        "function_declarator" => return None,
+        "pointer_declarator" => "*",
+        "array_declarator" => "[]",
+        "reference_declarator" => "&",
+        _ => "",
+    };
+    let inner = node
+        .child_by_field_name("name")
+        .or_else(|| node.child_by_field_name("declarator"))
+        .or_else(|| node.named_child(0))?;
+    let (name, inner_suffix) = declarator_name(inner, source)?;
+    Some((name, format!("{suffix}{inner_suffix}")))
 }
 
 impl LanguageParser for GenericTreeSitterParser {
@@ -393,6 +505,7 @@ impl LanguageParser for GenericTreeSitterParser {
         let root_node = tree.root_node();
         let mut functions = Vec::new();
         self.extract_functions_from_node(root_node, source, &mut functions, None);
+        crate::suppression::retain_unsuppressed(source, &mut functions, |f| f.start_line);
         Ok(functions)
     }
 
@@ -424,6 +537,7 @@ impl LanguageParser for GenericTreeSitterParser {
             "csharp" => Language::CSharp,
             "ruby" => Language::Ruby,
             "php" => Language::Php,
+            "elixir" => Language::Elixir,
             _ => Language::Unknown,
         }
     }
@@ -478,4 +592,54 @@ public class Calculator {
         assert_eq!(functions[0].name, "add");
         assert_eq!(functions[1].name, "multiply");
     }
+
+    #[test]
+    fn test_generic_type_fields_with_go() {
+        let mut parser = GenericTreeSitterParser::from_language_name("go").unwrap();
+
+        let source = r#"
+package main
+
+type User struct {
+    ID, Age int `json:"id"`
+    Name    string
+    *Base
+}
+"#;
+
+        let types = parser.extract_types(source, "test.go").unwrap();
+        assert_eq!(types.len(), 1);
+        assert_eq!(types[0].kind, TypeDefKind::Struct);
+        assert_eq!(
+            types[0].fields,
+            vec![
+                GenericFieldDef::new("ID", "int"),
+                GenericFieldDef::new("Age", "int"),
+                GenericFieldDef::new("Name", "string"),
+                GenericFieldDef::new("Base", "*Base"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generic_type_fields_with_cpp() {
+        let mut parser = GenericTreeSitterParser::from_language_name("cpp").unwrap();
+
+        let source = r#"
+class Node {
+public:
+    int values[4];
+    Node *next;
+    void print();
+};
+"#;
+
+        let types = parser.extract_types(source, "test.cpp").unwrap();
+        assert_eq!(types[0].name, "Node");
+        assert_eq!(types[0].kind, TypeDefKind::Class);
+        assert_eq!(
+            types[0].fields,
+            vec![GenericFieldDef::new("values", "int[]"), GenericFieldDef::new("next", "Node*")]
+        );
+    }
 }
@@ -0,0 +1,7 @@
+/// FNV-1a, used instead of `DefaultHasher` because LSH signatures, baselines and caches must
+/// stay stable across builds
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
@@ -1,5 +1,7 @@
 use crate::tree::TreeNode;
+use crate::type_extractor::{PropertyDefinition, TypeDefinition, TypeKind};
 use std::error::Error;
+use std::fmt;
 use std::rc::Rc;
 
 /// Supported programming languages
@@ -16,14 +18,15 @@ pub enum Language {
     CSharp,
     Ruby,
     Php,
+    Elixir,
     Unknown,
 }
 
 impl Language {
     pub fn from_extension(ext: &str) -> Option<Self> {
         match ext.to_lowercase().as_str() {
-            "js" | "mjs" | "cjs" => Some(Language::JavaScript),
-            "ts" | "tsx" => Some(Language::TypeScript),
+            "js" | "jsx" | "mjs" | "cjs" => Some(Language::JavaScript),
+            "ts" | "tsx" | "mts" | "cts" => Some(Language::TypeScript),
             "py" => Some(Language::Python),
             "rs" => Some(Language::Rust),
             "go" => Some(Language::Go),
@@ -33,13 +36,52 @@ impl Language {
             "cs" => Some(Language::CSharp),
             "rb" => Some(Language::Ruby),
             "php" => Some(Language::Php),
+            "ex" | "exs" => Some(Language::Elixir),
             _ => None,
         }
     }
 
+    /// File extensions recognized by [`Language::from_extension`]
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            Language::JavaScript => &["js", "jsx", "mjs", "cjs"],
+            Language::TypeScript => &["ts", "tsx", "mts", "cts"],
+            Language::Python => &["py"],
+            Language::Rust => &["rs"],
+            Language::Go => &["go"],
+            Language::Java => &["java"],
+            Language::C => &["c", "h"],
+            Language::Cpp => &["cpp", "cc", "cxx", "hpp", "hxx", "c++"],
+            Language::CSharp => &["cs"],
+            Language::Ruby => &["rb"],
+            Language::Php => &["php"],
+            Language::Elixir => &["ex", "exs"],
+            Language::Unknown => &[],
+        }
+    }
+
     pub fn from_filename(filename: &str) -> Option<Self> {
         filename.split('.').next_back().and_then(Self::from_extension)
     }
+
+    /// Name of the language's section in project config files, e.g. `[python]`.
+    /// JavaScript shares the `typescript` section.
+    pub fn config_name(&self) -> &'static str {
+        match self {
+            Language::JavaScript | Language::TypeScript => "typescript",
+            Language::Python => "python",
+            Language::Rust => "rust",
+            Language::Go => "go",
+            Language::Java => "java",
+            Language::C => "c",
+            Language::Cpp => "cpp",
+            Language::CSharp => "csharp",
+            Language::Ruby => "ruby",
+            Language::Php => "php",
+            Language::Elixir => "elixir",
+            Language::Unknown => "unknown",
+        }
+    }
 }
 
 /// Generic function definition that works across languages
@@ -58,14 +100,35 @@ pub struct GenericFunctionDef {
     pub decorators: Vec<String>,
 }
 
+impl GenericFunctionDef {
+    /// Number of lines spanned by the whole function
+    pub fn line_count(&self) -> u32 {
+        self.end_line - self.start_line + 1
+    }
+}
+
 /// Generic type definition that works across languages
 #[derive(Debug, Clone)]
 pub struct GenericTypeDef {
     pub name: String,
-    pub kind: String, // "struct", "enum", "type_alias", etc.
+    pub kind: TypeDefKind,
     pub start_line: u32,
     pub end_line: u32,
-    pub fields: Vec<String>, // Fields for structs, variants for enums, etc.
+    pub fields: Vec<GenericFieldDef>, // Fields for structs and classes, variants for enums, etc.
+}
+
+/// A field of a type definition, or a variant of an enum
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenericFieldDef {
+    pub name: String,
+    /// Declared type as written in the source; empty when the language does not state one
+    pub type_annotation: String,
+}
+
+impl GenericFieldDef {
+    pub fn new(name: impl Into<String>, type_annotation: impl Into<String>) -> Self {
+        Self { name: name.into(), type_annotation: type_annotation.into() }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -75,6 +138,86 @@ pub enum TypeDefKind {
     TypeAlias,
     Enum,
     Struct,
+    Module,
+    /// Any other kind, named after the language's own construct (e.g. `implementation`)
+    Other(String),
+}
+
+impl TypeDefKind {
+    /// Classify a tree-sitter node kind such as `struct_specifier` or `class_declaration`
+    pub fn from_node_kind(kind: &str) -> Self {
+        if kind.contains("struct") || kind.contains("union") || kind.contains("record") {
+            Self::Struct
+        } else if kind.contains("enum") {
+            Self::Enum
+        } else if kind.contains("interface") || kind.contains("trait") || kind.contains("protocol")
+        {
+            Self::Interface
+        } else if kind.contains("class") {
+            Self::Class
+        } else if kind.contains("module") {
+            Self::Module
+        } else if kind.contains("alias") || kind == "type_definition" {
+            Self::TypeAlias
+        } else {
+            Self::Other(kind.to_string())
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Class => "class",
+            Self::Interface => "interface",
+            Self::TypeAlias => "type_alias",
+            Self::Enum => "enum",
+            Self::Struct => "struct",
+            Self::Module => "module",
+            Self::Other(kind) => kind,
+        }
+    }
+}
+
+impl fmt::Display for TypeDefKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl GenericTypeDef {
+    /// Names of the fields (or variants) in declaration order
+    pub fn field_names(&self) -> Vec<&str> {
+        self.fields.iter().map(|field| field.name.as_str()).collect()
+    }
+
+    /// Convert to the language-neutral form scored by the `type_comparator`
+    pub fn to_type_definition(&self, file_path: &str) -> TypeDefinition {
+        let kind = match &self.kind {
+            TypeDefKind::Interface => TypeKind::Interface,
+            TypeDefKind::TypeAlias => TypeKind::TypeAlias,
+            TypeDefKind::Enum => TypeKind::Enum,
+            TypeDefKind::Struct => TypeKind::Struct,
+            TypeDefKind::Class | TypeDefKind::Module | TypeDefKind::Other(_) => TypeKind::Class,
+        };
+        TypeDefinition {
+            name: self.name.clone(),
+            kind,
+            properties: self
+                .fields
+                .iter()
+                .map(|field| PropertyDefinition {
+                    name: field.name.clone(),
+                    type_annotation: field.type_annotation.clone(),
+                    optional: false,
+                    readonly: false,
+                })
+                .collect(),
+            generics: Vec::new(),
+            extends: Vec::new(),
+            start_line: self.start_line as usize,
+            end_line: self.end_line as usize,
+            file_path: file_path.to_string(),
+        }
+    }
 }
 
 /// Trait for language-specific parsers
@@ -117,12 +260,56 @@ mod tests {
         assert_eq!(Language::from_filename("test.py"), Some(Language::Python));
         assert_eq!(Language::from_filename("test.rs"), Some(Language::Rust));
         assert_eq!(Language::from_filename("test.go"), Some(Language::Go));
+        assert_eq!(Language::from_filename("test.exs"), Some(Language::Elixir));
         assert_eq!(Language::from_filename("test.txt"), None);
     }
 
+    #[test]
+    fn test_extensions_round_trip() {
+        for language in [Language::JavaScript, Language::Cpp, Language::Elixir] {
+            for ext in language.extensions() {
+                assert_eq!(Language::from_extension(ext), Some(language));
+            }
+        }
+    }
+
     #[test]
     fn test_case_insensitive_extension() {
         assert_eq!(Language::from_extension("JS"), Some(Language::JavaScript));
         assert_eq!(Language::from_extension("Py"), Some(Language::Python));
     }
+
+    #[test]
+    fn test_type_def_kind_from_node_kind() {
+        assert_eq!(TypeDefKind::from_node_kind("struct_specifier"), TypeDefKind::Struct);
+        assert_eq!(TypeDefKind::from_node_kind("class_declaration"), TypeDefKind::Class);
+        assert_eq!(TypeDefKind::from_node_kind("interface_type"), TypeDefKind::Interface);
+        assert_eq!(TypeDefKind::from_node_kind("enum_declaration"), TypeDefKind::Enum);
+        assert_eq!(TypeDefKind::from_node_kind("type_definition"), TypeDefKind::TypeAlias);
+        assert_eq!(
+            TypeDefKind::from_node_kind("function_type"),
+            TypeDefKind::Other("function_type".to_string())
+        );
+    }
+
+    #[test]
+    fn test_generic_type_to_type_definition() {
+        let type_def = GenericTypeDef {
+            name: "User".to_string(),
+            kind: TypeDefKind::Struct,
+            start_line: 3,
+            end_line: 7,
+            fields: vec![
+                GenericFieldDef::new("ID", "int64"),
+                GenericFieldDef::new("Name", "string"),
+            ],
+        };
+
+        let converted = type_def.to_type_definition("user.go");
+        assert_eq!(converted.kind, TypeKind::Struct);
+        assert_eq!(converted.file_path, "user.go");
+        assert_eq!((converted.start_line, converted.end_line), (3, 7));
+        assert_eq!(converted.properties[1].name, "Name");
+        assert_eq!(converted.properties[1].type_annotation, "string");
+    }
 }
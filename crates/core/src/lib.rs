@@ -1,18 +1,24 @@
 #![allow(clippy::uninlined_format_args)]
 
 pub mod apted;
+pub mod apted_optimal;
 pub mod ast_exchange;
 pub mod ast_fingerprint;
+pub mod cross_language;
+pub mod edit_script;
 pub mod enhanced_similarity;
 pub mod fast_similarity;
 pub mod function_extractor;
 pub mod generic_overlap_detector;
 pub mod generic_parser_config;
 pub mod generic_tree_sitter_parser;
+mod hashing;
 pub mod language_parser;
+pub mod lsh;
 pub mod overlap_detector;
 pub mod parser;
 pub mod subtree_fingerprint;
+pub mod suppression;
 pub mod tree;
 pub mod tsed;
 pub mod type_comparator;
@@ -20,21 +26,39 @@ pub mod type_extractor;
 pub mod type_normalizer;
 
 // CLI utilities
+pub mod cli_baseline;
+pub mod cli_cache;
+pub mod cli_changes;
+pub mod cli_clone_classes;
+pub mod cli_config;
 pub mod cli_file_utils;
+pub mod cli_ignore;
 pub mod cli_output;
 pub mod cli_parallel;
+pub mod cli_pipeline;
+pub mod cli_sarif;
+pub mod cli_types;
 
-pub use apted::{compute_edit_distance, APTEDOptions};
+pub use apted::{compute_edit_distance, APTEDOptions, TreeEditAlgorithm};
+pub use apted_optimal::{
+    compute_apted_distance, compute_apted_distance_with_costs,
+    compute_apted_edit_script_with_costs, CostModel,
+};
+pub use edit_script::{compute_edit_script, describe_node, EditOperation, EditScript};
 pub use enhanced_similarity::{
     calculate_enhanced_similarity, calculate_semantic_similarity, EnhancedSimilarityOptions,
 };
 pub use function_extractor::{
-    compare_functions, extract_functions, find_similar_functions_across_files,
-    find_similar_functions_in_file, FunctionDefinition, FunctionType, SimilarityResult,
+    compare_function_trees, compare_functions, diff_functions, extract_functions,
+    find_similar_changed_functions, find_similar_functions_across_files,
+    find_similar_functions_in_file, find_similar_parsed_functions, parse_function_tree,
+    parse_functions, FunctionDefinition, FunctionType, ParsedFunction, SimilarityResult,
 };
 pub use parser::{ast_to_tree_node, parse_and_convert_to_tree};
-pub use tree::TreeNode;
-pub use tsed::{calculate_tsed, calculate_tsed_from_code, TSEDOptions};
+pub use tree::{ArenaTree, AsArenaTree, LineIndex, NodeView, SourceSpan, TreeNode};
+pub use tsed::{
+    calculate_tsed, calculate_tsed_from_code, calculate_tsed_with_edit_script, TSEDOptions,
+};
 
 // Type-related exports
 pub use type_comparator::{
@@ -54,7 +78,7 @@ pub use type_normalizer::{
 };
 
 // Fast similarity exports
-pub use ast_fingerprint::AstFingerprint;
+pub use ast_fingerprint::{AstFingerprint, FingerprintVocabulary, FAST_FINGERPRINT_THRESHOLD};
 pub use fast_similarity::{
     find_similar_functions_across_files_fast, find_similar_functions_fast, FastSimilarityOptions,
 };
@@ -0,0 +1,318 @@
+//! MinHash / LSH candidate generation.
+//!
+//! Comparing every pair of functions with TSED is quadratic. Instead each function tree is
+//! reduced to a set of AST shingles (vertical n-grams of node labels plus adjacent sibling
+//! labels) and summarized by a MinHash signature of `bands * rows` values. The signature is cut
+//! into bands and every band is hashed into a bucket: only functions sharing a bucket become
+//! candidate pairs, so candidate generation grows with the number of functions and collisions
+//! rather than with the number of pairs.
+//!
+//! Two functions whose shingle sets have Jaccard similarity `s` collide with probability
+//! `1 - (1 - s^rows)^bands`. More bands raise recall, more rows per band raise precision.
+//! Only node kinds are shingled, identifiers are ignored, so renamed copies still collide.
+
+use crate::hashing::fnv1a;
+use crate::tree::{AsArenaTree, NodeView};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Shape of the MinHash signatures and of the LSH bands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LshOptions {
+    /// Number of bands; a pair is a candidate when any band matches
+    pub bands: usize,
+    /// MinHash values per band
+    pub rows: usize,
+    /// Length of the vertical label n-grams
+    pub shingle_size: usize,
+}
+
+impl Default for LshOptions {
+    fn default() -> Self {
+        Self { bands: 32, rows: 3, shingle_size: 3 }
+    }
+}
+
+impl LshOptions {
+    /// Options with `bands` bands of `rows` values; zero counts are raised to 1, since an
+    /// empty band would prune every pair
+    pub fn new(bands: usize, rows: usize) -> Self {
+        Self { bands: bands.max(1), rows: rows.max(1), ..Self::default() }
+    }
+
+    /// Probability that two functions with shingle Jaccard similarity `jaccard` are candidates
+    pub fn collision_probability(&self, jaccard: f64) -> f64 {
+        1.0 - (1.0 - jaccard.powi(self.rows as i32)).powi(self.bands as i32)
+    }
+
+    fn signature_len(&self) -> usize {
+        self.bands * self.rows
+    }
+}
+
+/// Which field of a tree node holds its kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    /// Trees from the tree-sitter parsers: the label is the node kind, the value its text
+    Label,
+    /// Trees from the oxc converter: the value is the node kind, the label often a name
+    Value,
+}
+
+impl NodeKind {
+    fn of<'a>(self, node: NodeView<'a>) -> &'a str {
+        match self {
+            NodeKind::Label => node.label,
+            NodeKind::Value => node.value,
+        }
+    }
+}
+
+/// MinHash signature of the shingles of a tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinHashSignature(Vec<u64>);
+
+impl MinHashSignature {
+    /// Signature of `tree`, `None` for an empty tree
+    pub fn from_tree(
+        tree: &impl AsArenaTree,
+        kind: NodeKind,
+        options: &LshOptions,
+    ) -> Option<Self> {
+        let shingles = shingles(tree, kind, options.shingle_size);
+        if shingles.is_empty() {
+            return None;
+        }
+
+        let signature = (0..options.signature_len())
+            .map(|i| {
+                let seed = mix(i as u64 + 1);
+                shingles.iter().map(|&shingle| mix(shingle ^ seed)).min().unwrap_or(u64::MAX)
+            })
+            .collect();
+        Some(Self(signature))
+    }
+
+    /// Fraction of equal values, an estimate of the Jaccard similarity of the shingle sets
+    pub fn similarity(&self, other: &MinHashSignature) -> f64 {
+        let len = self.0.len().min(other.0.len());
+        if len == 0 {
+            return 0.0;
+        }
+        let equal = self.0.iter().zip(&other.0).filter(|(a, b)| a == b).count();
+        equal as f64 / len as f64
+    }
+}
+
+/// Distinct shingle hashes: the kinds of each node and its nearest ancestors, and the kinds
+/// of adjacent siblings
+fn shingles(tree: &impl AsArenaTree, kind: NodeKind, shingle_size: usize) -> Vec<u64> {
+    let tree = tree.as_arena();
+    let labels: Vec<u64> =
+        (0..tree.len()).map(|id| fnv1a(kind.of(tree.node(id)).as_bytes())).collect();
+
+    let mut shingles = Vec::with_capacity(tree.len() * 2);
+    for id in 0..tree.len() {
+        let mut hash = labels[id];
+        let mut ancestor = tree.parent(id);
+        for _ in 1..shingle_size {
+            let Some(parent) = ancestor else { break };
+            hash = mix(hash ^ labels[parent]);
+            ancestor = tree.parent(parent);
+        }
+        shingles.push(hash);
+
+        for pair in tree.children(id).windows(2) {
+            shingles.push(mix(labels[pair[0]].rotate_left(1) ^ labels[pair[1]] ^ 0x5151));
+        }
+    }
+    shingles.sort_unstable();
+    shingles.dedup();
+    shingles
+}
+
+/// splitmix64 finalizer, used as the family of MinHash hash functions
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// Buckets of items whose signatures share a band
+#[derive(Debug, Default)]
+pub struct LshIndex {
+    buckets: HashMap<u64, Vec<usize>>,
+}
+
+impl LshIndex {
+    /// Index `(id, signature)` items; signatures must come from the same `options`
+    pub fn new<'a>(
+        options: &LshOptions,
+        signatures: impl IntoIterator<Item = (usize, &'a MinHashSignature)>,
+    ) -> Self {
+        let mut buckets: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (id, signature) in signatures {
+            for (band, values) in signature.0.chunks(options.rows.max(1)).enumerate() {
+                let key = values.iter().fold(mix(band as u64), |hash, &value| mix(hash ^ value));
+                buckets.entry(key).or_default().push(id);
+            }
+        }
+        Self { buckets }
+    }
+
+    /// Distinct `(a, b)` pairs with `a < b` sharing at least one bucket, sorted
+    pub fn candidates(&self) -> Vec<(usize, usize)> {
+        let mut pairs = HashSet::new();
+        for ids in self.buckets.values() {
+            for (i, &a) in ids.iter().enumerate() {
+                for &b in &ids[i + 1..] {
+                    if a != b {
+                        pairs.insert((a.min(b), a.max(b)));
+                    }
+                }
+            }
+        }
+        let mut pairs: Vec<(usize, usize)> = pairs.into_iter().collect();
+        pairs.sort_unstable();
+        pairs
+    }
+}
+
+/// Number of distinct pairs among `n` items
+pub fn pair_count(n: usize) -> usize {
+    n * n.saturating_sub(1) / 2
+}
+
+/// `(i, j)` pairs of parsed trees to compare: the LSH candidates with `lsh`, every pair
+/// otherwise. Missing trees are never paired; the pruning is recorded in `stats`.
+pub fn select_pairs<T: AsArenaTree>(
+    trees: &[Option<T>],
+    kind: NodeKind,
+    lsh: Option<&LshOptions>,
+    stats: &CandidateStats,
+) -> Vec<(usize, usize)> {
+    let parsed: Vec<usize> = (0..trees.len()).filter(|&i| trees[i].is_some()).collect();
+    let pairs = match lsh {
+        Some(lsh) => {
+            let signatures: Vec<(usize, MinHashSignature)> = parsed
+                .iter()
+                .filter_map(|&i| {
+                    Some((i, MinHashSignature::from_tree(trees[i].as_ref()?, kind, lsh)?))
+                })
+                .collect();
+            LshIndex::new(lsh, signatures.iter().map(|(i, sig)| (*i, sig))).candidates()
+        }
+        None => parsed
+            .iter()
+            .enumerate()
+            .flat_map(|(n, &i)| parsed[n + 1..].iter().map(move |&j| (i, j)))
+            .collect(),
+    };
+    stats.record(pair_count(parsed.len()), pairs.len());
+    pairs
+}
+
+/// Pruning statistics of candidate generation, shared between worker threads
+#[derive(Debug, Default)]
+pub struct CandidateStats {
+    pairs: AtomicUsize,
+    candidates: AtomicUsize,
+}
+
+impl CandidateStats {
+    /// Record that `candidates` out of `pairs` possible pairs were kept
+    pub fn record(&self, pairs: usize, candidates: usize) {
+        self.pairs.fetch_add(pairs, Ordering::Relaxed);
+        self.candidates.fetch_add(candidates, Ordering::Relaxed);
+    }
+
+    pub fn pairs(&self) -> usize {
+        self.pairs.load(Ordering::Relaxed)
+    }
+
+    pub fn candidates(&self) -> usize {
+        self.candidates.load(Ordering::Relaxed)
+    }
+
+    /// Share of the possible pairs that were never compared
+    pub fn pruning_rate(&self) -> f64 {
+        match self.pairs() {
+            0 => 0.0,
+            pairs => 1.0 - self.candidates() as f64 / pairs as f64,
+        }
+    }
+
+    /// One line summary, e.g. for `--stats`
+    pub fn summary(&self) -> String {
+        format!(
+            "Candidate pairs: {} of {} compared ({:.1}% pruned)",
+            self.candidates(),
+            self.pairs(),
+            self.pruning_rate() * 100.0
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_and_convert_to_tree;
+
+    fn signature(code: &str) -> MinHashSignature {
+        let tree = parse_and_convert_to_tree("test.ts", code).unwrap();
+        MinHashSignature::from_tree(&tree, NodeKind::Value, &LshOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn test_renamed_copies_collide() {
+        let a =
+            signature("function a(xs) { let t = 0; for (const x of xs) { t += x; } return t; }");
+        let b =
+            signature("function b(ys) { let s = 0; for (const y of ys) { s += y; } return s; }");
+        let c = signature(
+            "class P { constructor(name) { this.name = name; } greet() { console.log(this.name); } }",
+        );
+
+        assert_eq!(a.similarity(&b), 1.0);
+        assert!(a.similarity(&c) < 0.5);
+
+        let options = LshOptions::default();
+        let index = LshIndex::new(&options, [(0, &a), (1, &b), (2, &c)]);
+        assert_eq!(index.candidates(), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn test_collision_probability() {
+        let options = LshOptions::default();
+        assert!(options.collision_probability(0.8) > 0.99);
+        assert!(options.collision_probability(0.1) < 0.1);
+        let precise = LshOptions::new(8, 8);
+        assert!(precise.collision_probability(0.5) < options.collision_probability(0.5));
+        assert_eq!(LshOptions::new(0, 0), LshOptions::new(1, 1));
+    }
+
+    #[test]
+    fn test_select_pairs() {
+        let code = "function a(xs) { let t = 0; for (const x of xs) { t += x; } return t; }";
+        let tree = parse_and_convert_to_tree("test.ts", code).unwrap();
+        let trees = [Some(tree.clone()), None, Some(tree)];
+        let stats = CandidateStats::default();
+
+        let all = select_pairs(&trees, NodeKind::Value, None, &stats);
+        let lsh = select_pairs(&trees, NodeKind::Value, Some(&LshOptions::default()), &stats);
+        assert_eq!(all, vec![(0, 2)]);
+        assert_eq!(lsh, vec![(0, 2)]);
+        assert_eq!((stats.pairs(), stats.candidates()), (2, 2));
+    }
+
+    #[test]
+    fn test_candidate_stats() {
+        let stats = CandidateStats::default();
+        assert_eq!(stats.pruning_rate(), 0.0);
+        stats.record(pair_count(5), 2);
+        stats.record(pair_count(1), 0);
+        assert_eq!(stats.pairs(), 10);
+        assert!((stats.pruning_rate() - 0.8).abs() < 1e-9);
+        assert_eq!(stats.summary(), "Candidate pairs: 2 of 10 compared (80.0% pruned)");
+    }
+}
@@ -5,6 +5,7 @@ use crate::{
         detect_partial_overlaps, generate_subtree_fingerprints, IndexedFunction, OverlapOptions,
         PartialOverlap,
     },
+    tree::ArenaTree,
     tsed::{calculate_tsed, TSEDOptions},
 };
 use std::collections::HashMap;
@@ -21,21 +22,26 @@ pub fn find_function_overlaps(
     let target_functions =
         extract_functions("target.ts", target_code).map_err(|e| anyhow::anyhow!(e))?;
 
-    // Parse and index functions
+    // Parse and index every function once
     let mut all_overlaps = Vec::new();
+    if source_functions.is_empty() {
+        return Ok(all_overlaps);
+    }
+    let targets = target_functions
+        .iter()
+        .map(|target_func| index_function(target_func, target_code, "target.ts"))
+        .collect::<Result<Vec<_>, _>>()?;
 
     for source_func in &source_functions {
         let source_indexed = index_function(source_func, source_code, "source.ts")?;
 
-        for target_func in &target_functions {
+        for (target_func, target_indexed) in target_functions.iter().zip(&targets) {
             // Skip if comparing the same function in the same file
             // (but allow comparing functions with same name in different files)
             if source_func.name == target_func.name && source_code == target_code {
                 continue;
             }
 
-            let target_indexed = index_function(target_func, target_code, "target.ts")?;
-
             // Debug output
             #[cfg(test)]
             {
@@ -45,7 +51,7 @@ pub fn find_function_overlaps(
             }
 
             // Detect overlaps
-            let overlaps = detect_partial_overlaps(&source_indexed, &target_indexed, options);
+            let overlaps = detect_partial_overlaps(&source_indexed, target_indexed, options);
             all_overlaps.extend(overlaps);
         }
     }
@@ -125,7 +131,10 @@ fn index_function(
     let tree = parse_and_convert_to_tree(file_name, &func_code).map_err(|e| anyhow::anyhow!(e))?;
 
     // Generate fingerprints for all subtrees
-    let (root_fp, subtrees) = generate_subtree_fingerprints(&tree, 0, func.start_line);
+    let (root_fp, subtrees) = generate_subtree_fingerprints(
+        &ArenaTree::from_node(&tree),
+        func.start_line.saturating_sub(1),
+    );
 
     // Create indexed function
     let mut indexed = IndexedFunction::new(func.name.clone(), file_name.to_string(), root_fp);
@@ -152,35 +161,34 @@ pub fn find_overlaps_with_similarity(
     let mut detailed_overlaps = Vec::new();
 
     for overlap in overlaps {
+        // Spans put the overlap on real lines, so the duplicated block can always be shown
+        let source_segment =
+            extract_code_segment(source_code, overlap.source_lines.0, overlap.source_lines.1)?;
+        let target_segment =
+            extract_code_segment(target_code, overlap.target_lines.0, overlap.target_lines.1)?;
+
         // For high-similarity overlaps, calculate exact TSED similarity
-        if overlap.similarity > 0.9 {
-            // Extract the overlapping code segments
-            let source_segment =
-                extract_code_segment(source_code, overlap.source_lines.0, overlap.source_lines.1)?;
-            let target_segment =
-                extract_code_segment(target_code, overlap.target_lines.0, overlap.target_lines.1)?;
-
-            // Parse and calculate exact similarity
-            let source_tree = parse_and_convert_to_tree("source.ts", &source_segment)
-                .map_err(|e| anyhow::anyhow!(e))?;
-            let target_tree = parse_and_convert_to_tree("target.ts", &target_segment)
-                .map_err(|e| anyhow::anyhow!(e))?;
-            let exact_similarity = calculate_tsed(&source_tree, &target_tree, tsed_options);
-
-            detailed_overlaps.push(DetailedOverlap {
-                overlap: overlap.clone(),
-                exact_similarity,
-                source_code: source_segment,
-                target_code: target_segment,
-            });
+        let exact_similarity = if overlap.similarity > 0.9 {
+            // Windows may cut through a statement; keep the estimate when they don't parse
+            match (
+                parse_and_convert_to_tree("source.ts", &source_segment),
+                parse_and_convert_to_tree("target.ts", &target_segment),
+            ) {
+                (Ok(source_tree), Ok(target_tree)) => {
+                    calculate_tsed(&source_tree, &target_tree, tsed_options)
+                }
+                _ => overlap.similarity,
+            }
         } else {
-            detailed_overlaps.push(DetailedOverlap {
-                overlap: overlap.clone(),
-                exact_similarity: overlap.similarity,
-                source_code: String::new(),
-                target_code: String::new(),
-            });
-        }
+            overlap.similarity
+        };
+
+        detailed_overlaps.push(DetailedOverlap {
+            overlap,
+            exact_similarity,
+            source_code: source_segment,
+            target_code: target_segment,
+        });
     }
 
     Ok(detailed_overlaps)
@@ -285,6 +293,50 @@ function utilityFunction() {
         // Check that we found overlaps (may not always detect For specifically due to windowing)
     }
 
+    #[test]
+    fn test_overlaps_point_at_source_lines() {
+        let code = r#"
+function first(items) {
+    const out = [];
+    for (let i = 0; i < items.length; i++) {
+        out.push(items[i] * 2);
+    }
+    return out;
+}
+
+function second(items) {
+    const out = [];
+    for (let i = 0; i < items.length; i++) {
+        out.push(items[i] * 2);
+    }
+    return out;
+}
+"#;
+        let options = OverlapOptions {
+            min_window_size: 5,
+            max_window_size: 20,
+            threshold: 0.8,
+            size_tolerance: 0.2,
+        };
+        let overlaps =
+            find_overlaps_with_similarity(code, code, &options, &TSEDOptions::default()).unwrap();
+        assert!(!overlaps.is_empty());
+
+        for detailed in &overlaps {
+            let (source_start, source_end) = detailed.overlap.source_lines;
+            let (target_start, target_end) = detailed.overlap.target_lines;
+            // Every block lies inside one of the two functions (lines 2-8 and 10-16)
+            for (start, end) in [(source_start, source_end), (target_start, target_end)] {
+                assert!(start <= end);
+                assert!((2..=8).contains(&start) || (10..=16).contains(&start));
+                assert!(end <= 16);
+            }
+            assert!(!detailed.source_code.is_empty());
+            assert!(!detailed.target_code.is_empty());
+        }
+        assert!(overlaps.iter().any(|detailed| detailed.source_code.contains("for (let i = 0")));
+    }
+
     #[test]
     fn test_extract_code_segment() {
         let code = "line1\nline2\nline3\nline4\nline5";
@@ -4,10 +4,10 @@ use oxc_ast::ast::{
     Program, PropertyKey, Statement, VariableDeclarator,
 };
 use oxc_parser::Parser;
-use oxc_span::SourceType;
+use oxc_span::{GetSpan, SourceType, Span};
 use std::rc::Rc;
 
-use crate::tree::TreeNode;
+use crate::tree::{LineIndex, SourceSpan, TreeNode};
 
 /// Parse TypeScript code and convert to `TreeNode` structure
 ///
@@ -30,12 +30,16 @@ pub fn parse_and_convert_to_tree(
     Ok(ast_to_tree_node(&ret.program, &mut id_counter))
 }
 
+/// Convert a parsed program to a `TreeNode` tree, numbering nodes from `id_counter`.
+///
+/// Every node carries the [`SourceSpan`] of the AST node it was built from.
 pub fn ast_to_tree_node(program: &Program, id_counter: &mut usize) -> Rc<TreeNode> {
-    let mut root = TreeNode::new("Program".to_string(), "Program".to_string(), *id_counter);
-    *id_counter += 1;
+    let mut cx = Converter { id_counter, lines: LineIndex::new(program.source_text) };
+    let cx = &mut cx;
+    let mut root = cx.node("Program".to_string(), "Program".to_string(), program.span);
 
     for stmt in &program.body {
-        if let Some(child) = statement_to_tree_node(stmt, id_counter) {
+        if let Some(child) = statement_to_tree_node(stmt, cx) {
             root.add_child(child);
         }
     }
@@ -43,23 +47,37 @@ pub fn ast_to_tree_node(program: &Program, id_counter: &mut usize) -> Rc<TreeNod
     Rc::new(root)
 }
 
-fn statement_to_tree_node(stmt: &Statement, id_counter: &mut usize) -> Option<Rc<TreeNode>> {
+/// State shared while converting one program
+struct Converter<'a> {
+    id_counter: &'a mut usize,
+    lines: LineIndex,
+}
+
+impl Converter<'_> {
+    fn node(&mut self, label: String, value: String, span: Span) -> TreeNode {
+        let id = *self.id_counter;
+        *self.id_counter += 1;
+        let span = SourceSpan::from_offsets(&self.lines, span.start as usize, span.end as usize);
+        TreeNode::new(label, value, id).with_span(span)
+    }
+}
+
+fn statement_to_tree_node(stmt: &Statement, cx: &mut Converter<'_>) -> Option<Rc<TreeNode>> {
     match stmt {
         Statement::FunctionDeclaration(func) => {
             let label = func.id.as_ref().map_or("Function", |id| id.name.as_str()).to_string();
-            let mut node = TreeNode::new(label, "FunctionDeclaration".to_string(), *id_counter);
-            *id_counter += 1;
+            let mut node = cx.node(label, "FunctionDeclaration".to_string(), func.span);
 
             // Add parameters
             for param in &func.params.items {
-                if let Some(param_node) = formal_parameter_to_tree_node(param, id_counter) {
+                if let Some(param_node) = formal_parameter_to_tree_node(param, cx) {
                     node.add_child(param_node);
                 }
             }
 
             // Add body
             if let Some(body) = &func.body {
-                if let Some(body_node) = function_body_to_tree_node(body, id_counter) {
+                if let Some(body_node) = function_body_to_tree_node(body, cx) {
                     node.add_child(body_node);
                 }
             }
@@ -68,12 +86,11 @@ fn statement_to_tree_node(stmt: &Statement, id_counter: &mut usize) -> Option<Rc
         }
         Statement::ClassDeclaration(class) => {
             let label = class.id.as_ref().map_or("Class", |id| id.name.as_str()).to_string();
-            let mut node = TreeNode::new(label, "ClassDeclaration".to_string(), *id_counter);
-            *id_counter += 1;
+            let mut node = cx.node(label, "ClassDeclaration".to_string(), class.span);
 
             // Add class body elements
             for element in &class.body.body {
-                if let Some(elem_node) = class_element_to_tree_node(element, id_counter) {
+                if let Some(elem_node) = class_element_to_tree_node(element, cx) {
                     node.add_child(elem_node);
                 }
             }
@@ -81,15 +98,14 @@ fn statement_to_tree_node(stmt: &Statement, id_counter: &mut usize) -> Option<Rc
             Some(Rc::new(node))
         }
         Statement::VariableDeclaration(var_decl) => {
-            let mut node = TreeNode::new(
+            let mut node = cx.node(
                 "VariableDeclaration".to_string(),
                 "VariableDeclaration".to_string(),
-                *id_counter,
+                var_decl.span,
             );
-            *id_counter += 1;
 
             for decl in &var_decl.declarations {
-                if let Some(decl_node) = variable_declarator_to_tree_node(decl, id_counter) {
+                if let Some(decl_node) = variable_declarator_to_tree_node(decl, cx) {
                     node.add_child(decl_node);
                 }
             }
@@ -97,27 +113,26 @@ fn statement_to_tree_node(stmt: &Statement, id_counter: &mut usize) -> Option<Rc
             Some(Rc::new(node))
         }
         Statement::ExpressionStatement(expr_stmt) => {
-            expression_to_tree_node(&expr_stmt.expression, id_counter)
+            expression_to_tree_node(&expr_stmt.expression, cx)
         }
-        Statement::BlockStatement(block) => block_statement_to_tree_node(block, id_counter),
+        Statement::BlockStatement(block) => block_statement_to_tree_node(block, cx),
         Statement::IfStatement(if_stmt) => {
             let mut node =
-                TreeNode::new("IfStatement".to_string(), "IfStatement".to_string(), *id_counter);
-            *id_counter += 1;
+                cx.node("IfStatement".to_string(), "IfStatement".to_string(), if_stmt.span);
 
             // Add test expression
-            if let Some(test_node) = expression_to_tree_node(&if_stmt.test, id_counter) {
+            if let Some(test_node) = expression_to_tree_node(&if_stmt.test, cx) {
                 node.add_child(test_node);
             }
 
             // Add consequent
-            if let Some(cons_node) = statement_to_tree_node(&if_stmt.consequent, id_counter) {
+            if let Some(cons_node) = statement_to_tree_node(&if_stmt.consequent, cx) {
                 node.add_child(cons_node);
             }
 
             // Add alternate if exists
             if let Some(alt) = &if_stmt.alternate {
-                if let Some(alt_node) = statement_to_tree_node(alt, id_counter) {
+                if let Some(alt_node) = statement_to_tree_node(alt, cx) {
                     node.add_child(alt_node);
                 }
             }
@@ -125,15 +140,14 @@ fn statement_to_tree_node(stmt: &Statement, id_counter: &mut usize) -> Option<Rc
             Some(Rc::new(node))
         }
         Statement::ReturnStatement(ret_stmt) => {
-            let mut node = TreeNode::new(
+            let mut node = cx.node(
                 "ReturnStatement".to_string(),
                 "ReturnStatement".to_string(),
-                *id_counter,
+                ret_stmt.span,
             );
-            *id_counter += 1;
 
             if let Some(arg) = &ret_stmt.argument {
-                if let Some(arg_node) = expression_to_tree_node(arg, id_counter) {
+                if let Some(arg_node) = expression_to_tree_node(arg, cx) {
                     node.add_child(arg_node);
                 }
             }
@@ -142,75 +156,62 @@ fn statement_to_tree_node(stmt: &Statement, id_counter: &mut usize) -> Option<Rc
         }
         _ => {
             // For other statement types, create a generic node
-            let node = TreeNode::new("Statement".to_string(), "Statement".to_string(), *id_counter);
-            *id_counter += 1;
+            let node = cx.node("Statement".to_string(), "Statement".to_string(), stmt.span());
             Some(Rc::new(node))
         }
     }
 }
 
-fn expression_to_tree_node(expr: &Expression, id_counter: &mut usize) -> Option<Rc<TreeNode>> {
+fn expression_to_tree_node(expr: &Expression, cx: &mut Converter<'_>) -> Option<Rc<TreeNode>> {
     match expr {
         Expression::Identifier(ident) => {
-            let node = TreeNode::new(
-                ident.name.as_str().to_string(),
-                "Identifier".to_string(),
-                *id_counter,
-            );
-            *id_counter += 1;
+            let node =
+                cx.node(ident.name.as_str().to_string(), "Identifier".to_string(), ident.span);
             Some(Rc::new(node))
         }
         Expression::StringLiteral(str_lit) => {
             let label = format!("\"{}\"", str_lit.value.as_str());
-            let node = TreeNode::new(label, "StringLiteral".to_string(), *id_counter);
-            *id_counter += 1;
+            let node = cx.node(label, "StringLiteral".to_string(), str_lit.span);
             Some(Rc::new(node))
         }
         Expression::NumericLiteral(num_lit) => {
             let label = num_lit.value.to_string();
-            let node = TreeNode::new(label, "NumericLiteral".to_string(), *id_counter);
-            *id_counter += 1;
+            let node = cx.node(label, "NumericLiteral".to_string(), num_lit.span);
             Some(Rc::new(node))
         }
         Expression::BooleanLiteral(bool_lit) => {
             let label = bool_lit.value.to_string();
-            let node = TreeNode::new(label, "BooleanLiteral".to_string(), *id_counter);
-            *id_counter += 1;
+            let node = cx.node(label, "BooleanLiteral".to_string(), bool_lit.span);
             Some(Rc::new(node))
         }
         Expression::BinaryExpression(bin_expr) => {
-            let mut node = TreeNode::new(
+            let mut node = cx.node(
                 format!("{:?}", bin_expr.operator),
                 "BinaryExpression".to_string(),
-                *id_counter,
+                bin_expr.span,
             );
-            *id_counter += 1;
 
-            if let Some(left_node) = expression_to_tree_node(&bin_expr.left, id_counter) {
+            if let Some(left_node) = expression_to_tree_node(&bin_expr.left, cx) {
                 node.add_child(left_node);
             }
 
-            if let Some(right_node) = expression_to_tree_node(&bin_expr.right, id_counter) {
+            if let Some(right_node) = expression_to_tree_node(&bin_expr.right, cx) {
                 node.add_child(right_node);
             }
 
             Some(Rc::new(node))
         }
         Expression::CallExpression(call_expr) => {
-            let mut node = TreeNode::new(
-                "CallExpression".to_string(),
-                "CallExpression".to_string(),
-                *id_counter,
-            );
-            *id_counter += 1;
+            let mut node =
+                cx.node("CallExpression".to_string(), "CallExpression".to_string(), call_expr.span);
 
-            if let Some(callee_node) = expression_to_tree_node(&call_expr.callee, id_counter) {
+            if let Some(callee_node) = expression_to_tree_node(&call_expr.callee, cx) {
                 node.add_child(callee_node);
             }
 
             for arg in &call_expr.arguments {
                 if let Some(expr) = arg.as_expression() {
-                    if let Some(arg_node) = expression_to_tree_node(expr, id_counter) {
+                    if let Some(arg_node) = expression_to_tree_node(expr, cx) {
                         node.add_child(arg_node);
                     }
                 }
@@ -219,16 +220,15 @@ fn expression_to_tree_node(expr: &Expression, id_counter: &mut usize) -> Option<
             Some(Rc::new(node))
         }
         Expression::ArrowFunctionExpression(arrow) => {
-            let mut node = TreeNode::new(
+            let mut node = cx.node(
                 "ArrowFunction".to_string(),
                 "ArrowFunctionExpression".to_string(),
-                *id_counter,
+                arrow.span,
             );
-            *id_counter += 1;
 
             // Add parameters
             for param in &arrow.params.items {
-                if let Some(param_node) = formal_parameter_to_tree_node(param, id_counter) {
+                if let Some(param_node) = formal_parameter_to_tree_node(param, cx) {
                     node.add_child(param_node);
                 }
             }
@@ -239,15 +239,13 @@ fn expression_to_tree_node(expr: &Expression, id_counter: &mut usize) -> Option<
                 if let Some(Statement::ExpressionStatement(expr_stmt)) =
                     arrow.body.statements.first()
                 {
-                    if let Some(expr_node) =
-                        expression_to_tree_node(&expr_stmt.expression, id_counter)
-                    {
+                    if let Some(expr_node) = expression_to_tree_node(&expr_stmt.expression, cx) {
                         node.add_child(expr_node);
                     }
                 }
             } else {
                 // Block body (e.g., => { return x + 1; })
-                if let Some(body_node) = function_body_to_tree_node(&arrow.body, id_counter) {
+                if let Some(body_node) = function_body_to_tree_node(&arrow.body, cx) {
                     node.add_child(body_node);
                 }
             }
@@ -256,9 +254,7 @@ fn expression_to_tree_node(expr: &Expression, id_counter: &mut usize) -> Option<
         }
         _ => {
             // For other expression types, create a generic node
-            let node =
-                TreeNode::new("Expression".to_string(), "Expression".to_string(), *id_counter);
-            *id_counter += 1;
+            let node = cx.node("Expression".to_string(), "Expression".to_string(), expr.span());
             Some(Rc::new(node))
         }
     }
@@ -266,24 +262,21 @@ fn expression_to_tree_node(expr: &Expression, id_counter: &mut usize) -> Option<
 
 fn formal_parameter_to_tree_node(
     param: &FormalParameter,
-    id_counter: &mut usize,
+    cx: &mut Converter<'_>,
 ) -> Option<Rc<TreeNode>> {
     let label = match &param.pattern.kind {
         BindingPatternKind::BindingIdentifier(ident) => ident.name.as_str().to_string(),
         _ => "Parameter".to_string(),
     };
-    let node = TreeNode::new(label, "Parameter".to_string(), *id_counter);
-    *id_counter += 1;
+    let node = cx.node(label, "Parameter".to_string(), param.span);
     Some(Rc::new(node))
 }
 
-fn function_body_to_tree_node(body: &FunctionBody, id_counter: &mut usize) -> Option<Rc<TreeNode>> {
-    let mut node =
-        TreeNode::new("BlockStatement".to_string(), "BlockStatement".to_string(), *id_counter);
-    *id_counter += 1;
+fn function_body_to_tree_node(body: &FunctionBody, cx: &mut Converter<'_>) -> Option<Rc<TreeNode>> {
+    let mut node = cx.node("BlockStatement".to_string(), "BlockStatement".to_string(), body.span);
 
     for stmt in &body.statements {
-        if let Some(stmt_node) = statement_to_tree_node(stmt, id_counter) {
+        if let Some(stmt_node) = statement_to_tree_node(stmt, cx) {
             node.add_child(stmt_node);
         }
     }
@@ -293,14 +286,12 @@ fn function_body_to_tree_node(body: &FunctionBody, id_counter: &mut usize) -> Op
 
 fn block_statement_to_tree_node(
     block: &BlockStatement,
-    id_counter: &mut usize,
+    cx: &mut Converter<'_>,
 ) -> Option<Rc<TreeNode>> {
-    let mut node =
-        TreeNode::new("BlockStatement".to_string(), "BlockStatement".to_string(), *id_counter);
-    *id_counter += 1;
+    let mut node = cx.node("BlockStatement".to_string(), "BlockStatement".to_string(), block.span);
 
     for stmt in &block.body {
-        if let Some(stmt_node) = statement_to_tree_node(stmt, id_counter) {
+        if let Some(stmt_node) = statement_to_tree_node(stmt, cx) {
             node.add_child(stmt_node);
         }
     }
@@ -310,17 +301,16 @@ fn block_statement_to_tree_node(
 
 fn variable_declarator_to_tree_node(
     decl: &VariableDeclarator,
-    id_counter: &mut usize,
+    cx: &mut Converter<'_>,
 ) -> Option<Rc<TreeNode>> {
     let label = match &decl.id.kind {
         BindingPatternKind::BindingIdentifier(ident) => ident.name.as_str().to_string(),
         _ => "Variable".to_string(),
     };
-    let mut node = TreeNode::new(label, "VariableDeclarator".to_string(), *id_counter);
-    *id_counter += 1;
+    let mut node = cx.node(label, "VariableDeclarator".to_string(), decl.span);
 
     if let Some(init) = &decl.init {
-        if let Some(init_node) = expression_to_tree_node(init, id_counter) {
+        if let Some(init_node) = expression_to_tree_node(init, cx) {
             node.add_child(init_node);
         }
     }
@@ -330,7 +320,7 @@ fn variable_declarator_to_tree_node(
 
 fn class_element_to_tree_node(
     element: &ClassElement,
-    id_counter: &mut usize,
+    cx: &mut Converter<'_>,
 ) -> Option<Rc<TreeNode>> {
     match element {
         ClassElement::MethodDefinition(method) => {
@@ -339,12 +329,11 @@ fn class_element_to_tree_node(
                 PropertyKey::PrivateIdentifier(ident) => format!("#{}", ident.name.as_str()),
                 _ => "Method".to_string(),
             };
-            let mut node = TreeNode::new(label, "MethodDefinition".to_string(), *id_counter);
-            *id_counter += 1;
+            let mut node = cx.node(label, "MethodDefinition".to_string(), method.span);
 
             // Add method body
             if let Some(body) = &method.value.body {
-                if let Some(body_node) = function_body_to_tree_node(body, id_counter) {
+                if let Some(body_node) = function_body_to_tree_node(body, cx) {
                     node.add_child(body_node);
                 }
             }
@@ -357,10 +346,32 @@ fn class_element_to_tree_node(
                 PropertyKey::PrivateIdentifier(ident) => format!("#{}", ident.name.as_str()),
                 _ => "Property".to_string(),
             };
-            let node = TreeNode::new(label, "PropertyDefinition".to_string(), *id_counter);
-            *id_counter += 1;
+            let node = cx.node(label, "PropertyDefinition".to_string(), prop.span);
             Some(Rc::new(node))
         }
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nodes_carry_spans() {
+        let source = "const a = 1;\nfunction add(x, y) {\n  return x + y;\n}\n";
+        let tree = parse_and_convert_to_tree("test.ts", source).unwrap();
+        assert_eq!(tree.span.unwrap().start_line, 1);
+
+        let func = &tree.children[1];
+        let span = func.span.unwrap();
+        assert_eq!(func.label, "add");
+        assert_eq!((span.start_line, span.start_column, span.end_line), (2, 1, 4));
+        assert_eq!(&source[span.start_byte..span.end_byte], source[13..].trim_end());
+
+        let ret = &func.children[2].children[0];
+        let span = ret.span.unwrap();
+        assert_eq!(ret.label, "ReturnStatement");
+        assert_eq!((span.start_line, span.start_column), (3, 3));
+    }
+}
@@ -1,7 +1,6 @@
-use crate::tree::TreeNode;
+use crate::tree::ArenaTree;
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
-use std::rc::Rc;
 
 /// Fingerprint for a subtree in the AST
 #[derive(Debug, Clone)]
@@ -172,59 +171,70 @@ impl Default for OverlapOptions {
     }
 }
 
-/// Generate fingerprint for a tree node and all its subtrees
+/// Generate the fingerprint of a tree and of each of its subtrees (in preorder)
+///
+/// `line_offset` is the number of file lines before the parsed source, so that the node
+/// spans of a tree parsed from a single function map back to lines of the whole file.
 pub fn generate_subtree_fingerprints(
-    node: &Rc<TreeNode>,
-    depth: u32,
-    parent_line_offset: u32,
+    tree: &ArenaTree,
+    line_offset: u32,
 ) -> (SubtreeFingerprint, Vec<SubtreeFingerprint>) {
-    let mut hasher = std::collections::hash_map::DefaultHasher::new();
-    let mut all_fingerprints = Vec::new();
-    let mut child_hashes = Vec::new();
-    let mut total_weight = 1u32; // Current node counts as 1
+    let n = tree.len();
+    let mut hashes = vec![0u64; n];
+    let mut lines = vec![(0u32, 0u32); n];
 
-    // Hash the node type/label
-    node.label.hash(&mut hasher);
-
-    // Process children
-    for child in &node.children {
-        let (child_fp, child_subtrees) =
-            generate_subtree_fingerprints(child, depth + 1, parent_line_offset);
-
-        // Add child's hash to our list
-        child_hashes.push(child_fp.hash);
-        child_fp.hash.hash(&mut hasher);
-
-        // Add child's weight to total
-        total_weight += child_fp.weight;
-
-        // Collect all subtree fingerprints
-        all_fingerprints.push(child_fp);
-        all_fingerprints.extend(child_subtrees);
-    }
-
-    // Hash the value if not empty
-    if !node.value.is_empty() {
-        node.value.hash(&mut hasher);
+    // Children have larger ids than their parents, so walk the arena backwards
+    for id in (0..n).rev() {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        tree.label(id).hash(&mut hasher);
+        for &child in tree.children(id) {
+            hashes[child].hash(&mut hasher);
+        }
+        if !tree.value(id).is_empty() {
+            tree.value(id).hash(&mut hasher);
+        }
+        hashes[id] = hasher.finish();
+
+        lines[id] = match tree.span(id) {
+            Some(span) => (line_offset + span.start_line, line_offset + span.end_line),
+            // Synthetic nodes cover their descendants
+            None if !tree.is_leaf(id) => {
+                let descendants = tree.subtree(id).skip(1);
+                let start = descendants.clone().map(|d| lines[d].0).min().unwrap_or(line_offset);
+                let end = descendants.map(|d| lines[d].1).max().unwrap_or(line_offset);
+                (start, end)
+            }
+            // Trees built without spans: use the node id as a proxy for line numbers
+            None => {
+                let start_line = line_offset + 1 + id as u32;
+                (start_line, start_line + 1)
+            }
+        };
     }
 
-    let hash = hasher.finish();
-
-    // Calculate line numbers (simplified - using node id as proxy for line numbers)
-    let start_line = parent_line_offset + node.id as u32;
-    let end_line = start_line + total_weight;
-
-    let fingerprint = SubtreeFingerprint {
-        weight: total_weight,
-        hash,
-        child_hashes,
-        start_line,
-        end_line,
-        node_type: node.label.clone(),
-        depth,
+    let fingerprint = |id: usize| SubtreeFingerprint {
+        weight: tree.size(id) as u32,
+        hash: hashes[id],
+        child_hashes: tree.children(id).iter().map(|&child| hashes[child]).collect(),
+        start_line: lines[id].0,
+        end_line: lines[id].1,
+        node_type: tree.label(id).to_string(),
+        depth: tree.depth(id),
     };
 
-    (fingerprint, all_fingerprints)
+    if n == 0 {
+        let empty = SubtreeFingerprint {
+            weight: 0,
+            hash: 0,
+            child_hashes: Vec::new(),
+            start_line: line_offset,
+            end_line: line_offset,
+            node_type: String::new(),
+            depth: 0,
+        };
+        return (empty, Vec::new());
+    }
+    (fingerprint(0), (1..n).map(fingerprint).collect())
 }
 
 /// Create sliding windows of subtrees
@@ -234,21 +244,23 @@ pub fn create_sliding_windows(
 ) -> Vec<SubtreeFingerprint> {
     let mut windows = Vec::new();
 
-    // Get all subtrees sorted by start line
+    // Get all subtrees in source order, enclosing subtrees before the ones they contain
     let mut all_subtrees: Vec<&SubtreeFingerprint> =
         indexed_func.subtree_index.values().flatten().collect();
-    all_subtrees.sort_by_key(|fp| fp.start_line);
+    all_subtrees.sort_by_key(|fp| (fp.start_line, std::cmp::Reverse(fp.end_line), fp.depth));
 
     // Create windows by combining adjacent subtrees
     for i in 0..all_subtrees.len() {
         let mut current_weight = 0;
         let mut window_hashes = Vec::new();
+        let mut end_line = all_subtrees[i].end_line;
         let mut hasher = std::collections::hash_map::DefaultHasher::new();
 
         for j in i..all_subtrees.len() {
             current_weight += all_subtrees[j].weight;
             window_hashes.push(all_subtrees[j].hash);
             all_subtrees[j].hash.hash(&mut hasher);
+            end_line = end_line.max(all_subtrees[j].end_line);
 
             if current_weight >= window_size {
                 // Create a synthetic fingerprint for this window
@@ -257,7 +269,7 @@ pub fn create_sliding_windows(
                     hash: hasher.finish(),
                     child_hashes: window_hashes.clone(),
                     start_line: all_subtrees[i].start_line,
-                    end_line: all_subtrees[j].end_line,
+                    end_line,
                     node_type: format!("Window[{}..{}]", i, j),
                     depth: 0,
                 };
@@ -0,0 +1,242 @@
+//! Inline suppression comments.
+//!
+//! A function is left out of similarity checks when it is annotated in the source:
+//!
+//! ```text
+//! // similarity-ignore: kept in sync with the python copy
+//! function parse() { ... }
+//!
+//! def helper():  # similarity-ignore
+//!     ...
+//!
+//! # similarity-ignore-next-function generated by protoc
+//! @decorator
+//! def generated(): ...
+//! ```
+//!
+//! `similarity-ignore` applies to a function starting on the same line or right after
+//! the comment (blank lines, other comments, decorators and attributes may sit in
+//! between). `similarity-ignore-next-function` applies to the next function starting
+//! after the comment, wherever it is. Anything after the marker is treated as the reason.
+
+/// Marker suppressing the function on or right below the comment
+pub const IGNORE_MARKER: &str = "similarity-ignore";
+/// Marker suppressing the next function after the comment
+pub const IGNORE_NEXT_FUNCTION_MARKER: &str = "similarity-ignore-next-function";
+
+const COMMENT_PREFIXES: [&str; 5] = ["//", "#", "/*", "--", "*"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuppressionKind {
+    /// `similarity-ignore`
+    Adjacent,
+    /// `similarity-ignore-next-function`
+    NextFunction,
+}
+
+/// A suppression comment found in a source file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suppression {
+    /// 1-based line of the comment
+    pub line: u32,
+    pub kind: SuppressionKind,
+    pub reason: Option<String>,
+    /// Last line a function may start on to be covered by an adjacent suppression
+    last_line: u32,
+}
+
+/// Find all suppression comments in `source`
+pub fn find_suppressions(source: &str) -> Vec<Suppression> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut suppressions = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        let Some((kind, reason)) = parse_comment(line) else {
+            continue;
+        };
+        // Trailing comments only cover their own line
+        let trailing = !is_comment_only(line);
+        let last_line = if trailing {
+            index + 1
+        } else {
+            lines[index + 1..]
+                .iter()
+                .position(|next| !is_annotation_line(next))
+                .map_or(lines.len(), |offset| index + 2 + offset)
+        };
+        suppressions.push(Suppression {
+            line: (index + 1) as u32,
+            kind,
+            reason,
+            last_line: last_line as u32,
+        });
+    }
+    suppressions
+}
+
+/// Drop the functions suppressed by comments in `source`
+pub fn retain_unsuppressed<T>(
+    source: &str,
+    functions: &mut Vec<T>,
+    start_line: impl Fn(&T) -> u32,
+) {
+    if !source.contains(IGNORE_MARKER) {
+        return;
+    }
+    let suppressions = find_suppressions(source);
+    if suppressions.is_empty() {
+        return;
+    }
+
+    let mut suppressed = vec![false; functions.len()];
+    for suppression in &suppressions {
+        match suppression.kind {
+            SuppressionKind::Adjacent => {
+                for (i, function) in functions.iter().enumerate() {
+                    let line = start_line(function);
+                    if suppression.line <= line && line <= suppression.last_line {
+                        suppressed[i] = true;
+                    }
+                }
+            }
+            SuppressionKind::NextFunction => {
+                let next =
+                    functions.iter().map(&start_line).filter(|&line| line > suppression.line).min();
+                if let Some(next) = next {
+                    for (i, function) in functions.iter().enumerate() {
+                        if start_line(function) == next {
+                            suppressed[i] = true;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut flags = suppressed.into_iter();
+    functions.retain(|_| !flags.next().unwrap_or(false));
+}
+
+/// Parse a suppression marker inside a comment on `line`
+fn parse_comment(line: &str) -> Option<(SuppressionKind, Option<String>)> {
+    let mut search_from = 0;
+    while let Some(offset) = line[search_from..].find(IGNORE_MARKER) {
+        let start = search_from + offset;
+        search_from = start + IGNORE_MARKER.len();
+
+        let before = line[..start].trim_end();
+        if !COMMENT_PREFIXES.iter().any(|prefix| before.ends_with(prefix)) {
+            continue;
+        }
+
+        let (kind, rest) = if line[start..].starts_with(IGNORE_NEXT_FUNCTION_MARKER) {
+            (SuppressionKind::NextFunction, &line[start + IGNORE_NEXT_FUNCTION_MARKER.len()..])
+        } else {
+            (SuppressionKind::Adjacent, &line[start + IGNORE_MARKER.len()..])
+        };
+        // Reject longer words such as `similarity-ignored`
+        if rest.starts_with(|c: char| c.is_alphanumeric() || c == '-' || c == '_') {
+            continue;
+        }
+
+        let reason = rest
+            .trim()
+            .trim_end_matches("*/")
+            .trim_end_matches("-->")
+            .trim()
+            .trim_start_matches([':', '-', '('])
+            .trim_end_matches(')')
+            .trim();
+        let reason = (!reason.is_empty()).then(|| reason.to_string());
+        return Some((kind, reason));
+    }
+    None
+}
+
+fn is_comment_only(line: &str) -> bool {
+    let line = line.trim_start();
+    COMMENT_PREFIXES.iter().any(|prefix| line.starts_with(prefix)) && !line.starts_with("#[")
+}
+
+/// Lines that may separate a suppression comment from the function it annotates
+fn is_annotation_line(line: &str) -> bool {
+    let line = line.trim_start();
+    line.is_empty()
+        || is_comment_only(line)
+        || line.starts_with("#[")
+        || line.starts_with('@')
+        || line.starts_with("*/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kept(source: &str, starts: &[u32]) -> Vec<u32> {
+        let mut functions = starts.to_vec();
+        retain_unsuppressed(source, &mut functions, |line| *line);
+        functions
+    }
+
+    #[test]
+    fn test_parse_comment_styles() {
+        assert_eq!(parse_comment("// similarity-ignore"), Some((SuppressionKind::Adjacent, None)));
+        assert_eq!(
+            parse_comment("    # similarity-ignore: kept in sync with rust"),
+            Some((SuppressionKind::Adjacent, Some("kept in sync with rust".to_string())))
+        );
+        assert_eq!(
+            parse_comment("/* similarity-ignore-next-function generated */"),
+            Some((SuppressionKind::NextFunction, Some("generated".to_string())))
+        );
+        assert_eq!(
+            parse_comment("def f():  # similarity-ignore (deliberate)"),
+            Some((SuppressionKind::Adjacent, Some("deliberate".to_string())))
+        );
+        assert_eq!(parse_comment("let s = \"similarity-ignore\";"), None);
+        assert_eq!(parse_comment("// similarity-ignored"), None);
+    }
+
+    #[test]
+    fn test_adjacent_suppression() {
+        let source = "\
+// similarity-ignore: deliberate copy
+#[inline]
+
+fn a() {}
+fn b() {}
+fn c() {} // similarity-ignore
+fn d() {}
+";
+        assert_eq!(kept(source, &[4, 5, 6, 7]), vec![5, 7]);
+    }
+
+    #[test]
+    fn test_next_function_suppression() {
+        let source = "\
+# similarity-ignore-next-function
+x = 1
+
+def a():
+    pass
+
+def b():
+    pass
+";
+        assert_eq!(kept(source, &[4, 7]), vec![7]);
+    }
+
+    #[test]
+    fn test_adjacent_does_not_reach_past_code() {
+        let source = "# similarity-ignore\nx = 1\ndef a():\n    pass\n";
+        assert_eq!(kept(source, &[3]), vec![3]);
+    }
+
+    #[test]
+    fn test_reasons_are_reported() {
+        let suppressions = find_suppressions("x\n-- similarity-ignore because\n");
+        assert_eq!(suppressions.len(), 1);
+        assert_eq!(suppressions[0].line, 2);
+        assert_eq!(suppressions[0].reason.as_deref(), Some("because"));
+    }
+}
@@ -1,5 +1,96 @@
+use serde::de::Error as _;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
 use std::rc::Rc;
 
+/// Location of a node in the parsed source.
+///
+/// Offsets are byte offsets; lines and columns are 1-based, columns counted in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SourceSpan {
+    pub start_byte: usize,
+    pub end_byte: usize,
+    pub start_line: u32,
+    pub start_column: u32,
+    pub end_line: u32,
+    pub end_column: u32,
+}
+
+impl SourceSpan {
+    /// Span from `start` to `end` byte offsets of the source indexed by `lines`
+    #[must_use]
+    pub fn from_offsets(lines: &LineIndex, start: usize, end: usize) -> Self {
+        let (start_line, start_column) = lines.line_column(start);
+        let (end_line, end_column) = lines.line_column(end);
+        SourceSpan {
+            start_byte: start,
+            end_byte: end,
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+        }
+    }
+
+    /// Smallest span covering both `self` and `other`
+    #[must_use]
+    pub fn merge(self, other: SourceSpan) -> Self {
+        let (start_byte, start_line, start_column) = if other.start_byte < self.start_byte {
+            (other.start_byte, other.start_line, other.start_column)
+        } else {
+            (self.start_byte, self.start_line, self.start_column)
+        };
+        let (end_byte, end_line, end_column) = if other.end_byte > self.end_byte {
+            (other.end_byte, other.end_line, other.end_column)
+        } else {
+            (self.end_byte, self.end_line, self.end_column)
+        };
+        SourceSpan { start_byte, end_byte, start_line, start_column, end_line, end_column }
+    }
+
+    /// Move the span down by `lines` lines, for trees parsed from a slice of a file
+    #[must_use]
+    pub fn offset_lines(self, lines: u32) -> Self {
+        SourceSpan { start_line: self.start_line + lines, end_line: self.end_line + lines, ..self }
+    }
+}
+
+impl From<tree_sitter::Range> for SourceSpan {
+    fn from(range: tree_sitter::Range) -> Self {
+        SourceSpan {
+            start_byte: range.start_byte,
+            end_byte: range.end_byte,
+            start_line: range.start_point.row as u32 + 1,
+            start_column: range.start_point.column as u32 + 1,
+            end_line: range.end_point.row as u32 + 1,
+            end_column: range.end_point.column as u32 + 1,
+        }
+    }
+}
+
+/// Byte offset to line/column lookup for a source text
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    #[must_use]
+    pub fn new(source: &str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(source.match_indices('\n').map(|(i, _)| i + 1));
+        LineIndex { line_starts }
+    }
+
+    /// 1-based line and column of a byte offset
+    #[must_use]
+    pub fn line_column(&self, offset: usize) -> (u32, u32) {
+        let line = self.line_starts.partition_point(|&start| start <= offset).saturating_sub(1);
+        ((line + 1) as u32, (offset - self.line_starts[line] + 1) as u32)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TreeNode {
     pub label: String,
@@ -7,26 +98,479 @@ pub struct TreeNode {
     pub children: Vec<Rc<TreeNode>>,
     pub id: usize,
     pub subtree_size: Option<usize>,
+    /// Where the node was parsed from, when known
+    pub span: Option<SourceSpan>,
 }
 
 impl TreeNode {
     #[must_use]
     pub fn new(label: String, value: String, id: usize) -> Self {
-        TreeNode { label, value, children: Vec::new(), id, subtree_size: None }
+        TreeNode { label, value, children: Vec::new(), id, subtree_size: None, span: None }
+    }
+
+    #[must_use]
+    pub fn with_span(mut self, span: SourceSpan) -> Self {
+        self.span = Some(span);
+        self
     }
 
     pub fn add_child(&mut self, child: Rc<TreeNode>) {
         self.children.push(child);
     }
 
+    /// Number of nodes in this subtree, counted with an explicit stack so deep trees
+    /// cannot overflow the call stack
     #[must_use]
     pub fn get_subtree_size(&self) -> usize {
-        // Since we can't mutate through Rc, we'll calculate it each time
-        // In a real implementation, you might want to use RefCell for interior mutability
-        let mut size = 1;
-        for child in &self.children {
-            size += child.get_subtree_size();
+        let mut size = 0;
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            size += 1;
+            stack.extend(node.children.iter().map(Rc::as_ref));
         }
         size
     }
 }
+
+/// Flat, immutable tree stored in preorder.
+///
+/// Node `0` is the root and the subtree of node `id` spans the ids `id..id + size(id)`.
+/// Subtree sizes, depths, postorder numbers and leftmost leaves are computed once when
+/// the tree is built, so the edit distance algorithms never walk a subtree to size it.
+/// Unlike `Rc<TreeNode>` the arena is `Send + Sync`: a function can be parsed once and
+/// its tree shared between worker threads.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArenaTree {
+    /// Labels and values of all nodes, concatenated
+    text: String,
+    labels: Vec<(u32, u32)>,
+    values: Vec<(u32, u32)>,
+    spans: Vec<Option<SourceSpan>>,
+    parents: Vec<Option<usize>>,
+    /// Children of `id` are `child_ids[child_offsets[id]..child_offsets[id + 1]]`
+    child_offsets: Vec<usize>,
+    child_ids: Vec<usize>,
+    sizes: Vec<usize>,
+    depths: Vec<u32>,
+    /// Postorder number of each node and the node at each postorder number
+    postorder: Vec<usize>,
+    postorder_to_id: Vec<usize>,
+    /// Postorder number of the leftmost leaf below each node
+    leftmost_leaves: Vec<usize>,
+}
+
+/// Node data of an [`ArenaTree`] as it is serialized; the lookup tables are rebuilt when a
+/// tree is read back. Flat columns keep deep trees within the deserializer's recursion limit.
+#[derive(Serialize, Deserialize)]
+struct ArenaParts {
+    text: String,
+    labels: Vec<(u32, u32)>,
+    values: Vec<(u32, u32)>,
+    spans: Vec<Option<SourceSpan>>,
+    parents: Vec<Option<usize>>,
+}
+
+impl Serialize for ArenaTree {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ArenaTree", 5)?;
+        state.serialize_field("text", &self.text)?;
+        state.serialize_field("labels", &self.labels)?;
+        state.serialize_field("values", &self.values)?;
+        state.serialize_field("spans", &self.spans)?;
+        state.serialize_field("parents", &self.parents)?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for ArenaTree {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        ArenaTree::from_parts(ArenaParts::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+/// Label, value and span of a node, borrowed from an [`ArenaTree`] or a [`TreeNode`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeView<'a> {
+    pub label: &'a str,
+    pub value: &'a str,
+    pub span: Option<SourceSpan>,
+}
+
+impl<'a> From<&'a TreeNode> for NodeView<'a> {
+    fn from(node: &'a TreeNode) -> Self {
+        NodeView { label: &node.label, value: &node.value, span: node.span }
+    }
+}
+
+impl ArenaTree {
+    /// Flatten a `TreeNode` tree; node ids become preorder positions
+    #[must_use]
+    pub fn from_node(root: &TreeNode) -> Self {
+        let mut tree = ArenaTree::default();
+
+        // Preorder walk with an explicit stack so deep trees cannot overflow it
+        let mut stack: Vec<(&TreeNode, Option<usize>, u32)> = vec![(root, None, 0)];
+        while let Some((node, parent, depth)) = stack.pop() {
+            let label = tree.push_text(&node.label);
+            let value = tree.push_text(&node.value);
+            tree.labels.push(label);
+            tree.values.push(value);
+            tree.spans.push(node.span);
+            tree.parents.push(parent);
+            tree.depths.push(depth);
+
+            let id = tree.labels.len() - 1;
+            for child in node.children.iter().rev() {
+                stack.push((child, Some(id), depth + 1));
+            }
+        }
+
+        tree.build_tables();
+        tree
+    }
+
+    /// Rebuild an arena from its node data; `parents` must describe a preorder numbering
+    fn from_parts(parts: ArenaParts) -> Result<Self, String> {
+        let ArenaParts { text, labels, values, spans, parents } = parts;
+        let n = labels.len();
+        if values.len() != n || spans.len() != n || parents.len() != n {
+            return Err("arena tree columns differ in length".to_string());
+        }
+        for &(start, end) in labels.iter().chain(&values) {
+            if text.get(start as usize..end as usize).is_none() {
+                return Err(format!("text range {start}..{end} is out of bounds"));
+            }
+        }
+
+        // The parent of each node must be on the path from the root to the previous node
+        let mut depths = Vec::with_capacity(n);
+        let mut path: Vec<usize> = Vec::new();
+        for (id, &parent) in parents.iter().enumerate() {
+            match parent {
+                None if id == 0 => {}
+                None => return Err(format!("node {id} has no parent")),
+                Some(parent) => {
+                    while path.last().is_some_and(|&top| top != parent) {
+                        path.pop();
+                    }
+                    if path.is_empty() {
+                        return Err(format!("node {id} is not in preorder"));
+                    }
+                }
+            }
+            depths.push(path.len() as u32);
+            path.push(id);
+        }
+
+        let mut tree =
+            ArenaTree { text, labels, values, spans, parents, depths, ..Default::default() };
+        tree.build_tables();
+        Ok(tree)
+    }
+
+    /// Fill the child lists, sizes and postorder tables from the parents and depths
+    fn build_tables(&mut self) {
+        let tree = self;
+        let n = tree.labels.len();
+        if n == 0 {
+            return;
+        }
+        let mut child_counts = vec![0; n];
+        for parent in tree.parents.iter().flatten() {
+            child_counts[*parent] += 1;
+        }
+        tree.child_offsets = Vec::with_capacity(n + 1);
+        tree.child_offsets.push(0);
+        for count in &child_counts {
+            tree.child_offsets.push(tree.child_offsets.last().copied().unwrap_or(0) + count);
+        }
+        // Siblings appear left to right in preorder
+        let mut filled = vec![0; n];
+        tree.child_ids = vec![0; n.saturating_sub(1)];
+        for id in 1..n {
+            if let Some(parent) = tree.parents[id] {
+                tree.child_ids[tree.child_offsets[parent] + filled[parent]] = id;
+                filled[parent] += 1;
+            }
+        }
+
+        // Children have larger ids than their parents
+        tree.sizes = vec![1; n];
+        for id in (1..n).rev() {
+            if let Some(parent) = tree.parents[id] {
+                tree.sizes[parent] += tree.sizes[id];
+            }
+        }
+
+        // Nodes finishing before `id` are the earlier non-ancestors and its descendants
+        tree.postorder =
+            (0..n).map(|id| id - tree.depths[id] as usize + tree.sizes[id] - 1).collect();
+        tree.postorder_to_id = vec![0; n];
+        for id in 0..n {
+            tree.postorder_to_id[tree.postorder[id]] = id;
+        }
+        tree.leftmost_leaves = vec![0; n];
+        for id in (0..n).rev() {
+            tree.leftmost_leaves[id] = match tree.children(id).first() {
+                Some(&first) => tree.leftmost_leaves[first],
+                None => tree.postorder[id],
+            };
+        }
+    }
+
+    fn push_text(&mut self, text: &str) -> (u32, u32) {
+        let start = self.text.len() as u32;
+        self.text.push_str(text);
+        (start, self.text.len() as u32)
+    }
+
+    /// Number of nodes
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    #[must_use]
+    pub fn label(&self, id: usize) -> &str {
+        let (start, end) = self.labels[id];
+        &self.text[start as usize..end as usize]
+    }
+
+    #[must_use]
+    pub fn value(&self, id: usize) -> &str {
+        let (start, end) = self.values[id];
+        &self.text[start as usize..end as usize]
+    }
+
+    #[must_use]
+    pub fn span(&self, id: usize) -> Option<SourceSpan> {
+        self.spans[id]
+    }
+
+    #[must_use]
+    pub fn node(&self, id: usize) -> NodeView<'_> {
+        NodeView { label: self.label(id), value: self.value(id), span: self.spans[id] }
+    }
+
+    #[must_use]
+    pub fn parent(&self, id: usize) -> Option<usize> {
+        self.parents[id]
+    }
+
+    #[must_use]
+    pub fn children(&self, id: usize) -> &[usize] {
+        &self.child_ids[self.child_offsets[id]..self.child_offsets[id + 1]]
+    }
+
+    #[must_use]
+    pub fn is_leaf(&self, id: usize) -> bool {
+        self.child_offsets[id] == self.child_offsets[id + 1]
+    }
+
+    /// Number of nodes in the subtree rooted at `id`
+    #[must_use]
+    pub fn size(&self, id: usize) -> usize {
+        self.sizes[id]
+    }
+
+    /// Subtree sizes of all nodes, indexed by id
+    #[must_use]
+    pub fn sizes(&self) -> &[usize] {
+        &self.sizes
+    }
+
+    /// Distance from the root (the root has depth 0)
+    #[must_use]
+    pub fn depth(&self, id: usize) -> u32 {
+        self.depths[id]
+    }
+
+    /// Postorder numbers of all nodes, indexed by id
+    #[must_use]
+    pub fn postorder(&self) -> &[usize] {
+        &self.postorder
+    }
+
+    /// Node ids in postorder
+    #[must_use]
+    pub fn postorder_to_id(&self) -> &[usize] {
+        &self.postorder_to_id
+    }
+
+    /// Postorder numbers of the leftmost leaf below each node, indexed by id
+    #[must_use]
+    pub fn leftmost_leaves(&self) -> &[usize] {
+        &self.leftmost_leaves
+    }
+
+    /// Ids of the subtree rooted at `id`, in preorder
+    #[must_use]
+    pub fn subtree(&self, id: usize) -> std::ops::Range<usize> {
+        id..id + self.sizes[id]
+    }
+
+    /// Rebuild a `TreeNode` tree (node ids are the arena ids)
+    #[must_use]
+    pub fn to_tree_node(&self) -> Option<Rc<TreeNode>> {
+        let mut built: Vec<Option<TreeNode>> = vec![None; self.len()];
+        for id in (0..self.len()).rev() {
+            let mut node =
+                TreeNode::new(self.label(id).to_string(), self.value(id).to_string(), id);
+            node.span = self.spans[id];
+            for &child in self.children(id) {
+                if let Some(child) = built[child].take() {
+                    node.add_child(Rc::new(child));
+                }
+            }
+            built[id] = Some(node);
+        }
+        built.into_iter().next().flatten().map(Rc::new)
+    }
+}
+
+impl From<&TreeNode> for ArenaTree {
+    fn from(node: &TreeNode) -> Self {
+        ArenaTree::from_node(node)
+    }
+}
+
+/// Trees the similarity algorithms accept: an [`ArenaTree`] is used as is, a `TreeNode`
+/// is flattened once per call
+pub trait AsArenaTree {
+    fn as_arena(&self) -> Cow<'_, ArenaTree>;
+}
+
+impl AsArenaTree for ArenaTree {
+    fn as_arena(&self) -> Cow<'_, ArenaTree> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl AsArenaTree for TreeNode {
+    fn as_arena(&self) -> Cow<'_, ArenaTree> {
+        Cow::Owned(ArenaTree::from_node(self))
+    }
+}
+
+impl AsArenaTree for Rc<TreeNode> {
+    fn as_arena(&self) -> Cow<'_, ArenaTree> {
+        Cow::Owned(ArenaTree::from_node(self))
+    }
+}
+
+impl<T: AsArenaTree + ?Sized> AsArenaTree for &T {
+    fn as_arena(&self) -> Cow<'_, ArenaTree> {
+        (**self).as_arena()
+    }
+}
+
+/// Nodes of a `TreeNode` tree in the order of their [`ArenaTree`] ids
+pub(crate) fn preorder_nodes(root: &Rc<TreeNode>) -> Vec<Rc<TreeNode>> {
+    let mut out = Vec::new();
+    let mut stack = vec![root.clone()];
+    while let Some(node) = stack.pop() {
+        stack.extend(node.children.iter().rev().cloned());
+        out.push(node);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_index() {
+        let lines = LineIndex::new("ab\ncd\n\nef");
+        assert_eq!(lines.line_column(0), (1, 1));
+        assert_eq!(lines.line_column(2), (1, 3));
+        assert_eq!(lines.line_column(3), (2, 1));
+        assert_eq!(lines.line_column(6), (3, 1));
+        assert_eq!(lines.line_column(8), (4, 2));
+
+        let span = SourceSpan::from_offsets(&lines, 4, 8);
+        assert_eq!(
+            (span.start_line, span.start_column, span.end_line, span.end_column),
+            (2, 2, 4, 2)
+        );
+        assert_eq!(span.offset_lines(10).start_line, 12);
+    }
+
+    fn leaf(label: &str) -> Rc<TreeNode> {
+        Rc::new(TreeNode::new(label.to_string(), String::new(), 0))
+    }
+
+    fn node(label: &str, children: Vec<Rc<TreeNode>>) -> Rc<TreeNode> {
+        let mut node = TreeNode::new(label.to_string(), label.to_lowercase(), 0);
+        for child in children {
+            node.add_child(child);
+        }
+        Rc::new(node)
+    }
+
+    #[test]
+    fn test_arena_tree_tables() {
+        // a(b(c d) e)
+        let root = node("A", vec![node("B", vec![leaf("c"), leaf("d")]), leaf("e")]);
+        let tree = ArenaTree::from_node(&root);
+
+        assert_eq!(tree.len(), 5);
+        assert_eq!((tree.label(0), tree.value(0)), ("A", "a"));
+        assert_eq!(tree.children(0), &[1, 4]);
+        assert_eq!(tree.children(1), &[2, 3]);
+        assert!(tree.is_leaf(4));
+        assert_eq!(tree.sizes(), &[5, 3, 1, 1, 1]);
+        assert_eq!((tree.depth(0), tree.depth(3), tree.depth(4)), (0, 2, 1));
+        assert_eq!(tree.parent(3), Some(1));
+        assert_eq!(tree.postorder(), &[4, 2, 0, 1, 3]);
+        assert_eq!(tree.postorder_to_id(), &[2, 3, 1, 4, 0]);
+        assert_eq!(tree.leftmost_leaves(), &[0, 0, 0, 1, 3]);
+        assert_eq!(tree.subtree(1), 1..4);
+    }
+
+    #[test]
+    fn test_arena_tree_round_trip() {
+        let root = node("A", vec![node("B", vec![leaf("c")]), leaf("d")]);
+        let tree = ArenaTree::from_node(&root);
+        let rebuilt = tree.to_tree_node().unwrap();
+        assert_eq!(ArenaTree::from_node(&rebuilt), tree);
+        assert_eq!(preorder_nodes(&root).len(), 4);
+
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<ArenaTree>();
+    }
+
+    #[test]
+    fn test_arena_tree_serialization() {
+        // A chain far deeper than serde_json's recursion limit, with a span on every node
+        let depth = 1000;
+        let mut tree_node = TreeNode::new("leaf".to_string(), "x".to_string(), depth);
+        for id in (0..depth).rev() {
+            let span = SourceSpan { start_byte: id, end_byte: id + 1, ..Default::default() };
+            let mut parent = TreeNode::new("block".to_string(), String::new(), id).with_span(span);
+            parent.add_child(Rc::new(tree_node));
+            tree_node = parent;
+        }
+        let tree = ArenaTree::from_node(&tree_node);
+        assert_eq!(tree_node.get_subtree_size(), depth + 1);
+
+        let json = serde_json::to_string(&tree).unwrap();
+        let restored: ArenaTree = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, tree);
+        assert_eq!(restored.len(), depth + 1);
+        assert_eq!(restored.span(10).map(|span| span.start_byte), Some(10));
+        assert_eq!(restored.depth(depth), depth as u32);
+
+        let empty = serde_json::to_string(&ArenaTree::default()).unwrap();
+        assert_eq!(serde_json::from_str::<ArenaTree>(&empty).unwrap(), ArenaTree::default());
+
+        // Parents must describe a preorder numbering
+        let broken = r#"{"text":"ab","labels":[[0,1],[1,2]],"values":[[0,0],[0,0]],"spans":[null,null],"parents":[null,null]}"#;
+        assert!(serde_json::from_str::<ArenaTree>(broken).is_err());
+    }
+}
@@ -1,5 +1,6 @@
-use crate::apted::{compute_edit_distance, APTEDOptions};
-use crate::tree::TreeNode;
+use crate::apted::{compute_edit_distance, APTEDOptions, TreeEditAlgorithm};
+use crate::edit_script::{compute_edit_script, EditScript};
+use crate::tree::{AsArenaTree, TreeNode};
 use std::rc::Rc;
 
 #[derive(Debug, Clone)]
@@ -19,6 +20,7 @@ impl Default for TSEDOptions {
                 delete_cost: 1.0,
                 insert_cost: 1.0,
                 compare_values: false, // TypeScript default: structural comparison only
+                algorithm: TreeEditAlgorithm::Recursive,
             },
             min_lines: 5,       // Increased default to better filter trivial matches
             min_tokens: None,   // No token limit by default
@@ -30,13 +32,42 @@ impl Default for TSEDOptions {
 
 /// Calculate TSED (Tree Structure Edit Distance) similarity between two trees
 /// Returns a value between 0.0 and 1.0, where 1.0 means identical
+///
+/// Accepts `Rc<TreeNode>` trees as well as prebuilt [`crate::ArenaTree`]s; pass arenas when
+/// the same tree is compared many times so it is only flattened once.
 #[must_use]
-#[allow(clippy::cast_precision_loss)]
-pub fn calculate_tsed(tree1: &Rc<TreeNode>, tree2: &Rc<TreeNode>, options: &TSEDOptions) -> f64 {
-    let distance = compute_edit_distance(tree1, tree2, &options.apted_options);
+pub fn calculate_tsed<T1, T2>(tree1: &T1, tree2: &T2, options: &TSEDOptions) -> f64
+where
+    T1: AsArenaTree + ?Sized,
+    T2: AsArenaTree + ?Sized,
+{
+    let (tree1, tree2) = (tree1.as_arena(), tree2.as_arena());
+    let distance = compute_edit_distance(&*tree1, &*tree2, &options.apted_options);
+    similarity_from_distance(distance, tree1.len(), tree2.len(), options)
+}
+
+/// Calculate TSED similarity together with the edit script that explains it
+#[must_use]
+pub fn calculate_tsed_with_edit_script(
+    tree1: &Rc<TreeNode>,
+    tree2: &Rc<TreeNode>,
+    options: &TSEDOptions,
+) -> (f64, EditScript) {
+    let script = compute_edit_script(tree1, tree2, &options.apted_options);
+    let (size1, size2) = script.tree_sizes();
+    let similarity = similarity_from_distance(script.distance, size1, size2, options);
+    (similarity, script)
+}
 
-    let size1 = tree1.get_subtree_size() as f64;
-    let size2 = tree2.get_subtree_size() as f64;
+#[allow(clippy::cast_precision_loss)]
+fn similarity_from_distance(
+    distance: f64,
+    size1: usize,
+    size2: usize,
+    options: &TSEDOptions,
+) -> f64 {
+    let size1 = size1 as f64;
+    let size2 = size2 as f64;
 
     // TSED normalization: Use the larger tree size
     // This ensures that when comparing trees of different sizes,
@@ -177,4 +208,23 @@ mod tests {
         // Should have lower similarity due to structural differences
         assert!(similarity < 0.7);
     }
+
+    #[test]
+    fn test_edit_script_matches_similarity() {
+        let tree1 = crate::parse_and_convert_to_tree(
+            "a.ts",
+            "function f(a) { if (a) { return 1; } return 2; }",
+        )
+        .unwrap();
+        let tree2 =
+            crate::parse_and_convert_to_tree("b.ts", "function f(a) { return 2; }").unwrap();
+        let mut options = TSEDOptions::default();
+        options.apted_options.algorithm = TreeEditAlgorithm::Apted;
+
+        let (similarity, script) = calculate_tsed_with_edit_script(&tree1, &tree2, &options);
+        assert_eq!(similarity, calculate_tsed(&tree1, &tree2, &options));
+        assert!(script.top_level_operations().iter().any(|op| {
+            matches!(op, crate::EditOperation::Delete(node) if node.label == "IfStatement")
+        }));
+    }
 }
@@ -23,6 +23,9 @@ pub enum TypeKind {
     Interface,
     TypeAlias,
     TypeLiteral,
+    Struct,
+    Enum,
+    Class,
 }
 
 #[derive(Debug, Clone)]
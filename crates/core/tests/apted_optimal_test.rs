@@ -0,0 +1,235 @@
+use similarity_core::{
+    compute_apted_distance, compute_apted_distance_with_costs, compute_edit_distance,
+    parse_and_convert_to_tree, APTEDOptions, CostModel, NodeView, TreeEditAlgorithm, TreeNode,
+};
+use std::rc::Rc;
+
+fn node(label: &str, children: Vec<Rc<TreeNode>>) -> Rc<TreeNode> {
+    let mut n = TreeNode::new(label.to_string(), String::new(), 0);
+    for child in children {
+        n.add_child(child);
+    }
+    Rc::new(n)
+}
+
+/// Label-dependent, asymmetric costs to exercise every branch of the cost model
+struct WeightedCosts;
+
+impl CostModel for WeightedCosts {
+    fn delete(&self, node: NodeView<'_>) -> f64 {
+        1.0 + (node.label.len() % 3) as f64 * 0.5
+    }
+
+    fn insert(&self, node: NodeView<'_>) -> f64 {
+        0.75 + (node.label.as_bytes()[0] % 2) as f64
+    }
+
+    fn rename(&self, from: NodeView<'_>, to: NodeView<'_>) -> f64 {
+        if from.label == to.label {
+            0.0
+        } else {
+            1.3
+        }
+    }
+}
+
+/// Reference implementation: classic Zhang–Shasha over left-to-right postorder
+fn zhang_shasha<C: CostModel>(t1: &TreeNode, t2: &TreeNode, costs: &C) -> f64 {
+    fn flatten<'a>(node: &'a TreeNode, nodes: &mut Vec<&'a TreeNode>, lld: &mut Vec<usize>) {
+        let first = nodes.len();
+        for child in &node.children {
+            flatten(child, nodes, lld);
+        }
+        lld.push(if node.children.is_empty() { nodes.len() } else { lld[first] });
+        nodes.push(node);
+    }
+
+    let (mut n1, mut l1, mut n2, mut l2) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    flatten(t1, &mut n1, &mut l1);
+    flatten(t2, &mut n2, &mut l2);
+    let keyroots = |l: &[usize]| -> Vec<usize> {
+        (0..l.len()).filter(|&i| !(i + 1..l.len()).any(|j| l[j] == l[i])).collect()
+    };
+
+    let mut td = vec![vec![0.0; n2.len()]; n1.len()];
+    for &i in &keyroots(&l1) {
+        for &j in &keyroots(&l2) {
+            let (li, lj) = (l1[i], l2[j]);
+            let mut fd = vec![vec![0.0; j - lj + 2]; i - li + 2];
+            for x in 1..fd.len() {
+                fd[x][0] = fd[x - 1][0] + costs.delete(n1[li + x - 1].into());
+            }
+            for y in 1..fd[0].len() {
+                fd[0][y] = fd[0][y - 1] + costs.insert(n2[lj + y - 1].into());
+            }
+            for x in 1..fd.len() {
+                for y in 1..fd[0].len() {
+                    let (a, b) = (li + x - 1, lj + y - 1);
+                    let del = fd[x - 1][y] + costs.delete(n1[a].into());
+                    let ins = fd[x][y - 1] + costs.insert(n2[b].into());
+                    if l1[a] == li && l2[b] == lj {
+                        let ren = fd[x - 1][y - 1] + costs.rename(n1[a].into(), n2[b].into());
+                        fd[x][y] = del.min(ins).min(ren);
+                        td[a][b] = fd[x][y];
+                    } else {
+                        fd[x][y] = del.min(ins).min(fd[l1[a] - li][l2[b] - lj] + td[a][b]);
+                    }
+                }
+            }
+        }
+    }
+    td[n1.len() - 1][n2.len() - 1]
+}
+
+/// Small deterministic generator so failures are reproducible without extra crates
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 =
+            self.0.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+        ((self.0 >> 33) as usize) % bound
+    }
+}
+
+fn random_tree(rng: &mut Lcg, budget: &mut usize, depth: usize) -> Rc<TreeNode> {
+    const LABELS: [&str; 4] = ["a", "bb", "ccc", "d"];
+    let label = LABELS[rng.next(LABELS.len())];
+    *budget = budget.saturating_sub(1);
+    let mut children = Vec::new();
+    if depth < 6 {
+        let fanout = rng.next(4);
+        for _ in 0..fanout {
+            if *budget == 0 {
+                break;
+            }
+            children.push(random_tree(rng, budget, depth + 1));
+        }
+    }
+    node(label, children)
+}
+
+fn unit_costs() -> APTEDOptions {
+    APTEDOptions { rename_cost: 1.0, compare_values: false, ..Default::default() }
+}
+
+fn assert_close(actual: f64, expected: f64, context: &str) {
+    assert!((actual - expected).abs() < 1e-9, "{context}: expected {expected}, got {actual}");
+}
+
+#[test]
+fn test_matches_zhang_shasha_on_random_trees() {
+    let mut rng = Lcg(42);
+    let options = unit_costs();
+    for round in 0..300 {
+        let mut budget1 = 1 + rng.next(18);
+        let mut budget2 = 1 + rng.next(18);
+        let t1 = random_tree(&mut rng, &mut budget1, 0);
+        let t2 = random_tree(&mut rng, &mut budget2, 0);
+        assert_close(
+            compute_apted_distance(&t1, &t2, &options),
+            zhang_shasha(&t1, &t2, &options),
+            &format!("round {round}"),
+        );
+    }
+}
+
+#[test]
+fn test_matches_zhang_shasha_with_weighted_costs() {
+    let mut rng = Lcg(7);
+    for round in 0..200 {
+        let mut budget1 = 1 + rng.next(24);
+        let mut budget2 = 1 + rng.next(24);
+        let t1 = random_tree(&mut rng, &mut budget1, 0);
+        let t2 = random_tree(&mut rng, &mut budget2, 0);
+        assert_close(
+            compute_apted_distance_with_costs(&t1, &t2, &WeightedCosts),
+            zhang_shasha(&t1, &t2, &WeightedCosts),
+            &format!("round {round}"),
+        );
+    }
+}
+
+#[test]
+fn test_distance_is_symmetric_for_symmetric_costs() {
+    let mut rng = Lcg(2024);
+    let options = APTEDOptions { rename_cost: 0.3, ..unit_costs() };
+    for _ in 0..50 {
+        let mut budget1 = 1 + rng.next(20);
+        let mut budget2 = 1 + rng.next(20);
+        let t1 = random_tree(&mut rng, &mut budget1, 0);
+        let t2 = random_tree(&mut rng, &mut budget2, 0);
+        assert_close(
+            compute_apted_distance(&t1, &t2, &options),
+            compute_apted_distance(&t2, &t1, &options),
+            "symmetry",
+        );
+    }
+}
+
+#[test]
+fn test_empty_like_trees() {
+    let leaf = node("a", vec![]);
+    let wide = node("a", (0..5).map(|_| node("b", vec![])).collect());
+    assert_close(compute_apted_distance(&leaf, &wide, &unit_costs()), 5.0, "insert children");
+    assert_close(compute_apted_distance(&wide, &leaf, &unit_costs()), 5.0, "delete children");
+}
+
+#[test]
+fn test_backend_selectable_via_options() {
+    // Moving `x` up one level: exact distance is a delete plus an insert
+    let t1 =
+        node("a", vec![node("b", vec![node("x", vec![node("y", vec![])])]), node("c", vec![])]);
+    let t2 =
+        node("a", vec![node("b", vec![]), node("x", vec![node("y", vec![])]), node("c", vec![])]);
+
+    let exact = APTEDOptions { algorithm: TreeEditAlgorithm::Apted, ..unit_costs() };
+    assert_close(compute_edit_distance(&t1, &t2, &exact), 2.0, "apted backend");
+
+    let recursive = compute_edit_distance(&t1, &t2, &unit_costs());
+    assert!(recursive >= 2.0, "recursive approximation never undercuts the exact distance");
+}
+
+#[test]
+fn test_compare_values_in_cost_model() {
+    let mut with_value = TreeNode::new("Identifier".to_string(), "foo".to_string(), 0);
+    with_value.add_child(node("a", vec![]));
+    let mut other_value = TreeNode::new("Identifier".to_string(), "bar".to_string(), 0);
+    other_value.add_child(node("a", vec![]));
+    let (t1, t2) = (Rc::new(with_value), Rc::new(other_value));
+
+    let structural = APTEDOptions { algorithm: TreeEditAlgorithm::Apted, ..unit_costs() };
+    assert_close(compute_edit_distance(&t1, &t2, &structural), 0.0, "labels only");
+    let by_value = APTEDOptions { compare_values: true, ..structural };
+    assert_close(compute_edit_distance(&t1, &t2, &by_value), 1.0, "labels and values");
+}
+
+#[test]
+fn test_real_code_matches_reference() {
+    let code1 = r#"
+        function sum(items) {
+            let total = 0;
+            for (const item of items) {
+                if (item > 0) { total += item; }
+            }
+            return total;
+        }
+    "#;
+    let code2 = r#"
+        function sum(items) {
+            let total = 0;
+            if (items.length > 0) {
+                for (const item of items) { total += item; }
+            }
+            return total;
+        }
+    "#;
+    let t1 = parse_and_convert_to_tree("a.js", code1).unwrap();
+    let t2 = parse_and_convert_to_tree("b.js", code2).unwrap();
+    let options = APTEDOptions { rename_cost: 0.3, ..unit_costs() };
+    assert_close(
+        compute_apted_distance(&t1, &t2, &options),
+        zhang_shasha(&t1, &t2, &options),
+        "parsed functions",
+    );
+}
@@ -1,7 +1,8 @@
 use similarity_core::language_parser::{
-    GenericFunctionDef, GenericTypeDef, Language, LanguageParser,
+    GenericFunctionDef, GenericTypeDef, Language, LanguageParser, TypeDefKind,
 };
-use similarity_core::tree::TreeNode;
+use similarity_core::suppression::retain_unsuppressed;
+use similarity_core::tree::{SourceSpan, TreeNode};
 use std::error::Error;
 use std::rc::Rc;
 use tree_sitter::{Node, Parser};
@@ -158,7 +159,8 @@ impl ElixirParser {
         let current_id = *id;
         *id += 1;
 
-        let mut tree_node = TreeNode::new(label, value, current_id);
+        let mut tree_node =
+            TreeNode::new(label, value, current_id).with_span(SourceSpan::from(node.range()));
 
         for child in node.children(&mut node.walk()) {
             let child_node = Self::build_tree_from_node(child, source, id);
@@ -171,7 +173,7 @@ impl ElixirParser {
 
 impl LanguageParser for ElixirParser {
     fn language(&self) -> Language {
-        Language::Unknown // TODO: Add Language::Elixir to core
+        Language::Elixir
     }
 
     fn parse(
@@ -193,6 +195,7 @@ impl LanguageParser for ElixirParser {
 
         let mut functions = Vec::new();
         self.extract_functions_from_node(tree.root_node(), source, &mut functions, None);
+        retain_unsuppressed(source, &mut functions, |f| f.start_line);
         Ok(functions)
     }
 
@@ -227,12 +230,10 @@ impl ElixirParser {
                             start_line: node.start_position().row as u32 + 1,
                             end_line: node.end_position().row as u32 + 1,
                             kind: match target_text {
-                                "defmodule" => "module",
-                                "defprotocol" => "protocol",
-                                "defimpl" => "implementation",
-                                _ => "unknown",
-                            }
-                            .to_string(),
+                                "defmodule" => TypeDefKind::Module,
+                                "defprotocol" => TypeDefKind::Interface,
+                                _ => TypeDefKind::Other("implementation".to_string()),
+                            },
                             fields: Vec::new(),
                         });
                     }
@@ -0,0 +1,38 @@
+use crate::elixir_parser::ElixirParser;
+use similarity_core::cli_pipeline::LanguageSupport;
+use similarity_core::language_parser::{GenericFunctionDef, LanguageParser};
+use similarity_core::FingerprintVocabulary;
+use std::error::Error;
+
+/// Elixir support for the shared analysis pipeline
+pub struct ElixirLanguage;
+
+impl LanguageSupport for ElixirLanguage {
+    fn name(&self) -> &str {
+        "Elixir"
+    }
+
+    fn default_extensions(&self) -> Vec<&str> {
+        vec!["ex", "exs"]
+    }
+
+    fn create_parser(&self) -> Result<Box<dyn LanguageParser>, Box<dyn Error + Send + Sync>> {
+        Ok(Box::new(ElixirParser::new()?))
+    }
+
+    fn fingerprint_vocabulary(&self) -> FingerprintVocabulary {
+        FingerprintVocabulary::elixir()
+    }
+
+    fn describe_function(&self, func: &GenericFunctionDef) -> String {
+        func.name.clone()
+    }
+
+    fn container_label(&self) -> &str {
+        "Modules"
+    }
+
+    fn lists_functions(&self) -> bool {
+        true
+    }
+}
@@ -1,4 +1,4 @@
 pub mod elixir_parser;
-pub mod parallel;
+pub mod language;
 
 pub use elixir_parser::ElixirParser;
@@ -1,9 +1,17 @@
 use anyhow::Result;
-use clap::Parser;
-
-mod check;
-mod elixir_parser;
-mod parallel;
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser};
+use similarity_core::cli_baseline::{finish_baseline, load_baseline, DEFAULT_BASELINE_FILE};
+use similarity_core::cli_clone_classes::Linkage;
+use similarity_core::cli_output::{FailThresholds, OutputFormat, Report};
+use similarity_core::cli_parallel::CompareOptions;
+use similarity_core::cli_pipeline::{
+    check_overlaps, check_paths, LanguageSupport, PipelineOptions,
+};
+use similarity_core::lsh::LshOptions;
+use similarity_core::{OverlapOptions, TSEDOptions, TypeComparisonOptions};
+use similarity_elixir::language::ElixirLanguage;
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "similarity-elixir")]
@@ -54,6 +62,26 @@ struct Cli {
     #[arg(long)]
     no_fast: bool,
 
+    /// Only compare functions whose MinHash signatures share an LSH band
+    #[arg(long)]
+    lsh: bool,
+
+    /// Number of LSH bands; more bands find more candidate pairs (higher recall)
+    #[arg(long, value_name = "N", default_value = "32", value_parser = clap::value_parser!(u32).range(1..))]
+    lsh_bands: u32,
+
+    /// MinHash values per LSH band; more rows find fewer candidate pairs (higher precision)
+    #[arg(long, value_name = "N", default_value = "3", value_parser = clap::value_parser!(u32).range(1..))]
+    lsh_rows: u32,
+
+    /// Print how many function pairs were compared and how many candidate generation pruned
+    #[arg(long)]
+    stats: bool,
+
+    /// Exclude paths matching the given patterns (can be specified multiple times)
+    #[arg(long)]
+    exclude: Vec<String>,
+
     /// Enable experimental overlap detection mode
     #[arg(long = "experimental-overlap")]
     overlap: bool,
@@ -69,250 +97,166 @@ struct Cli {
     /// Size tolerance for overlap detection (0.0-1.0)
     #[arg(long, default_value = "0.25")]
     overlap_size_tolerance: f64,
-}
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+    /// Output format (text, json, sarif)
+    #[arg(long, default_value = "text")]
+    format: OutputFormat,
 
-    let functions_enabled = true; // Elixir always has functions enabled
-    let overlap_enabled = cli.overlap;
+    /// Project config file (default: nearest similarity.toml, pyproject.toml or package.json)
+    #[arg(long)]
+    project_config: Option<PathBuf>,
 
-    println!("Analyzing Elixir code similarity...\n");
+    /// Only report duplicate pairs that are not recorded in this baseline file
+    #[arg(long, value_name = "FILE")]
+    baseline: Option<PathBuf>,
 
-    let separator = "-".repeat(60);
+    /// Record the current duplicate pairs as a baseline [default: .similarity-baseline.json]
+    #[arg(long, value_name = "FILE", num_args = 0..=1, default_missing_value = DEFAULT_BASELINE_FILE)]
+    write_baseline: Option<PathBuf>,
 
-    // Run functions analysis
-    if !overlap_enabled || functions_enabled {
-        println!("=== Function Similarity ===");
-        check::check_paths(
-            cli.paths.clone(),
-            cli.threshold,
-            cli.rename_cost,
-            cli.extensions.as_ref(),
-            cli.min_lines.unwrap_or(3),
-            cli.min_tokens,
-            cli.no_size_penalty,
-            cli.print,
-            !cli.no_fast,
-            cli.filter_function.as_ref(),
-            cli.filter_function_body.as_ref(),
-        )?;
-    }
+    /// Exit with code 3 when any duplicate is reported
+    #[arg(long)]
+    fail_on_duplicates: bool,
 
-    // Run overlap analysis if enabled
-    if overlap_enabled && functions_enabled {
-        println!("\n{separator}\n");
-    }
+    /// Exit with code 3 when more duplicate pairs than this are reported
+    #[arg(long, value_name = "N")]
+    max_duplicates: Option<usize>,
 
-    if overlap_enabled {
-        println!("=== Overlap Detection ===");
-        check_overlaps(
-            cli.paths,
-            cli.threshold,
-            cli.extensions.as_ref(),
-            cli.print,
-            cli.overlap_min_window,
-            cli.overlap_max_window,
-            cli.overlap_size_tolerance,
-        )?;
-    }
+    /// Exit with code 3 when the reported pairs duplicate more lines than this
+    #[arg(long, value_name = "N")]
+    max_duplicated_lines: Option<u32>,
 
-    Ok(())
+    /// Group duplicate pairs into clone classes instead of listing every pair
+    #[arg(long)]
+    clone_classes: bool,
+
+    /// How pairs are merged into clone classes: single (transitive) or complete
+    #[arg(long, value_name = "LINKAGE", default_value = "single")]
+    linkage: Linkage,
 }
 
-#[allow(clippy::too_many_arguments)]
-fn check_overlaps(
-    paths: Vec<String>,
-    threshold: f64,
-    extensions: Option<&Vec<String>>,
-    print: bool,
-    min_window_size: u32,
-    max_window_size: u32,
-    size_tolerance: f64,
-) -> anyhow::Result<()> {
-    use crate::elixir_parser::ElixirParser;
-    use ignore::WalkBuilder;
-    use similarity_core::{find_overlaps_across_files_generic, OverlapOptions};
-    use std::collections::{HashMap, HashSet};
-    use std::fs;
-    use std::path::Path;
-
-    let default_extensions = vec!["ex", "exs"];
-    let exts: Vec<&str> =
-        extensions.map_or(default_extensions, |v| v.iter().map(String::as_str).collect());
-
-    let mut files = Vec::new();
-    let mut visited = HashSet::new();
-
-    // Process each path
-    for path_str in &paths {
-        let path = Path::new(path_str);
-
-        if path.is_file() {
-            // If it's a file, check extension and add it
-            if let Some(ext) = path.extension() {
-                if let Some(ext_str) = ext.to_str() {
-                    if exts.contains(&ext_str) {
-                        if let Ok(canonical) = path.canonicalize() {
-                            if visited.insert(canonical.clone()) {
-                                files.push(path.to_path_buf());
-                            }
-                        }
-                    }
-                }
-            }
-        } else if path.is_dir() {
-            // If it's a directory, walk it respecting .gitignore
-            let walker = WalkBuilder::new(path).follow_links(false).build();
-
-            for entry in walker {
-                let entry = entry?;
-                let entry_path = entry.path();
-
-                // Skip if not a file
-                if !entry_path.is_file() {
-                    continue;
-                }
-
-                // Check extension
-                if let Some(ext) = entry_path.extension() {
-                    if let Some(ext_str) = ext.to_str() {
-                        if exts.contains(&ext_str) {
-                            // Get canonical path to avoid duplicates
-                            if let Ok(canonical) = entry_path.canonicalize() {
-                                if visited.insert(canonical.clone()) {
-                                    files.push(entry_path.to_path_buf());
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        } else {
-            eprintln!("Warning: Path not found: {path_str}");
+impl Cli {
+    fn fail_thresholds(&self) -> FailThresholds {
+        FailThresholds {
+            fail_on_duplicates: self.fail_on_duplicates,
+            max_duplicates: self.max_duplicates,
+            max_duplicated_lines: self.max_duplicated_lines,
         }
     }
 
-    if files.is_empty() {
-        println!("No Elixir files found in specified paths");
-        return Ok(());
+    fn lsh_options(&self) -> Option<LshOptions> {
+        self.lsh.then(|| LshOptions::new(self.lsh_bands as usize, self.lsh_rows as usize))
     }
 
-    println!("Checking {} files for overlapping code...\n", files.len());
-
-    // Read all file contents
-    let mut file_contents = HashMap::new();
-    for file in &files {
-        match fs::read_to_string(file) {
-            Ok(content) => {
-                let file_str = file.to_string_lossy().to_string();
-                file_contents.insert(file_str, content);
-            }
-            Err(e) => {
-                eprintln!("Error reading {}: {}", file.display(), e);
-            }
+    fn pipeline_options(&self) -> PipelineOptions {
+        let mut tsed_options = TSEDOptions::default();
+        tsed_options.apted_options.rename_cost = self.rename_cost;
+        tsed_options.min_lines = self.min_lines.unwrap_or(3);
+        tsed_options.min_tokens = self.min_tokens;
+        tsed_options.size_penalty = !self.no_size_penalty;
+
+        PipelineOptions {
+            compare: CompareOptions {
+                threshold: self.threshold,
+                tsed_options,
+                fast_mode: !self.no_fast,
+                lsh: self.lsh_options(),
+            },
+            extensions: self.extensions.clone(),
+            exclude: self.exclude.clone(),
+            filter_function: self.filter_function.clone(),
+            filter_function_body: self.filter_function_body.clone(),
+            print: self.print,
+            stats: self.stats,
+            cross_file: false,
+            cross_language: false,
+            types: TypeComparisonOptions::default(),
+            overlap: OverlapOptions {
+                min_window_size: self.overlap_min_window,
+                max_window_size: self.overlap_max_window,
+                threshold: self.threshold,
+                size_tolerance: self.overlap_size_tolerance,
+            },
         }
     }
+}
 
-    // Set up overlap options
-    let options = OverlapOptions { min_window_size, max_window_size, threshold, size_tolerance };
-
-    // Create Elixir parser
-    let mut parser = ElixirParser::new()
-        .map_err(|e| anyhow::anyhow!("Failed to create Elixir parser: {}", e))?;
-
-    // Find overlaps
-    let overlaps = find_overlaps_across_files_generic(&mut parser, &file_contents, &options)
-        .map_err(|e| anyhow::anyhow!("Failed to find overlaps: {}", e))?;
-
-    if overlaps.is_empty() {
-        println!("\nNo code overlaps found!");
-    } else {
-        println!("\nCode overlaps found:");
-        println!("{}", "-".repeat(60));
-
-        for overlap_with_files in &overlaps {
-            let overlap = &overlap_with_files.overlap;
-            let source_path = get_relative_path(&overlap_with_files.source_file);
-            let target_path = get_relative_path(&overlap_with_files.target_file);
-
-            println!(
-                "\nSimilarity: {:.2}% | {} nodes | {}",
-                overlap.similarity * 100.0,
-                overlap.node_count,
-                overlap.node_type
-            );
-            println!(
-                "  {}:{} | L{}-{} in function: {}",
-                source_path,
-                overlap.source_lines.0,
-                overlap.source_lines.0,
-                overlap.source_lines.1,
-                overlap.source_function
-            );
-            println!(
-                "  {}:{} | L{}-{} in function: {}",
-                target_path,
-                overlap.target_lines.0,
-                overlap.target_lines.0,
-                overlap.target_lines.1,
-                overlap.target_function
-            );
-
-            if print {
-                // Extract and display the overlapping code
-                if let Some(source_content) = file_contents.get(&overlap_with_files.source_file) {
-                    if let Some(target_content) = file_contents.get(&overlap_with_files.target_file)
-                    {
-                        println!("\n\x1b[36m--- Source Code ---\x1b[0m");
-                        if let Ok(source_segment) = extract_code_lines(
-                            source_content,
-                            overlap.source_lines.0,
-                            overlap.source_lines.1,
-                        ) {
-                            println!("{source_segment}");
-                        }
-
-                        println!("\n\x1b[36m--- Target Code ---\x1b[0m");
-                        if let Ok(target_segment) = extract_code_lines(
-                            target_content,
-                            overlap.target_lines.0,
-                            overlap.target_lines.1,
-                        ) {
-                            println!("{target_segment}");
-                        }
-                    }
-                }
-            }
-        }
+fn main() -> Result<()> {
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches)?;
+    let explicit = |id: &str| {
+        matches.try_contains_id(id).unwrap_or(false)
+            && matches.value_source(id) == Some(ValueSource::CommandLine)
+    };
+    let runs = cli.pipeline_options().for_files(
+        cli.project_config.as_deref(),
+        &cli.paths,
+        &["elixir"],
+        &ElixirLanguage.default_extensions(),
+        explicit,
+    )?;
 
-        println!("\nTotal overlaps found: {}", overlaps.len());
+    let functions_enabled = true; // Elixir always has functions enabled
+    let overlap_enabled = cli.overlap;
+
+    let format = cli.format;
+    let text = format.is_text();
+    // Grouped pairs are only printed as clone classes once every analyzer has run
+    let pair_format = format.for_pairs(cli.clone_classes);
+    let fail_thresholds = cli.fail_thresholds();
+    let baseline = load_baseline(cli.baseline.as_deref(), cli.write_baseline.as_deref())?;
+    let mut report = Report::new("similarity-elixir", env!("CARGO_PKG_VERSION"));
+
+    if text {
+        println!("Analyzing Elixir code similarity...\n");
     }
 
-    Ok(())
-}
+    let separator = "-".repeat(60);
 
-fn get_relative_path(file_path: &str) -> String {
-    if let Ok(current_dir) = std::env::current_dir() {
-        std::path::Path::new(file_path)
-            .strip_prefix(&current_dir)
-            .unwrap_or(std::path::Path::new(file_path))
-            .to_string_lossy()
-            .to_string()
-    } else {
-        file_path.to_string()
+    // Run functions analysis
+    if !overlap_enabled || functions_enabled {
+        if pair_format.is_text() {
+            println!("=== Function Similarity ===");
+        }
+        for (paths, options) in &runs {
+            let entries = check_paths(
+                &ElixirLanguage,
+                paths.clone(),
+                options,
+                pair_format,
+                baseline.as_ref(),
+            )?;
+            report.extend(entries);
+        }
     }
-}
 
-fn extract_code_lines(code: &str, start_line: u32, end_line: u32) -> Result<String, String> {
-    let lines: Vec<_> = code.lines().collect();
+    // Run overlap analysis if enabled
+    if pair_format.is_text() && overlap_enabled && functions_enabled {
+        println!("\n{separator}\n");
+    }
 
-    if start_line as usize > lines.len() || end_line as usize > lines.len() {
-        return Err("Line numbers out of bounds".to_string());
+    if overlap_enabled {
+        if pair_format.is_text() {
+            println!("=== Overlap Detection ===");
+        }
+        for (paths, options) in &runs {
+            let entries = check_overlaps(
+                &ElixirLanguage,
+                paths.clone(),
+                options,
+                pair_format,
+                baseline.as_ref(),
+            )?;
+            report.extend(entries);
+        }
     }
 
-    let start = (start_line as usize).saturating_sub(1);
-    let end = (end_line as usize).min(lines.len());
+    if cli.clone_classes {
+        report.group_clone_classes(cli.linkage);
+    }
+    report.print(format)?;
+    finish_baseline(baseline.as_ref(), cli.write_baseline.as_deref(), &report.duplicates, text)?;
+    fail_thresholds.enforce(&report.duplicates);
 
-    Ok(lines[start..end].join("\n"))
+    Ok(())
 }
@@ -154,3 +154,36 @@ end
         .stdout(predicate::str::contains("map_example"))
         .stdout(predicate::str::contains("pipeline"));
 }
+
+#[test]
+fn test_elixir_json_output() {
+    let (_dir, file_path) = create_elixir_file(
+        r#"
+defmodule Example do
+  def similar_function1(list) do
+    list
+    |> Enum.filter(&(&1 > 0))
+    |> Enum.map(&(&1 * 2))
+    |> Enum.sum()
+  end
+
+  def similar_function2(items) do
+    items
+    |> Enum.filter(&(&1 > 0))
+    |> Enum.map(&(&1 * 2))
+    |> Enum.sum()
+  end
+end
+"#,
+    );
+
+    let mut cmd = Command::cargo_bin("similarity-elixir").unwrap();
+    cmd.arg(&file_path).arg("-t").arg("0.8").arg("--format").arg("json");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::starts_with("{"))
+        .stdout(predicate::str::contains("\"name\": \"similar_function1\""))
+        .stdout(predicate::str::contains("\"name\": \"similar_function2\""))
+        .stdout(predicate::str::contains("Found 2 functions").not());
+}
@@ -36,8 +36,8 @@ echo "   # Use the custom configuration"
 echo "   similarity-generic sample.go --config my-config.json"
 echo
 
-echo "8. Analyze multiple files:"
-echo "   find . -name '*.go' -exec similarity-generic {} --language go \;"
+echo "8. Analyze a directory, comparing functions across files:"
+echo "   similarity-generic . --language go"
 echo
 
 echo "9. Output in VSCode-compatible format (default):"
@@ -0,0 +1,144 @@
+use similarity_core::cli_pipeline::LanguageSupport;
+use similarity_core::generic_parser_config::{GenericParserConfig, GrammarLibrary};
+use similarity_core::generic_tree_sitter_parser::GenericTreeSitterParser;
+use similarity_core::language_parser::{GenericFunctionDef, Language, LanguageParser};
+use similarity_core::{FingerprintVocabulary, TypeComparisonOptions};
+use std::error::Error;
+use tree_sitter_language::LanguageFn;
+
+/// A tree-sitter grammar described by a [`GenericParserConfig`], for the shared analysis
+/// pipeline. The grammar is bundled with the binary or loaded from the config's `grammar`.
+pub struct GenericLanguage {
+    grammar: tree_sitter::Language,
+    config: GenericParserConfig,
+    language: Language,
+}
+
+impl GenericLanguage {
+    /// Support for `config`; without a `grammar` library its `language` must name one of
+    /// the bundled grammars
+    pub fn new(config: GenericParserConfig) -> anyhow::Result<Self> {
+        let grammar: tree_sitter::Language = match (&config.grammar, config.language.as_str()) {
+            (Some(library), language) => load_grammar(library, language)?,
+            (None, "go") => tree_sitter_go::LANGUAGE.into(),
+            (None, "java") => tree_sitter_java::LANGUAGE.into(),
+            (None, "c") => tree_sitter_c::LANGUAGE.into(),
+            (None, "cpp") => tree_sitter_cpp::LANGUAGE.into(),
+            (None, "csharp") => tree_sitter_c_sharp::LANGUAGE.into(),
+            (None, "ruby") => tree_sitter_ruby::LANGUAGE.into(),
+            (None, language) => {
+                return Err(anyhow::anyhow!(
+                    "Unsupported language: {} (set `grammar` in the config to load a compiled \
+                     tree-sitter grammar)",
+                    language
+                ))
+            }
+        };
+        let language = GenericTreeSitterParser::new(grammar.clone(), config.clone())
+            .map_err(|e| anyhow::anyhow!("Failed to create parser: {}", e))?
+            .language();
+        // Without extensions nothing would be scanned and the run would silently pass
+        if config.extensions.is_empty() && language.extensions().is_empty() {
+            return Err(anyhow::anyhow!(
+                "No file extensions for language {} (set `extensions` in the config)",
+                config.language
+            ));
+        }
+        Ok(Self { grammar, config, language })
+    }
+
+    /// Support for a bundled language with its built-in config, e.g. `go`
+    pub fn from_language_name(name: &str) -> anyhow::Result<Self> {
+        let parser = GenericTreeSitterParser::from_language_name(name)
+            .map_err(|e| anyhow::anyhow!("Failed to create parser: {}", e))?;
+        Self::new(parser.config().clone())
+    }
+
+    pub fn config(&self) -> &GenericParserConfig {
+        &self.config
+    }
+
+    /// Create a parser for the language
+    pub fn parser(&self) -> anyhow::Result<GenericTreeSitterParser> {
+        GenericTreeSitterParser::new(self.grammar.clone(), self.config.clone())
+            .map_err(|e| anyhow::anyhow!("Failed to create parser: {}", e))
+    }
+}
+
+impl LanguageSupport for GenericLanguage {
+    fn name(&self) -> &str {
+        &self.config.language
+    }
+
+    fn default_extensions(&self) -> Vec<&str> {
+        if self.config.extensions.is_empty() {
+            self.language.extensions().to_vec()
+        } else {
+            self.config.extensions.iter().map(String::as_str).collect()
+        }
+    }
+
+    fn create_parser(&self) -> Result<Box<dyn LanguageParser>, Box<dyn Error + Send + Sync>> {
+        Ok(Box::new(GenericTreeSitterParser::new(self.grammar.clone(), self.config.clone())?))
+    }
+
+    fn fingerprint_vocabulary(&self) -> FingerprintVocabulary {
+        self.config.fingerprint_vocabulary()
+    }
+
+    fn is_test_function(&self, func: &GenericFunctionDef) -> bool {
+        let Some(patterns) = &self.config.test_patterns else {
+            return false;
+        };
+        patterns.name_prefixes.iter().any(|prefix| func.name.starts_with(prefix.as_str()))
+            || patterns.name_suffixes.iter().any(|suffix| func.name.ends_with(suffix.as_str()))
+            || func.decorators.iter().any(|decorator| {
+                patterns
+                    .attribute_patterns
+                    .iter()
+                    .any(|pattern| decorator.contains(pattern.as_str()))
+            })
+    }
+
+    fn supports_types(&self) -> bool {
+        true
+    }
+
+    fn configure_types(&self, options: &mut TypeComparisonOptions) {
+        // Structs are only compared with structs, classes with classes and so on
+        options.allow_cross_kind_comparison = false;
+    }
+}
+
+/// Load the grammar exported by a compiled tree-sitter library. The library stays loaded
+/// until the process exits, since the returned language points into it.
+fn load_grammar(library: &GrammarLibrary, language: &str) -> anyhow::Result<tree_sitter::Language> {
+    let symbol = library.symbol_name(language);
+
+    // SAFETY: a grammar library only holds the parse tables and the function returning them
+    let loaded = unsafe { libloading::Library::new(&library.path) }
+        .map_err(|e| anyhow::anyhow!("Failed to load grammar: {}", e))?;
+    let loaded: &'static libloading::Library = Box::leak(Box::new(loaded));
+    // SAFETY: tree-sitter grammars export `const TSLanguage *tree_sitter_<name>(void)`
+    let language_fn = unsafe {
+        let function = loaded
+            .get::<unsafe extern "C" fn() -> *const ()>(symbol.as_bytes())
+            .map_err(|e| anyhow::anyhow!("Failed to load grammar function {}: {}", symbol, e))?;
+        LanguageFn::from_raw(*function)
+    };
+
+    let grammar = tree_sitter::Language::new(language_fn);
+    let version = grammar.version();
+    if !(tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION..=tree_sitter::LANGUAGE_VERSION)
+        .contains(&version)
+    {
+        return Err(anyhow::anyhow!(
+            "Grammar {} was generated for tree-sitter ABI {}, but ABI {}-{} is supported",
+            library.path.display(),
+            version,
+            tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION,
+            tree_sitter::LANGUAGE_VERSION
+        ));
+    }
+    Ok(grammar)
+}
@@ -1,23 +1,40 @@
 use anyhow::Result;
-use clap::Parser;
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser};
+use similarity_core::cli_baseline::{finish_baseline, load_baseline, DEFAULT_BASELINE_FILE};
+use similarity_core::cli_clone_classes::Linkage;
+use similarity_core::cli_output::{FailThresholds, OutputFormat, Report};
+use similarity_core::cli_parallel::{load_files_parallel, CompareOptions};
+use similarity_core::cli_pipeline::{
+    check_overlaps, check_paths, check_types, LanguageSupport, PipelineOptions,
+};
 use similarity_core::generic_parser_config::GenericParserConfig;
-use similarity_core::generic_tree_sitter_parser::GenericTreeSitterParser;
-use similarity_core::language_parser::LanguageParser;
-use similarity_core::tsed::{calculate_tsed, TSEDOptions};
-use similarity_core::APTEDOptions;
-use std::fs;
+use similarity_core::language_parser::Language;
+use similarity_core::lsh::LshOptions;
+use similarity_core::tsed::TSEDOptions;
+use similarity_core::{OverlapOptions, TypeComparisonOptions};
+use similarity_generic::language::GenericLanguage;
 use std::path::PathBuf;
 
 // Include auto-generated language configs
 include!(concat!(env!("OUT_DIR"), "/language_configs.rs"));
 
+/// Languages with a bundled grammar, picked by file extension when neither `--language`
+/// nor `--config` is given
+const LANGUAGES: [Language; 6] =
+    [Language::Go, Language::Java, Language::C, Language::Cpp, Language::CSharp, Language::Ruby];
+
 #[derive(Parser)]
 #[command(name = "similarity-generic")]
 #[command(about = "Generic code similarity analyzer using tree-sitter")]
 struct Cli {
-    /// Path to analyze
-    #[arg(required_unless_present_any = ["supported", "show_config"])]
-    path: Option<PathBuf>,
+    /// Paths to analyze (files or directories)
+    #[arg(default_value = ".")]
+    paths: Vec<String>,
+
+    /// Print code in output
+    #[arg(short, long)]
+    print: bool,
 
     /// Language configuration file (JSON)
     #[arg(short, long, conflicts_with_all = ["language", "supported", "show_config"])]
@@ -36,13 +53,73 @@ struct Cli {
     show_functions: bool,
 
     /// Show supported languages
-    #[arg(long, conflicts_with_all = ["path", "config", "language", "show_functions", "show_config"])]
+    #[arg(long, conflicts_with_all = ["paths", "config", "language", "show_functions", "show_config"])]
     supported: bool,
 
     /// Show example configuration for a language
-    #[arg(long, value_name = "LANGUAGE", conflicts_with_all = ["path", "config", "language", "show_functions", "supported"])]
+    #[arg(long, value_name = "LANGUAGE", conflicts_with_all = ["paths", "config", "language", "show_functions", "supported"])]
     show_config: Option<String>,
 
+    /// Disable function similarity checking
+    #[arg(long = "no-functions")]
+    no_functions: bool,
+
+    /// Enable type similarity checking for the config's `type_nodes` (experimental)
+    #[arg(long = "experimental-types")]
+    types: bool,
+
+    /// File extensions to check (default: every extension of the language)
+    #[arg(short, long, value_delimiter = ',')]
+    extensions: Option<Vec<String>>,
+
+    /// Minimum lines for functions to be considered
+    #[arg(short, long, default_value = "3")]
+    min_lines: Option<u32>,
+
+    /// Minimum tokens for functions to be considered
+    #[arg(long)]
+    min_tokens: Option<u32>,
+
+    /// Rename cost for APTED algorithm
+    #[arg(short, long, default_value = "0.3")]
+    rename_cost: f64,
+
+    /// Filter functions by name (substring match)
+    #[arg(long)]
+    filter_function: Option<String>,
+
+    /// Filter functions by body content (substring match)
+    #[arg(long)]
+    filter_function_body: Option<String>,
+
+    /// Skip test functions, as named by the config's `test_patterns`
+    #[arg(long)]
+    skip_test: bool,
+
+    /// Disable fast mode (compare every pair without the fingerprint pre-filter)
+    #[arg(long)]
+    no_fast: bool,
+
+    /// Only compare functions whose MinHash signatures share an LSH band
+    #[arg(long)]
+    lsh: bool,
+
+    /// Number of LSH bands; more bands find more candidate pairs (higher recall)
+    #[arg(long, value_name = "N", default_value = "32", value_parser = clap::value_parser!(u32).range(1..))]
+    lsh_bands: u32,
+
+    /// MinHash values per LSH band; more rows find fewer candidate pairs (higher precision)
+    #[arg(long, value_name = "N", default_value = "3", value_parser = clap::value_parser!(u32).range(1..))]
+    lsh_rows: u32,
+
+    /// Print how many function pairs were compared and how many candidate generation pruned
+    #[arg(long)]
+    stats: bool,
+
+    /// Exclude paths matching the given patterns (can be specified multiple times)
+    #[arg(long)]
+    exclude: Vec<String>,
+
     /// Enable experimental overlap detection mode
     #[arg(long = "experimental-overlap")]
     overlap: bool,
@@ -58,11 +135,99 @@ struct Cli {
     /// Size tolerance for overlap detection (0.0-1.0)
     #[arg(long, default_value = "0.25")]
     overlap_size_tolerance: f64,
+
+    /// Output format (text, json, sarif)
+    #[arg(long, default_value = "text")]
+    format: OutputFormat,
+
+    /// Project config file (default: nearest similarity.toml, pyproject.toml or package.json)
+    #[arg(long)]
+    project_config: Option<PathBuf>,
+
+    /// Only report duplicate pairs that are not recorded in this baseline file
+    #[arg(long, value_name = "FILE")]
+    baseline: Option<PathBuf>,
+
+    /// Record the current duplicate pairs as a baseline [default: .similarity-baseline.json]
+    #[arg(long, value_name = "FILE", num_args = 0..=1, default_missing_value = DEFAULT_BASELINE_FILE)]
+    write_baseline: Option<PathBuf>,
+
+    /// Exit with code 3 when any duplicate is reported
+    #[arg(long)]
+    fail_on_duplicates: bool,
+
+    /// Exit with code 3 when more duplicate pairs than this are reported
+    #[arg(long, value_name = "N")]
+    max_duplicates: Option<usize>,
+
+    /// Exit with code 3 when the reported pairs duplicate more lines than this
+    #[arg(long, value_name = "N")]
+    max_duplicated_lines: Option<u32>,
+
+    /// Group duplicate pairs into clone classes instead of listing every pair
+    #[arg(long)]
+    clone_classes: bool,
+
+    /// How pairs are merged into clone classes: single (transitive) or complete
+    #[arg(long, value_name = "LINKAGE", default_value = "single")]
+    linkage: Linkage,
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+impl Cli {
+    fn fail_thresholds(&self) -> FailThresholds {
+        FailThresholds {
+            fail_on_duplicates: self.fail_on_duplicates,
+            max_duplicates: self.max_duplicates,
+            max_duplicated_lines: self.max_duplicated_lines,
+        }
+    }
+
+    fn lsh_options(&self) -> Option<LshOptions> {
+        self.lsh.then(|| LshOptions::new(self.lsh_bands as usize, self.lsh_rows as usize))
+    }
+
+    fn pipeline_options(&self) -> PipelineOptions {
+        let mut tsed_options = TSEDOptions::default();
+        tsed_options.apted_options.rename_cost = self.rename_cost;
+        tsed_options.min_lines = self.min_lines.unwrap_or(3);
+        tsed_options.min_tokens = self.min_tokens;
+        // Getters and small helpers are the norm in these languages; keep them comparable
+        tsed_options.size_penalty = false;
+        tsed_options.skip_test = self.skip_test;
+
+        PipelineOptions {
+            compare: CompareOptions {
+                threshold: self.threshold,
+                tsed_options,
+                fast_mode: !self.no_fast,
+                lsh: self.lsh_options(),
+            },
+            extensions: self.extensions.clone(),
+            exclude: self.exclude.clone(),
+            filter_function: self.filter_function.clone(),
+            filter_function_body: self.filter_function_body.clone(),
+            print: self.print,
+            stats: self.stats,
+            cross_file: true,
+            cross_language: false,
+            types: TypeComparisonOptions::default(),
+            overlap: OverlapOptions {
+                min_window_size: self.overlap_min_window,
+                max_window_size: self.overlap_max_window,
+                threshold: self.threshold,
+                size_tolerance: self.overlap_size_tolerance,
+            },
+        }
+    }
+}
 
+fn main() -> Result<()> {
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches)?;
+    let explicit = |id: &str| {
+        matches.try_contains_id(id).unwrap_or(false)
+            && matches.value_source(id) == Some(ValueSource::CommandLine)
+    };
     // Handle --supported option
     if cli.supported {
         println!("Supported languages for generic tree-sitter parser:");
@@ -73,6 +238,9 @@ fn main() -> Result<()> {
         println!("  csharp     - C# language");
         println!("  ruby       - Ruby language");
         println!();
+        println!("Other languages: pass --config with a `grammar` entry naming a compiled");
+        println!("tree-sitter grammar library (.so, .dylib or .dll) to load it at runtime.");
+        println!();
         println!("Note: For Python, TypeScript, and Rust, use the dedicated implementations:");
         println!("  similarity-py  - Optimized Python analyzer");
         println!("  similarity-ts  - Optimized TypeScript/JavaScript analyzer");
@@ -103,251 +271,203 @@ fn main() -> Result<()> {
     }
 
     // Normal parsing mode
-    let path = cli.path.ok_or_else(|| anyhow::anyhow!("Path is required"))?;
-
-    let config = if let Some(config_path) = &cli.config {
-        GenericParserConfig::from_file(config_path)
-            .map_err(|e| anyhow::anyhow!("Failed to load config: {}", e))?
-    } else if let Some(lang) = &cli.language {
-        // First try to load from embedded configs
-        if let Some(config_json) =
-            LANGUAGE_CONFIGS.get(lang.as_str()).or_else(|| match lang.as_str() {
-                "cpp" => LANGUAGE_CONFIGS.get("cpp"),
-                "c++" => LANGUAGE_CONFIGS.get("cpp"),
-                "csharp" => LANGUAGE_CONFIGS.get("csharp"),
-                "cs" => LANGUAGE_CONFIGS.get("csharp"),
-                "ruby" => LANGUAGE_CONFIGS.get("ruby"),
-                "rb" => LANGUAGE_CONFIGS.get("ruby"),
-                _ => None,
-            })
-        {
-            serde_json::from_str(config_json)
-                .map_err(|e| anyhow::anyhow!("Failed to parse embedded config: {}", e))?
-        } else {
-            // Fall back to hardcoded configs
-            match lang.as_str() {
-                "go" => GenericParserConfig::go(),
-                "java" => GenericParserConfig::java(),
-                "c" => GenericParserConfig::c(),
-                "cpp" | "c++" => GenericParserConfig::cpp(),
-                "csharp" | "cs" => GenericParserConfig::csharp(),
-                "ruby" | "rb" => GenericParserConfig::ruby(),
-                _ => {
-                    eprintln!("Error: Language '{lang}' is not supported by similarity-generic.");
-                    eprintln!("Use --supported to see available languages.");
-                    if matches!(
-                        lang.as_str(),
-                        "python" | "py" | "rust" | "rs" | "javascript" | "js" | "typescript" | "ts"
-                    ) {
-                        eprintln!();
-                        eprintln!("Note: For {lang}, use the dedicated implementation:");
-                        match lang.as_str() {
-                            "python" | "py" => eprintln!("  similarity-py"),
-                            "rust" | "rs" => eprintln!("  similarity-rs (planned)"),
-                            "javascript" | "js" | "typescript" | "ts" => {
-                                eprintln!("  similarity-ts")
-                            }
-                            _ => {}
-                        }
-                    }
-                    return Err(anyhow::anyhow!("Unsupported language"));
-                }
-            }
+    let fail_thresholds = cli.fail_thresholds();
+    let selected = selected_language(&cli)?;
+    let extensions: Vec<&str> = match &selected {
+        Some(language) => language.default_extensions(),
+        None => {
+            LANGUAGES.iter().flat_map(|language| language.extensions().iter().copied()).collect()
         }
-    } else {
-        return Err(anyhow::anyhow!("Either --config or --language must be provided"));
-    };
-
-    // Create parser based on language
-    let language = match config.language.as_str() {
-        "go" => tree_sitter_go::LANGUAGE.into(),
-        "java" => tree_sitter_java::LANGUAGE.into(),
-        "c" => tree_sitter_c::LANGUAGE.into(),
-        "cpp" => tree_sitter_cpp::LANGUAGE.into(),
-        "csharp" => tree_sitter_c_sharp::LANGUAGE.into(),
-        "ruby" => tree_sitter_ruby::LANGUAGE.into(),
-        _ => return Err(anyhow::anyhow!("Unsupported language: {}", config.language)),
     };
+    let mut sections = vec!["generic"];
+    sections.extend(cli.language.as_deref());
+    let runs = cli.pipeline_options().for_files(
+        cli.project_config.as_deref(),
+        &cli.paths,
+        &sections,
+        &extensions,
+        explicit,
+    )?;
+    let languages = language_files(selected, &runs)?;
+
+    let format = cli.format;
+    // Grouped pairs are only printed as clone classes once the analysis has run
+    let pair_format = format.for_pairs(cli.clone_classes);
+    let baseline = load_baseline(cli.baseline.as_deref(), cli.write_baseline.as_deref())?;
+    let mut report = Report::new("similarity-generic", env!("CARGO_PKG_VERSION"));
+
+    if languages.is_empty() && format.is_text() {
+        println!("No supported files found in the specified paths.");
+    }
 
-    let mut parser = GenericTreeSitterParser::new(language, config.clone())
-        .map_err(|e| anyhow::anyhow!("Failed to create parser: {}", e))?;
-
-    // Read file
-    let content = fs::read_to_string(&path)?;
-    let filename = path.to_string_lossy();
-
-    // Run appropriate analysis based on mode
-    if cli.overlap {
-        // Overlap detection mode
-        check_overlaps(
-            path,
-            parser,
-            cli.threshold,
-            cli.overlap_min_window,
-            cli.overlap_max_window,
-            cli.overlap_size_tolerance,
-        )?;
-    } else {
-        // Normal similarity detection mode
-        // Extract functions
-        let functions = parser
-            .extract_functions(&content, &filename)
-            .map_err(|e| anyhow::anyhow!("Failed to extract functions: {}", e))?;
-
-        if cli.show_functions {
-            println!("Found {} functions:", functions.len());
-            for func in &functions {
-                println!("  {} {}:{}-{}", func.name, filename, func.start_line, func.end_line);
+    let separator = "-".repeat(60);
+    for (index, (language, groups)) in languages.iter().enumerate() {
+        if pair_format.is_text() && languages.len() > 1 {
+            if index > 0 {
+                println!("\n{separator}\n");
             }
-            println!();
+            println!("=== {} ===", language.name());
         }
 
-        // Compare functions
-        if functions.len() >= 2 {
-            println!("Comparing functions for similarity...");
-
-            let tsed_options = TSEDOptions {
-                apted_options: APTEDOptions {
-                    rename_cost: 0.3,
-                    delete_cost: 1.0,
-                    insert_cost: 1.0,
-                    compare_values: false,
-                },
-                min_lines: 1,
-                min_tokens: None,
-                size_penalty: false,
-                skip_test: false,
-            };
-
-            for i in 0..functions.len() {
-                for j in (i + 1)..functions.len() {
-                    let func1 = &functions[i];
-                    let func2 = &functions[j];
-
-                    // Extract function bodies
-                    let lines: Vec<&str> = content.lines().collect();
-                    let body1 =
-                        extract_function_body(&lines, func1.body_start_line, func1.body_end_line);
-                    let body2 =
-                        extract_function_body(&lines, func2.body_start_line, func2.body_end_line);
-
-                    // Parse and compare
-                    let tree1 =
-                        parser.parse(&body1, &format!("{}:{}", filename, func1.name)).map_err(
-                            |e| anyhow::anyhow!("Failed to parse function {}: {}", func1.name, e),
-                        )?;
-                    let tree2 =
-                        parser.parse(&body2, &format!("{}:{}", filename, func2.name)).map_err(
-                            |e| anyhow::anyhow!("Failed to parse function {}: {}", func2.name, e),
-                        )?;
-
-                    let similarity = calculate_tsed(&tree1, &tree2, &tsed_options);
-
-                    if similarity >= cli.threshold {
-                        println!("  {} <-> {}: {:.2}%", func1.name, func2.name, similarity * 100.0);
-                    }
+        for (files, options) in groups {
+            // Run appropriate analysis based on mode
+            if cli.overlap {
+                // Overlap detection mode
+                let files = files.iter().map(|file| file.to_string_lossy().to_string()).collect();
+                let entries =
+                    check_overlaps(language, files, options, pair_format, baseline.as_ref())?;
+                report.extend(entries);
+                continue;
+            }
+
+            if !cli.no_functions {
+                if cli.show_functions && format.is_text() {
+                    show_functions(language, files);
+                }
+
+                if pair_format.is_text() {
+                    println!("Comparing functions for similarity...");
                 }
+                let files = files.iter().map(|file| file.to_string_lossy().to_string()).collect();
+                let entries =
+                    check_paths(language, files, options, pair_format, baseline.as_ref())?;
+                report.extend(entries);
+            }
+
+            if cli.types {
+                let files = files.iter().map(|file| file.to_string_lossy().to_string()).collect();
+                let entries =
+                    check_types(language, files, options, pair_format, baseline.as_ref())?;
+                report.extend(entries);
             }
         }
     }
 
-    Ok(())
-}
-
-fn extract_function_body(lines: &[&str], start_line: u32, end_line: u32) -> String {
-    let start_idx = (start_line.saturating_sub(1)) as usize;
-    let end_idx = std::cmp::min(end_line as usize, lines.len());
-
-    if start_idx >= lines.len() {
-        return String::new();
+    if cli.clone_classes {
+        report.group_clone_classes(cli.linkage);
     }
+    report.print(format)?;
+    finish_baseline(
+        baseline.as_ref(),
+        cli.write_baseline.as_deref(),
+        &report.duplicates,
+        format.is_text(),
+    )?;
+    fail_thresholds.enforce(&report.duplicates);
 
-    lines[start_idx..end_idx].join("\n")
+    Ok(())
 }
 
-fn check_overlaps(
-    path: PathBuf,
-    mut parser: GenericTreeSitterParser,
-    threshold: f64,
-    min_window_size: u32,
-    max_window_size: u32,
-    size_tolerance: f64,
-) -> anyhow::Result<()> {
-    use similarity_core::{find_overlaps_across_files_generic, OverlapOptions};
-    use std::collections::HashMap;
-
-    println!("Checking for overlapping code...\n");
+/// Files of one language grouped by their options
+type FileGroups<'a> = Vec<(Vec<PathBuf>, &'a PipelineOptions)>;
 
-    // Read file content
-    let content = fs::read_to_string(&path)?;
-    let filename = path.to_string_lossy().to_string();
-
-    // Create file contents map
-    let mut file_contents = HashMap::new();
-    file_contents.insert(filename.clone(), content.clone());
+/// The language given with `--config` or `--language`
+fn selected_language(cli: &Cli) -> Result<Option<GenericLanguage>> {
+    let config = if let Some(config_path) = &cli.config {
+        GenericParserConfig::from_file(config_path)
+            .map_err(|e| anyhow::anyhow!("Failed to load config: {}", e))?
+    } else if let Some(lang) = &cli.language {
+        builtin_config(lang)?
+    } else {
+        return Ok(None);
+    };
+    GenericLanguage::new(config).map(Some)
+}
 
-    // Set up overlap options
-    let options = OverlapOptions { min_window_size, max_window_size, threshold, size_tolerance };
+/// The languages to analyze with their files: the `selected` one, or every bundled
+/// language whose extension occurs among the files
+fn language_files(
+    selected: Option<GenericLanguage>,
+    runs: &[(Vec<String>, PipelineOptions)],
+) -> Result<Vec<(GenericLanguage, FileGroups<'_>)>> {
+    if let Some(language) = selected {
+        let groups = runs
+            .iter()
+            .map(|(files, options)| (files.iter().map(PathBuf::from).collect(), options))
+            .collect();
+        return Ok(vec![(language, groups)]);
+    }
 
-    // Find overlaps
-    let overlaps = find_overlaps_across_files_generic(&mut parser, &file_contents, &options)
-        .map_err(|e| anyhow::anyhow!("Failed to find overlaps: {}", e))?;
+    // Hand every file to the language of its extension
+    let mut languages = Vec::new();
+    for language in LANGUAGES {
+        let groups: FileGroups = runs
+            .iter()
+            .map(|(files, options)| {
+                let files: Vec<PathBuf> = files
+                    .iter()
+                    .filter(|file| Language::from_filename(file) == Some(language))
+                    .map(PathBuf::from)
+                    .collect();
+                (files, options)
+            })
+            .filter(|(files, _)| !files.is_empty())
+            .collect();
+        if !groups.is_empty() {
+            let config = builtin_config(language.config_name())?;
+            languages.push((GenericLanguage::new(config)?, groups));
+        }
+    }
+    Ok(languages)
+}
 
-    if overlaps.is_empty() {
-        println!("\nNo code overlaps found!");
-    } else {
-        println!("\nCode overlaps found:");
-        println!("{}", "-".repeat(60));
-
-        for overlap_with_files in &overlaps {
-            let overlap = &overlap_with_files.overlap;
-
-            println!(
-                "\nSimilarity: {:.2}% | {} nodes | {}",
-                overlap.similarity * 100.0,
-                overlap.node_count,
-                overlap.node_type
-            );
-            println!(
-                "  L{}-{} in function: {}",
-                overlap.source_lines.0, overlap.source_lines.1, overlap.source_function
-            );
-            println!(
-                "  L{}-{} in function: {}",
-                overlap.target_lines.0, overlap.target_lines.1, overlap.target_function
-            );
-
-            // Extract and display the overlapping code
-            println!("\n\x1b[36m--- Source Code ---\x1b[0m");
-            if let Ok(source_segment) =
-                extract_code_lines(&content, overlap.source_lines.0, overlap.source_lines.1)
-            {
-                println!("{source_segment}");
-            }
+/// The embedded config of a bundled language, e.g. `go`, `cpp` or its alias `c++`
+fn builtin_config(lang: &str) -> Result<GenericParserConfig> {
+    // First try to load from embedded configs
+    if let Some(config_json) = LANGUAGE_CONFIGS.get(lang).or_else(|| match lang {
+        "cpp" => LANGUAGE_CONFIGS.get("cpp"),
+        "c++" => LANGUAGE_CONFIGS.get("cpp"),
+        "csharp" => LANGUAGE_CONFIGS.get("csharp"),
+        "cs" => LANGUAGE_CONFIGS.get("csharp"),
+        "ruby" => LANGUAGE_CONFIGS.get("ruby"),
+        "rb" => LANGUAGE_CONFIGS.get("ruby"),
+        _ => None,
+    }) {
+        return serde_json::from_str(config_json)
+            .map_err(|e| anyhow::anyhow!("Failed to parse embedded config: {}", e));
+    }
 
-            println!("\n\x1b[36m--- Target Code ---\x1b[0m");
-            if let Ok(target_segment) =
-                extract_code_lines(&content, overlap.target_lines.0, overlap.target_lines.1)
-            {
-                println!("{target_segment}");
+    // Fall back to hardcoded configs
+    match lang {
+        "go" => Ok(GenericParserConfig::go()),
+        "java" => Ok(GenericParserConfig::java()),
+        "c" => Ok(GenericParserConfig::c()),
+        "cpp" | "c++" => Ok(GenericParserConfig::cpp()),
+        "csharp" | "cs" => Ok(GenericParserConfig::csharp()),
+        "ruby" | "rb" => Ok(GenericParserConfig::ruby()),
+        _ => {
+            eprintln!("Error: Language '{lang}' is not supported by similarity-generic.");
+            eprintln!("Use --supported to see available languages.");
+            if matches!(
+                lang,
+                "python" | "py" | "rust" | "rs" | "javascript" | "js" | "typescript" | "ts"
+            ) {
+                eprintln!();
+                eprintln!("Note: For {lang}, use the dedicated implementation:");
+                match lang {
+                    "python" | "py" => eprintln!("  similarity-py"),
+                    "rust" | "rs" => eprintln!("  similarity-rs (planned)"),
+                    "javascript" | "js" | "typescript" | "ts" => {
+                        eprintln!("  similarity-ts")
+                    }
+                    _ => {}
+                }
             }
+            Err(anyhow::anyhow!("Unsupported language"))
         }
-
-        println!("\nTotal overlaps found: {}", overlaps.len());
     }
-
-    Ok(())
 }
 
-fn extract_code_lines(code: &str, start_line: u32, end_line: u32) -> Result<String, String> {
-    let lines: Vec<_> = code.lines().collect();
-
-    if start_line as usize > lines.len() || end_line as usize > lines.len() {
-        return Err("Line numbers out of bounds".to_string());
+/// List the functions extracted from `files`
+fn show_functions(language: &GenericLanguage, files: &[PathBuf]) {
+    let file_data = load_files_parallel(files, language);
+    let count: usize = file_data.iter().map(|data| data.functions.len()).sum();
+    println!("Found {count} functions:");
+    for data in &file_data {
+        let filename = data.path.to_string_lossy();
+        for func in &data.functions {
+            println!("  {} {}:{}-{}", func.name, filename, func.start_line, func.end_line);
+        }
     }
-
-    let start = (start_line as usize).saturating_sub(1);
-    let end = (end_line as usize).min(lines.len());
-
-    Ok(lines[start..end].join("\n"))
+    println!();
 }
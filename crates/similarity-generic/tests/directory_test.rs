@@ -0,0 +1,187 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn create_test_file(dir: &Path, filename: &str, content: &str) {
+    let file_path = dir.join(filename);
+    fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+    fs::write(file_path, content).unwrap();
+}
+
+const GO_ORDERS: &str = r#"package orders
+
+func TotalPrice(items []Item) int {
+	total := 0
+	for _, item := range items {
+		total += item.Price * item.Quantity
+	}
+	return total
+}
+"#;
+
+const GO_INVOICES: &str = r#"package invoices
+
+func InvoiceAmount(lines []Line) int {
+	amount := 0
+	for _, line := range lines {
+		amount += line.Price * line.Quantity
+	}
+	return amount
+}
+"#;
+
+const JAVA_SERVICE: &str = r#"public class Service {
+    public int countActive(List<User> users) {
+        int count = 0;
+        for (User user : users) {
+            if (user.isActive()) {
+                count++;
+            }
+        }
+        return count;
+    }
+
+    public int countAdmins(List<User> members) {
+        int admins = 0;
+        for (User member : members) {
+            if (member.isAdmin()) {
+                admins++;
+            }
+        }
+        return admins;
+    }
+}
+"#;
+
+#[test]
+fn test_directory_cross_file_duplicates() {
+    let dir = TempDir::new().unwrap();
+    create_test_file(dir.path(), "orders/orders.go", GO_ORDERS);
+    create_test_file(dir.path(), "invoices/invoices.go", GO_INVOICES);
+
+    Command::cargo_bin("similarity-generic")
+        .unwrap()
+        .arg(dir.path())
+        .arg("--language")
+        .arg("go")
+        .arg("--threshold")
+        .arg("0.8")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Checking 2 files for duplicates..."))
+        .stdout(predicate::str::is_match(r"invoices\.go:3-9 function InvoiceAmount").unwrap())
+        .stdout(predicate::str::is_match(r"orders\.go:3-9 function TotalPrice").unwrap())
+        .stdout(predicate::str::contains("Total duplicate pairs found: 1"));
+}
+
+#[test]
+fn test_explicit_files_ignore_extension() {
+    let dir = TempDir::new().unwrap();
+    create_test_file(dir.path(), "orders.gotmpl", GO_ORDERS);
+    create_test_file(dir.path(), "invoices.gotmpl", GO_INVOICES);
+
+    Command::cargo_bin("similarity-generic")
+        .unwrap()
+        .arg(dir.path().join("orders.gotmpl"))
+        .arg(dir.path().join("invoices.gotmpl"))
+        .arg("--language")
+        .arg("go")
+        .arg("--threshold")
+        .arg("0.8")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Checking 2 files for duplicates..."))
+        .stdout(predicate::str::contains("Total duplicate pairs found: 1"));
+}
+
+#[test]
+fn test_language_detected_per_extension() {
+    let dir = TempDir::new().unwrap();
+    create_test_file(dir.path(), "orders.go", GO_ORDERS);
+    create_test_file(dir.path(), "invoices.go", GO_INVOICES);
+    create_test_file(dir.path(), "Service.java", JAVA_SERVICE);
+    create_test_file(dir.path(), "notes.txt", "not code");
+
+    Command::cargo_bin("similarity-generic")
+        .unwrap()
+        .arg(dir.path())
+        .arg("--threshold")
+        .arg("0.8")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("=== go ==="))
+        .stdout(predicate::str::contains("=== java ==="))
+        .stdout(predicate::str::contains("function TotalPrice"))
+        .stdout(predicate::str::contains("method countActive"));
+}
+
+#[test]
+fn test_exclude_and_min_lines() {
+    let dir = TempDir::new().unwrap();
+    create_test_file(dir.path(), "orders/orders.go", GO_ORDERS);
+    create_test_file(dir.path(), "vendor/invoices.go", GO_INVOICES);
+
+    Command::cargo_bin("similarity-generic")
+        .unwrap()
+        .arg(dir.path())
+        .arg("--threshold")
+        .arg("0.8")
+        .arg("--exclude")
+        .arg("**/vendor/**")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Checking 1 files for duplicates..."))
+        .stdout(predicate::str::contains("No duplicate functions found!"));
+
+    Command::cargo_bin("similarity-generic")
+        .unwrap()
+        .arg(dir.path())
+        .arg("--threshold")
+        .arg("0.8")
+        .arg("--min-lines")
+        .arg("10")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No duplicate functions found!"));
+}
+
+#[test]
+fn test_types_across_files() {
+    let dir = TempDir::new().unwrap();
+    create_test_file(
+        dir.path(),
+        "api/user.go",
+        "package api\n\ntype UserDTO struct {\n\tID    int64\n\tName  string\n\tEmail string\n}\n",
+    );
+    create_test_file(
+        dir.path(),
+        "store/user.go",
+        "package store\n\ntype UserRecord struct {\n\tID    int64\n\tName  string\n\tEmail string\n}\n",
+    );
+
+    Command::cargo_bin("similarity-generic")
+        .unwrap()
+        .arg(dir.path())
+        .arg("--experimental-types")
+        .arg("--no-functions")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("similar-type: UserDTO (struct)"))
+        .stdout(predicate::str::contains("similar-type: UserRecord (struct)"))
+        .stdout(predicate::str::contains("Total similar type pairs found: 1"));
+}
+
+#[test]
+fn test_no_supported_files() {
+    let dir = TempDir::new().unwrap();
+    create_test_file(dir.path(), "notes.txt", "not code");
+
+    Command::cargo_bin("similarity-generic")
+        .unwrap()
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No supported files found in the specified paths."));
+}
@@ -0,0 +1,188 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+#[cfg(unix)]
+use std::process;
+#[cfg(unix)]
+use std::sync::OnceLock;
+use tempfile::TempDir;
+
+fn kotlin_config(grammar: &str) -> String {
+    format!(
+        r#"{{
+  "language": "kotlin",
+  "extensions": ["kt"],
+  {grammar}
+  "function_nodes": ["function_declaration"],
+  "type_nodes": ["class_declaration"],
+  "field_mappings": {{
+    "name_field": "name",
+    "params_field": "parameters",
+    "body_field": "body"
+  }},
+  "value_nodes": ["simple_identifier"],
+  "test_patterns": null,
+  "custom_mappings": null
+}}"#
+    )
+}
+
+#[test]
+fn test_missing_grammar_library() {
+    let dir = TempDir::new().unwrap();
+    let config = dir.path().join("kotlin.json");
+    fs::write(&config, kotlin_config(r#""grammar": { "path": "grammars/kotlin.so" },"#)).unwrap();
+    fs::write(dir.path().join("Main.kt"), "fun main() {}\n").unwrap();
+
+    Command::cargo_bin("similarity-generic")
+        .unwrap()
+        .arg(dir.path())
+        .arg("--config")
+        .arg(&config)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Failed to load grammar"))
+        .stderr(predicate::str::contains("kotlin.so"));
+}
+
+#[test]
+fn test_unknown_language_without_grammar() {
+    let dir = TempDir::new().unwrap();
+    let config = dir.path().join("kotlin.json");
+    fs::write(&config, kotlin_config("")).unwrap();
+
+    Command::cargo_bin("similarity-generic")
+        .unwrap()
+        .arg(dir.path())
+        .arg("--config")
+        .arg(&config)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unsupported language: kotlin"))
+        .stderr(predicate::str::contains("set `grammar` in the config"));
+}
+
+/// The bundled Go grammar compiled from its `parser.c` into a shared library, as a user
+/// would build a grammar that is not bundled
+#[cfg(unix)]
+fn go_grammar_library() -> &'static Path {
+    static LIBRARY: OnceLock<PathBuf> = OnceLock::new();
+    LIBRARY.get_or_init(|| {
+        let metadata = process::Command::new(env!("CARGO"))
+            .args(["metadata", "--format-version", "1", "--offline"])
+            .output()
+            .expect("cargo metadata");
+        assert!(metadata.status.success(), "{}", String::from_utf8_lossy(&metadata.stderr));
+        let metadata: serde_json::Value = serde_json::from_slice(&metadata.stdout).unwrap();
+        let manifest = metadata["packages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|package| package["name"] == "tree-sitter-go")
+            .and_then(|package| package["manifest_path"].as_str())
+            .expect("tree-sitter-go package");
+        let source = Path::new(manifest).parent().unwrap().join("src");
+
+        let library = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!(
+            "{}go_grammar{}",
+            std::env::consts::DLL_PREFIX,
+            std::env::consts::DLL_SUFFIX
+        ));
+        let status = process::Command::new("cc")
+            .args(["-shared", "-fPIC", "-O0", "-std=c11", "-I"])
+            .arg(&source)
+            .arg(source.join("parser.c"))
+            .arg("-o")
+            .arg(&library)
+            .status()
+            .expect("C compiler");
+        assert!(status.success());
+        library
+    })
+}
+
+/// Go functions under a language name without a bundled grammar, so they can only be
+/// parsed with the library
+#[cfg(unix)]
+fn golib_config(extensions: &str) -> String {
+    format!(
+        r#"{{
+  "language": "golib",
+  "extensions": {extensions},
+  "grammar": {{ "path": {library:?}, "symbol": "tree_sitter_go" }},
+  "function_nodes": ["function_declaration", "method_declaration"],
+  "type_nodes": ["type_declaration"],
+  "field_mappings": {{
+    "name_field": "name",
+    "params_field": "parameters",
+    "body_field": "body"
+  }},
+  "value_nodes": ["identifier", "int_literal"],
+  "test_patterns": null,
+  "custom_mappings": null
+}}"#,
+        library = go_grammar_library()
+    )
+}
+
+#[cfg(unix)]
+#[test]
+fn test_grammar_library() {
+    let dir = TempDir::new().unwrap();
+    let config = dir.path().join("golib.json");
+    fs::write(&config, golib_config(r#"["gox"]"#)).unwrap();
+    fs::write(
+        dir.path().join("orders.gox"),
+        r#"package orders
+
+func TotalPrice(items []Item) int {
+	total := 0
+	for _, item := range items {
+		total += item.Price * item.Quantity
+	}
+	return total
+}
+
+func InvoiceAmount(lines []Line) int {
+	amount := 0
+	for _, line := range lines {
+		amount += line.Price * line.Quantity
+	}
+	return amount
+}
+"#,
+    )
+    .unwrap();
+
+    Command::cargo_bin("similarity-generic")
+        .unwrap()
+        .arg(dir.path())
+        .arg("--config")
+        .arg(&config)
+        .arg("--threshold")
+        .arg("0.8")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("function TotalPrice"))
+        .stdout(predicate::str::contains("function InvoiceAmount"))
+        .stdout(predicate::str::contains("Total duplicate pairs found: 1"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_grammar_library_without_extensions() {
+    let dir = TempDir::new().unwrap();
+    let config = dir.path().join("golib.json");
+    fs::write(&config, golib_config("[]")).unwrap();
+
+    Command::cargo_bin("similarity-generic")
+        .unwrap()
+        .arg(dir.path())
+        .arg("--config")
+        .arg(&config)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No file extensions for language golib"));
+}
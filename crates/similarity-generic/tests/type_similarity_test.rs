@@ -0,0 +1,127 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+fn create_test_file(dir: &TempDir, filename: &str, content: &str) -> std::path::PathBuf {
+    let file_path = dir.path().join(filename);
+    fs::write(&file_path, content).unwrap();
+    file_path
+}
+
+#[test]
+fn test_go_struct_similarity() {
+    let dir = TempDir::new().unwrap();
+    let file = create_test_file(
+        &dir,
+        "models.go",
+        r#"
+package models
+
+type UserDTO struct {
+	ID    int64  `json:"id"`
+	Name  string `json:"name"`
+	Email string `json:"email"`
+}
+
+type UserRecord struct {
+	ID    int64
+	Name  string
+	Email string
+}
+
+type Writer interface {
+	Write(p []byte) (n int, err error)
+}
+"#,
+    );
+
+    Command::cargo_bin("similarity-generic")
+        .unwrap()
+        .arg(&file)
+        .arg("--language")
+        .arg("go")
+        .arg("--experimental-types")
+        .arg("--no-functions")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("similar-type: UserDTO (struct)"))
+        .stdout(predicate::str::contains("similar-type: UserRecord (struct)"))
+        .stdout(predicate::str::contains("Writer").not())
+        .stdout(predicate::str::contains("Total similar type pairs found: 1"));
+}
+
+#[test]
+fn test_java_class_similarity() {
+    let dir = TempDir::new().unwrap();
+    let file = create_test_file(
+        &dir,
+        "Models.java",
+        r#"
+class OrderRequest {
+    private long customerId;
+    private String note, currency;
+
+    long getCustomerId() { long unused = 0; return customerId; }
+}
+
+class OrderCommand {
+    private long customerId;
+    private String note;
+    private String currency;
+}
+
+class Money {
+    private int amount;
+}
+"#,
+    );
+
+    Command::cargo_bin("similarity-generic")
+        .unwrap()
+        .arg(&file)
+        .arg("--language")
+        .arg("java")
+        .arg("--experimental-types")
+        .arg("--no-functions")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("similar-type: OrderRequest (class)"))
+        .stdout(predicate::str::contains("similar-type: OrderCommand (class)"))
+        .stdout(predicate::str::contains("unused").not())
+        .stdout(predicate::str::contains("Total similar type pairs found: 1"));
+}
+
+#[test]
+fn test_csharp_properties_and_fields_compare_equal() {
+    let dir = TempDir::new().unwrap();
+    let file = create_test_file(
+        &dir,
+        "Models.cs",
+        r#"
+public class CustomerDto {
+    public int Id { get; set; }
+    public string Name { get; set; }
+    public string Email { get; set; }
+}
+
+public class CustomerModel {
+    public int Id;
+    public string Name;
+    public string Email;
+}
+"#,
+    );
+
+    Command::cargo_bin("similarity-generic")
+        .unwrap()
+        .arg(&file)
+        .arg("--language")
+        .arg("csharp")
+        .arg("--experimental-types")
+        .arg("--no-functions")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("similar-type: CustomerDto (class)"))
+        .stdout(predicate::str::contains("similar-type: CustomerModel (class)"));
+}
@@ -0,0 +1,36 @@
+use crate::python_parser::PythonParser;
+use similarity_core::cli_pipeline::LanguageSupport;
+use similarity_core::language_parser::LanguageParser;
+use similarity_core::TypeDefinition;
+use std::error::Error;
+
+/// Python support for the shared analysis pipeline
+pub struct PythonLanguage;
+
+impl LanguageSupport for PythonLanguage {
+    fn name(&self) -> &str {
+        "Python"
+    }
+
+    fn default_extensions(&self) -> Vec<&str> {
+        vec!["py"]
+    }
+
+    fn create_parser(&self) -> Result<Box<dyn LanguageParser>, Box<dyn Error + Send + Sync>> {
+        Ok(Box::new(PythonParser::new()?))
+    }
+
+    fn supports_types(&self) -> bool {
+        true
+    }
+
+    fn extract_types(
+        &self,
+        filename: &str,
+        content: &str,
+    ) -> Result<Vec<TypeDefinition>, Box<dyn Error + Send + Sync>> {
+        // Base classes are not scored: a dataclass and a pydantic model with the same fields
+        // are still duplicates
+        PythonParser::new()?.extract_type_definitions(content, filename)
+    }
+}
@@ -1,9 +1,11 @@
 #![allow(clippy::io_other_error)]
 
 use similarity_core::language_parser::{
-    GenericFunctionDef, GenericTypeDef, Language, LanguageParser,
+    GenericFieldDef, GenericFunctionDef, GenericTypeDef, Language, LanguageParser, TypeDefKind,
 };
-use similarity_core::tree::TreeNode;
+use similarity_core::suppression::retain_unsuppressed;
+use similarity_core::tree::{SourceSpan, TreeNode};
+use similarity_core::{PropertyDefinition, TypeDefinition, TypeKind};
 use std::error::Error;
 use std::rc::Rc;
 use tree_sitter::{Node, Parser};
@@ -25,6 +27,29 @@ impl PythonParser {
         Ok(Self { parser })
     }
 
+    /// Extract classes with their annotated attributes for type comparison.
+    ///
+    /// Class-level annotations cover `@dataclass`, `TypedDict`, pydantic `BaseModel` and
+    /// `NamedTuple` fields; plain classes also contribute the `self.<name>` attributes set
+    /// in `__init__`, typed by their annotation or the parameter they are assigned from.
+    pub fn extract_type_definitions(
+        &mut self,
+        source: &str,
+        file_path: &str,
+    ) -> Result<Vec<TypeDefinition>, Box<dyn Error + Send + Sync>> {
+        let tree = self.parser.parse(source, None).ok_or_else(|| {
+            Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Failed to parse Python source",
+            )) as Box<dyn Error + Send + Sync>
+        })?;
+
+        let mut types = Vec::new();
+        collect_class_definitions(tree.root_node(), source, file_path, &mut types);
+        types.retain(|type_def| !type_def.properties.is_empty());
+        Ok(types)
+    }
+
     #[allow(clippy::only_used_in_recursion)]
     fn convert_node(&self, node: Node, source: &str, id_counter: &mut usize) -> TreeNode {
         let current_id = *id_counter;
@@ -38,7 +63,8 @@ impl PythonParser {
             _ => "".to_string(),
         };
 
-        let mut tree_node = TreeNode::new(label, value, current_id);
+        let mut tree_node =
+            TreeNode::new(label, value, current_id).with_span(SourceSpan::from(node.range()));
 
         for child in node.children(&mut node.walk()) {
             let child_node = self.convert_node(child, source, id_counter);
@@ -253,13 +279,15 @@ impl LanguageParser for PythonParser {
         })?;
 
         let root_node = tree.root_node();
-        Ok(self.extract_functions_from_node(root_node, source, None))
+        let mut functions = self.extract_functions_from_node(root_node, source, None);
+        retain_unsuppressed(source, &mut functions, |f| f.start_line);
+        Ok(functions)
     }
 
     fn extract_types(
         &mut self,
         source: &str,
-        _filename: &str,
+        filename: &str,
     ) -> Result<Vec<GenericTypeDef>, Box<dyn Error + Send + Sync>> {
         let tree = self.parser.parse(source, None).ok_or_else(|| {
             Box::new(std::io::Error::new(
@@ -268,85 +296,266 @@ impl LanguageParser for PythonParser {
             )) as Box<dyn Error + Send + Sync>
         })?;
 
-        let root_node = tree.root_node();
-        let mut types = Vec::new();
+        let mut classes = Vec::new();
+        collect_class_definitions(tree.root_node(), source, filename, &mut classes);
+        Ok(classes
+            .into_iter()
+            .map(|class| GenericTypeDef {
+                name: class.name,
+                kind: TypeDefKind::Class,
+                start_line: class.start_line as u32,
+                end_line: class.end_line as u32,
+                fields: class
+                    .properties
+                    .into_iter()
+                    .map(|p| GenericFieldDef::new(p.name, p.type_annotation))
+                    .collect(),
+            })
+            .collect())
+    }
+
+    fn language(&self) -> Language {
+        Language::Python
+    }
+}
 
-        fn visit_node_for_types(node: Node, source: &str, types: &mut Vec<GenericTypeDef>) {
-            if node.kind() == "class_definition" {
-                if let Some(name_node) = node.child_by_field_name("name") {
-                    if let Ok(name) = name_node.utf8_text(source.as_bytes()) {
-                        types.push(GenericTypeDef {
-                            name: name.to_string(),
-                            kind: "class".to_string(),
-                            start_line: node.start_position().row as u32 + 1,
-                            end_line: node.end_position().row as u32 + 1,
-                            fields: extract_class_fields(node, source),
-                        });
+/// Base classes whose annotated attributes declare fields rather than class variables
+const MODEL_BASES: &[&str] = &["BaseModel", "TypedDict", "NamedTuple"];
+
+fn collect_class_definitions(
+    node: Node,
+    source: &str,
+    file_path: &str,
+    types: &mut Vec<TypeDefinition>,
+) {
+    if node.kind() == "class_definition" {
+        if let Some(type_def) = class_definition(node, source, file_path) {
+            types.push(type_def);
+        }
+    }
+
+    for child in node.children(&mut node.walk()) {
+        collect_class_definitions(child, source, file_path, types);
+    }
+}
+
+fn class_definition(node: Node, source: &str, file_path: &str) -> Option<TypeDefinition> {
+    let text = |node: Node| source[node.byte_range()].to_string();
+    let name = text(node.child_by_field_name("name")?);
+
+    let mut bases = Vec::new();
+    let mut generics = Vec::new();
+    let mut total = true;
+    if let Some(superclasses) = node.child_by_field_name("superclasses") {
+        for base in superclasses.named_children(&mut superclasses.walk()) {
+            match base.kind() {
+                "keyword_argument" => {
+                    let keyword = base.child_by_field_name("name").map(text);
+                    let value = base.child_by_field_name("value").map(text);
+                    if keyword.as_deref() == Some("total") && value.as_deref() == Some("False") {
+                        total = false;
                     }
                 }
-            }
-
-            // Continue traversing
-            let mut cursor = node.walk();
-            for child in node.children(&mut cursor) {
-                visit_node_for_types(child, source, types);
+                "subscript" => {
+                    let Some(value) = base.child_by_field_name("value") else {
+                        continue;
+                    };
+                    let base_name = last_segment(&text(value)).to_string();
+                    if base_name == "Generic" || base_name == "Protocol" {
+                        generics.extend(
+                            base.children_by_field_name("subscript", &mut base.walk()).map(text),
+                        );
+                    } else {
+                        bases.push(base_name);
+                    }
+                }
+                _ => bases.push(last_segment(&text(base)).to_string()),
             }
         }
+    }
 
-        fn extract_class_fields(node: Node, source: &str) -> Vec<String> {
-            let mut fields = Vec::new();
-
-            if let Some(body) = node.child_by_field_name("body") {
-                let mut cursor = body.walk();
-                for child in body.children(&mut cursor) {
-                    // Look for instance variable assignments in __init__ method
-                    if child.kind() == "function_definition" {
-                        if let Some(name_node) = child.child_by_field_name("name") {
-                            if let Ok(name) = name_node.utf8_text(source.as_bytes()) {
-                                if name == "__init__" {
-                                    // Extract self.field assignments from __init__
-                                    if let Some(func_body) = child.child_by_field_name("body") {
-                                        extract_self_assignments(func_body, source, &mut fields);
-                                    }
-                                }
-                            }
-                        }
+    let is_dataclass = node.parent().is_some_and(|parent| {
+        parent.kind() == "decorated_definition"
+            && parent.children(&mut parent.walk()).filter(|child| child.kind() == "decorator").any(
+                |decorator| {
+                    let target = text(decorator);
+                    let target = target.trim_start_matches('@');
+                    last_segment(target.split('(').next().unwrap_or(target)) == "dataclass"
+                },
+            )
+    });
+    let is_model = is_dataclass || bases.iter().any(|base| MODEL_BASES.contains(&base.as_str()));
+
+    let mut properties = Vec::new();
+    if let Some(body) = node.child_by_field_name("body") {
+        for statement in body.named_children(&mut body.walk()) {
+            match statement.kind() {
+                "expression_statement" => {
+                    let Some(assignment) = statement.named_child(0) else {
+                        continue;
+                    };
+                    if let Some((name, annotation)) = annotated_attribute(assignment, source) {
+                        push_attribute(&mut properties, name, annotation, !total);
                     }
                 }
+                "function_definition" if !is_model => {
+                    if statement.child_by_field_name("name").map(text).as_deref()
+                        == Some("__init__")
+                    {
+                        init_attributes(statement, source, &mut properties);
+                    }
+                }
+                _ => {}
             }
+        }
+    }
+
+    Some(TypeDefinition {
+        name,
+        kind: TypeKind::Class,
+        properties,
+        generics,
+        extends: bases,
+        start_line: node.start_position().row + 1,
+        end_line: node.end_position().row + 1,
+        file_path: file_path.to_string(),
+    })
+}
 
-            fields
+/// `name: annotation` of a class-level annotated assignment, skipping class variables
+fn annotated_attribute(assignment: Node, source: &str) -> Option<(String, String)> {
+    if assignment.kind() != "assignment" {
+        return None;
+    }
+    let left = assignment.child_by_field_name("left")?;
+    let annotation = assignment.child_by_field_name("type")?;
+    let annotation = normalize_annotation(&source[annotation.byte_range()]);
+    if left.kind() != "identifier"
+        || annotation.trim_start_matches("typing.").starts_with("ClassVar")
+    {
+        return None;
+    }
+    Some((source[left.byte_range()].to_string(), annotation))
+}
+
+/// Attributes assigned to `self` in `__init__`, typed by their own annotation or by the
+/// annotation of the parameter they are assigned from (`Any` otherwise)
+fn init_attributes(init: Node, source: &str, properties: &mut Vec<PropertyDefinition>) {
+    let mut parameter_types = Vec::new();
+    if let Some(parameters) = init.child_by_field_name("parameters") {
+        for parameter in parameters.named_children(&mut parameters.walk()) {
+            let name = match parameter.kind() {
+                "typed_parameter" => parameter.named_child(0),
+                "typed_default_parameter" => parameter.child_by_field_name("name"),
+                _ => None,
+            };
+            if let (Some(name), Some(annotation)) = (name, parameter.child_by_field_name("type")) {
+                parameter_types.push((
+                    source[name.byte_range()].to_string(),
+                    normalize_annotation(&source[annotation.byte_range()]),
+                ));
+            }
         }
+    }
 
-        fn extract_self_assignments(node: Node, source: &str, fields: &mut Vec<String>) {
-            let mut cursor = node.walk();
-            for child in node.children(&mut cursor) {
-                if child.kind() == "assignment" {
-                    if let Some(left) = child.child(0) {
-                        if left.kind() == "attribute" {
-                            if let Ok(text) = left.utf8_text(source.as_bytes()) {
-                                if text.starts_with("self.") {
-                                    let field_name = text.trim_start_matches("self.");
-                                    if !fields.contains(&field_name.to_string()) {
-                                        fields.push(field_name.to_string());
-                                    }
-                                }
-                            }
-                        }
-                    }
+    fn visit(
+        node: Node,
+        source: &str,
+        parameter_types: &[(String, String)],
+        properties: &mut Vec<PropertyDefinition>,
+    ) {
+        for child in node.named_children(&mut node.walk()) {
+            if child.kind() == "assignment" {
+                let left = child.child_by_field_name("left").map(|left| &source[left.byte_range()]);
+                if let Some(attribute) = left.and_then(|left| left.strip_prefix("self.")) {
+                    let annotation = match child.child_by_field_name("type") {
+                        Some(annotation) => normalize_annotation(&source[annotation.byte_range()]),
+                        None => child
+                            .child_by_field_name("right")
+                            .and_then(|right| {
+                                let right = &source[right.byte_range()];
+                                parameter_types.iter().find(|(name, _)| name == right)
+                            })
+                            .map_or_else(
+                                || "Any".to_string(),
+                                |(_, annotation)| annotation.clone(),
+                            ),
+                    };
+                    push_attribute(properties, attribute.to_string(), annotation, false);
                 }
-                // Recursively check nested nodes
-                extract_self_assignments(child, source, fields);
+            }
+            // Nested functions and classes assign their own attributes
+            if !matches!(child.kind(), "function_definition" | "class_definition") {
+                visit(child, source, parameter_types, properties);
             }
         }
+    }
 
-        visit_node_for_types(root_node, source, &mut types);
-        Ok(types)
+    if let Some(body) = init.child_by_field_name("body") {
+        visit(body, source, &parameter_types, properties);
     }
+}
 
-    fn language(&self) -> Language {
-        Language::Python
+fn push_attribute(
+    properties: &mut Vec<PropertyDefinition>,
+    name: String,
+    annotation: String,
+    optional: bool,
+) {
+    if properties.iter().any(|property| property.name == name) {
+        return;
     }
+    let bare = annotation.trim_start_matches("typing.");
+    let optional = optional
+        || bare.starts_with("Optional[")
+        || bare.starts_with("NotRequired[")
+        || annotation.split('|').any(|member| member.trim() == "None");
+    properties.push(PropertyDefinition {
+        name,
+        type_annotation: annotation,
+        optional,
+        readonly: false,
+    });
+}
+
+/// Collapse whitespace and spell `Optional[X]` and `Union[X, Y]` as `X | None` and `X | Y`
+fn normalize_annotation(text: &str) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let bare = collapsed.trim_start_matches("typing.");
+    let Some((outer, inner)) = bare.split_once('[') else {
+        return collapsed;
+    };
+    let Some(inner) = inner.strip_suffix(']') else {
+        return collapsed;
+    };
+
+    // Split the subscript on the commas outside nested brackets
+    let mut members = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, c) in inner.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            ',' if depth == 0 => {
+                members.push(normalize_annotation(&inner[start..index]));
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    members.push(normalize_annotation(&inner[start..]));
+
+    match outer {
+        "Optional" if members.len() == 1 => format!("{} | None", members[0]),
+        "Union" => members.join(" | "),
+        _ => collapsed,
+    }
+}
+
+/// `BaseModel` for `pydantic.BaseModel`
+fn last_segment(path: &str) -> &str {
+    path.rsplit('.').next().unwrap_or(path)
 }
 
 #[cfg(test)]
@@ -401,7 +610,61 @@ class Admin(User):
         let types = parser.extract_types(source, "test.py").unwrap();
         assert_eq!(types.len(), 2);
         assert_eq!(types[0].name, "User");
-        assert_eq!(types[0].kind, "class");
+        assert_eq!(types[0].kind, TypeDefKind::Class);
+        assert_eq!(types[0].fields, vec![GenericFieldDef::new("name", "Any")]);
         assert_eq!(types[1].name, "Admin");
     }
+
+    #[test]
+    fn test_python_type_definitions() {
+        let mut parser = PythonParser::new().unwrap();
+        let source = r#"
+@dataclasses.dataclass(frozen=True)
+class Page(Generic[T]):
+    items: list[T]
+    cursor: Optional[str] = None
+    registry: ClassVar[dict] = {}
+
+    def __init__(self):
+        self.ignored = 1
+
+class Movie(TypedDict, total=False):
+    title: str
+
+class Account:
+    kind = "basic"
+
+    def __init__(self, owner: str, balance):
+        self.owner = owner
+        self.balance: typing.Union[int, float] = balance
+        self.history = []
+
+class Empty:
+    pass
+"#;
+
+        let types = parser.extract_type_definitions(source, "test.py").unwrap();
+        assert_eq!(types.len(), 3);
+
+        let page = &types[0];
+        assert_eq!(page.kind, TypeKind::Class);
+        assert_eq!(page.generics, vec!["T"]);
+        assert!(page.extends.is_empty());
+        let fields: Vec<_> =
+            page.properties.iter().map(|p| (p.name.as_str(), p.type_annotation.as_str())).collect();
+        assert_eq!(fields, vec![("items", "list[T]"), ("cursor", "str | None")]);
+        assert!(page.properties[1].optional);
+
+        let movie = &types[1];
+        assert_eq!(movie.extends, vec!["TypedDict"]);
+        assert!(movie.properties[0].optional);
+
+        let account = &types[2];
+        let fields: Vec<_> = account
+            .properties
+            .iter()
+            .map(|p| (p.name.as_str(), p.type_annotation.as_str()))
+            .collect();
+        assert_eq!(fields, vec![("owner", "str"), ("balance", "int | float"), ("history", "Any")]);
+    }
 }
@@ -163,3 +163,304 @@ def longer_func2():
         .stdout(predicate::str::contains("longer_func2"))
         .stdout(predicate::str::contains("f1").not());
 }
+
+#[test]
+fn test_python_json_output() {
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("test.py");
+
+    let content = r#"
+def process_items(items):
+    result = []
+    for item in items:
+        if item > 0:
+            result.append(item * 2)
+    return result
+
+def handle_items(data):
+    output = []
+    for d in data:
+        if d > 0:
+            output.append(d * 2)
+    return output
+"#;
+
+    fs::write(&file_path, content).unwrap();
+
+    Command::cargo_bin("similarity-py")
+        .unwrap()
+        .arg(&file_path)
+        .arg("--threshold")
+        .arg("0.8")
+        .arg("--format")
+        .arg("json")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("{"))
+        .stdout(predicate::str::contains("\"kind\": \"function\""))
+        .stdout(predicate::str::contains("\"name\": \"process_items\""))
+        .stdout(predicate::str::contains("\"name\": \"handle_items\""))
+        .stdout(predicate::str::contains("\"start_line\": 2"))
+        .stdout(predicate::str::contains("Duplicates in").not());
+}
+
+#[test]
+fn test_python_sarif_output() {
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("test.py");
+
+    let content = r#"
+def process_items(items):
+    result = []
+    for item in items:
+        if item > 0:
+            result.append(item * 2)
+    return result
+
+def handle_items(data):
+    output = []
+    for d in data:
+        if d > 0:
+            output.append(d * 2)
+    return output
+"#;
+
+    fs::write(&file_path, content).unwrap();
+
+    Command::cargo_bin("similarity-py")
+        .unwrap()
+        .arg(&file_path)
+        .arg("--threshold")
+        .arg("0.8")
+        .arg("--format")
+        .arg("sarif")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"name\": \"similarity-py\""))
+        .stdout(predicate::str::contains("\"ruleId\": \"duplicate-function\""))
+        .stdout(predicate::str::contains("\"startLine\": 2"));
+}
+
+#[test]
+fn test_python_sarif_relative_uri_for_absolute_path() {
+    let dir = tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    let source = r#"
+def process_items(items):
+    result = []
+    for item in items:
+        if item > 0:
+            result.append(item * 2)
+    return result
+
+def handle_items(data):
+    output = []
+    for d in data:
+        if d > 0:
+            output.append(d * 2)
+    return output
+"#;
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(root.join("src/test.py"), source).unwrap();
+
+    Command::cargo_bin("similarity-py")
+        .unwrap()
+        .current_dir(&root)
+        .arg(root.join("src/test.py"))
+        .arg("--threshold")
+        .arg("0.8")
+        .arg("--format")
+        .arg("sarif")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"uri\": \"src/test.py\""))
+        .stdout(predicate::str::contains("%SRCROOT%"))
+        .stdout(predicate::str::contains("file://").not());
+}
+
+#[test]
+fn test_similarity_ignore_file() {
+    let dir = tempdir().unwrap();
+    let source = r#"
+class TestCase:
+    def setUp(self):
+        x = 1
+        y = 2
+        z = x + y
+        return z
+
+    def setUpAgain(self):
+        x = 1
+        y = 2
+        z = x + y
+        return z
+
+def total(values):
+    s = 0
+    for v in values:
+        s += v
+    return s
+
+def total_again(values):
+    s = 0
+    for v in values:
+        s += v
+    return s
+"#;
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::create_dir_all(dir.path().join("gen")).unwrap();
+    fs::write(dir.path().join("src/tests.py"), source).unwrap();
+    fs::write(dir.path().join("gen/api_pb.py"), source).unwrap();
+    fs::write(dir.path().join(".similarity-ignore"), "*_pb.*\nsrc/**:setUp*\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("similarity-py").unwrap();
+    cmd.arg(dir.path())
+        .arg("--threshold")
+        .arg("0.7")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Checking 1 files"))
+        .stdout(predicate::str::contains("total_again"))
+        .stdout(predicate::str::contains("setUp").not());
+}
+
+#[test]
+fn test_ignore_file_name_rules_apply_to_types_and_overlaps() {
+    let dir = tempdir().unwrap();
+    let source = r#"
+from dataclasses import dataclass
+
+@dataclass
+class CreateOrderRequest:
+    customer_id: int
+    items: list[str]
+
+@dataclass
+class CreateOrderCommand:
+    customer_id: int
+    items: list[str]
+
+def summarize(orders):
+    total = 0
+    for order in orders:
+        if order.paid and order.amount > 0:
+            total += order.amount * order.quantity
+        else:
+            print("skipping", order.id)
+    return total
+
+def report(orders):
+    print("report")
+    total = 0
+    for order in orders:
+        if order.paid and order.amount > 0:
+            total += order.amount * order.quantity
+        else:
+            print("skipping", order.id)
+    print(total)
+    return total
+"#;
+    fs::write(dir.path().join("orders.py"), source).unwrap();
+
+    let run = || {
+        Command::cargo_bin("similarity-py")
+            .unwrap()
+            .arg(dir.path())
+            .args(["--no-functions", "--experimental-types", "--experimental-overlap"])
+            .assert()
+            .success()
+    };
+    run()
+        .stdout(predicate::str::contains("similar-type: CreateOrderCommand"))
+        .stdout(predicate::str::contains("in function: report"));
+
+    fs::write(
+        dir.path().join(".similarity-ignore"),
+        "*:CreateOrderCommand
+:report
+",
+    )
+    .unwrap();
+    run()
+        .stdout(predicate::str::contains("Checking 1 files for similar types"))
+        .stdout(predicate::str::contains("CreateOrderCommand").not())
+        .stdout(predicate::str::contains("in function: report").not());
+}
+
+#[test]
+fn test_python_model_similarity() {
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("models.py");
+
+    let content = r#"
+from dataclasses import dataclass
+from typing import Optional
+from pydantic import BaseModel
+
+class CreateOrderRequest(BaseModel):
+    customer_id: int
+    items: list[str]
+    note: Optional[str] = None
+
+@dataclass
+class CreateOrderCommand:
+    customer_id: int
+    items: list[str]
+    note: str | None = None
+
+class Logger:
+    def __init__(self, level: int):
+        self.level = level
+"#;
+
+    fs::write(&file_path, content).unwrap();
+
+    Command::cargo_bin("similarity-py")
+        .unwrap()
+        .arg(&file_path)
+        .arg("--experimental-types")
+        .arg("--no-functions")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("=== Type Similarity ==="))
+        .stdout(predicate::str::contains("similar-type: CreateOrderRequest (class)"))
+        .stdout(predicate::str::contains("similar-type: CreateOrderCommand (class)"))
+        .stdout(predicate::str::contains("Logger").not())
+        .stdout(predicate::str::contains("Total similar type pairs found: 1"));
+}
+
+#[test]
+fn test_overlap_respects_exclude() {
+    let dir = tempdir().unwrap();
+    let source = r#"
+def summarize(orders):
+    total = 0
+    for order in orders:
+        if order.paid:
+            total += order.amount * order.quantity
+    return total
+"#;
+    fs::create_dir_all(dir.path().join("vendor")).unwrap();
+    fs::write(dir.path().join("orders.py"), source).unwrap();
+    fs::write(dir.path().join("vendor/orders_copy.py"), source).unwrap();
+
+    Command::cargo_bin("similarity-py")
+        .unwrap()
+        .arg(dir.path())
+        .arg("--no-functions")
+        .arg("--experimental-overlap")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Checking 2 files for overlapping code"));
+
+    Command::cargo_bin("similarity-py")
+        .unwrap()
+        .arg(dir.path())
+        .arg("--no-functions")
+        .arg("--experimental-overlap")
+        .arg("--exclude")
+        .arg("**/vendor/**")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Checking 1 files for overlapping code"));
+}
@@ -41,6 +41,7 @@ def compute_total(values):
             delete_cost: 1.0,
             insert_cost: 1.0,
             compare_values: false,
+            ..Default::default()
         },
         min_lines: 3,
         min_tokens: None,
@@ -80,6 +81,7 @@ def multiply(x, y):
             delete_cost: 1.0,
             insert_cost: 1.0,
             compare_values: true,
+            ..Default::default()
         },
     };
 
@@ -115,6 +117,7 @@ def filter_positive(numbers):
             delete_cost: 1.0,
             insert_cost: 1.0,
             compare_values: false,
+            ..Default::default()
         },
         min_lines: 1,
         min_tokens: None,
@@ -161,6 +164,7 @@ class MathOperations:
             delete_cost: 1.0,
             insert_cost: 1.0,
             compare_values: false,
+            ..Default::default()
         },
         min_lines: 3,
         min_tokens: None,
@@ -198,6 +202,7 @@ def title(self):
             delete_cost: 1.0,
             insert_cost: 1.0,
             compare_values: false,
+            ..Default::default()
         },
         min_lines: 1,
         min_tokens: None,
@@ -237,6 +242,7 @@ def bar():
             delete_cost: 1.0,
             insert_cost: 1.0,
             compare_values: true, // Compare values to detect different function names
+            ..Default::default()
         },
     };
 
@@ -273,6 +279,7 @@ def get_numbers(n):
             delete_cost: 1.0,
             insert_cost: 1.0,
             compare_values: false,
+            ..Default::default()
         },
         min_lines: 1,
         min_tokens: None,
@@ -311,6 +318,7 @@ async def get_data(endpoint):
             delete_cost: 1.0,
             insert_cost: 1.0,
             compare_values: false,
+            ..Default::default()
         },
         min_lines: 1,
         min_tokens: None,
@@ -0,0 +1,79 @@
+use crate::rust_parser::RustParser;
+use similarity_core::cli_pipeline::LanguageSupport;
+use similarity_core::language_parser::{GenericFunctionDef, LanguageParser};
+use similarity_core::{TSEDOptions, TypeComparisonOptions, TypeDefinition};
+use std::collections::HashSet;
+use std::error::Error;
+
+/// Rust support for the shared analysis pipeline
+pub struct RustLanguage;
+
+impl LanguageSupport for RustLanguage {
+    fn name(&self) -> &str {
+        "Rust"
+    }
+
+    fn default_extensions(&self) -> Vec<&str> {
+        vec!["rs"]
+    }
+
+    fn create_parser(&self) -> Result<Box<dyn LanguageParser>, Box<dyn Error + Send + Sync>> {
+        Ok(Box::new(RustParser::new()?))
+    }
+
+    fn configure(&self, options: &mut TSEDOptions) {
+        // Rust: compare both node labels and values
+        options.apted_options.compare_values = true;
+    }
+
+    fn compared_lines(&self, func: &GenericFunctionDef) -> (u32, u32) {
+        // Use the complete function, not just the body
+        (func.start_line, func.end_line)
+    }
+
+    fn is_test_function(&self, func: &GenericFunctionDef) -> bool {
+        func.name.starts_with("test_") || func.decorators.iter().any(|d| d.contains("test"))
+    }
+
+    fn supports_types(&self) -> bool {
+        true
+    }
+
+    fn extract_types(
+        &self,
+        filename: &str,
+        content: &str,
+    ) -> Result<Vec<TypeDefinition>, Box<dyn Error + Send + Sync>> {
+        RustParser::new()?.extract_type_definitions(content, filename)
+    }
+
+    fn configure_types(&self, options: &mut TypeComparisonOptions) {
+        // Structs are only compared with structs and enums with enums
+        options.allow_cross_kind_comparison = false;
+    }
+
+    fn type_similarity(
+        &self,
+        type1: &TypeDefinition,
+        type2: &TypeDefinition,
+        similarity: f64,
+    ) -> f64 {
+        similarity * signature_agreement(type1, type2)
+    }
+}
+
+/// Factor (0.8-1.0) by which differing derives and type parameter counts lower the
+/// similarity of two types whose fields match
+fn signature_agreement(type1: &TypeDefinition, type2: &TypeDefinition) -> f64 {
+    let derives1: HashSet<_> = type1.extends.iter().collect();
+    let derives2: HashSet<_> = type2.extends.iter().collect();
+    let union = derives1.union(&derives2).count();
+    let shared_derives = if union == 0 {
+        1.0
+    } else {
+        derives1.intersection(&derives2).count() as f64 / union as f64
+    };
+    let same_arity = if type1.generics.len() == type2.generics.len() { 1.0 } else { 0.0 };
+
+    0.8 + 0.1 * shared_derives + 0.1 * same_arity
+}
@@ -1,7 +1,9 @@
 use similarity_core::language_parser::{
-    GenericFunctionDef, GenericTypeDef, Language, LanguageParser,
+    GenericFieldDef, GenericFunctionDef, GenericTypeDef, Language, LanguageParser, TypeDefKind,
 };
-use similarity_core::tree::TreeNode;
+use similarity_core::suppression::retain_unsuppressed;
+use similarity_core::tree::{SourceSpan, TreeNode};
+use similarity_core::{PropertyDefinition, TypeDefinition, TypeKind};
 use std::error::Error;
 use std::rc::Rc;
 use tree_sitter::{Node, Parser};
@@ -20,6 +22,26 @@ impl RustParser {
         Ok(RustParser { parser })
     }
 
+    /// Extract structs and enums with their fields (or variants) for type comparison.
+    ///
+    /// Type parameters are renamed by position (`T0`, `T1`, ...) in field types so that
+    /// `Page<T>` and `Page<U>` compare equal, and derives are recorded in `extends`.
+    pub fn extract_type_definitions(
+        &mut self,
+        source: &str,
+        file_path: &str,
+    ) -> Result<Vec<TypeDefinition>, Box<dyn Error + Send + Sync>> {
+        let tree = self.parser.parse(source, None).ok_or_else(|| {
+            Box::new(std::io::Error::new(std::io::ErrorKind::InvalidData, "Failed to parse source"))
+                as Box<dyn Error + Send + Sync>
+        })?;
+
+        let mut types = Vec::new();
+        collect_type_definitions(tree.root_node(), source, file_path, &mut types);
+        types.retain(|type_def| !type_def.properties.is_empty());
+        Ok(types)
+    }
+
     fn extract_functions_from_node<'a>(
         &self,
         node: Node<'a>,
@@ -216,7 +238,9 @@ impl RustParser {
     }
 
     #[allow(clippy::only_used_in_recursion)]
-    fn convert_node_to_tree(&self, node: Node, source: &str) -> Rc<TreeNode> {
+    /// `prefix` is the length of the wrapper `parse` put in front of the source, which is
+    /// subtracted from the recorded spans so they point into the original source
+    fn convert_node_to_tree(&self, node: Node, source: &str, prefix: usize) -> Rc<TreeNode> {
         let label = node.kind().to_string();
 
         let value = match node.kind() {
@@ -239,11 +263,22 @@ impl RustParser {
             _ => String::new(),
         };
 
-        let mut tree_node = TreeNode::new(label, value, 0);
+        let mut span = SourceSpan::from(node.range());
+        if prefix > 0 {
+            span.start_byte = span.start_byte.saturating_sub(prefix);
+            span.end_byte = span.end_byte.saturating_sub(prefix);
+            if span.start_line == 1 {
+                span.start_column = span.start_column.saturating_sub(prefix as u32).max(1);
+            }
+            if span.end_line == 1 {
+                span.end_column = span.end_column.saturating_sub(prefix as u32).max(1);
+            }
+        }
+        let mut tree_node = TreeNode::new(label, value, 0).with_span(span);
 
         for child in node.children(&mut node.walk()) {
             if !child.is_extra() {
-                tree_node.add_child(self.convert_node_to_tree(child, source));
+                tree_node.add_child(self.convert_node_to_tree(child, source, prefix));
             }
         }
 
@@ -296,10 +331,13 @@ impl RustParser {
                     for field in child.children(&mut child.walk()) {
                         if field.kind() == "field_declaration" {
                             if let Some(field_name) = field.child_by_field_name("name") {
-                                let field_name_str = source
-                                    [field_name.byte_range().start..field_name.byte_range().end]
-                                    .to_string();
-                                fields.push(field_name_str);
+                                let field_type = field
+                                    .child_by_field_name("type")
+                                    .map_or("", |field_type| &source[field_type.byte_range()]);
+                                fields.push(GenericFieldDef::new(
+                                    &source[field_name.byte_range()],
+                                    field_type,
+                                ));
                             }
                         }
                     }
@@ -311,7 +349,7 @@ impl RustParser {
         if !name.is_empty() {
             Some(GenericTypeDef {
                 name,
-                kind: "struct".to_string(),
+                kind: TypeDefKind::Struct,
                 start_line: (node.start_position().row + 1) as u32,
                 end_line: (node.end_position().row + 1) as u32,
                 fields,
@@ -336,10 +374,13 @@ impl RustParser {
                     for variant in child.children(&mut child.walk()) {
                         if variant.kind() == "enum_variant" {
                             if let Some(variant_name) = variant.child_by_field_name("name") {
-                                let variant_name_str = source[variant_name.byte_range().start
-                                    ..variant_name.byte_range().end]
-                                    .to_string();
-                                variants.push(variant_name_str);
+                                let payload = variant
+                                    .child_by_field_name("body")
+                                    .map_or("", |payload| &source[payload.byte_range()]);
+                                variants.push(GenericFieldDef::new(
+                                    &source[variant_name.byte_range()],
+                                    payload,
+                                ));
                             }
                         }
                     }
@@ -351,7 +392,7 @@ impl RustParser {
         if !name.is_empty() {
             Some(GenericTypeDef {
                 name,
-                kind: "enum".to_string(),
+                kind: TypeDefKind::Enum,
                 start_line: (node.start_position().row + 1) as u32,
                 end_line: (node.end_position().row + 1) as u32,
                 fields: variants,
@@ -374,7 +415,7 @@ impl RustParser {
         if !name.is_empty() {
             Some(GenericTypeDef {
                 name,
-                kind: "type_alias".to_string(),
+                kind: TypeDefKind::TypeAlias,
                 start_line: (node.start_position().row + 1) as u32,
                 end_line: (node.end_position().row + 1) as u32,
                 fields: Vec::new(),
@@ -385,6 +426,169 @@ impl RustParser {
     }
 }
 
+fn collect_type_definitions(
+    node: Node,
+    source: &str,
+    file_path: &str,
+    types: &mut Vec<TypeDefinition>,
+) {
+    let kind = match node.kind() {
+        "struct_item" => TypeKind::Struct,
+        "enum_item" => TypeKind::Enum,
+        _ => {
+            for child in node.children(&mut node.walk()) {
+                collect_type_definitions(child, source, file_path, types);
+            }
+            return;
+        }
+    };
+
+    let Some(name) = node.child_by_field_name("name") else {
+        return;
+    };
+    let generics = type_parameter_names(node, source);
+    let properties = match (&kind, node.child_by_field_name("body")) {
+        (TypeKind::Enum, Some(body)) => variant_properties(body, source, &generics),
+        (_, Some(body)) => field_properties(body, source, &generics),
+        (_, None) => Vec::new(),
+    };
+
+    types.push(TypeDefinition {
+        name: source[name.byte_range()].to_string(),
+        kind,
+        properties,
+        generics,
+        extends: derives(node, source),
+        start_line: node.start_position().row + 1,
+        end_line: node.end_position().row + 1,
+        file_path: file_path.to_string(),
+    });
+}
+
+/// Names of the lifetime, type and const parameters of an item, in declaration order
+fn type_parameter_names(node: Node, source: &str) -> Vec<String> {
+    let Some(parameters) = node.child_by_field_name("type_parameters") else {
+        return Vec::new();
+    };
+    parameters
+        .named_children(&mut parameters.walk())
+        .filter_map(|parameter| parameter.child_by_field_name("name"))
+        .map(|name| source[name.byte_range()].to_string())
+        .collect()
+}
+
+/// Fields of a struct body; tuple struct fields are named by their position
+fn field_properties(body: Node, source: &str, generics: &[String]) -> Vec<PropertyDefinition> {
+    let property = |name: String, type_node: Node| {
+        let type_annotation = normalize_field_type(&source[type_node.byte_range()], generics);
+        PropertyDefinition {
+            name,
+            optional: type_annotation.starts_with("Option<"),
+            type_annotation,
+            readonly: false,
+        }
+    };
+
+    match body.kind() {
+        "field_declaration_list" => body
+            .named_children(&mut body.walk())
+            .filter(|field| field.kind() == "field_declaration")
+            .filter_map(|field| {
+                let name = field.child_by_field_name("name")?;
+                let type_node = field.child_by_field_name("type")?;
+                Some(property(source[name.byte_range()].to_string(), type_node))
+            })
+            .collect(),
+        "ordered_field_declaration_list" => body
+            .children_by_field_name("type", &mut body.walk())
+            .enumerate()
+            .map(|(index, type_node)| property(index.to_string(), type_node))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Variants of an enum body, typed by their payload (`()` for unit variants)
+fn variant_properties(body: Node, source: &str, generics: &[String]) -> Vec<PropertyDefinition> {
+    body.named_children(&mut body.walk())
+        .filter(|variant| variant.kind() == "enum_variant")
+        .filter_map(|variant| {
+            let name = variant.child_by_field_name("name")?;
+            let payload = variant
+                .child_by_field_name("body")
+                .map_or("()", |payload| &source[payload.byte_range()]);
+            Some(PropertyDefinition {
+                name: source[name.byte_range()].to_string(),
+                type_annotation: normalize_field_type(payload, generics),
+                optional: false,
+                readonly: false,
+            })
+        })
+        .collect()
+}
+
+/// Collapse whitespace and rename the item's type parameters by position
+fn normalize_field_type(text: &str, generics: &[String]) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut normalized = String::with_capacity(collapsed.len());
+    let mut chars = collapsed.chars().peekable();
+    while let Some(c) = chars.next() {
+        if !(c.is_alphanumeric() || c == '_' || c == '\'') {
+            normalized.push(c);
+            continue;
+        }
+        let mut word = c.to_string();
+        while let Some(&next) = chars.peek() {
+            if !(next.is_alphanumeric() || next == '_') {
+                break;
+            }
+            word.push(next);
+            chars.next();
+        }
+        match generics.iter().position(|generic| *generic == word) {
+            Some(index) if word.starts_with('\'') => normalized.push_str(&format!("'l{index}")),
+            Some(index) => normalized.push_str(&format!("T{index}")),
+            None => normalized.push_str(&word),
+        }
+    }
+    normalized
+}
+
+/// Trait names from the `#[derive(...)]` attributes directly above an item
+fn derives(node: Node, source: &str) -> Vec<String> {
+    let mut derives = Vec::new();
+    let mut sibling = node.prev_named_sibling();
+    while let Some(attribute_item) = sibling {
+        match attribute_item.kind() {
+            "attribute_item" => {}
+            "line_comment" | "block_comment" => {
+                sibling = attribute_item.prev_named_sibling();
+                continue;
+            }
+            _ => break,
+        }
+        let text = &source[attribute_item.byte_range()];
+        let arguments = text
+            .trim_start_matches("#[")
+            .trim_end_matches(']')
+            .trim()
+            .strip_prefix("derive")
+            .map(str::trim)
+            .and_then(|rest| rest.strip_prefix('('))
+            .and_then(|rest| rest.strip_suffix(')'));
+        if let Some(arguments) = arguments {
+            // Attributes are visited bottom-up; keep the derives in source order
+            let names = arguments
+                .split(',')
+                .map(|path| path.rsplit("::").next().unwrap_or(path).trim().to_string())
+                .filter(|name| !name.is_empty());
+            derives.splice(0..0, names);
+        }
+        sibling = attribute_item.prev_named_sibling();
+    }
+    derives
+}
+
 fn find_first_function(node: Node) -> Option<Node> {
     if node.kind() == "function_item" {
         return Some(node);
@@ -407,11 +611,13 @@ impl LanguageParser for RustParser {
     ) -> Result<Rc<TreeNode>, Box<dyn Error + Send + Sync>> {
         // If the source looks like a function body (starts with whitespace or directly with code),
         // wrap it in a minimal function context for parsing
+        const WRAPPER_PREFIX: &str = "fn __dummy() { ";
         let wrapped_source = if source.trim_start() != source || !source.starts_with("fn ") {
-            format!("fn __dummy() {{ {source} }}")
+            format!("{WRAPPER_PREFIX}{source} }}")
         } else {
             source.to_string()
         };
+        let prefix = if wrapped_source != source { WRAPPER_PREFIX.len() } else { 0 };
 
         let tree = self.parser.parse(&wrapped_source, None).ok_or_else(|| {
             Box::new(std::io::Error::new(
@@ -433,13 +639,20 @@ impl LanguageParser for RustParser {
                         let mut block_children = Vec::new();
                         for block_child in child.children(&mut child.walk()) {
                             if block_child.kind() != "{" && block_child.kind() != "}" {
-                                block_children
-                                    .push(self.convert_node_to_tree(block_child, &wrapped_source));
+                                block_children.push(self.convert_node_to_tree(
+                                    block_child,
+                                    &wrapped_source,
+                                    prefix,
+                                ));
                             }
                         }
 
                         // Create a synthetic root node containing just the body content
                         let mut root = TreeNode::new("block_content".to_string(), String::new(), 0);
+                        root.span = block_children
+                            .iter()
+                            .filter_map(|child| child.span)
+                            .reduce(SourceSpan::merge);
                         for child in block_children {
                             root.add_child(child);
                         }
@@ -449,7 +662,7 @@ impl LanguageParser for RustParser {
             }
         }
 
-        Ok(self.convert_node_to_tree(root_node, &wrapped_source))
+        Ok(self.convert_node_to_tree(root_node, &wrapped_source, prefix))
     }
 
     fn extract_functions(
@@ -465,6 +678,7 @@ impl LanguageParser for RustParser {
         let root_node = tree.root_node();
         let mut functions = Vec::new();
         self.extract_functions_from_node(root_node, source, &mut functions, false);
+        retain_unsuppressed(source, &mut functions, |f| f.start_line);
         Ok(functions)
     }
 
@@ -572,17 +786,89 @@ type Distance = f64;
 
         // Check struct
         assert_eq!(types[0].name, "Point");
-        assert_eq!(types[0].kind, "struct");
-        assert_eq!(types[0].fields, vec!["x", "y"]);
+        assert_eq!(types[0].kind, TypeDefKind::Struct);
+        assert_eq!(types[0].field_names(), vec!["x", "y"]);
+        assert_eq!(types[0].fields[0].type_annotation, "f64");
 
         // Check enum
         assert_eq!(types[1].name, "Color");
-        assert_eq!(types[1].kind, "enum");
-        assert_eq!(types[1].fields, vec!["Red", "Green", "Blue", "RGB"]);
+        assert_eq!(types[1].kind, TypeDefKind::Enum);
+        assert_eq!(types[1].field_names(), vec!["Red", "Green", "Blue", "RGB"]);
+        assert_eq!(types[1].fields[3].type_annotation, "(u8, u8, u8)");
 
         // Check type alias
         // TODO: Fix type alias detection
         // assert_eq!(types[2].name, "Distance");
-        // assert_eq!(types[2].kind, "type_alias");
+        // assert_eq!(types[2].kind, TypeDefKind::TypeAlias);
+    }
+
+    #[test]
+    fn test_rust_type_definitions() {
+        let mut parser = RustParser::new().unwrap();
+        let source = r#"
+#[derive(Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+#[derive(serde::Serialize)]
+pub struct Page<'a, T> {
+    pub items: Vec<T>,
+    pub cursor: Option<&'a str>,
+}
+
+struct Meters(f64);
+
+struct Marker;
+
+enum Event<T> {
+    Started,
+    Progress(T, u8),
+    Finished { code: i32 },
+}
+"#;
+
+        let types = parser.extract_type_definitions(source, "test.rs").unwrap();
+        // Unit structs have nothing to compare
+        assert_eq!(types.len(), 3);
+
+        let page = &types[0];
+        assert_eq!(page.name, "Page");
+        assert_eq!(page.kind, TypeKind::Struct);
+        assert_eq!(page.generics, vec!["'a", "T"]);
+        assert_eq!(page.extends, vec!["Debug", "Clone", "Serialize"]);
+        assert_eq!(page.properties[0].type_annotation, "Vec<T1>");
+        assert_eq!(page.properties[1].type_annotation, "Option<&'l0 str>");
+        assert!(page.properties[1].optional);
+
+        let meters = &types[1];
+        assert_eq!(meters.properties[0].name, "0");
+        assert_eq!(meters.properties[0].type_annotation, "f64");
+
+        let event = &types[2];
+        assert_eq!(event.kind, TypeKind::Enum);
+        let variants: Vec<_> = event
+            .properties
+            .iter()
+            .map(|p| (p.name.as_str(), p.type_annotation.as_str()))
+            .collect();
+        assert_eq!(
+            variants,
+            vec![("Started", "()"), ("Progress", "(T0, u8)"), ("Finished", "{ code: i32 }")]
+        );
+    }
+
+    #[test]
+    fn test_spans_of_wrapped_body() {
+        let mut parser = RustParser::new().unwrap();
+        let source = "let x = 1;\nx + 2";
+        let tree = parser.parse(source, "test.rs").unwrap();
+
+        let span = tree.span.unwrap();
+        assert_eq!((span.start_line, span.end_line), (1, 2));
+
+        // Spans point into the body, not the wrapper added for parsing
+        let first = tree.children[0].span.unwrap();
+        assert_eq!((first.start_byte, first.start_column), (0, 1));
+        assert_eq!(&source[first.start_byte..first.end_byte], "let x = 1;");
+        let second = tree.children[1].span.unwrap();
+        assert_eq!((second.start_line, second.start_column), (2, 1));
     }
 }
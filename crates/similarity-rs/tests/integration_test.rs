@@ -227,3 +227,135 @@ fn longer_func2() -> i32 {
         .stdout(predicate::str::contains("longer_func2"))
         .stdout(predicate::str::contains("f1").not());
 }
+
+#[test]
+fn test_rust_json_output() {
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("test.rs");
+
+    let content = r#"
+fn process_items(items: &[i32]) -> Vec<i32> {
+    let mut result = Vec::new();
+    for item in items {
+        if *item > 0 {
+            result.push(item * 2);
+        }
+    }
+    result
+}
+
+fn handle_items(data: &[i32]) -> Vec<i32> {
+    let mut output = Vec::new();
+    for d in data {
+        if *d > 0 {
+            output.push(d * 2);
+        }
+    }
+    output
+}
+"#;
+
+    fs::write(&file_path, content).unwrap();
+
+    Command::cargo_bin("similarity-rs")
+        .unwrap()
+        .arg(&file_path)
+        .arg("--threshold")
+        .arg("0.8")
+        .arg("--format")
+        .arg("json")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("{"))
+        .stdout(predicate::str::contains("\"tool\": \"similarity-rs\""))
+        .stdout(predicate::str::contains("\"name\": \"process_items\""))
+        .stdout(predicate::str::contains("\"similarity\":"))
+        .stdout(predicate::str::contains("Total duplicate pairs").not());
+}
+
+#[test]
+fn test_inline_suppression_comment() {
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("test.rs");
+
+    let content = r#"
+fn process_items(items: &[i32]) -> Vec<i32> {
+    let mut result = Vec::new();
+    for item in items {
+        if *item > 0 {
+            result.push(item * 2);
+        }
+    }
+    result
+}
+
+// similarity-ignore: mirrors process_items on purpose
+#[inline]
+fn handle_items(data: &[i32]) -> Vec<i32> {
+    let mut output = Vec::new();
+    for d in data {
+        if *d > 0 {
+            output.push(d * 2);
+        }
+    }
+    output
+}
+"#;
+
+    fs::write(&file_path, content).unwrap();
+
+    Command::cargo_bin("similarity-rs")
+        .unwrap()
+        .arg(&file_path)
+        .arg("--threshold")
+        .arg("0.8")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("handle_items").not());
+}
+
+#[test]
+fn test_fail_thresholds_exit_code() {
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("test.rs");
+
+    let content = r#"
+fn process_items(items: &[i32]) -> Vec<i32> {
+    let mut result = Vec::new();
+    for item in items {
+        if *item > 0 {
+            result.push(item * 2);
+        }
+    }
+    result
+}
+
+fn handle_items(data: &[i32]) -> Vec<i32> {
+    let mut output = Vec::new();
+    for d in data {
+        if *d > 0 {
+            output.push(d * 2);
+        }
+    }
+    output
+}
+"#;
+
+    fs::write(&file_path, content).unwrap();
+
+    let run = |extra: &[&str]| {
+        Command::cargo_bin("similarity-rs")
+            .unwrap()
+            .arg(&file_path)
+            .args(["--threshold", "0.8"])
+            .args(extra)
+            .assert()
+    };
+
+    run(&[]).success();
+    run(&["--fail-on-duplicates"]).code(3).stderr(predicate::str::contains("1 duplicate pairs"));
+    run(&["--max-duplicates", "1"]).success();
+    run(&["--max-duplicates", "0"]).code(3);
+    run(&["--max-duplicated-lines", "100"]).success();
+    run(&["--max-duplicated-lines", "3"]).code(3).stderr(predicate::str::contains("lines"));
+}
@@ -23,6 +23,7 @@ fn test_full_function_similarity() {
             delete_cost: 1.0,
             insert_cost: 1.0,
             compare_values: true,
+            ..Default::default()
         },
         min_lines: 1,
         min_tokens: None,
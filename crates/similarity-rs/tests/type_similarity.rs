@@ -0,0 +1,106 @@
+use assert_cmd::Command;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn test_experimental_types_finds_duplicated_structs_across_crates() {
+    let dir = tempdir().unwrap();
+    let api = dir.path().join("api");
+    let storage = dir.path().join("storage");
+    fs::create_dir_all(&api).unwrap();
+    fs::create_dir_all(&storage).unwrap();
+
+    fs::write(
+        api.join("dto.rs"),
+        r#"
+#[derive(Debug, Clone)]
+pub struct UserDto {
+    pub id: u64,
+    pub name: String,
+    pub email: Option<String>,
+}
+
+pub enum Shape {
+    Circle(f64),
+    Square { side: f64 },
+}
+"#,
+    )
+    .unwrap();
+    fs::write(
+        storage.join("model.rs"),
+        r#"
+#[derive(Debug, Clone)]
+pub struct UserRecord {
+    pub id: u64,
+    pub name: String,
+    pub email: Option<String>,
+}
+
+pub struct Circle {
+    pub radius: f64,
+}
+"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("similarity-rs").unwrap();
+    cmd.arg(dir.path())
+        .arg("--experimental-types")
+        .arg("--no-functions")
+        .arg("--threshold")
+        .arg("0.8");
+
+    let output = cmd.assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+
+    assert!(stdout.contains("=== Type Similarity ==="));
+    assert!(stdout.contains("similar-type: UserDto (struct)"));
+    assert!(stdout.contains("similar-type: UserRecord (struct)"));
+    // Structs are not compared with enums
+    assert!(!stdout.contains("Shape"));
+    assert!(stdout.contains("Total similar type pairs found: 1"));
+}
+
+#[test]
+fn test_experimental_types_requires_matching_derives_for_full_similarity() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("lib.rs"),
+        r#"
+#[derive(Debug, Clone, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+}
+"#,
+    )
+    .unwrap();
+
+    // The fields match, but the derives do not
+    let mut cmd = Command::cargo_bin("similarity-rs").unwrap();
+    cmd.arg(dir.path())
+        .arg("--experimental-types")
+        .arg("--no-functions")
+        .arg("--threshold")
+        .arg("0.95");
+
+    let output = cmd.assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("No similar types found!"));
+}
+
+#[test]
+fn test_no_analyzer_enabled_is_an_error() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("lib.rs"), "pub struct Empty;\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("similarity-rs").unwrap();
+    cmd.arg(dir.path()).arg("--no-functions");
+    cmd.assert().failure();
+}
@@ -1,6 +1,7 @@
 #![allow(clippy::uninlined_format_args)]
 
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use similarity_core::lsh::CandidateStats;
 use similarity_core::TSEDOptions;
 use similarity_ts::parallel::{
     check_cross_file_duplicates_parallel, check_within_file_duplicates_parallel,
@@ -134,7 +135,7 @@ fn benchmark_load_files_comparison(c: &mut Criterion) {
 
         group.bench_with_input(BenchmarkId::new("parallel", num_files), &file_paths, |b, paths| {
             b.iter(|| {
-                let file_data = load_files_parallel(paths);
+                let file_data = load_files_parallel(paths, None);
                 black_box(file_data)
             });
         });
@@ -154,24 +155,28 @@ fn benchmark_within_file_comparison(c: &mut Criterion) {
     for &num_files in &[10, 20, 50] {
         let test_files = setup_test_files(num_files, 30);
         let file_paths: Vec<PathBuf> = test_files.iter().map(|(p, _)| p.clone()).collect();
+        let file_data = load_files_parallel(&file_paths, None);
 
         group.throughput(Throughput::Elements(num_files as u64));
 
-        group.bench_with_input(
-            BenchmarkId::new("sequential", num_files),
-            &file_paths,
-            |b, paths| {
-                b.iter(|| {
-                    let results =
-                        check_within_file_duplicates_sequential(paths, 0.8, &options, false);
-                    black_box(results)
-                });
-            },
-        );
+        group.bench_with_input(BenchmarkId::new("sequential", num_files), &file_data, |b, data| {
+            b.iter(|| {
+                let results = check_within_file_duplicates_sequential(data, 0.8, &options, false);
+                black_box(results)
+            });
+        });
 
-        group.bench_with_input(BenchmarkId::new("parallel", num_files), &file_paths, |b, paths| {
+        group.bench_with_input(BenchmarkId::new("parallel", num_files), &file_data, |b, data| {
             b.iter(|| {
-                let results = check_within_file_duplicates_parallel(paths, 0.8, &options, false);
+                let results = check_within_file_duplicates_parallel(
+                    data,
+                    0.8,
+                    &options,
+                    false,
+                    None,
+                    None,
+                    &CandidateStats::default(),
+                );
                 black_box(results)
             });
         });
@@ -195,7 +200,7 @@ fn benchmark_cross_file_comparison(c: &mut Criterion) {
 
         // Pre-load file data for cross-file comparison
         let file_data_seq = load_files_sequential(&file_paths);
-        let file_data_par = load_files_parallel(&file_paths);
+        let file_data_par = load_files_parallel(&file_paths, None);
 
         group.throughput(Throughput::Elements((num_files * num_files) as u64));
 
@@ -215,7 +220,15 @@ fn benchmark_cross_file_comparison(c: &mut Criterion) {
             &file_data_par,
             |b, data| {
                 b.iter(|| {
-                    let results = check_cross_file_duplicates_parallel(data, 0.8, &options, false);
+                    let results = check_cross_file_duplicates_parallel(
+                        data,
+                        0.8,
+                        &options,
+                        None,
+                        None,
+                        None,
+                        &CandidateStats::default(),
+                    );
                     black_box(results)
                 });
             },
@@ -234,6 +247,7 @@ fn benchmark_scaling(c: &mut Criterion) {
     let num_files = 50;
     let test_files = setup_test_files(num_files, 20);
     let file_paths: Vec<PathBuf> = test_files.iter().map(|(p, _)| p.clone()).collect();
+    let file_data = load_files_parallel(&file_paths, None);
 
     let options = TSEDOptions { size_penalty: false, min_lines: 3, ..TSEDOptions::default() };
 
@@ -241,13 +255,20 @@ fn benchmark_scaling(c: &mut Criterion) {
     let thread_counts = vec![1, 2, 4, 8];
 
     for &threads in &thread_counts {
-        group.bench_with_input(BenchmarkId::new("threads", threads), &file_paths, |b, paths| {
+        group.bench_with_input(BenchmarkId::new("threads", threads), &file_data, |b, data| {
             b.iter(|| {
                 // Set thread count for this iteration
                 rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap().install(
                     || {
-                        let results =
-                            check_within_file_duplicates_parallel(paths, 0.8, &options, false);
+                        let results = check_within_file_duplicates_parallel(
+                            data,
+                            0.8,
+                            &options,
+                            false,
+                            None,
+                            None,
+                            &CandidateStats::default(),
+                        );
                         black_box(results)
                     },
                 )
@@ -0,0 +1,134 @@
+#![allow(clippy::uninlined_format_args)]
+
+use crate::language::TypeScriptLanguage;
+use similarity_core::cli_baseline::Baseline;
+use similarity_core::cli_file_utils::{collect_files_excluding, create_exclude_matcher};
+use similarity_core::cli_output::{
+    extract_code_lines, format_function_output, relative_path, OutputFormat, ReportEntry,
+    ReportKind, ReportLocation,
+};
+use similarity_core::cli_pipeline::{LanguageSupport, PipelineOptions};
+use similarity_core::cross_language::{find_cross_language_duplicates, normalize_functions};
+use std::collections::HashMap;
+
+/// Compare the TypeScript/JavaScript functions under `paths` with the Python ones
+pub fn check_cross_language(
+    paths: &[String],
+    options: &PipelineOptions,
+    format: OutputFormat,
+    baseline: Option<&Baseline>,
+) -> anyhow::Result<Vec<ReportEntry>> {
+    let (threshold, print) = (options.compare.threshold, options.print);
+    let mut exts: Vec<&str> = match &options.extensions {
+        Some(extensions) => extensions.iter().map(String::as_str).collect(),
+        None => TypeScriptLanguage.default_extensions(),
+    };
+    exts.push("py");
+    let exclude_matcher = create_exclude_matcher(&options.exclude);
+    let files = collect_files_excluding(paths, &exts, exclude_matcher.as_ref())?;
+
+    if files.is_empty() {
+        if format.is_text() {
+            println!("No TypeScript/JavaScript or Python files found in specified paths");
+        }
+        return Ok(Vec::new());
+    }
+
+    if format.is_text() {
+        println!("Checking {} files for cross-language duplicates...", files.len());
+    }
+
+    let mut contents = HashMap::new();
+    let mut parsed = Vec::new();
+    for file in &files {
+        let file_str = file.to_string_lossy().to_string();
+        let content = match std::fs::read_to_string(file) {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!("Error reading {}: {}", file.display(), e);
+                continue;
+            }
+        };
+        match normalize_functions(&file_str, &content) {
+            Ok(functions) => parsed.push((file_str.clone(), functions)),
+            Err(e) => eprintln!("Error parsing {}: {}", file.display(), e),
+        }
+        contents.insert(file_str, content);
+    }
+
+    let mut matches =
+        find_cross_language_duplicates(&parsed, threshold, &options.compare.tsed_options);
+
+    let mut entries: Vec<ReportEntry> = matches
+        .iter()
+        .map(|m| {
+            ReportEntry::new(
+                ReportKind::Function,
+                ReportLocation::new(
+                    relative_path(m.file1),
+                    &m.func1.name,
+                    m.func1.start_line,
+                    m.func1.end_line,
+                ),
+                ReportLocation::new(
+                    relative_path(m.file2),
+                    &m.func2.name,
+                    m.func2.start_line,
+                    m.func2.end_line,
+                ),
+                m.similarity,
+            )
+        })
+        .collect();
+    if let Some(baseline) = baseline {
+        baseline.retain_new(&mut matches, &mut entries);
+    }
+    if !format.is_text() {
+        return Ok(entries);
+    }
+
+    if matches.is_empty() {
+        println!("\nNo cross-language duplicates found!");
+        return Ok(entries);
+    }
+
+    println!("\nFound {} cross-language duplicate pairs:", matches.len());
+    println!("{}", "-".repeat(60));
+
+    for m in &matches {
+        let (lines1, lines2) = (m.func1.line_count(), m.func2.line_count());
+        let avg_lines = (lines1 + lines2) as f64 / 2.0;
+        println!(
+            "\nSimilarity: {:.2}%, Score: {:.1} points (lines {}~{}, avg: {:.1})",
+            m.similarity * 100.0,
+            m.similarity * avg_lines,
+            lines1.min(lines2),
+            lines1.max(lines2),
+            avg_lines
+        );
+        for (file, func) in [(m.file1, m.func1), (m.file2, m.func2)] {
+            println!(
+                "  {}",
+                format_function_output(
+                    &relative_path(file),
+                    &func.name,
+                    func.start_line,
+                    func.end_line
+                )
+            );
+        }
+
+        if print {
+            for (file, func) in [(m.file1, m.func1), (m.file2, m.func2)] {
+                if let Some(content) = contents.get(file) {
+                    println!("\n\x1b[36m--- {}:{} ---\x1b[0m", relative_path(file), func.name);
+                    if let Ok(code) = extract_code_lines(content, func.start_line, func.end_line) {
+                        println!("{}", code);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(entries)
+}
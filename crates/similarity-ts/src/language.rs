@@ -0,0 +1,43 @@
+use crate::typescript_parser::TypeScriptParser;
+use similarity_core::cli_pipeline::LanguageSupport;
+use similarity_core::language_parser::LanguageParser;
+use similarity_core::type_extractor::extract_types_from_code;
+use similarity_core::TypeDefinition;
+use std::error::Error;
+
+/// TypeScript/JavaScript support for the shared analysis pipeline
+///
+/// Functions are compared by [`crate::check::check_paths`]; the pipeline covers types and
+/// overlapping code.
+pub struct TypeScriptLanguage;
+
+impl LanguageSupport for TypeScriptLanguage {
+    fn name(&self) -> &str {
+        "TypeScript"
+    }
+
+    fn default_extensions(&self) -> Vec<&str> {
+        vec!["ts", "tsx", "js", "jsx", "mjs", "cjs", "mts", "cts"]
+    }
+
+    fn create_parser(&self) -> Result<Box<dyn LanguageParser>, Box<dyn Error + Send + Sync>> {
+        Ok(Box::new(TypeScriptParser::new()))
+    }
+
+    fn supports_types(&self) -> bool {
+        true
+    }
+
+    fn extract_types(
+        &self,
+        filename: &str,
+        content: &str,
+    ) -> Result<Vec<TypeDefinition>, Box<dyn Error + Send + Sync>> {
+        match extract_types_from_code(content, filename) {
+            Ok(types) => Ok(types),
+            // Files that do not parse are skipped silently, as by similarity-ts
+            Err(e) if e.contains("Parse errors:") => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
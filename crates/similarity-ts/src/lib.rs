@@ -1,3 +1,6 @@
+pub mod check;
+pub mod cross_language;
+pub mod language;
 pub mod parallel;
 pub mod sequential;
 pub mod typescript_parser;
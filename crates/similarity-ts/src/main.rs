@@ -1,11 +1,33 @@
 #![allow(clippy::uninlined_format_args)]
 
-use clap::Parser;
-
-mod check;
-pub mod parallel;
-
-#[derive(Parser)]
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser};
+use similarity_core::cli_baseline::{
+    finish_baseline, load_baseline, Baseline, DEFAULT_BASELINE_FILE,
+};
+use similarity_core::cli_cache::{AnalysisCache, DEFAULT_CACHE_DIR};
+use similarity_core::cli_changes::ChangedLines;
+use similarity_core::cli_clone_classes::Linkage;
+use similarity_core::cli_ignore::SimilarityIgnore;
+use similarity_core::cli_output::{
+    relative_path, FailThresholds, OutputFormat, Report, ReportEntry, ReportKind, ReportLocation,
+};
+use similarity_core::cli_parallel::CompareOptions;
+use similarity_core::cli_pipeline::{
+    check_overlaps, collect_language_files, LanguageSupport, PipelineOptions,
+};
+use similarity_core::cli_types::{
+    display_similar_types, format_type_kind, show_comparison_details, show_type_details,
+    type_location, type_report_entries,
+};
+use similarity_core::lsh::LshOptions;
+use similarity_core::{OverlapOptions, TSEDOptions, TypeComparisonOptions};
+use similarity_ts::check::{self, CheckOptions};
+use similarity_ts::cross_language::check_cross_language;
+use similarity_ts::language::TypeScriptLanguage;
+use std::path::PathBuf;
+
+#[derive(Parser, Clone)]
 #[command(name = "similarity-ts")]
 #[command(about = "TypeScript/JavaScript code similarity analyzer")]
 #[command(version)]
@@ -18,6 +40,10 @@ struct Cli {
     #[arg(short, long)]
     print: bool,
 
+    /// Show which statements differ between each pair of duplicate functions
+    #[arg(long)]
+    show_diff: bool,
+
     /// Similarity threshold (0.0-1.0)
     #[arg(short, long, default_value = "0.87")]
     threshold: f64,
@@ -90,6 +116,22 @@ struct Cli {
     #[arg(long = "no-fast")]
     no_fast: bool,
 
+    /// Only compare functions whose MinHash signatures share an LSH band
+    #[arg(long)]
+    lsh: bool,
+
+    /// Number of LSH bands; more bands find more candidate pairs (higher recall)
+    #[arg(long, value_name = "N", default_value = "32", value_parser = clap::value_parser!(u32).range(1..))]
+    lsh_bands: u32,
+
+    /// MinHash values per LSH band; more rows find fewer candidate pairs (higher precision)
+    #[arg(long, value_name = "N", default_value = "3", value_parser = clap::value_parser!(u32).range(1..))]
+    lsh_rows: u32,
+
+    /// Print how many function pairs were compared and how many candidate generation pruned
+    #[arg(long)]
+    stats: bool,
+
     /// Exclude directories matching the given patterns (can be specified multiple times)
     #[arg(long)]
     exclude: Vec<String>,
@@ -109,213 +151,337 @@ struct Cli {
     /// Size tolerance for overlap detection (0.0-1.0)
     #[arg(long, default_value = "0.25")]
     overlap_size_tolerance: f64,
+
+    /// Also compare TypeScript/JavaScript functions with Python functions (scans .py files too)
+    #[arg(long)]
+    cross_language: bool,
+
+    /// Output format (text, json, sarif)
+    #[arg(long, default_value = "text")]
+    format: OutputFormat,
+
+    /// Project config file (default: nearest similarity.toml, pyproject.toml or package.json)
+    #[arg(long)]
+    project_config: Option<PathBuf>,
+
+    /// Only report duplicate pairs that are not recorded in this baseline file
+    #[arg(long, value_name = "FILE")]
+    baseline: Option<PathBuf>,
+
+    /// Record the current duplicate pairs as a baseline [default: .similarity-baseline.json]
+    #[arg(long, value_name = "FILE", num_args = 0..=1, default_missing_value = DEFAULT_BASELINE_FILE)]
+    write_baseline: Option<PathBuf>,
+
+    /// Only report duplicates of functions changed since a git revision [default: HEAD]
+    #[arg(
+        long,
+        value_name = "REV",
+        num_args = 0..=1,
+        default_missing_value = "HEAD",
+        conflicts_with = "diff_file"
+    )]
+    changed_since: Option<String>,
+
+    /// Only report duplicates of functions changed in a unified diff file
+    #[arg(long, value_name = "FILE")]
+    diff_file: Option<PathBuf>,
+
+    /// Exit with code 3 when any duplicate is reported
+    #[arg(long)]
+    fail_on_duplicates: bool,
+
+    /// Exit with code 3 when more duplicate pairs than this are reported
+    #[arg(long, value_name = "N")]
+    max_duplicates: Option<usize>,
+
+    /// Exit with code 3 when the reported pairs duplicate more lines than this
+    #[arg(long, value_name = "N")]
+    max_duplicated_lines: Option<u32>,
+
+    /// Reuse functions parsed by previous runs for files whose content did not change
+    #[arg(long)]
+    incremental: bool,
+
+    /// Cache directory used by --incremental
+    #[arg(long, value_name = "DIR", default_value = DEFAULT_CACHE_DIR)]
+    cache_dir: PathBuf,
+
+    /// Group duplicate pairs into clone classes instead of listing every pair
+    #[arg(long)]
+    clone_classes: bool,
+
+    /// How pairs are merged into clone classes: single (transitive) or complete
+    #[arg(long, value_name = "LINKAGE", default_value = "single")]
+    linkage: Linkage,
+}
+
+impl Cli {
+    fn fail_thresholds(&self) -> FailThresholds {
+        FailThresholds {
+            fail_on_duplicates: self.fail_on_duplicates,
+            max_duplicates: self.max_duplicates,
+            max_duplicated_lines: self.max_duplicated_lines,
+        }
+    }
+
+    fn lsh_options(&self) -> Option<LshOptions> {
+        self.lsh.then(|| LshOptions::new(self.lsh_bands as usize, self.lsh_rows as usize))
+    }
+
+    fn pipeline_options(&self) -> PipelineOptions {
+        let mut tsed_options = TSEDOptions::default();
+        tsed_options.apted_options.rename_cost = self.rename_cost;
+        tsed_options.min_lines = self.min_lines.unwrap_or(3);
+        tsed_options.min_tokens = self.min_tokens;
+        tsed_options.size_penalty = !self.no_size_penalty;
+
+        PipelineOptions {
+            compare: CompareOptions {
+                threshold: self.threshold,
+                tsed_options,
+                fast_mode: !self.no_fast,
+                lsh: self.lsh_options(),
+            },
+            extensions: self.extensions.clone(),
+            exclude: self.exclude.clone(),
+            filter_function: self.filter_function.clone(),
+            filter_function_body: self.filter_function_body.clone(),
+            print: self.print,
+            stats: self.stats,
+            cross_file: true,
+            cross_language: self.cross_language,
+            types: TypeComparisonOptions {
+                allow_cross_kind_comparison: self.allow_cross_kind,
+                structural_weight: self.structural_weight,
+                naming_weight: self.naming_weight,
+                ..Default::default()
+            },
+            overlap: OverlapOptions {
+                min_window_size: self.overlap_min_window,
+                max_window_size: self.overlap_max_window,
+                threshold: self.threshold,
+                size_tolerance: self.overlap_size_tolerance,
+            },
+        }
+    }
 }
 
 fn main() -> anyhow::Result<()> {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches)?;
+    if let (Some(_), Some(tokens)) = (cli.min_lines, cli.min_tokens) {
+        eprintln!(
+            "Warning: Both --min-lines and --min-tokens specified. Using --min-tokens={}",
+            tokens
+        );
+    }
 
-    let functions_enabled = !cli.no_functions;
-    let types_enabled = cli.types;
-    let overlap_enabled = cli.overlap;
+    // Paths with different project configs are analyzed separately, each with its own
+    let explicit = |id: &str| {
+        matches.try_contains_id(id).unwrap_or(false)
+            && matches.value_source(id) == Some(ValueSource::CommandLine)
+    };
+    let runs = cli.pipeline_options().for_files(
+        cli.project_config.as_deref(),
+        &cli.paths,
+        &["typescript"],
+        &TypeScriptLanguage.default_extensions(),
+        explicit,
+    )?;
 
     // Validate that at least one analyzer is enabled
-    if !functions_enabled && !types_enabled && !overlap_enabled {
-        eprintln!("Error: At least one analyzer must be enabled. Use --types to enable type checking, --overlap for overlap detection, or remove --no-functions.");
+    if cli.no_functions
+        && !cli.types
+        && !cli.overlap
+        && runs.iter().any(|(_, options)| !options.cross_language)
+    {
+        eprintln!("Error: At least one analyzer must be enabled. Use --types to enable type checking, --overlap for overlap detection, --cross-language for cross-language detection, or remove --no-functions.");
         return Err(anyhow::anyhow!("No analyzer enabled"));
     }
 
-    // Handle mutual exclusion of min_lines and min_tokens
-    let (min_lines, min_tokens) = match (cli.min_lines, cli.min_tokens) {
-        (Some(_), Some(tokens)) => {
-            eprintln!(
-                "Warning: Both --min-lines and --min-tokens specified. Using --min-tokens={}",
-                tokens
-            );
-            (None, Some(tokens))
-        }
-        (lines, tokens) => (lines, tokens),
+    let format = cli.format;
+    let text = format.is_text();
+    // Grouped pairs are only printed as clone classes once every analyzer has run
+    let pair_format = format.for_pairs(cli.clone_classes);
+    let fail_thresholds = cli.fail_thresholds();
+    let changes = match (&cli.changed_since, &cli.diff_file) {
+        (Some(rev), _) => Some(ChangedLines::from_git(rev)?),
+        (None, Some(diff)) => Some(ChangedLines::from_diff_file(diff)?),
+        (None, None) => None,
     };
+    let baseline = load_baseline(cli.baseline.as_deref(), cli.write_baseline.as_deref())?;
+    let cache = cli
+        .incremental
+        .then(|| AnalysisCache::open(&cli.cache_dir, "similarity-ts", env!("CARGO_PKG_VERSION")))
+        .transpose()?;
+    let mut report = Report::new("similarity-ts", env!("CARGO_PKG_VERSION"));
+
+    if text {
+        println!("Analyzing code similarity...\n");
+    }
 
-    println!("Analyzing code similarity...\n");
+    let separator = "-".repeat(60);
+    for (index, (paths, options)) in runs.iter().enumerate() {
+        if pair_format.is_text() && index > 0 {
+            println!("\n{}\n", separator);
+        }
+        let shared = Shared {
+            format: pair_format,
+            changes: changes.as_ref(),
+            baseline: baseline.as_ref(),
+            cache: cache.as_ref(),
+        };
+        report.extend(analyze(&cli, paths, options, &shared)?);
+    }
+
+    if cli.clone_classes {
+        report.group_clone_classes(cli.linkage);
+    }
+    report.print(format)?;
+    finish_baseline(baseline.as_ref(), cli.write_baseline.as_deref(), &report.duplicates, text)?;
+    fail_thresholds.enforce(&report.duplicates);
+
+    Ok(())
+}
+
+/// State shared by the analysis of every group of paths
+struct Shared<'a> {
+    format: OutputFormat,
+    changes: Option<&'a ChangedLines>,
+    baseline: Option<&'a Baseline>,
+    cache: Option<&'a AnalysisCache>,
+}
+
+/// Run the enabled analyzers on `paths` with the options of their project config
+fn analyze(
+    cli: &Cli,
+    paths: &[String],
+    options: &PipelineOptions,
+    shared: &Shared,
+) -> anyhow::Result<Vec<ReportEntry>> {
+    let functions_enabled = !cli.no_functions;
+    let types_enabled = cli.types;
+    let overlap_enabled = cli.overlap;
+    let cross_language_enabled = options.cross_language;
+
+    let pair_format = shared.format;
+    let baseline = shared.baseline;
+    let mut all_entries = Vec::new();
+
+    // Python files are only collected for the cross-language comparison
+    let ts_paths: Vec<String> = paths
+        .iter()
+        .filter(|path| !cross_language_enabled || !path.ends_with(".py"))
+        .cloned()
+        .collect();
 
     let separator = "-".repeat(60);
 
     // Run functions analysis if enabled
     if functions_enabled {
-        println!("=== Function Similarity ===");
-        check::check_paths(
-            cli.paths.clone(),
-            cli.threshold,
-            cli.rename_cost,
-            cli.extensions.as_ref(),
-            min_lines.unwrap_or(3),
-            min_tokens,
-            cli.no_size_penalty,
-            cli.print,
-            !cli.no_fast,
-            cli.filter_function.as_ref(),
-            cli.filter_function_body.as_ref(),
-            &cli.exclude,
-        )?;
+        if pair_format.is_text() {
+            println!("=== Function Similarity ===");
+        }
+        let options = CheckOptions {
+            pipeline: options.clone(),
+            show_diff: cli.show_diff,
+            changes: shared.changes,
+            cache: shared.cache,
+        };
+        let entries = check::check_paths(ts_paths.clone(), &options, pair_format, baseline)?;
+        all_entries.extend(entries);
     }
 
     // Run types analysis if enabled
-    if types_enabled && functions_enabled {
+    if pair_format.is_text() && types_enabled && functions_enabled {
         println!("\n{}\n", separator);
     }
 
     if types_enabled {
-        println!("=== Type Similarity ===");
-        check_types(
-            cli.paths.clone(),
-            cli.threshold,
-            cli.extensions.as_ref(),
-            cli.print,
-            cli.include_types,
-            cli.types_only,
-            cli.interfaces_only,
-            cli.allow_cross_kind,
-            cli.structural_weight,
-            cli.naming_weight,
-            cli.include_type_literals,
-            &cli.exclude,
-        )?;
+        if pair_format.is_text() {
+            println!("=== Type Similarity ===");
+        }
+        let filters = TypeFilters {
+            types_only: cli.types_only,
+            interfaces_only: cli.interfaces_only,
+            include_type_literals: cli.include_type_literals,
+        };
+        let entries = check_types(&ts_paths, options, &filters, pair_format, baseline)?;
+        all_entries.extend(entries);
     }
 
     // Run overlap analysis if enabled
-    if overlap_enabled && (functions_enabled || types_enabled) {
+    if pair_format.is_text() && overlap_enabled && (functions_enabled || types_enabled) {
         println!("\n{}\n", separator);
     }
 
     if overlap_enabled {
-        println!("=== Overlap Detection ===");
-        check_overlaps(
-            cli.paths,
-            cli.threshold,
-            cli.extensions.as_ref(),
-            cli.print,
-            cli.overlap_min_window,
-            cli.overlap_max_window,
-            cli.overlap_size_tolerance,
-            &cli.exclude,
-        )?;
+        if pair_format.is_text() {
+            println!("=== Overlap Detection ===");
+        }
+        let entries =
+            check_overlaps(&TypeScriptLanguage, ts_paths.clone(), options, pair_format, baseline)?;
+        all_entries.extend(entries);
     }
 
-    Ok(())
-}
-
-fn create_exclude_matcher(exclude_patterns: &[String]) -> Option<globset::GlobSet> {
-    if exclude_patterns.is_empty() {
-        return None;
+    // Run cross-language analysis if enabled
+    if pair_format.is_text()
+        && cross_language_enabled
+        && (functions_enabled || types_enabled || overlap_enabled)
+    {
+        println!("\n{}\n", separator);
     }
 
-    let mut builder = globset::GlobSetBuilder::new();
-    for pattern in exclude_patterns {
-        if let Ok(glob) = globset::Glob::new(pattern) {
-            builder.add(glob);
-        } else {
-            eprintln!("Warning: Invalid glob pattern: {}", pattern);
+    if cross_language_enabled {
+        if pair_format.is_text() {
+            println!("=== Cross-Language Similarity ===");
         }
+        let entries = check_cross_language(paths, options, pair_format, baseline)?;
+        all_entries.extend(entries);
     }
 
-    builder.build().ok()
+    Ok(all_entries)
 }
 
-#[allow(clippy::too_many_arguments)]
-fn check_types(
-    paths: Vec<String>,
-    threshold: f64,
-    extensions: Option<&Vec<String>>,
-    print: bool,
-    _include_types: bool,
+/// Which type definitions `--experimental-types` compares
+struct TypeFilters {
+    /// Only type aliases
     types_only: bool,
+    /// Only interfaces
     interfaces_only: bool,
-    allow_cross_kind: bool,
-    structural_weight: f64,
-    naming_weight: f64,
+    /// Also compare type literals with the type definitions
     include_type_literals: bool,
-    exclude_patterns: &[String],
-) -> anyhow::Result<()> {
-    use ignore::WalkBuilder;
+}
+
+fn check_types(
+    paths: &[String],
+    options: &PipelineOptions,
+    filters: &TypeFilters,
+    format: OutputFormat,
+    baseline: Option<&Baseline>,
+) -> anyhow::Result<Vec<ReportEntry>> {
     use similarity_core::{
         extract_type_literals_from_code, extract_types_from_code, find_similar_type_literals,
-        find_similar_types, TypeComparisonOptions, TypeKind,
+        find_similar_types, TypeKind,
     };
-    use std::collections::HashSet;
     use std::fs;
     use std::path::Path;
 
-    let default_extensions = vec!["ts", "tsx", "mts", "cts"];
-    let exts: Vec<&str> =
-        extensions.map_or(default_extensions, |v| v.iter().map(String::as_str).collect());
-
-    let exclude_matcher = create_exclude_matcher(exclude_patterns);
-    let mut files = Vec::new();
-    let mut visited = HashSet::new();
-
-    // Process each path
-    for path_str in &paths {
-        let path = Path::new(path_str);
-
-        if path.is_file() {
-            // If it's a file, check extension and add it
-            if let Some(ext) = path.extension() {
-                if let Some(ext_str) = ext.to_str() {
-                    if exts.contains(&ext_str) {
-                        if let Ok(canonical) = path.canonicalize() {
-                            if visited.insert(canonical.clone()) {
-                                files.push(path.to_path_buf());
-                            }
-                        }
-                    }
-                }
-            }
-        } else if path.is_dir() {
-            // If it's a directory, walk it respecting .gitignore
-            let walker = WalkBuilder::new(path).follow_links(false).build();
-
-            for entry in walker {
-                let entry = entry?;
-                let entry_path = entry.path();
-
-                // Skip if not a file
-                if !entry_path.is_file() {
-                    continue;
-                }
-
-                // Check if path should be excluded
-                if let Some(ref matcher) = exclude_matcher {
-                    if matcher.is_match(entry_path) {
-                        continue;
-                    }
-                }
+    let TypeFilters { types_only, interfaces_only, include_type_literals } = *filters;
+    let (threshold, print) = (options.compare.threshold, options.print);
+    let files = collect_language_files(&TypeScriptLanguage, paths, options)?;
 
-                // Check extension
-                if let Some(ext) = entry_path.extension() {
-                    if let Some(ext_str) = ext.to_str() {
-                        if exts.contains(&ext_str) {
-                            // Get canonical path to avoid duplicates
-                            if let Ok(canonical) = entry_path.canonicalize() {
-                                if visited.insert(canonical.clone()) {
-                                    files.push(entry_path.to_path_buf());
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        } else {
-            eprintln!("Warning: Path not found: {}", path_str);
+    if files.is_empty() {
+        if format.is_text() {
+            println!("No TypeScript files found in specified paths");
         }
+        return Ok(Vec::new());
     }
 
-    if files.is_empty() {
-        println!("No TypeScript files found in specified paths");
-        return Ok(());
+    if format.is_text() {
+        println!("Checking {} files for similar types...\n", files.len());
     }
 
-    println!("Checking {} files for similar types...\n", files.len());
-
     // Extract types from all files
     let mut all_types = Vec::new();
     let mut all_type_literals = Vec::new();
@@ -365,84 +531,74 @@ fn check_types(
         }
     }
 
+    // Drop types and type literals listed in .similarity-ignore
+    let ignore = SimilarityIgnore::discover(paths);
+    all_types.retain(|type_def| !ignore.is_ignored(Path::new(&type_def.file_path), &type_def.name));
+    all_type_literals
+        .retain(|literal| !ignore.is_ignored(Path::new(&literal.file_path), &literal.name));
+
     if all_types.is_empty() && all_type_literals.is_empty() {
-        println!("No type definitions or type literals found!");
-        return Ok(());
+        if format.is_text() {
+            println!("No type definitions or type literals found!");
+        }
+        return Ok(Vec::new());
     }
 
-    println!("Found {} type definitions", all_types.len());
-    if include_type_literals {
-        println!("Found {} type literals", all_type_literals.len());
+    if format.is_text() {
+        println!("Found {} type definitions", all_types.len());
+        if include_type_literals {
+            println!("Found {} type literals", all_type_literals.len());
+        }
     }
 
-    // Set up comparison options
-    let options = TypeComparisonOptions {
-        allow_cross_kind_comparison: allow_cross_kind,
-        structural_weight,
-        naming_weight,
-        ..Default::default()
-    };
-
     // Validate weights
-    if (structural_weight + naming_weight - 1.0).abs() > 0.001 {
+    let options = &options.types;
+    if (options.structural_weight + options.naming_weight - 1.0).abs() > 0.001 {
         eprintln!("Warning: structural_weight + naming_weight should equal 1.0");
     }
 
     // Find similar types across all files
-    let similar_pairs = find_similar_types(&all_types, threshold, &options);
+    let mut similar_pairs = find_similar_types(&all_types, threshold, options);
 
     // Find type literals similar to type definitions
-    let type_literal_pairs = if include_type_literals {
-        find_similar_type_literals(&all_type_literals, &all_types, threshold, &options)
+    let mut type_literal_pairs = if include_type_literals {
+        find_similar_type_literals(&all_type_literals, &all_types, threshold, options)
     } else {
         Vec::new()
     };
 
+    let mut entries = type_report_entries(&similar_pairs);
+    let mut literal_entries: Vec<ReportEntry> = type_literal_pairs
+        .iter()
+        .map(|pair| {
+            ReportEntry::new(
+                ReportKind::Type,
+                ReportLocation::new(
+                    relative_path(&pair.type_literal.file_path),
+                    &pair.type_literal.name,
+                    pair.type_literal.start_line as u32,
+                    pair.type_literal.end_line as u32,
+                ),
+                type_location(&pair.type_definition),
+                pair.result.similarity,
+            )
+        })
+        .collect();
+    if let Some(baseline) = baseline {
+        baseline.retain_new(&mut similar_pairs, &mut entries);
+        baseline.retain_new(&mut type_literal_pairs, &mut literal_entries);
+    }
+    entries.extend(literal_entries);
+
+    if !format.is_text() {
+        return Ok(entries);
+    }
+
     if similar_pairs.is_empty() && type_literal_pairs.is_empty() {
         println!("\nNo similar types found!");
     } else {
         if !similar_pairs.is_empty() {
-            println!("\nSimilar types found:");
-            println!("{}", "-".repeat(60));
-
-            for pair in &similar_pairs {
-                // Get relative paths
-                let relative_path1 = get_relative_path(&pair.type1.file_path);
-                let relative_path2 = get_relative_path(&pair.type2.file_path);
-
-                println!(
-                    "\nSimilarity: {:.2}% (structural: {:.2}%, naming: {:.2}%)",
-                    pair.result.similarity * 100.0,
-                    pair.result.structural_similarity * 100.0,
-                    pair.result.naming_similarity * 100.0
-                );
-                println!(
-                    "  {}:{} | L{}-{} similar-type: {} ({})",
-                    relative_path1,
-                    pair.type1.start_line,
-                    pair.type1.start_line,
-                    pair.type1.end_line,
-                    pair.type1.name,
-                    format_type_kind(&pair.type1.kind)
-                );
-                println!(
-                    "  {}:{} | L{}-{} similar-type: {} ({})",
-                    relative_path2,
-                    pair.type2.start_line,
-                    pair.type2.start_line,
-                    pair.type2.end_line,
-                    pair.type2.name,
-                    format_type_kind(&pair.type2.kind)
-                );
-
-                if print {
-                    show_type_details(&pair.type1);
-                    show_type_details(&pair.type2);
-                    show_comparison_details(&pair.result);
-                }
-            }
-
-            println!("\nTotal similar type pairs found: {}", similar_pairs.len());
+            display_similar_types(&similar_pairs, print);
         }
 
         if !type_literal_pairs.is_empty() {
@@ -450,8 +606,8 @@ fn check_types(
             println!("{}", "-".repeat(60));
 
             for pair in &type_literal_pairs {
-                let literal_path = get_relative_path(&pair.type_literal.file_path);
-                let def_path = get_relative_path(&pair.type_definition.file_path);
+                let literal_path = relative_path(&pair.type_literal.file_path);
+                let def_path = relative_path(&pair.type_definition.file_path);
 
                 println!(
                     "\nSimilarity: {:.2}% (structural: {:.2}%, naming: {:.2}%)",
@@ -487,48 +643,7 @@ fn check_types(
         }
     }
 
-    Ok(())
-}
-
-fn get_relative_path(file_path: &str) -> String {
-    if let Ok(current_dir) = std::env::current_dir() {
-        std::path::Path::new(file_path)
-            .strip_prefix(&current_dir)
-            .unwrap_or(std::path::Path::new(file_path))
-            .to_string_lossy()
-            .to_string()
-    } else {
-        file_path.to_string()
-    }
-}
-
-fn format_type_kind(kind: &similarity_core::TypeKind) -> &'static str {
-    match kind {
-        similarity_core::TypeKind::Interface => "interface",
-        similarity_core::TypeKind::TypeAlias => "type",
-        similarity_core::TypeKind::TypeLiteral => "type literal",
-    }
-}
-
-fn show_type_details(type_def: &similarity_core::TypeDefinition) {
-    println!("\n\x1b[36m--- {} ({}) ---\x1b[0m", type_def.name, format_type_kind(&type_def.kind));
-
-    if !type_def.generics.is_empty() {
-        println!("Generics: <{}>", type_def.generics.join(", "));
-    }
-
-    if !type_def.extends.is_empty() {
-        println!("Extends: {}", type_def.extends.join(", "));
-    }
-
-    if !type_def.properties.is_empty() {
-        println!("Properties:");
-        for prop in &type_def.properties {
-            let modifiers = if prop.readonly { "readonly " } else { "" };
-            let optional = if prop.optional { "?" } else { "" };
-            println!("  {}{}{}: {}", modifiers, prop.name, optional, prop.type_annotation);
-        }
-    }
+    Ok(entries)
 }
 
 fn show_type_literal_details(type_literal: &similarity_core::TypeLiteralDefinition) {
@@ -562,215 +677,3 @@ fn format_type_literal_context(context: &similarity_core::TypeLiteralContext) ->
         }
     }
 }
-
-#[allow(clippy::too_many_arguments)]
-fn check_overlaps(
-    paths: Vec<String>,
-    threshold: f64,
-    extensions: Option<&Vec<String>>,
-    print: bool,
-    min_window_size: u32,
-    max_window_size: u32,
-    size_tolerance: f64,
-    exclude_patterns: &[String],
-) -> anyhow::Result<()> {
-    use ignore::WalkBuilder;
-    use similarity_core::{find_overlaps_across_files, OverlapOptions};
-    use std::collections::{HashMap, HashSet};
-    use std::fs;
-    use std::path::Path;
-
-    let default_extensions = vec!["js", "ts", "jsx", "tsx", "mjs", "mts", "cjs", "cts"];
-    let exts: Vec<&str> =
-        extensions.map_or(default_extensions, |v| v.iter().map(String::as_str).collect());
-
-    let exclude_matcher = create_exclude_matcher(exclude_patterns);
-    let mut files = Vec::new();
-    let mut visited = HashSet::new();
-
-    // Process each path
-    for path_str in &paths {
-        let path = Path::new(path_str);
-
-        if path.is_file() {
-            // If it's a file, check extension and add it
-            if let Some(ext) = path.extension() {
-                if let Some(ext_str) = ext.to_str() {
-                    if exts.contains(&ext_str) {
-                        if let Ok(canonical) = path.canonicalize() {
-                            if visited.insert(canonical.clone()) {
-                                files.push(path.to_path_buf());
-                            }
-                        }
-                    }
-                }
-            }
-        } else if path.is_dir() {
-            // If it's a directory, walk it respecting .gitignore
-            let walker = WalkBuilder::new(path).follow_links(false).build();
-
-            for entry in walker {
-                let entry = entry?;
-                let entry_path = entry.path();
-
-                // Skip if not a file
-                if !entry_path.is_file() {
-                    continue;
-                }
-
-                // Check if path should be excluded
-                if let Some(ref matcher) = exclude_matcher {
-                    if matcher.is_match(entry_path) {
-                        continue;
-                    }
-                }
-
-                // Check extension
-                if let Some(ext) = entry_path.extension() {
-                    if let Some(ext_str) = ext.to_str() {
-                        if exts.contains(&ext_str) {
-                            // Get canonical path to avoid duplicates
-                            if let Ok(canonical) = entry_path.canonicalize() {
-                                if visited.insert(canonical.clone()) {
-                                    files.push(entry_path.to_path_buf());
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        } else {
-            eprintln!("Warning: Path not found: {}", path_str);
-        }
-    }
-
-    if files.is_empty() {
-        println!("No JavaScript/TypeScript files found in specified paths");
-        return Ok(());
-    }
-
-    println!("Checking {} files for overlapping code...\n", files.len());
-
-    // Read all file contents
-    let mut file_contents = HashMap::new();
-    for file in &files {
-        match fs::read_to_string(file) {
-            Ok(content) => {
-                let file_str = file.to_string_lossy().to_string();
-                file_contents.insert(file_str, content);
-            }
-            Err(e) => {
-                eprintln!("Error reading {}: {}", file.display(), e);
-            }
-        }
-    }
-
-    // Set up overlap options
-    let options = OverlapOptions { min_window_size, max_window_size, threshold, size_tolerance };
-
-    // Find overlaps
-    let overlaps = find_overlaps_across_files(&file_contents, &options)?;
-
-    if overlaps.is_empty() {
-        println!("\nNo code overlaps found!");
-    } else {
-        println!("\nCode overlaps found:");
-        println!("{}", "-".repeat(60));
-
-        for overlap_with_files in &overlaps {
-            let overlap = &overlap_with_files.overlap;
-            let source_path = get_relative_path(&overlap_with_files.source_file);
-            let target_path = get_relative_path(&overlap_with_files.target_file);
-
-            println!(
-                "\nSimilarity: {:.2}% | {} nodes | {}",
-                overlap.similarity * 100.0,
-                overlap.node_count,
-                overlap.node_type
-            );
-            println!(
-                "  {}:{} | L{}-{} in function: {}",
-                source_path,
-                overlap.source_lines.0,
-                overlap.source_lines.0,
-                overlap.source_lines.1,
-                overlap.source_function
-            );
-            println!(
-                "  {}:{} | L{}-{} in function: {}",
-                target_path,
-                overlap.target_lines.0,
-                overlap.target_lines.0,
-                overlap.target_lines.1,
-                overlap.target_function
-            );
-
-            if print {
-                // Extract and display the overlapping code
-                if let Some(source_content) = file_contents.get(&overlap_with_files.source_file) {
-                    if let Some(target_content) = file_contents.get(&overlap_with_files.target_file)
-                    {
-                        println!("\n\x1b[36m--- Source Code ---\x1b[0m");
-                        if let Ok(source_segment) = extract_code_lines(
-                            source_content,
-                            overlap.source_lines.0,
-                            overlap.source_lines.1,
-                        ) {
-                            println!("{}", source_segment);
-                        }
-
-                        println!("\n\x1b[36m--- Target Code ---\x1b[0m");
-                        if let Ok(target_segment) = extract_code_lines(
-                            target_content,
-                            overlap.target_lines.0,
-                            overlap.target_lines.1,
-                        ) {
-                            println!("{}", target_segment);
-                        }
-                    }
-                }
-            }
-        }
-
-        println!("\nTotal overlaps found: {}", overlaps.len());
-    }
-
-    Ok(())
-}
-
-fn extract_code_lines(code: &str, start_line: u32, end_line: u32) -> Result<String, String> {
-    let lines: Vec<_> = code.lines().collect();
-
-    if start_line as usize > lines.len() || end_line as usize > lines.len() {
-        return Err("Line numbers out of bounds".to_string());
-    }
-
-    let start = (start_line as usize).saturating_sub(1);
-    let end = (end_line as usize).min(lines.len());
-
-    Ok(lines[start..end].join("\n"))
-}
-
-fn show_comparison_details(result: &similarity_core::TypeComparisonResult) {
-    if !result.differences.missing_properties.is_empty() {
-        println!("Missing properties: {}", result.differences.missing_properties.join(", "));
-    }
-
-    if !result.differences.extra_properties.is_empty() {
-        println!("Extra properties: {}", result.differences.extra_properties.join(", "));
-    }
-
-    if !result.differences.type_mismatches.is_empty() {
-        println!("Type mismatches:");
-        for mismatch in &result.differences.type_mismatches {
-            println!("  {}: {} vs {}", mismatch.property, mismatch.type1, mismatch.type2);
-        }
-    }
-
-    if !result.differences.optionality_differences.is_empty() {
-        println!(
-            "Optionality differences: {}",
-            result.differences.optionality_differences.join(", ")
-        );
-    }
-}
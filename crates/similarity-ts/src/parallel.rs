@@ -1,32 +1,69 @@
 use rayon::prelude::*;
+use similarity_core::cli_cache::{file_hash, AnalysisCache, FunctionKey, PairCache};
+use similarity_core::cli_changes::ChangedLines;
+use similarity_core::lsh::{
+    pair_count, CandidateStats, LshIndex, LshOptions, MinHashSignature, NodeKind,
+};
 use similarity_core::{
-    extract_functions, find_similar_functions_fast, find_similar_functions_in_file,
-    FastSimilarityOptions, FunctionDefinition, SimilarityResult, TSEDOptions,
+    compare_function_trees, find_similar_parsed_functions, parse_functions, ParsedFunction,
+    SimilarityResult, TSEDOptions, FAST_FINGERPRINT_THRESHOLD,
 };
 use std::fs;
 use std::path::PathBuf;
 
-/// File with its content and extracted functions
+/// File with its content and extracted functions, each parsed once
 #[derive(Debug)]
 pub struct FileData {
     pub path: PathBuf,
     pub content: String,
-    pub functions: Vec<FunctionDefinition>,
+    pub functions: Vec<ParsedFunction>,
+    /// MinHash signature of each function, filled by [`compute_signatures`]
+    pub signatures: Vec<Option<MinHashSignature>>,
+}
+
+impl FileData {
+    /// Functions with a parsed tree, the only ones that can be compared
+    fn comparable_functions(&self) -> usize {
+        self.functions.iter().filter(|func| func.tree.is_some()).count()
+    }
+
+    fn indexed_signatures(&self) -> impl Iterator<Item = (usize, &MinHashSignature)> {
+        self.signatures.iter().enumerate().filter_map(|(i, sig)| Some((i, sig.as_ref()?)))
+    }
 }
 
-/// Load and parse files in parallel
-pub fn load_files_parallel(files: &[PathBuf]) -> Vec<FileData> {
+/// Compute the MinHash signatures used for LSH candidate generation
+pub fn compute_signatures(file_data: &mut [FileData], lsh: &LshOptions) {
+    file_data.par_iter_mut().for_each(|data| {
+        data.signatures = data
+            .functions
+            .iter()
+            .map(|func| MinHashSignature::from_tree(func.tree.as_ref()?, NodeKind::Value, lsh))
+            .collect();
+    });
+}
+
+/// Load and parse files in parallel.
+/// With a cache, files whose content was analyzed before are not parsed again.
+pub fn load_files_parallel(files: &[PathBuf], cache: Option<&AnalysisCache>) -> Vec<FileData> {
     files
         .par_iter()
         .filter_map(|file| {
             match fs::read_to_string(file) {
                 Ok(content) => {
                     let filename = file.to_string_lossy();
-                    // Extract functions, skip if parse error
-                    match extract_functions(&filename, &content) {
-                        Ok(functions) => Some(FileData { path: file.clone(), content, functions }),
-                        Err(_) => None, // Skip files with parse errors
-                    }
+                    let parse = || parse_functions(&filename, &content);
+                    let functions = match cache {
+                        Some(cache) => cache.get_or_insert_with(file, &content, parse),
+                        None => parse(),
+                    };
+                    // Skip files with parse errors
+                    functions.ok().map(|functions| FileData {
+                        path: file.clone(),
+                        content,
+                        functions,
+                        signatures: Vec::new(),
+                    })
                 }
                 Err(e) => {
                     eprintln!("Error reading {}: {}", file.display(), e);
@@ -37,96 +74,132 @@ pub fn load_files_parallel(files: &[PathBuf]) -> Vec<FileData> {
         .collect()
 }
 
-/// Check for duplicates within files in parallel
+/// Check for duplicates within files in parallel.
+/// With `changes`, only files with changed lines are checked. With `lsh`, only functions
+/// whose signatures collide are compared.
 pub fn check_within_file_duplicates_parallel(
-    files: &[PathBuf],
+    file_data: &[FileData],
     threshold: f64,
     options: &TSEDOptions,
     fast_mode: bool,
+    changes: Option<&ChangedLines>,
+    lsh: Option<&LshOptions>,
+    stats: &CandidateStats,
 ) -> Vec<(PathBuf, Vec<SimilarityResult>)> {
-    files
+    let fingerprint_threshold = fast_mode.then_some(FAST_FINGERPRINT_THRESHOLD);
+    file_data
         .par_iter()
-        .filter_map(|file| match fs::read_to_string(file) {
-            Ok(code) => {
-                let file_str = file.to_string_lossy();
+        .filter(|data| changes.is_none_or(|changes| changes.is_file_changed(&data.path)))
+        .filter_map(|data| {
+            let candidates =
+                lsh.map(|lsh| LshIndex::new(lsh, data.indexed_signatures()).candidates());
+            let possible = pair_count(data.comparable_functions());
+            stats.record(possible, candidates.as_ref().map_or(possible, Vec::len));
 
-                let similar_pairs = if fast_mode {
-                    let fast_options = FastSimilarityOptions {
-                        fingerprint_threshold: 0.3,
-                        similarity_threshold: threshold,
-                        tsed_options: options.clone(),
-                        debug_stats: false,
-                    };
-                    find_similar_functions_fast(&file_str, &code, &fast_options).ok()
-                } else {
-                    find_similar_functions_in_file(&file_str, &code, threshold, options).ok()
-                };
-
-                similar_pairs.and_then(|pairs| {
-                    if pairs.is_empty() {
-                        None
-                    } else {
-                        Some((file.clone(), pairs))
-                    }
-                })
-            }
-            Err(_) => None,
+            let pairs = find_similar_parsed_functions(
+                &data.functions,
+                threshold,
+                options,
+                fingerprint_threshold,
+                candidates.as_deref(),
+            );
+            (!pairs.is_empty()).then(|| (data.path.clone(), pairs))
         })
         .collect()
 }
 
-/// Check for duplicates across files using parallel processing
+/// Check for duplicates across files using parallel processing.
+/// With `changes`, only pairs involving a changed function are compared, changed side first.
+/// With `pairs`, pairs of functions from files unchanged since the previous run are not
+/// compared again. With `lsh`, only functions whose signatures collide are compared.
 pub fn check_cross_file_duplicates_parallel(
     file_data: &[FileData],
     threshold: f64,
     options: &TSEDOptions,
-    _fast_mode: bool,
+    changes: Option<&ChangedLines>,
+    pairs: Option<&PairCache>,
+    lsh: Option<&LshOptions>,
+    stats: &CandidateStats,
 ) -> Vec<(String, SimilarityResult, String)> {
     // Prepare all function pairs with file information
     let mut all_functions = Vec::new();
+    let mut keys = Vec::new();
+    let mut changed = Vec::new();
+    let mut signatures = Vec::new();
     for data in file_data {
         let filename = data.path.to_string_lossy().to_string();
+        let file = pairs.map(|_| file_hash(&data.content));
+        let lines: Vec<&str> = data.content.lines().collect();
         for func in &data.functions {
-            all_functions.push((filename.clone(), data.content.clone(), func.clone()));
+            let def = &func.definition;
+            // The whole function text: the size penalty depends on its line count
+            keys.push(file.map(|file| {
+                let start = (def.start_line as usize).saturating_sub(1).min(lines.len());
+                let end = (def.end_line as usize).clamp(start, lines.len());
+                FunctionKey::new(file, &lines[start..end].join("\n"))
+            }));
+            changed.push(
+                changes.is_none_or(|changes| {
+                    changes.touches(&data.path, def.start_line, def.end_line)
+                }),
+            );
+            all_functions.push((filename.clone(), func));
         }
+        signatures.extend(data.signatures.iter().map(Option::as_ref));
     }
 
-    // Generate all cross-file pairs
+    // Generate the cross-file pairs: every pair, or only the LSH collisions
     let mut pairs_to_check = Vec::new();
-    for i in 0..all_functions.len() {
-        for j in (i + 1)..all_functions.len() {
-            let (file1, _, _) = &all_functions[i];
-            let (file2, _, _) = &all_functions[j];
-
-            // Only check across different files
-            if file1 != file2 {
-                pairs_to_check.push((i, j));
+    let mut push_pair = |i: usize, j: usize| {
+        // Only check across different files
+        let ((file1, func1), (file2, func2)) = (&all_functions[i], &all_functions[j]);
+        if file1 == file2 || func1.tree.is_none() || func2.tree.is_none() {
+            return;
+        }
+        if changed[i] {
+            pairs_to_check.push((i, j));
+        } else if changed[j] {
+            pairs_to_check.push((j, i));
+        }
+    };
+    match lsh {
+        Some(lsh) => {
+            let indexed = signatures.iter().enumerate().filter_map(|(i, sig)| Some((i, (*sig)?)));
+            for (i, j) in LshIndex::new(lsh, indexed).candidates() {
+                push_pair(i, j);
+            }
+        }
+        None => {
+            for i in 0..all_functions.len() {
+                for j in (i + 1)..all_functions.len() {
+                    push_pair(i, j);
+                }
             }
         }
     }
+    let per_file: Vec<usize> = file_data.iter().map(FileData::comparable_functions).collect();
+    let same_file: usize = per_file.iter().map(|&n| pair_count(n)).sum();
+    stats.record(pair_count(per_file.iter().sum()) - same_file, pairs_to_check.len());
 
-    // Process pairs in parallel
+    // Process pairs in parallel; the trees were parsed once when loading the files
     pairs_to_check
         .into_par_iter()
         .filter_map(|(i, j)| {
-            let (file1, content1, func1) = &all_functions[i];
-            let (file2, content2, func2) = &all_functions[j];
+            let (file1, func1) = &all_functions[i];
+            let (file2, func2) = &all_functions[j];
+            let (tree1, tree2) = (func1.tree.as_ref()?, func2.tree.as_ref()?);
+            let (def1, def2) = (&func1.definition, &func2.definition);
 
-            // Use core's compare_functions
-            match similarity_core::compare_functions(func1, func2, content1, content2, options) {
-                Ok(similarity) => {
-                    if similarity >= threshold {
-                        Some((
-                            file1.clone(),
-                            SimilarityResult::new(func1.clone(), func2.clone(), similarity),
-                            file2.clone(),
-                        ))
-                    } else {
-                        None
-                    }
-                }
-                Err(_) => None,
-            }
+            let compare = || compare_function_trees(def1, def2, tree1, tree2, options);
+            let similarity = match (pairs, keys[i], keys[j]) {
+                (Some(pairs), Some(key1), Some(key2)) => pairs.similar(key1, key2, compare)?,
+                _ => Some(compare()).filter(|&similarity| similarity >= threshold)?,
+            };
+            Some((
+                file1.clone(),
+                SimilarityResult::new(def1.clone(), def2.clone(), similarity),
+                file2.clone(),
+            ))
         })
         .collect()
 }
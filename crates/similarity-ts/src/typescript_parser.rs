@@ -1,6 +1,6 @@
 use similarity_core::function_extractor::extract_functions;
 use similarity_core::language_parser::{
-    GenericFunctionDef, GenericTypeDef, Language, LanguageParser,
+    GenericFieldDef, GenericFunctionDef, GenericTypeDef, Language, LanguageParser, TypeDefKind,
 };
 use similarity_core::parser::parse_and_convert_to_tree;
 use similarity_core::tree::TreeNode;
@@ -80,13 +80,20 @@ impl LanguageParser for TypeScriptParser {
             .map(|t| GenericTypeDef {
                 name: t.name,
                 kind: match t.kind {
-                    TypeKind::Interface => "interface".to_string(),
-                    TypeKind::TypeAlias => "type_alias".to_string(),
-                    TypeKind::TypeLiteral => "type_literal".to_string(),
+                    TypeKind::Interface => TypeDefKind::Interface,
+                    TypeKind::TypeAlias => TypeDefKind::TypeAlias,
+                    TypeKind::TypeLiteral => TypeDefKind::Other("type_literal".to_string()),
+                    TypeKind::Struct => TypeDefKind::Struct,
+                    TypeKind::Enum => TypeDefKind::Enum,
+                    TypeKind::Class => TypeDefKind::Class,
                 },
                 start_line: t.start_line as u32,
                 end_line: t.end_line as u32,
-                fields: t.properties.into_iter().map(|p| p.name).collect(),
+                fields: t
+                    .properties
+                    .into_iter()
+                    .map(|p| GenericFieldDef::new(p.name, p.type_annotation))
+                    .collect(),
             })
             .collect())
     }
@@ -134,8 +141,9 @@ type UserID = string | number;
         let types = parser.extract_types(source, "test.ts").unwrap();
         assert_eq!(types.len(), 2);
         assert_eq!(types[0].name, "User");
-        assert_eq!(types[0].kind, "interface");
+        assert_eq!(types[0].kind, TypeDefKind::Interface);
+        assert_eq!(types[0].fields[0], GenericFieldDef::new("name", "string"));
         assert_eq!(types[1].name, "UserID");
-        assert_eq!(types[1].kind, "type_alias");
+        assert_eq!(types[1].kind, TypeDefKind::TypeAlias);
     }
 }
@@ -330,3 +330,534 @@ export function found() {
         .success()
         .stdout(predicate::str::contains("No duplicate functions found"));
 }
+
+#[test]
+fn test_json_output_format() {
+    let dir = tempdir().unwrap();
+    let sample_path = dir.path().join("sample.ts");
+
+    fs::write(
+        &sample_path,
+        r#"
+export function calculateSum(numbers: number[]): number {
+    let total = 0;
+    for (const num of numbers) {
+        total += num;
+    }
+    return total;
+}
+
+export function computeTotal(values: number[]): number {
+    let sum = 0;
+    for (const val of values) {
+        sum += val;
+    }
+    return sum;
+}
+"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("similarity-ts").unwrap();
+    cmd.arg(dir.path())
+        .arg("-t")
+        .arg("0.8")
+        .arg("--no-size-penalty")
+        .arg("--format")
+        .arg("json")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("{"))
+        .stdout(predicate::str::contains("\"tool\": \"similarity-ts\""))
+        .stdout(predicate::str::contains("\"kind\": \"function\""))
+        .stdout(predicate::str::contains("\"name\": \"calculateSum\""))
+        .stdout(predicate::str::contains("\"impact\":"))
+        .stdout(predicate::str::contains("\"priority\":"))
+        .stdout(predicate::str::contains("Analyzing").not());
+}
+
+#[test]
+fn test_sarif_output_format() {
+    let dir = tempdir().unwrap();
+    let sample_path = dir.path().join("sample.ts");
+
+    fs::write(
+        &sample_path,
+        r#"
+export function calculateSum(numbers: number[]): number {
+    let total = 0;
+    for (const num of numbers) {
+        total += num;
+    }
+    return total;
+}
+
+export function computeTotal(values: number[]): number {
+    let sum = 0;
+    for (const val of values) {
+        sum += val;
+    }
+    return sum;
+}
+"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("similarity-ts").unwrap();
+    cmd.arg(dir.path())
+        .arg("-t")
+        .arg("0.8")
+        .arg("--no-size-penalty")
+        .arg("--format")
+        .arg("sarif")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"version\": \"2.1.0\""))
+        .stdout(predicate::str::contains("\"ruleId\": \"duplicate-function\""))
+        .stdout(predicate::str::contains("\"relatedLocations\""))
+        .stdout(predicate::str::contains("sample.ts"));
+}
+
+#[test]
+fn test_show_diff_lists_differing_statements() {
+    let dir = tempdir().unwrap();
+    let sample_path = dir.path().join("sample.ts");
+
+    fs::write(
+        &sample_path,
+        r#"
+export function calculateSum(numbers: number[]): number {
+    let total = 0;
+    for (const num of numbers) {
+        total += num;
+    }
+    return total;
+}
+
+export function computeTotal(numbers: number[]): number {
+    let total = 0;
+    for (const num of numbers) {
+        total += num;
+    }
+    if (total < 0) {
+        throw new Error("negative");
+    }
+    return total;
+}
+"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("similarity-ts").unwrap();
+    cmd.arg(dir.path())
+        .arg("-t")
+        .arg("0.7")
+        .arg("--no-size-penalty")
+        .arg("--show-diff")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Differences:"))
+        .stdout(predicate::str::contains("+ IfStatement"));
+}
+
+#[test]
+fn test_project_config_applies_under_cli_flags() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("sample.ts"),
+        r#"
+export function calculateSum(numbers: number[]): number {
+    let total = 0;
+    for (const num of numbers) {
+        total += num;
+    }
+    return total;
+}
+
+export function computeTotal(values: number[]): number {
+    let sum = 0;
+    for (const val of values) {
+        sum += val;
+    }
+    return sum;
+}
+"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("similarity.toml"),
+        "threshold = 0.5\n\n[typescript]\nthreshold = 0.999\nsize-penalty = false\n",
+    )
+    .unwrap();
+
+    // The typescript section raises the threshold above the pair's similarity
+    Command::cargo_bin("similarity-ts")
+        .unwrap()
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No duplicate functions found"));
+
+    // An explicit flag wins over the config file
+    Command::cargo_bin("similarity-ts")
+        .unwrap()
+        .arg(dir.path())
+        .arg("-t")
+        .arg("0.8")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("calculateSum"));
+}
+
+#[test]
+fn test_baseline_hides_known_duplicates() {
+    let dir = tempdir().unwrap();
+    let pair = r#"
+export function calculateSum(numbers: number[]): number {
+    let total = 0;
+    for (const num of numbers) {
+        total += num;
+    }
+    return total;
+}
+
+export function computeTotal(values: number[]): number {
+    let sum = 0;
+    for (const val of values) {
+        sum += val;
+    }
+    return sum;
+}
+"#;
+    fs::write(dir.path().join("sample.ts"), pair).unwrap();
+
+    Command::cargo_bin("similarity-ts")
+        .unwrap()
+        .current_dir(dir.path())
+        .args([".", "--threshold", "0.7", "--no-size-penalty", "--write-baseline"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Wrote 1 duplicate pairs to baseline"));
+    assert!(dir.path().join(".similarity-baseline.json").exists());
+
+    // Moving the known pair down does not resurface it
+    fs::write(dir.path().join("sample.ts"), format!("const a = 1;\n{pair}")).unwrap();
+    Command::cargo_bin("similarity-ts")
+        .unwrap()
+        .current_dir(dir.path())
+        .args([
+            ".",
+            "--threshold",
+            "0.7",
+            "--no-size-penalty",
+            "--baseline",
+            ".similarity-baseline.json",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No duplicate functions found"))
+        .stdout(predicate::str::contains("1 known duplicate pairs hidden by the baseline"));
+
+    // A newly introduced copy is reported
+    fs::write(
+        dir.path().join("other.ts"),
+        r#"
+export function addAll(items: number[]): number {
+    let acc = 0;
+    for (const item of items) {
+        acc += item;
+    }
+    return acc;
+}
+"#,
+    )
+    .unwrap();
+    Command::cargo_bin("similarity-ts")
+        .unwrap()
+        .current_dir(dir.path())
+        .args([
+            ".",
+            "--threshold",
+            "0.7",
+            "--no-size-penalty",
+            "--baseline",
+            ".similarity-baseline.json",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("addAll"));
+}
+
+#[test]
+fn test_diff_file_scopes_to_changed_functions() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("a.ts"),
+        r#"export function calculateSum(numbers: number[]): number {
+    let total = 0;
+    for (const num of numbers) {
+        total += num;
+    }
+    return total;
+}
+"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("b.ts"),
+        r#"export function greet(name: string): string {
+    const greeting = "Hello";
+    const message = greeting + ", " + name;
+    return message.trim();
+}
+
+export function computeTotal(values: number[]): number {
+    let sum = 0;
+    for (const val of values) {
+        sum += val;
+    }
+    return sum;
+}
+"#,
+    )
+    .unwrap();
+
+    let diff = |start: u32, count: u32| {
+        format!("--- a/b.ts\n+++ b/b.ts\n@@ -{start},{count} +{start},{count} @@\n")
+    };
+    let run = |diff: String| {
+        fs::write(dir.path().join("changes.diff"), diff).unwrap();
+        Command::cargo_bin("similarity-ts")
+            .unwrap()
+            .current_dir(dir.path())
+            .args([".", "--threshold", "0.85", "--no-size-penalty", "--diff-file", "changes.diff"])
+            .assert()
+            .success()
+    };
+
+    // Touching computeTotal reports its copy in the unchanged file
+    run(diff(9, 1)).stdout(predicate::str::contains("calculateSum"));
+    // Touching only greet leaves the old duplicate out
+    run(diff(2, 1)).stdout(predicate::str::contains("No duplicate functions found"));
+}
+
+#[test]
+fn test_clone_classes_group_copies() {
+    let dir = tempdir().unwrap();
+    let copy = |name: &str| {
+        format!(
+            r#"
+export function {name}(numbers: number[]): number {{
+    let total = 0;
+    for (const num of numbers) {{
+        total += num;
+    }}
+    return total;
+}}
+"#
+        )
+    };
+    let source: String = ["sumA", "sumB", "sumC", "sumD"].iter().map(|name| copy(name)).collect();
+    fs::write(dir.path().join("sample.ts"), source).unwrap();
+
+    Command::cargo_bin("similarity-ts")
+        .unwrap()
+        .current_dir(dir.path())
+        .args([".", "--threshold", "0.9", "--no-size-penalty", "--clone-classes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Class 1: 4 functions"))
+        .stdout(predicate::str::contains("21 removable lines"))
+        .stdout(predicate::str::contains("Similarity:").not());
+
+    Command::cargo_bin("similarity-ts")
+        .unwrap()
+        .current_dir(dir.path())
+        .args([
+            ".",
+            "--threshold",
+            "0.9",
+            "--no-size-penalty",
+            "--clone-classes",
+            "--format",
+            "json",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"clone_classes\""))
+        .stdout(predicate::str::contains("\"removable_lines\": 21"));
+}
+
+#[test]
+fn test_incremental_reuses_parsed_files() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("sample.ts"),
+        r#"export function calculateSum(numbers: number[]): number {
+    let total = 0;
+    for (const num of numbers) {
+        total += num;
+    }
+    return total;
+}
+
+export function computeTotal(values: number[]): number {
+    let sum = 0;
+    for (const val of values) {
+        sum += val;
+    }
+    return sum;
+}
+"#,
+    )
+    .unwrap();
+
+    let run = || {
+        Command::cargo_bin("similarity-ts")
+            .unwrap()
+            .current_dir(dir.path())
+            .args([".", "--threshold", "0.7", "--no-size-penalty", "--incremental"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("calculateSum"))
+            .stdout(predicate::str::contains("computeTotal"))
+    };
+
+    run().stdout(predicate::str::contains("Reused 0 of 1 files from the cache"));
+    assert!(dir.path().join(".similarity-cache/.gitignore").exists());
+    run().stdout(predicate::str::contains("Reused 1 of 1 files from the cache"));
+}
+
+#[test]
+fn test_incremental_reuses_cross_file_comparisons() {
+    let dir = tempdir().unwrap();
+    let function = |name: &str| {
+        format!(
+            r#"export function {name}(numbers: number[]): number {{
+    let total = 0;
+    for (const num of numbers) {{
+        total += num;
+    }}
+    return total;
+}}
+"#
+        )
+    };
+    fs::write(dir.path().join("a.ts"), function("calculateSum")).unwrap();
+    fs::write(dir.path().join("b.ts"), function("computeTotal")).unwrap();
+
+    let run = || {
+        Command::cargo_bin("similarity-ts")
+            .unwrap()
+            .current_dir(dir.path())
+            .args([".", "--threshold", "0.7", "--no-size-penalty", "--incremental"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("calculateSum"))
+            .stdout(predicate::str::contains("computeTotal"))
+    };
+
+    run().stdout(predicate::str::contains("Reused 0 of 1 cross-file comparisons"));
+    run().stdout(predicate::str::contains("Reused 1 of 1 cross-file comparisons"));
+
+    // Only the pairs with the new file are compared
+    fs::write(dir.path().join("c.ts"), function("addAll")).unwrap();
+    run()
+        .stdout(predicate::str::contains("addAll"))
+        .stdout(predicate::str::contains("Reused 1 of 3 cross-file comparisons"));
+}
+
+#[test]
+fn test_lsh_compares_colliding_functions() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("a.ts"),
+        r#"export function calculateSum(numbers: number[]): number {
+    let total = 0;
+    for (const num of numbers) {
+        total += num;
+    }
+    return total;
+}
+"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("b.ts"),
+        r#"export function computeTotal(values: number[]): number {
+    let sum = 0;
+    for (const value of values) {
+        sum += value;
+    }
+    return sum;
+}
+
+export class Greeter {
+    constructor(private name: string) {}
+    greet(): void {
+        console.log(`Hello, ${this.name}`);
+        console.log("Welcome");
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    Command::cargo_bin("similarity-ts")
+        .unwrap()
+        .current_dir(dir.path())
+        .args([".", "--threshold", "0.7", "--no-size-penalty", "--lsh", "--stats"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("calculateSum"))
+        .stdout(predicate::str::contains("computeTotal"))
+        .stderr(predicate::str::contains("Candidate pairs:"))
+        .stderr(predicate::str::contains("pruned"));
+}
+
+#[test]
+fn test_cross_language_duplicates() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("validate.ts"),
+        r#"export function isValidEmail(email: string): boolean {
+    if (email.length === 0) {
+        return false;
+    }
+    const parts = email.split("@");
+    return parts.length === 2 && parts[1].includes(".");
+}
+"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("validate.py"),
+        r#"def is_valid_email(email: str) -> bool:
+    """Check that an email address looks valid."""
+    if len(email) == 0:
+        return False
+    parts = email.split("@")
+    return len(parts) == 2 and "." in parts[1]
+"#,
+    )
+    .unwrap();
+
+    Command::cargo_bin("similarity-ts")
+        .unwrap()
+        .current_dir(dir.path())
+        .args([".", "--cross-language", "--no-functions", "--no-size-penalty"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("=== Cross-Language Similarity ==="))
+        .stdout(predicate::str::contains("isValidEmail"))
+        .stdout(predicate::str::contains("is_valid_email"));
+
+    // Without the flag, Python files are not scanned
+    Command::cargo_bin("similarity-ts")
+        .unwrap()
+        .current_dir(dir.path())
+        .args([".", "--no-size-penalty"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("is_valid_email").not());
+}
@@ -0,0 +1,552 @@
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use similarity_core::cli_baseline::{
+    finish_baseline, load_baseline, Baseline, DEFAULT_BASELINE_FILE,
+};
+use similarity_core::cli_clone_classes::Linkage;
+use similarity_core::cli_config::{load_settings, SimilaritySettings};
+use similarity_core::cli_file_utils::{collect_files, create_exclude_matcher};
+use similarity_core::cli_output::{FailThresholds, OutputFormat, Report, ReportEntry};
+use similarity_core::cli_parallel::CompareOptions;
+use similarity_core::cli_pipeline::{
+    check_overlaps, check_paths, check_types, LanguageSupport, PipelineOptions,
+};
+use similarity_core::language_parser::Language;
+use similarity_core::lsh::LshOptions;
+use similarity_core::{OverlapOptions, TSEDOptions};
+use similarity_elixir::language::ElixirLanguage;
+use similarity_generic::language::GenericLanguage;
+use similarity_py::language::PythonLanguage;
+use similarity_rs::language::RustLanguage;
+use similarity_ts::check::CheckOptions;
+use similarity_ts::cross_language::check_cross_language;
+use similarity_ts::language::TypeScriptLanguage;
+use std::num::NonZeroU32;
+use std::path::PathBuf;
+
+/// Analyzed languages in report order; JavaScript files are analyzed with TypeScript
+const LANGUAGES: [Language; 10] = [
+    Language::TypeScript,
+    Language::Python,
+    Language::Rust,
+    Language::Elixir,
+    Language::Go,
+    Language::Java,
+    Language::C,
+    Language::Cpp,
+    Language::CSharp,
+    Language::Ruby,
+];
+
+#[derive(Parser)]
+#[command(name = "similarity")]
+#[command(about = "Code similarity analyzer for every supported language")]
+#[command(version)]
+struct Cli {
+    /// Paths to analyze (files or directories)
+    #[arg(default_value = ".")]
+    paths: Vec<String>,
+
+    /// Only analyze these languages (e.g. typescript,python or ts,py) [default: all]
+    #[arg(long, value_delimiter = ',')]
+    languages: Option<Vec<String>>,
+
+    /// Print code in output
+    #[arg(short, long)]
+    print: bool,
+
+    /// Similarity threshold (0.0-1.0) [default: 0.87 for TypeScript, 0.85 otherwise]
+    #[arg(short, long)]
+    threshold: Option<f64>,
+
+    /// Minimum lines for functions to be considered [default: 3]
+    #[arg(short, long)]
+    min_lines: Option<u32>,
+
+    /// Minimum tokens for functions to be considered [default: 30 for Rust]
+    #[arg(long)]
+    min_tokens: Option<u32>,
+
+    /// Rename cost for APTED algorithm [default: 0.3]
+    #[arg(short, long)]
+    rename_cost: Option<f64>,
+
+    /// Disable size penalty for very different sized functions
+    #[arg(long)]
+    no_size_penalty: bool,
+
+    /// Disable fast mode with bloom filter pre-filtering
+    #[arg(long)]
+    no_fast: bool,
+
+    /// Only compare functions whose MinHash signatures share an LSH band
+    #[arg(long)]
+    lsh: bool,
+
+    /// Number of LSH bands; more bands find more candidate pairs (higher recall) [default: 32]
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    lsh_bands: Option<u32>,
+
+    /// MinHash values per LSH band; more rows find fewer candidate pairs (higher precision) [default: 3]
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    lsh_rows: Option<u32>,
+
+    /// Print how many function pairs were compared and how many candidate generation pruned
+    #[arg(long)]
+    stats: bool,
+
+    /// Skip test functions (Rust)
+    #[arg(long)]
+    skip_test: bool,
+
+    /// Disable function similarity
+    #[arg(long)]
+    no_functions: bool,
+
+    /// Disable type similarity (TypeScript, Python, Rust and the generic languages)
+    #[arg(long)]
+    no_types: bool,
+
+    /// Disable overlap detection
+    #[arg(long)]
+    no_overlap: bool,
+
+    /// Also compare TypeScript/JavaScript functions with Python functions
+    #[arg(long)]
+    cross_language: bool,
+
+    /// Minimum window size for overlap detection (number of nodes) [default: 8]
+    #[arg(long)]
+    overlap_min_window: Option<u32>,
+
+    /// Maximum window size for overlap detection (number of nodes) [default: 25]
+    #[arg(long)]
+    overlap_max_window: Option<u32>,
+
+    /// Size tolerance for overlap detection (0.0-1.0) [default: 0.25]
+    #[arg(long)]
+    overlap_size_tolerance: Option<f64>,
+
+    /// Exclude paths matching the given patterns (can be specified multiple times)
+    #[arg(long)]
+    exclude: Vec<String>,
+
+    /// Output format (text, json, sarif)
+    #[arg(long, default_value = "text")]
+    format: OutputFormat,
+
+    /// Project config file (default: nearest similarity.toml, pyproject.toml or package.json)
+    #[arg(long)]
+    project_config: Option<PathBuf>,
+
+    /// Only report duplicate pairs that are not recorded in this baseline file
+    #[arg(long, value_name = "FILE")]
+    baseline: Option<PathBuf>,
+
+    /// Record the current duplicate pairs as a baseline [default: .similarity-baseline.json]
+    #[arg(long, value_name = "FILE", num_args = 0..=1, default_missing_value = DEFAULT_BASELINE_FILE)]
+    write_baseline: Option<PathBuf>,
+
+    /// Exit with code 3 when any duplicate is reported
+    #[arg(long)]
+    fail_on_duplicates: bool,
+
+    /// Exit with code 3 when more duplicate pairs than this are reported
+    #[arg(long, value_name = "N")]
+    max_duplicates: Option<usize>,
+
+    /// Exit with code 3 when the reported pairs duplicate more lines than this
+    #[arg(long, value_name = "N")]
+    max_duplicated_lines: Option<u32>,
+
+    /// Group duplicate pairs into clone classes instead of listing every pair
+    #[arg(long)]
+    clone_classes: bool,
+
+    /// How pairs are merged into clone classes: single (transitive) or complete
+    #[arg(long, value_name = "LINKAGE", default_value = "single")]
+    linkage: Linkage,
+}
+
+impl Cli {
+    fn fail_thresholds(&self) -> FailThresholds {
+        FailThresholds {
+            fail_on_duplicates: self.fail_on_duplicates,
+            max_duplicates: self.max_duplicates,
+            max_duplicated_lines: self.max_duplicated_lines,
+        }
+    }
+
+    /// Languages selected with `--languages`, in report order
+    fn selected_languages(&self) -> Result<Vec<Language>> {
+        let Some(names) = &self.languages else {
+            return Ok(LANGUAGES.to_vec());
+        };
+        let selected = names
+            .iter()
+            .map(|name| {
+                let name = name.to_lowercase();
+                LANGUAGES
+                    .into_iter()
+                    .find(|language| language.config_name() == name)
+                    .or_else(|| Language::from_extension(&name).map(analyzer_language))
+                    .filter(|language| LANGUAGES.contains(language))
+                    .ok_or_else(|| anyhow!("Unsupported language: {}", name))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(LANGUAGES.into_iter().filter(|language| selected.contains(language)).collect())
+    }
+}
+
+/// Options for one language: command line flags win over the project config
+/// (including its `[<language>]` section), which wins over the language's defaults
+struct LanguageOptions {
+    threshold: f64,
+    rename_cost: f64,
+    min_lines: u32,
+    min_tokens: Option<u32>,
+    size_penalty: bool,
+    fast: bool,
+    lsh: Option<LshOptions>,
+    skip_test: bool,
+    overlap: OverlapOptions,
+    exclude: Option<globset::GlobSet>,
+}
+
+impl LanguageOptions {
+    fn resolve(cli: &Cli, settings: &SimilaritySettings, language: Language) -> Self {
+        let default_threshold = if language == Language::TypeScript { 0.87 } else { 0.85 };
+        let default_min_tokens = (language == Language::Rust).then_some(30);
+        let lsh = (cli.lsh || settings.lsh.unwrap_or(false)).then(|| {
+            LshOptions::new(
+                cli.lsh_bands.or(settings.lsh_bands.map(NonZeroU32::get)).unwrap_or(32) as usize,
+                cli.lsh_rows.or(settings.lsh_rows.map(NonZeroU32::get)).unwrap_or(3) as usize,
+            )
+        });
+        let exclude: Vec<String> = cli.exclude.iter().chain(&settings.exclude).cloned().collect();
+        let threshold = cli.threshold.or(settings.threshold).unwrap_or(default_threshold);
+        let overlap = OverlapOptions {
+            min_window_size: cli.overlap_min_window.or(settings.overlap_min_window).unwrap_or(8),
+            max_window_size: cli.overlap_max_window.or(settings.overlap_max_window).unwrap_or(25),
+            threshold,
+            size_tolerance: cli
+                .overlap_size_tolerance
+                .or(settings.overlap_size_tolerance)
+                .unwrap_or(0.25),
+        };
+
+        LanguageOptions {
+            threshold,
+            rename_cost: cli.rename_cost.or(settings.rename_cost).unwrap_or(0.3),
+            min_lines: cli.min_lines.or(settings.min_lines).unwrap_or(3),
+            min_tokens: cli.min_tokens.or(settings.min_tokens).or(default_min_tokens),
+            size_penalty: !cli.no_size_penalty && settings.size_penalty.unwrap_or(true),
+            fast: !cli.no_fast && settings.fast.unwrap_or(true),
+            lsh,
+            skip_test: cli.skip_test || settings.skip_test.unwrap_or(false),
+            overlap,
+            exclude: create_exclude_matcher(&exclude),
+        }
+    }
+
+    fn pipeline_options(&self, cli: &Cli, extensions: Vec<String>) -> PipelineOptions {
+        let mut tsed_options = TSEDOptions::default();
+        tsed_options.apted_options.rename_cost = self.rename_cost;
+        tsed_options.min_lines = self.min_lines;
+        tsed_options.min_tokens = self.min_tokens;
+        tsed_options.size_penalty = self.size_penalty;
+        tsed_options.skip_test = self.skip_test;
+
+        PipelineOptions {
+            compare: CompareOptions {
+                threshold: self.threshold,
+                tsed_options,
+                fast_mode: self.fast,
+                lsh: self.lsh,
+            },
+            extensions: Some(extensions),
+            print: cli.print,
+            stats: cli.stats,
+            overlap: self.overlap.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let languages = cli.selected_languages()?;
+
+    // Validate that at least one analyzer is enabled
+    let analyzers_enabled = !(cli.no_functions && cli.no_types && cli.no_overlap);
+    if !analyzers_enabled && !cli.cross_language {
+        eprintln!("Error: At least one analyzer must be enabled. Remove --no-functions, --no-types or --no-overlap, or pass --cross-language.");
+        return Err(anyhow!("No analyzer enabled"));
+    }
+
+    let format = cli.format;
+    let text = format.is_text();
+    // Grouped pairs are only printed as clone classes once every language has run
+    let pair_format = format.for_pairs(cli.clone_classes);
+    let fail_thresholds = cli.fail_thresholds();
+    let baseline = load_baseline(cli.baseline.as_deref(), cli.write_baseline.as_deref())?;
+    let mut report = Report::new("similarity", env!("CARGO_PKG_VERSION"));
+
+    // Walk the tree once for every language
+    let extensions: Vec<&str> = languages
+        .iter()
+        .flat_map(|language| analyzed_languages(*language))
+        .flat_map(|language| language.extensions().iter().copied())
+        .collect();
+    let files = collect_files(&cli.paths, &extensions)?;
+
+    if text {
+        println!("Analyzing code similarity...\n");
+    }
+
+    let separator = "-".repeat(60);
+    let mut analyzed = 0;
+    let cross_language =
+        languages.contains(&Language::TypeScript) && languages.contains(&Language::Python);
+    // With every analyzer disabled only the cross-language comparison runs
+    let languages = if analyzers_enabled { languages } else { Vec::new() };
+    for language in languages {
+        let language_files: Vec<PathBuf> = files
+            .iter()
+            .filter(|file| {
+                Language::from_filename(&file.to_string_lossy()).map(analyzer_language)
+                    == Some(language)
+            })
+            .cloned()
+            .collect();
+        let groups = load_settings(
+            cli.project_config.as_deref(),
+            &language_files,
+            &[language.config_name()],
+        )?;
+        let mut language_analyzed = false;
+        for (settings, files) in groups {
+            let options = LanguageOptions::resolve(&cli, &settings, language);
+            let language_files: Vec<String> = files
+                .iter()
+                .filter(|file| {
+                    options.exclude.as_ref().is_none_or(|matcher| !matcher.is_match(file))
+                })
+                .map(|file| file.to_string_lossy().to_string())
+                .collect();
+            if language_files.is_empty() {
+                continue;
+            }
+
+            // Files with different configs share the section of their language
+            if !language_analyzed {
+                if pair_format.is_text() {
+                    if analyzed > 0 {
+                        println!("\n{separator}\n");
+                    }
+                    println!("=== {} ===", display_name(language));
+                }
+                analyzed += 1;
+                language_analyzed = true;
+            }
+
+            let entries = check_language(
+                language,
+                language_files,
+                &options,
+                &cli,
+                pair_format,
+                baseline.as_ref(),
+            )?;
+            report.extend(entries);
+        }
+    }
+
+    // TypeScript/JavaScript functions are compared with the Python ones after both languages
+    if cross_language {
+        let files: Vec<PathBuf> = files
+            .iter()
+            .filter(|file| {
+                matches!(
+                    Language::from_filename(&file.to_string_lossy()).map(analyzer_language),
+                    Some(Language::TypeScript | Language::Python)
+                )
+            })
+            .cloned()
+            .collect();
+        let groups = load_settings(cli.project_config.as_deref(), &files, &["typescript"])?;
+        for (settings, files) in groups {
+            if !cli.cross_language && !settings.cross_language.unwrap_or(false) {
+                continue;
+            }
+            let options = LanguageOptions::resolve(&cli, &settings, Language::TypeScript);
+            let files: Vec<String> = files
+                .iter()
+                .filter(|file| {
+                    options.exclude.as_ref().is_none_or(|matcher| !matcher.is_match(file))
+                })
+                .map(|file| file.to_string_lossy().to_string())
+                .collect();
+            if files.is_empty() {
+                continue;
+            }
+
+            if pair_format.is_text() {
+                if analyzed > 0 {
+                    println!("\n{separator}\n");
+                }
+                println!("=== Cross-Language Similarity ===");
+            }
+            analyzed += 1;
+
+            let extensions = analyzer_extensions(Language::TypeScript);
+            let pipeline = options.pipeline_options(&cli, extensions);
+            let entries = check_cross_language(&files, &pipeline, pair_format, baseline.as_ref())?;
+            report.extend(entries);
+        }
+    }
+
+    if analyzed == 0 && text {
+        println!("No supported files found in the specified paths.");
+    }
+
+    if cli.clone_classes {
+        report.group_clone_classes(cli.linkage);
+    }
+    report.print(format)?;
+    finish_baseline(baseline.as_ref(), cli.write_baseline.as_deref(), &report.duplicates, text)?;
+    fail_thresholds.enforce(&report.duplicates);
+
+    Ok(())
+}
+
+/// Run the analyzers of `language` on `files`
+fn check_language(
+    language: Language,
+    files: Vec<String>,
+    options: &LanguageOptions,
+    cli: &Cli,
+    format: OutputFormat,
+    baseline: Option<&Baseline>,
+) -> Result<Vec<ReportEntry>> {
+    let pipeline = options.pipeline_options(cli, analyzer_extensions(language));
+
+    match language {
+        Language::TypeScript => {
+            let check_functions = |files| {
+                // similarity-ts always compares functions across files
+                let pipeline = PipelineOptions { cross_file: true, ..pipeline.clone() };
+                let options = CheckOptions { pipeline, ..Default::default() };
+                similarity_ts::check::check_paths(files, &options, format, baseline)
+            };
+            run_analyzers(
+                &TypeScriptLanguage,
+                files,
+                &pipeline,
+                cli,
+                format,
+                baseline,
+                check_functions,
+            )
+        }
+        Language::Python => {
+            let check_functions =
+                |files| check_paths(&PythonLanguage, files, &pipeline, format, baseline);
+            run_analyzers(&PythonLanguage, files, &pipeline, cli, format, baseline, check_functions)
+        }
+        Language::Rust => {
+            let check_functions =
+                |files| check_paths(&RustLanguage, files, &pipeline, format, baseline);
+            run_analyzers(&RustLanguage, files, &pipeline, cli, format, baseline, check_functions)
+        }
+        Language::Elixir => {
+            let check_functions =
+                |files| check_paths(&ElixirLanguage, files, &pipeline, format, baseline);
+            run_analyzers(&ElixirLanguage, files, &pipeline, cli, format, baseline, check_functions)
+        }
+        _ => {
+            let generic = GenericLanguage::from_language_name(language.config_name())?;
+            let check_functions = |files| check_paths(&generic, files, &pipeline, format, baseline);
+            run_analyzers(&generic, files, &pipeline, cli, format, baseline, check_functions)
+        }
+    }
+}
+
+/// Run the enabled analyzers on `files`; functions are compared by `check_functions`
+fn run_analyzers<L: LanguageSupport>(
+    language: &L,
+    files: Vec<String>,
+    pipeline: &PipelineOptions,
+    cli: &Cli,
+    format: OutputFormat,
+    baseline: Option<&Baseline>,
+    check_functions: impl FnOnce(Vec<String>) -> Result<Vec<ReportEntry>>,
+) -> Result<Vec<ReportEntry>> {
+    let mut entries = Vec::new();
+    let mut sections = 0;
+    let mut section = |title: &str| {
+        if format.is_text() {
+            if sections > 0 {
+                println!();
+            }
+            println!("--- {title} ---");
+        }
+        sections += 1;
+    };
+
+    if !cli.no_functions {
+        section("Function Similarity");
+        entries.extend(check_functions(files.clone())?);
+    }
+    if !cli.no_types && language.supports_types() {
+        section("Type Similarity");
+        entries.extend(check_types(language, files.clone(), pipeline, format, baseline)?);
+    }
+    if !cli.no_overlap {
+        section("Overlap Detection");
+        entries.extend(check_overlaps(language, files, pipeline, format, baseline)?);
+    }
+
+    Ok(entries)
+}
+
+/// The analyzer responsible for files of `language`
+fn analyzer_language(language: Language) -> Language {
+    match language {
+        Language::JavaScript => Language::TypeScript,
+        language => language,
+    }
+}
+
+/// Languages whose files an analyzer handles
+fn analyzed_languages(language: Language) -> Vec<Language> {
+    match language {
+        Language::TypeScript => vec![Language::TypeScript, Language::JavaScript],
+        language => vec![language],
+    }
+}
+
+/// Extensions of every file analyzed as `language`
+fn analyzer_extensions(language: Language) -> Vec<String> {
+    analyzed_languages(language)
+        .iter()
+        .flat_map(|language| language.extensions().iter().map(|ext| ext.to_string()))
+        .collect()
+}
+
+fn display_name(language: Language) -> &'static str {
+    match language {
+        Language::TypeScript | Language::JavaScript => "TypeScript/JavaScript",
+        Language::Python => "Python",
+        Language::Rust => "Rust",
+        Language::Elixir => "Elixir",
+        Language::Go => "Go",
+        Language::Java => "Java",
+        Language::C => "C",
+        Language::Cpp => "C++",
+        Language::CSharp => "C#",
+        Language::Ruby => "Ruby",
+        Language::Php => "PHP",
+        Language::Unknown => "Unknown",
+    }
+}
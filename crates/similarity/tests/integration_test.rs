@@ -0,0 +1,294 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn write_mixed_project(dir: &Path) {
+    fs::write(
+        dir.join("validate.py"),
+        r#"def validate_user(user):
+    if not user.name:
+        raise ValueError("name is required")
+    if user.age < 0:
+        raise ValueError("age must be positive")
+    return True
+
+def validate_admin(admin):
+    if not admin.name:
+        raise ValueError("name is required")
+    if admin.age < 0:
+        raise ValueError("age must be positive")
+    return True
+"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.join("sum.go"),
+        r#"package main
+
+func sumA(xs []int) int {
+	total := 0
+	for _, x := range xs {
+		total += x
+	}
+	return total
+}
+
+func sumB(values []int) int {
+	acc := 0
+	for _, v := range values {
+		acc += v
+	}
+	return acc
+}
+"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_reports_every_language_in_one_run() {
+    let dir = tempdir().unwrap();
+    write_mixed_project(dir.path());
+
+    Command::cargo_bin("similarity")
+        .unwrap()
+        .current_dir(dir.path())
+        .arg(".")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("=== Python ==="))
+        .stdout(predicate::str::contains("validate_admin"))
+        .stdout(predicate::str::contains("=== Go ==="))
+        .stdout(predicate::str::contains("sumB"))
+        .stdout(predicate::str::contains("=== Rust ===").not());
+}
+
+#[test]
+fn test_merged_json_report_and_language_filter() {
+    let dir = tempdir().unwrap();
+    write_mixed_project(dir.path());
+
+    let output = Command::cargo_bin("similarity")
+        .unwrap()
+        .current_dir(dir.path())
+        .args([".", "--format", "json"])
+        .output()
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["tool"], "similarity");
+    let files: Vec<&str> = report["duplicates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["first"]["file"].as_str().unwrap())
+        .collect();
+    assert!(files.iter().any(|file| file.ends_with("validate.py")));
+    assert!(files.iter().any(|file| file.ends_with("sum.go")));
+
+    Command::cargo_bin("similarity")
+        .unwrap()
+        .current_dir(dir.path())
+        .args([".", "--languages", "go"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("sumB"))
+        .stdout(predicate::str::contains("validate_admin").not());
+}
+
+#[test]
+fn test_per_language_config_section() {
+    let dir = tempdir().unwrap();
+    write_mixed_project(dir.path());
+    fs::write(dir.path().join("similarity.toml"), "[python]\nthreshold = 1.01\n").unwrap();
+
+    Command::cargo_bin("similarity")
+        .unwrap()
+        .current_dir(dir.path())
+        .args([".", "--fail-on-duplicates"])
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains("sumB"))
+        .stdout(predicate::str::contains("validate_admin").not());
+}
+
+#[test]
+fn test_reports_types_and_overlaps_unless_disabled() {
+    let dir = tempdir().unwrap();
+    write_mixed_project(dir.path());
+    fs::write(
+        dir.path().join("models.py"),
+        r#"from dataclasses import dataclass
+
+@dataclass
+class User:
+    name: str
+    email: str
+    age: int
+
+@dataclass
+class Customer:
+    name: str
+    email: str
+    age: int
+"#,
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("similarity")
+        .unwrap()
+        .current_dir(dir.path())
+        .args([".", "--languages", "py", "--format", "json"])
+        .output()
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let kinds: Vec<&str> = report["duplicates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["kind"].as_str().unwrap())
+        .collect();
+    assert!(kinds.contains(&"function"));
+    assert!(kinds.contains(&"type"));
+
+    Command::cargo_bin("similarity")
+        .unwrap()
+        .current_dir(dir.path())
+        .args([".", "--languages", "py"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--- Function Similarity ---"))
+        .stdout(predicate::str::contains("--- Type Similarity ---"))
+        .stdout(predicate::str::contains("Customer"))
+        .stdout(predicate::str::contains("--- Overlap Detection ---"));
+
+    Command::cargo_bin("similarity")
+        .unwrap()
+        .current_dir(dir.path())
+        .args([".", "--languages", "py", "--no-functions", "--no-types"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--- Overlap Detection ---"))
+        .stdout(predicate::str::contains("--- Function Similarity ---").not())
+        .stdout(predicate::str::contains("Customer").not());
+
+    Command::cargo_bin("similarity")
+        .unwrap()
+        .current_dir(dir.path())
+        .args([".", "--no-functions", "--no-types", "--no-overlap"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("At least one analyzer must be enabled"));
+}
+
+#[test]
+fn test_each_root_uses_its_own_config() {
+    let dir = tempdir().unwrap();
+    for root in ["strict", "loose"] {
+        fs::create_dir_all(dir.path().join(root)).unwrap();
+        write_mixed_project(&dir.path().join(root));
+    }
+    fs::write(dir.path().join("strict/similarity.toml"), "threshold = 1.01\n").unwrap();
+    fs::write(dir.path().join("loose/similarity.toml"), "threshold = 0.5\n").unwrap();
+
+    let output = Command::cargo_bin("similarity")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["strict", "loose", "--languages", "py,go", "--no-types", "--no-overlap"])
+        .args(["--format", "json"])
+        .output()
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let files: Vec<&str> = report["duplicates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["first"]["file"].as_str().unwrap())
+        .collect();
+    assert!(files.contains(&"loose/validate.py"), "{files:?}");
+    assert!(files.contains(&"loose/sum.go"), "{files:?}");
+    assert!(files.iter().all(|file| !file.starts_with("strict")), "{files:?}");
+}
+
+#[test]
+fn test_overrides_and_nested_configs_apply_when_scanning_the_root() {
+    let dir = tempdir().unwrap();
+    for root in ["strict", "loose"] {
+        fs::create_dir_all(dir.path().join(root)).unwrap();
+        write_mixed_project(&dir.path().join(root));
+    }
+    fs::write(
+        dir.path().join("similarity.toml"),
+        "threshold = 0.99\n[[overrides]]\npath = \"strict\"\nthreshold = 1.01\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("loose/similarity.toml"), "threshold = 0.5\n").unwrap();
+
+    let output = Command::cargo_bin("similarity")
+        .unwrap()
+        .current_dir(dir.path())
+        .args([".", "--languages", "py,go", "--no-types", "--no-overlap"])
+        .args(["--format", "json"])
+        .output()
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let files: Vec<&str> = report["duplicates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["first"]["file"].as_str().unwrap())
+        .collect();
+    assert!(files.contains(&"./loose/validate.py"), "{files:?}");
+    assert!(files.contains(&"./loose/sum.go"), "{files:?}");
+    assert!(files.iter().all(|file| !file.contains("strict")), "{files:?}");
+}
+
+#[test]
+fn test_cross_language_duplicates() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("validate.ts"),
+        r#"export function isValidEmail(email: string): boolean {
+    if (email.length === 0) {
+        return false;
+    }
+    const parts = email.split("@");
+    return parts.length === 2 && parts[1].includes(".");
+}
+"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("validate.py"),
+        r#"def is_valid_email(email: str) -> bool:
+    """Check that an email address looks valid."""
+    if len(email) == 0:
+        return False
+    parts = email.split("@")
+    return len(parts) == 2 and "." in parts[1]
+"#,
+    )
+    .unwrap();
+
+    Command::cargo_bin("similarity")
+        .unwrap()
+        .current_dir(dir.path())
+        .args([".", "--cross-language", "--no-functions", "--no-types", "--no-overlap"])
+        .arg("--no-size-penalty")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("=== Cross-Language Similarity ==="))
+        .stdout(predicate::str::contains("isValidEmail"))
+        .stdout(predicate::str::contains("is_valid_email"))
+        .stdout(predicate::str::contains("=== Python ===").not());
+
+    // Without the flag, the languages are only compared with themselves
+    Command::cargo_bin("similarity")
+        .unwrap()
+        .current_dir(dir.path())
+        .args([".", "--no-size-penalty"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Cross-Language").not());
+}